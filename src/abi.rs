@@ -12,8 +12,17 @@
 //!
 //! [Solidity Contract ABI Specification]: https://docs.soliditylang.org/en/develop/abi-spec.html
 
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+pub use builder::{AbiBuilder, AbiBuilderError};
+pub use token::Token;
+
+mod builder;
+pub mod codec;
+mod token;
+
 /// A complete Contract ABI.
 ///
 /// The ABI is represented as a JSON array containing functions, events, and errors.
@@ -33,6 +42,251 @@ impl Abi {
     pub fn from_items(items: Vec<AbiItem>) -> Self {
         Self { items }
     }
+
+    /// Count the items of each kind.
+    pub fn summary(&self) -> AbiSummary {
+        let mut summary = AbiSummary::default();
+        for item in &self.items {
+            match item {
+                AbiItem::Function(_) => summary.functions += 1,
+                AbiItem::Constructor(_) => summary.constructor = true,
+                AbiItem::Receive(_) => summary.receive = true,
+                AbiItem::Fallback(_) => summary.fallback = true,
+                AbiItem::Event(_) => summary.events += 1,
+                AbiItem::Error(_) => summary.errors += 1,
+            }
+        }
+        summary
+    }
+
+    /// Decode the data of a revert: the built-in `Error(string)` of
+    /// `require` and `revert` with a message, the built-in `Panic(uint256)`
+    /// of failed assertions and arithmetic errors, or a custom error of this
+    /// ABI, chosen by the selector the data starts with.
+    ///
+    /// The compiler only uses small panic codes, so a code that does not fit
+    /// in a `u64` is reported as an invalid value.
+    pub fn decode_revert(&self, data: &[u8]) -> Result<Revert<'_>, RevertError> {
+        if data.is_empty() {
+            return Ok(Revert::Empty);
+        }
+        let (prefix, payload) = data
+            .split_first_chunk::<4>()
+            .ok_or(RevertError::TooShort(data.len()))?;
+        let builtin = |ty: &str| Param {
+            name: String::new(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: None,
+            internal_type: None,
+        };
+
+        if *prefix == selector("Error(string)") {
+            let Some(Token::String(message)) = codec::decode(&[builtin("string")], payload)?.pop()
+            else {
+                unreachable!("a string decodes to a string token");
+            };
+            return Ok(Revert::Message(message));
+        }
+        if *prefix == selector("Panic(uint256)") {
+            let Some(Token::Uint(code)) = codec::decode(&[builtin("uint256")], payload)?.pop()
+            else {
+                unreachable!("a uint256 decodes to a uint token");
+            };
+            let (high, low) = code.split_at(24);
+            if high.iter().any(|byte| *byte != 0) {
+                return Err(codec::DecodeError::InvalidValue("uint256".to_string()).into());
+            }
+            return Ok(Revert::Panic(u64::from_be_bytes(low.try_into().unwrap())));
+        }
+        let error = self
+            .items
+            .iter()
+            .find_map(|item| match item {
+                AbiItem::Error(error) if error.selector() == *prefix => Some(error),
+                _ => None,
+            })
+            .ok_or(RevertError::UnknownSelector(*prefix))?;
+        let values = codec::decode(&error.inputs, payload)?;
+        Ok(Revert::Custom { error, values })
+    }
+
+    /// Check that every item in the ABI can be encoded by ABI coder v1.
+    ///
+    /// All incompatible parameters are collected, so the caller can report
+    /// every problem at once instead of fixing them one by one.
+    pub fn check_abicoder_v1(&self) -> Result<(), Vec<AbiCoderV1Error>> {
+        let mut errors = Vec::new();
+        for item in &self.items {
+            item.collect_abicoder_v1_errors(&mut errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Revert data decoded by [`Abi::decode_revert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revert<'a> {
+    /// A revert without data, such as `revert()` or a `require` without a
+    /// message.
+    Empty,
+    /// The message of `Error(string)`.
+    Message(String),
+    /// The code of `Panic(uint256)`, such as `0x11` for an arithmetic
+    /// overflow or `0x01` for a failed `assert`.
+    Panic(u64),
+    /// A custom error of the ABI, with one value per parameter.
+    Custom {
+        error: &'a Error,
+        values: Vec<Token>,
+    },
+}
+
+/// An error decoding revert data.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RevertError {
+    /// The data is shorter than a selector.
+    #[error("revert data of {0} bytes is shorter than a selector")]
+    TooShort(usize),
+
+    /// The selector is neither a built-in error nor an error of the ABI.
+    #[error("unknown error selector 0x{}", hex::encode(.0))]
+    UnknownSelector([u8; 4]),
+
+    /// The data after the selector is not a valid encoding of the error's
+    /// parameters.
+    #[error(transparent)]
+    Decode(#[from] codec::DecodeError),
+}
+
+/// Lists the ABI the way it would be declared in Solidity, one item per
+/// line: the constructor, receive, and fallback functions, then the other
+/// functions grouped by state mutability, then events, then errors. Groups
+/// are separated by blank lines.
+impl fmt::Display for Abi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut special = Vec::new();
+        let mut functions: [Vec<String>; 4] = Default::default();
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+        for item in &self.items {
+            match item {
+                AbiItem::Function(function) => {
+                    let mut line = format!(
+                        "function {}({})",
+                        function.name,
+                        display_params(&function.inputs)
+                    );
+                    if function.state_mutability != StateMutability::Nonpayable {
+                        line.push_str(&format!(" {}", function.state_mutability));
+                    }
+                    if !function.outputs.is_empty() {
+                        line.push_str(&format!(" returns ({})", display_params(&function.outputs)));
+                    }
+                    functions[function.state_mutability.rank() as usize].push(line);
+                }
+                AbiItem::Constructor(constructor) => {
+                    let mut line = format!("constructor({})", display_params(&constructor.inputs));
+                    if constructor.state_mutability == StateMutability::Payable {
+                        line.push_str(" payable");
+                    }
+                    special.push(line);
+                }
+                AbiItem::Receive(_) => special.push("receive() payable".to_string()),
+                AbiItem::Fallback(fallback) => special.push(match fallback.state_mutability {
+                    StateMutability::Payable => "fallback() payable".to_string(),
+                    _ => "fallback()".to_string(),
+                }),
+                AbiItem::Event(event) => {
+                    let inputs: Vec<String> = event
+                        .inputs
+                        .iter()
+                        .map(|param| {
+                            let mut input = param.canonical_type();
+                            if param.is_indexed() {
+                                input.push_str(" indexed");
+                            }
+                            if !param.name.is_empty() {
+                                input.push_str(&format!(" {}", param.name));
+                            }
+                            input
+                        })
+                        .collect();
+                    let mut line = format!("event {}({})", event.name, inputs.join(", "));
+                    if event.anonymous {
+                        line.push_str(" anonymous");
+                    }
+                    events.push(line);
+                }
+                AbiItem::Error(error) => {
+                    errors.push(format!(
+                        "error {}({})",
+                        error.name,
+                        display_params(&error.inputs)
+                    ));
+                }
+            }
+        }
+
+        let groups = std::iter::once(special)
+            .chain(functions)
+            .chain([events, errors])
+            .filter(|group| !group.is_empty());
+        for (index, group) in groups.enumerate() {
+            if index > 0 {
+                f.write_str("\n\n")?;
+            }
+            f.write_str(&group.join("\n"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of items of each kind in an [`Abi`], from [`Abi::summary`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbiSummary {
+    /// The number of functions, other than the constructor, receive, and
+    /// fallback functions.
+    pub functions: usize,
+    /// The number of events.
+    pub events: usize,
+    /// The number of errors.
+    pub errors: usize,
+    /// Whether the ABI has a constructor.
+    pub constructor: bool,
+    /// Whether the ABI has a receive function.
+    pub receive: bool,
+    /// Whether the ABI has a fallback function.
+    pub fallback: bool,
+}
+
+/// Writes the counts, such as `3 functions, 1 event, 0 errors, fallback`.
+impl fmt::Display for AbiSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = |count: usize, noun: &str| match count {
+            1 => format!("1 {}", noun),
+            count => format!("{} {}s", count, noun),
+        };
+        let mut parts = vec![
+            count(self.functions, "function"),
+            count(self.events, "event"),
+            count(self.errors, "error"),
+        ];
+        for (present, name) in [
+            (self.constructor, "constructor"),
+            (self.receive, "receive"),
+            (self.fallback, "fallback"),
+        ] {
+            if present {
+                parts.push(name.to_string());
+            }
+        }
+        f.write_str(&parts.join(", "))
+    }
 }
 
 /// An ABI item, which can be a function, constructor, receive, fallback, event, or error.
@@ -137,9 +391,13 @@ pub struct Error {
     pub inputs: Vec<Param>,
 }
 
-/// A parameter in a function, constructor, or error.
+/// A parameter of any ABI item, or a component of a tuple.
+///
+/// Parameters of functions, constructors, errors and events, and the
+/// components of tuples, all have this shape; [`Param`], [`EventParam`],
+/// and [`Component`] name it by role. Only event parameters are `indexed`.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct Param {
+pub struct Parameter {
     /// The name of the parameter.
     pub name: String,
 
@@ -147,61 +405,320 @@ pub struct Param {
     #[serde(rename = "type")]
     pub r#type: String,
 
-    /// The components of a tuple type (if this parameter is a tuple).
-    pub components: Option<Vec<Component>>,
+    /// The components of a tuple type, which are parameters themselves.
+    pub components: Option<Vec<Parameter>>,
+
+    /// Whether an event parameter is indexed (stored in the event's topics),
+    /// or `None` for parameters of other items and for components.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<bool>,
 
     /// The internal Solidity type (e.g., "contract IERC20", "struct User").
     #[serde(rename = "internalType", default)]
-    pub internal_type: Option<String>,
+    pub internal_type: Option<InternalType>,
 }
 
-/// A parameter in an event.
-///
-/// Event parameters have an additional `indexed` field that indicates whether
-/// the parameter is stored in the event's topics (true) or in the data section (false).
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct EventParam {
-    /// The name of the parameter.
-    pub name: String,
+/// A parameter in a function, constructor, or error.
+pub type Param = Parameter;
 
-    /// The canonical type of the parameter.
-    #[serde(rename = "type")]
-    pub r#type: String,
+/// A parameter in an event, whose `indexed` field says whether it is stored
+/// in the event's topics or in the data section.
+pub type EventParam = Parameter;
 
-    /// The components of a tuple type (if this parameter is a tuple).
-    pub components: Option<Vec<Component>>,
+/// A component of a tuple type, which can itself be a tuple.
+pub type Component = Parameter;
 
-    /// Whether this parameter is indexed (stored in the event's topics).
-    pub indexed: bool,
+impl Parameter {
+    /// Whether this is an event parameter stored in the event's topics.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed == Some(true)
+    }
 
-    /// The internal Solidity type.
-    #[serde(rename = "internalType", default)]
-    pub internal_type: Option<String>,
+    /// The type with tuples expanded into the parenthesized list of their
+    /// components, as in signatures, such as `(address,uint256)[]`.
+    pub fn canonical_type(&self) -> String {
+        match (self.r#type.strip_prefix("tuple"), &self.components) {
+            (Some(suffix), Some(components)) => {
+                let types = components.iter().map(Parameter::canonical_type);
+                format!("{}{}", signature("", types), suffix)
+            }
+            _ => self.r#type.clone(),
+        }
+    }
+
+    /// Call `f` with this parameter and then with each of its components,
+    /// depth first, along with how deeply each is nested.
+    pub fn walk(&self, f: &mut impl FnMut(&Parameter, usize)) {
+        self.walk_at(0, f);
+    }
+
+    fn walk_at(&self, depth: usize, f: &mut impl FnMut(&Parameter, usize)) {
+        f(self, depth);
+        for component in self.components.iter().flatten() {
+            component.walk_at(depth + 1, f);
+        }
+    }
+
+    /// The types this parameter is made of, with tuples broken down into
+    /// their components, in the order they are encoded.
+    ///
+    /// Each leaf is named by its path from this parameter, such as
+    /// `order.items[0].price`. Components without a name are named by their
+    /// position. Fixed-size arrays of tuples are broken down element by
+    /// element, while the elements of a dynamic array of tuples are broken
+    /// down once, with `[]` in their path, as in `orders[].maker`. Arrays of
+    /// other types are leaves.
+    pub fn flatten(&self) -> Vec<FlatParameter> {
+        let mut leaves = Vec::new();
+        flatten_into(&self.r#type, self, self.name.clone(), &mut leaves);
+        leaves
+    }
 }
 
-/// A component of a tuple type.
+/// A leaf of a parameter broken down by [`Parameter::flatten`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatParameter {
+    /// The path from the flattened parameter, such as `order.items[0].price`.
+    pub path: String,
+    /// The canonical type of the leaf, which is not a tuple.
+    pub r#type: String,
+    /// The internal Solidity type of the leaf.
+    pub internal_type: Option<InternalType>,
+}
+
+/// Break down a value of type `ty`, which is the type of `parameter` or of
+/// an element of it, into leaves under `path`.
+fn flatten_into(ty: &str, parameter: &Parameter, path: String, leaves: &mut Vec<FlatParameter>) {
+    if !ty.starts_with("tuple") {
+        leaves.push(FlatParameter {
+            path,
+            r#type: ty.to_string(),
+            internal_type: parameter.internal_type.clone(),
+        });
+        return;
+    }
+    if let Some(element) = strip_array_suffix(ty) {
+        let length = &ty[element.len() + 1..ty.len() - 1];
+        match length.parse::<usize>() {
+            Ok(length) => {
+                for index in 0..length {
+                    flatten_into(element, parameter, format!("{}[{}]", path, index), leaves);
+                }
+            }
+            Err(_) => flatten_into(element, parameter, format!("{}[]", path), leaves),
+        }
+        return;
+    }
+    for (index, component) in parameter.components.iter().flatten().enumerate() {
+        let name = match component.name.as_str() {
+            "" => index.to_string(),
+            name => name.to_string(),
+        };
+        let path = if path.is_empty() {
+            name
+        } else {
+            format!("{}.{}", path, name)
+        };
+        flatten_into(&component.r#type, component, path, leaves);
+    }
+}
+
+/// The Solidity-level type behind an ABI parameter.
 ///
-/// Components have the same structure as parameters, but can be nested recursively
-/// to represent complex tuple types.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct Component {
-    /// The name of the component.
-    pub name: String,
+/// The compiler reports this as the `internalType` string (for example
+/// `struct Pool.Position[]` or `contract IERC20`). The canonical ABI `type`
+/// loses this information, so bindings generators use it to reconstruct the
+/// original structs, enums, contracts, and user-defined value types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InternalType {
+    /// A built-in type such as `uint256`, `bytes32`, or `address payable`.
+    Elementary(String),
 
-    /// The canonical type of the component.
-    #[serde(rename = "type")]
-    pub r#type: String,
+    /// A struct, optionally qualified by the contract that declares it.
+    Struct {
+        contract: Option<String>,
+        name: String,
+    },
 
-    /// Nested components (for nested tuples).
-    pub components: Option<Vec<Component>>,
+    /// An enum, optionally qualified by the contract that declares it.
+    Enum {
+        contract: Option<String>,
+        name: String,
+    },
 
-    /// The internal Solidity type.
-    #[serde(rename = "internalType", default)]
-    pub internal_type: Option<String>,
+    /// A contract or interface type, encoded as `address` in the ABI.
+    Contract(String),
+
+    /// A user-defined value type such as `type Price is uint128`.
+    UserDefinedValueType {
+        contract: Option<String>,
+        name: String,
+    },
+
+    /// A function type, kept as the full signature the compiler reports.
+    Function(String),
+
+    /// An array of another internal type, with `None` for dynamic length.
+    Array {
+        base: Box<InternalType>,
+        length: Option<usize>,
+    },
+
+    /// A string that does not parse as any of the above, kept as written.
+    ///
+    /// Only deserialization produces this, so that an ABI with an
+    /// `internalType` this crate does not understand still loads.
+    Other(String),
+}
+
+/// An error returned when an `internalType` string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InternalTypeError {
+    /// The string is empty or only contains a keyword without a name.
+    #[error("missing type name in internal type `{0}`")]
+    MissingName(String),
+
+    /// An array suffix has a length that is not a number.
+    #[error("invalid array length in internal type `{0}`")]
+    InvalidArrayLength(String),
+}
+
+impl InternalType {
+    /// Whether this is an array type.
+    pub fn is_array(&self) -> bool {
+        matches!(self, InternalType::Array { .. })
+    }
+
+    /// The innermost element type, after removing all array dimensions.
+    pub fn element(&self) -> &InternalType {
+        match self {
+            InternalType::Array { base, .. } => base.element(),
+            other => other,
+        }
+    }
+}
+
+impl std::str::FromStr for InternalType {
+    type Err = InternalTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.ends_with(']')
+            && let Some(start) = s.rfind('[')
+        {
+            let length = &s[start + 1..s.len() - 1];
+            let length = if length.is_empty() {
+                None
+            } else {
+                Some(
+                    length
+                        .parse()
+                        .map_err(|_| InternalTypeError::InvalidArrayLength(s.to_string()))?,
+                )
+            };
+            return Ok(InternalType::Array {
+                base: Box::new(s[..start].parse()?),
+                length,
+            });
+        }
+
+        let qualified = |name: &str| {
+            if name.is_empty() {
+                return Err(InternalTypeError::MissingName(s.to_string()));
+            }
+            Ok(match name.rsplit_once('.') {
+                Some((contract, name)) => (Some(contract.to_string()), name.to_string()),
+                None => (None, name.to_string()),
+            })
+        };
+
+        if let Some(name) = s.strip_prefix("struct ") {
+            let (contract, name) = qualified(name)?;
+            Ok(InternalType::Struct { contract, name })
+        } else if let Some(name) = s.strip_prefix("enum ") {
+            let (contract, name) = qualified(name)?;
+            Ok(InternalType::Enum { contract, name })
+        } else if let Some(name) = s.strip_prefix("contract ") {
+            if name.is_empty() {
+                return Err(InternalTypeError::MissingName(s.to_string()));
+            }
+            Ok(InternalType::Contract(name.to_string()))
+        } else if s.starts_with("function") {
+            Ok(InternalType::Function(s.to_string()))
+        } else if is_elementary_type(s) {
+            Ok(InternalType::Elementary(s.to_string()))
+        } else {
+            let (contract, name) = qualified(s)?;
+            Ok(InternalType::UserDefinedValueType { contract, name })
+        }
+    }
+}
+
+impl std::fmt::Display for InternalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let qualified = |contract: &Option<String>, name: &str| match contract {
+            Some(contract) => format!("{}.{}", contract, name),
+            None => name.to_string(),
+        };
+        match self {
+            InternalType::Elementary(name)
+            | InternalType::Function(name)
+            | InternalType::Other(name) => f.write_str(name),
+            InternalType::Struct { contract, name } => {
+                write!(f, "struct {}", qualified(contract, name))
+            }
+            InternalType::Enum { contract, name } => {
+                write!(f, "enum {}", qualified(contract, name))
+            }
+            InternalType::Contract(name) => write!(f, "contract {}", name),
+            InternalType::UserDefinedValueType { contract, name } => {
+                f.write_str(&qualified(contract, name))
+            }
+            InternalType::Array { base, length } => match length {
+                Some(length) => write!(f, "{}[{}]", base, length),
+                None => write!(f, "{}[]", base),
+            },
+        }
+    }
+}
+
+impl Serialize for InternalType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for InternalType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or(InternalType::Other(s)))
+    }
+}
+
+/// Whether a type name is one of Solidity's built-in elementary types.
+fn is_elementary_type(name: &str) -> bool {
+    let sized = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+    };
+    matches!(name, "address" | "address payable" | "bool" | "string")
+        || sized("uint")
+        || sized("int")
+        || sized("bytes")
+        || sized("ufixed")
+        || sized("fixed")
 }
 
 /// The state mutability of a function.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+///
+/// Mutabilities form a chain, `pure` ⊂ `view` ⊂ `nonpayable` ⊂ `payable`,
+/// in which each one allows everything the ones before it do.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum StateMutability {
     /// The function does not read or modify blockchain state.
@@ -217,6 +734,495 @@ pub enum StateMutability {
     Payable,
 }
 
+impl fmt::Display for StateMutability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StateMutability::Pure => "pure",
+            StateMutability::View => "view",
+            StateMutability::Nonpayable => "nonpayable",
+            StateMutability::Payable => "payable",
+        })
+    }
+}
+
+impl StateMutability {
+    /// Whether a function with this mutability may do everything a function
+    /// with `other` does, such as `view` allowing `pure`.
+    pub fn allows(self, other: Self) -> bool {
+        self.rank() >= other.rank()
+    }
+
+    /// The least mutability that allows both, which a function doing what
+    /// functions of both mutabilities do must have.
+    pub fn join(self, other: Self) -> Self {
+        if self.allows(other) { self } else { other }
+    }
+
+    /// The greatest mutability that both allow.
+    pub fn meet(self, other: Self) -> Self {
+        if self.allows(other) { other } else { self }
+    }
+
+    /// Whether the function reads blockchain state.
+    pub fn reads_state(self) -> bool {
+        self.allows(StateMutability::View)
+    }
+
+    /// Whether the function modifies blockchain state.
+    pub fn modifies_state(self) -> bool {
+        self.allows(StateMutability::Nonpayable)
+    }
+
+    /// Whether a function with mutability `derived` may override one with
+    /// mutability `base`.
+    ///
+    /// Overriding may only restrict mutability, as in `nonpayable` to `view`
+    /// or `view` to `pure`, except that a `payable` function must stay
+    /// `payable` and no other function may become `payable`.
+    pub fn is_compatible_override(base: Self, derived: Self) -> bool {
+        if base == StateMutability::Payable || derived == StateMutability::Payable {
+            return base == derived;
+        }
+        base.allows(derived)
+    }
+
+    /// Whether changing the mutability of a deployed function from `old` to
+    /// `new` keeps working for its callers.
+    ///
+    /// Callers keep working if the function does no more than before, and a
+    /// `nonpayable` function may also become `payable`, since calls without
+    /// Ether are still accepted. A `payable` function that stops accepting
+    /// Ether breaks callers that send it.
+    pub fn is_compatible_change(old: Self, new: Self) -> bool {
+        old.allows(new) && (old != StateMutability::Payable || new == StateMutability::Payable)
+            || (old == StateMutability::Nonpayable && new == StateMutability::Payable)
+    }
+
+    /// The position of the mutability in the chain.
+    fn rank(self) -> u8 {
+        match self {
+            StateMutability::Pure => 0,
+            StateMutability::View => 1,
+            StateMutability::Nonpayable => 2,
+            StateMutability::Payable => 3,
+        }
+    }
+}
+
+impl From<&crate::ast::StateMutability> for StateMutability {
+    fn from(mutability: &crate::ast::StateMutability) -> Self {
+        match mutability {
+            crate::ast::StateMutability::Pure => StateMutability::Pure,
+            crate::ast::StateMutability::View => StateMutability::View,
+            crate::ast::StateMutability::Nonpayable => StateMutability::Nonpayable,
+            crate::ast::StateMutability::Payable => StateMutability::Payable,
+        }
+    }
+}
+
+/// A parameter type that the legacy ABI coder v1 cannot encode.
+///
+/// Contracts compiled with `pragma abicoder v1` (the default before Solidity
+/// 0.8.0) cannot use tuples or arrays whose elements are dynamic types in their
+/// external interface. Signatures built from such parameters would not match
+/// anything the compiler accepts, so they are reported instead.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AbiCoderV1Error {
+    /// A tuple (struct) parameter, which requires ABI coder v2.
+    #[error("{item}: parameter `{param}` has tuple type `{ty}`, which requires ABI coder v2")]
+    Tuple {
+        item: String,
+        param: String,
+        ty: String,
+    },
+
+    /// An array whose element type is dynamic, such as `string[]` or `uint256[][]`.
+    #[error(
+        "{item}: parameter `{param}` has nested dynamic type `{ty}`, which requires ABI coder v2"
+    )]
+    NestedDynamicArray {
+        item: String,
+        param: String,
+        ty: String,
+    },
+}
+
+impl AbiItem {
+    /// Check that this item can be encoded by ABI coder v1.
+    pub fn check_abicoder_v1(&self) -> Result<(), Vec<AbiCoderV1Error>> {
+        let mut errors = Vec::new();
+        self.collect_abicoder_v1_errors(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn collect_abicoder_v1_errors(&self, errors: &mut Vec<AbiCoderV1Error>) {
+        let (label, params): (String, Vec<(&str, &str)>) = match self {
+            AbiItem::Function(f) => (
+                format!("function {}", f.name),
+                f.inputs
+                    .iter()
+                    .chain(&f.outputs)
+                    .map(|p| (p.name.as_str(), p.r#type.as_str()))
+                    .collect(),
+            ),
+            AbiItem::Constructor(c) => (
+                "constructor".to_string(),
+                c.inputs
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.r#type.as_str()))
+                    .collect(),
+            ),
+            AbiItem::Event(e) => (
+                format!("event {}", e.name),
+                e.inputs
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.r#type.as_str()))
+                    .collect(),
+            ),
+            AbiItem::Error(e) => (
+                format!("error {}", e.name),
+                e.inputs
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.r#type.as_str()))
+                    .collect(),
+            ),
+            AbiItem::Receive(_) | AbiItem::Fallback(_) => return,
+        };
+
+        for (param, ty) in params {
+            if let Some(error) = abicoder_v1_error(&label, param, ty) {
+                errors.push(error);
+            }
+        }
+    }
+}
+
+impl Function {
+    /// The canonical signature, such as `transfer(address,uint256)`.
+    ///
+    /// Tuples are expanded into the parenthesized list of their component
+    /// types, as in `submit((address,uint256)[])`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, self.inputs.iter().map(|p| p.canonical_type()))
+    }
+
+    /// The selector that calls to this function start with: the first four
+    /// bytes of the Keccak-256 hash of its [signature](Self::signature).
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+
+    /// Encode a call to this function with the given arguments: the
+    /// [selector](Self::selector) followed by the ABI encoding of the
+    /// arguments, one per input.
+    pub fn encode_call(&self, arguments: &[Token]) -> Result<Vec<u8>, codec::EncodeError> {
+        let mut data = self.selector().to_vec();
+        data.extend(codec::encode(&self.inputs, arguments)?);
+        Ok(data)
+    }
+}
+
+impl Constructor {
+    /// Decode the arguments a contract was deployed with.
+    ///
+    /// The input of a creation transaction is the creation bytecode followed
+    /// by the ABI-encoded constructor arguments. `bytecode` must be exactly
+    /// the creation bytecode that was deployed, with libraries linked, and
+    /// `input` the whole input of the transaction.
+    pub fn decode_arguments(
+        &self,
+        bytecode: &[u8],
+        input: &[u8],
+    ) -> Result<Vec<Token>, ConstructorArgumentsError> {
+        let arguments = input
+            .strip_prefix(bytecode)
+            .ok_or(ConstructorArgumentsError::BytecodeMismatch)?;
+        Ok(codec::decode(&self.inputs, arguments)?)
+    }
+}
+
+/// An error decoding the constructor arguments of a creation transaction.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConstructorArgumentsError {
+    /// The transaction input does not start with the creation bytecode.
+    #[error("transaction input does not start with the creation bytecode")]
+    BytecodeMismatch,
+
+    /// The creation bytecode is not valid hex, which is the case when it
+    /// has unlinked library placeholders.
+    #[error("invalid creation bytecode: {0}")]
+    InvalidBytecode(String),
+
+    /// The contract output lacks the ABI or the creation bytecode.
+    #[error("contract output has no `{0}`")]
+    MissingOutput(&'static str),
+
+    /// The arguments are not a valid encoding of the constructor inputs.
+    #[error(transparent)]
+    Decode(#[from] codec::DecodeError),
+}
+
+impl Event {
+    /// The canonical signature, such as `Transfer(address,address,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, self.inputs.iter().map(|p| p.canonical_type()))
+    }
+
+    /// The first topic of logs of this event: the Keccak-256 hash of its
+    /// [signature](Self::signature).
+    ///
+    /// Anonymous events do not store this topic, but it is still computed.
+    pub fn topic0(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+
+    /// Decode a log of this event into the name and value of each parameter,
+    /// in the order of the parameters.
+    ///
+    /// Indexed parameters are read from the topics, after the signature for
+    /// events that are not anonymous, and the others from the data. Indexed
+    /// parameters of dynamic or composite types are stored as their
+    /// [hash](EventParam::topic), which cannot be reversed, so their value is
+    /// the hash as a [`Token::FixedBytes`] of 32 bytes.
+    pub fn decode_log(
+        &self,
+        topics: &[[u8; 32]],
+        data: &[u8],
+    ) -> Result<Vec<(String, Token)>, LogError> {
+        let mut topics = topics.iter();
+        if !self.anonymous {
+            match topics.next() {
+                Some(topic0) if *topic0 == self.topic0() => {}
+                Some(_) => return Err(LogError::SignatureMismatch(self.name.clone())),
+                None => {
+                    return Err(LogError::TopicCount {
+                        expected: 1,
+                        found: 0,
+                    });
+                }
+            }
+        }
+        let indexed = self
+            .inputs
+            .iter()
+            .filter(|param| param.is_indexed())
+            .count();
+        if topics.len() != indexed {
+            let signature = usize::from(!self.anonymous);
+            return Err(LogError::TopicCount {
+                expected: indexed + signature,
+                found: topics.len() + signature,
+            });
+        }
+
+        let params: Vec<Param> = self
+            .inputs
+            .iter()
+            .filter(|param| !param.is_indexed())
+            .cloned()
+            .collect();
+        let mut values = codec::decode(&params, data)?.into_iter();
+        let mut decoded = Vec::with_capacity(self.inputs.len());
+        for param in &self.inputs {
+            let value = if !param.is_indexed() {
+                values.next().expect("one value per parameter")
+            } else {
+                let topic = topics.next().expect("one topic per indexed parameter");
+                if param.is_hashed() {
+                    Token::FixedBytes(topic.to_vec())
+                } else {
+                    let mut value = codec::decode(std::slice::from_ref(param), topic)?;
+                    value.pop().expect("one value per parameter")
+                }
+            };
+            decoded.push((param.name.clone(), value));
+        }
+        Ok(decoded)
+    }
+}
+
+/// An error decoding a log of an event.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LogError {
+    /// The log has a different number of topics than the event has indexed
+    /// parameters, plus one for the signature if it is not anonymous.
+    #[error("expected {expected} topics, got {found}")]
+    TopicCount { expected: usize, found: usize },
+
+    /// The first topic is not the signature of the event.
+    #[error("first topic is not the signature of event `{0}`")]
+    SignatureMismatch(String),
+
+    /// A topic or the data is not a valid encoding of the parameters.
+    #[error(transparent)]
+    Decode(#[from] codec::DecodeError),
+}
+
+impl Error {
+    /// The canonical signature, such as `InsufficientBalance(uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, self.inputs.iter().map(|p| p.canonical_type()))
+    }
+
+    /// The selector that revert data of this error starts with: the first
+    /// four bytes of the Keccak-256 hash of its [signature](Self::signature).
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+}
+
+impl Parameter {
+    /// Whether an indexed value of this parameter is stored as a hash, as
+    /// values of dynamic and composite types are.
+    fn is_hashed(&self) -> bool {
+        self.r#type == "string"
+            || self.r#type == "bytes"
+            || self.r#type == "tuple"
+            || strip_array_suffix(&self.r#type).is_some()
+    }
+
+    /// Compute the log topic that stores this parameter for a value.
+    ///
+    /// Indexed value types are stored as their 32-byte ABI encoding. Values
+    /// of dynamic or composite types do not fit in a topic, so the log stores
+    /// their Keccak-256 hash instead: of the contents for `string` and
+    /// `bytes`, and of the in-place encoding for arrays and structs, where
+    /// every element is padded to 32 bytes and arrays have no length. This is
+    /// the value to filter logs by, or to compare with a decoded topic.
+    pub fn topic(&self, value: &Token) -> Result<[u8; 32], TopicError> {
+        if !self.is_indexed() {
+            return Err(TopicError::NotIndexed(self.name.clone()));
+        }
+        if !value.matches_type(&self.r#type, self.components.as_deref()) {
+            return Err(TopicError::TypeMismatch {
+                param: self.name.clone(),
+                ty: self.r#type.clone(),
+            });
+        }
+        if !value.fits_type(&self.r#type, self.components.as_deref()) {
+            return Err(TopicError::OutOfRange {
+                param: self.name.clone(),
+                ty: self.r#type.clone(),
+            });
+        }
+
+        match value {
+            Token::String(string) => Ok(keccak256(string.as_bytes())),
+            Token::Bytes(bytes) => Ok(keccak256(bytes)),
+            Token::FixedArray(_) | Token::Array(_) | Token::Tuple(_) => {
+                let mut preimage = Vec::new();
+                value.encode_topic_preimage(&mut preimage);
+                Ok(keccak256(&preimage))
+            }
+            _ => {
+                let mut topic = Vec::with_capacity(32);
+                value.encode_topic_preimage(&mut topic);
+                Ok(topic.try_into().expect("value types encode to one word"))
+            }
+        }
+    }
+}
+
+/// An error computing the topic of an event parameter.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TopicError {
+    /// The parameter is not indexed, so it is stored in the log data.
+    #[error("event parameter `{0}` is not indexed")]
+    NotIndexed(String),
+
+    /// The value does not have the type of the parameter.
+    #[error("value does not match type `{ty}` of event parameter `{param}`")]
+    TypeMismatch { param: String, ty: String },
+
+    /// An integer in the value does not fit the width of its type, so the
+    /// contract could never emit it.
+    #[error("value does not fit type `{ty}` of event parameter `{param}`")]
+    OutOfRange { param: String, ty: String },
+}
+
+fn signature(name: &str, types: impl Iterator<Item = String>) -> String {
+    format!("{}({})", name, types.collect::<Vec<_>>().join(","))
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Parameters as in a declaration, such as `address to, uint256 amount`.
+fn display_params(params: &[Param]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| {
+            let ty = param.canonical_type();
+            match param.name.as_str() {
+                "" => ty,
+                name => format!("{} {}", ty, name),
+            }
+        })
+        .collect();
+    params.join(", ")
+}
+
+/// Hash data with Keccak-256, as used for selectors and event topics.
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Return the ABI coder v1 error for a parameter type, if any.
+fn abicoder_v1_error(item: &str, param: &str, ty: &str) -> Option<AbiCoderV1Error> {
+    if ty.starts_with("tuple") {
+        return Some(AbiCoderV1Error::Tuple {
+            item: item.to_string(),
+            param: param.to_string(),
+            ty: ty.to_string(),
+        });
+    }
+
+    let mut current = ty;
+    while let Some(element) = strip_array_suffix(current) {
+        if is_dynamic_type(element) {
+            return Some(AbiCoderV1Error::NestedDynamicArray {
+                item: item.to_string(),
+                param: param.to_string(),
+                ty: ty.to_string(),
+            });
+        }
+        current = element;
+    }
+    None
+}
+
+/// Strip the outermost array suffix from a type, returning the element type.
+fn strip_array_suffix(ty: &str) -> Option<&str> {
+    if !ty.ends_with(']') {
+        return None;
+    }
+    ty.rfind('[').map(|start| &ty[..start])
+}
+
+/// Whether a canonical type string is dynamically sized in the ABI encoding.
+///
+/// Tuples are treated as dynamic, since their components are not known from
+/// the type string alone.
+fn is_dynamic_type(ty: &str) -> bool {
+    ty == "string"
+        || ty == "bytes"
+        || ty.starts_with("tuple")
+        || ty.ends_with("[]")
+        || strip_array_suffix(ty).is_some_and(is_dynamic_type)
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -295,16 +1301,679 @@ mod tests {
             .into_iter()
             .filter_map(Result::ok)
             .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| entry.path().extension().map_or(false, |e| e == "json"))
+            .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
             .collect();
 
         entries.par_iter().for_each(|entry| {
             let content = fs::read_to_string(entry.path()).expect("Failed to read fixture file");
             let result: Result<Abi, serde_json::Error> = serde_json::from_str(&content);
-            if let Err(_) = result {
+            if result.is_err() {
                 let error_msg = find_deserialization_error(&content);
                 panic!("Failed to parse {:?}: {}", entry.path(), error_msg);
             }
         });
     }
+
+    fn param(name: &str, ty: &str) -> Param {
+        Param {
+            name: name.to_string(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: None,
+            internal_type: None,
+        }
+    }
+
+    #[test]
+    fn internal_type_parsing() {
+        let cases = [
+            ("uint256", InternalType::Elementary("uint256".into())),
+            (
+                "address payable",
+                InternalType::Elementary("address payable".into()),
+            ),
+            ("contract IERC20", InternalType::Contract("IERC20".into())),
+            (
+                "struct VmSafe.Log[]",
+                InternalType::Array {
+                    base: Box::new(InternalType::Struct {
+                        contract: Some("VmSafe".into()),
+                        name: "Log".into(),
+                    }),
+                    length: None,
+                },
+            ),
+            (
+                "enum AssetType",
+                InternalType::Enum {
+                    contract: None,
+                    name: "AssetType".into(),
+                },
+            ),
+            (
+                "MarketId",
+                InternalType::UserDefinedValueType {
+                    contract: None,
+                    name: "MarketId".into(),
+                },
+            ),
+            (
+                "string[2][]",
+                InternalType::Array {
+                    base: Box::new(InternalType::Array {
+                        base: Box::new(InternalType::Elementary("string".into())),
+                        length: Some(2),
+                    }),
+                    length: None,
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let parsed: InternalType = input.parse().unwrap();
+            assert_eq!(parsed, expected, "parsing {}", input);
+            assert_eq!(parsed.to_string(), input);
+        }
+
+        assert!("struct ".parse::<InternalType>().is_err());
+        assert!("uint256[x]".parse::<InternalType>().is_err());
+    }
+
+    #[test]
+    fn unknown_internal_types_deserialize() {
+        let json = r#"[{
+            "type": "function",
+            "name": "f",
+            "inputs": [
+                {"name": "a", "type": "uint256[]", "internalType": "uint256[N]"},
+                {"name": "b", "type": "address", "internalType": "contract IERC20"}
+            ],
+            "outputs": [],
+            "stateMutability": "view"
+        }]"#;
+        let abi: Abi = serde_json::from_str(json).unwrap();
+        let AbiItem::Function(function) = &abi.items[0] else {
+            panic!("expected a function");
+        };
+        assert_eq!(
+            function.inputs[0].internal_type,
+            Some(InternalType::Other("uint256[N]".to_string()))
+        );
+        assert_eq!(
+            function.inputs[1].internal_type,
+            Some(InternalType::Contract("IERC20".to_string()))
+        );
+        let reserialized = serde_json::to_value(&abi).unwrap();
+        assert_eq!(reserialized[0]["inputs"][0]["internalType"], "uint256[N]");
+    }
+
+    #[test]
+    fn abicoder_v1_accepts_static_and_flat_dynamic_types() {
+        let abi = Abi::from_items(vec![AbiItem::Function(Function {
+            name: "f".to_string(),
+            inputs: vec![
+                param("a", "uint256"),
+                param("b", "string"),
+                param("c", "uint256[]"),
+                param("d", "uint256[2][]"),
+            ],
+            outputs: vec![param("", "bytes")],
+            state_mutability: StateMutability::Nonpayable,
+        })]);
+        assert_eq!(abi.check_abicoder_v1(), Ok(()));
+    }
+
+    #[test]
+    fn abicoder_v1_rejects_tuples_and_nested_dynamic_arrays() {
+        let abi = Abi::from_items(vec![AbiItem::Function(Function {
+            name: "f".to_string(),
+            inputs: vec![
+                param("order", "tuple"),
+                param("names", "string[]"),
+                param("grid", "uint256[][]"),
+            ],
+            outputs: vec![],
+            state_mutability: StateMutability::View,
+        })]);
+        let errors = abi.check_abicoder_v1().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], AbiCoderV1Error::Tuple { param, .. } if param == "order"));
+        assert!(
+            matches!(&errors[1], AbiCoderV1Error::NestedDynamicArray { param, .. } if param == "names")
+        );
+        assert!(
+            matches!(&errors[2], AbiCoderV1Error::NestedDynamicArray { param, .. } if param == "grid")
+        );
+    }
+
+    #[test]
+    fn selectors_and_topics() {
+        let transfer = Function {
+            name: "transfer".to_string(),
+            inputs: vec![param("to", "address"), param("amount", "uint256")],
+            outputs: vec![param("", "bool")],
+            state_mutability: StateMutability::Nonpayable,
+        };
+        assert_eq!(transfer.signature(), "transfer(address,uint256)");
+        assert_eq!(hex::encode(transfer.selector()), "a9059cbb");
+        let mut to = [0u8; 20];
+        to[19] = 0x42;
+        assert_eq!(
+            hex::encode(
+                transfer
+                    .encode_call(&[Token::Address(to), Token::uint(1000)])
+                    .unwrap()
+            ),
+            concat!(
+                "a9059cbb",
+                "0000000000000000000000000000000000000000000000000000000000000042",
+                "00000000000000000000000000000000000000000000000000000000000003e8",
+            )
+        );
+
+        let submit = Function {
+            name: "submit".to_string(),
+            inputs: vec![Param {
+                components: Some(vec![
+                    Component {
+                        name: "to".to_string(),
+                        r#type: "address".to_string(),
+                        components: None,
+                        indexed: None,
+                        internal_type: None,
+                    },
+                    Component {
+                        name: "amount".to_string(),
+                        r#type: "uint256".to_string(),
+                        components: None,
+                        indexed: None,
+                        internal_type: None,
+                    },
+                ]),
+                ..param("orders", "tuple[]")
+            }],
+            outputs: Vec::new(),
+            state_mutability: StateMutability::Nonpayable,
+        };
+        assert_eq!(submit.signature(), "submit((address,uint256)[])");
+
+        let event_param = |name: &str, ty: &str, indexed: bool| EventParam {
+            name: name.to_string(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: Some(indexed),
+            internal_type: None,
+        };
+        let event = Event {
+            name: "Transfer".to_string(),
+            inputs: vec![
+                event_param("from", "address", true),
+                event_param("to", "address", true),
+                event_param("value", "uint256", false),
+            ],
+            anonymous: false,
+        };
+        assert_eq!(
+            hex::encode(event.topic0()),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+
+        let error = Error {
+            name: "Error".to_string(),
+            inputs: vec![param("message", "string")],
+        };
+        assert_eq!(hex::encode(error.selector()), "08c379a0");
+    }
+
+    #[test]
+    fn event_param_topics() {
+        let indexed = |name: &str, ty: &str| EventParam {
+            name: name.to_string(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: Some(true),
+            internal_type: None,
+        };
+
+        let mut address = [0u8; 20];
+        address[19] = 0x42;
+        let topic = indexed("from", "address")
+            .topic(&Token::Address(address))
+            .unwrap();
+        assert_eq!(topic[31], 0x42);
+        assert_eq!(topic[..31], [0u8; 31]);
+
+        let topic = indexed("value", "int8").topic(&Token::int(-1)).unwrap();
+        assert_eq!(topic, [0xff; 32]);
+
+        let out_of_range = |name: &str, ty: &str| {
+            Err(TopicError::OutOfRange {
+                param: name.to_string(),
+                ty: ty.to_string(),
+            })
+        };
+        let small = indexed("small", "uint8");
+        assert!(small.topic(&Token::uint(255)).is_ok());
+        assert_eq!(
+            small.topic(&Token::uint(256)),
+            out_of_range("small", "uint8")
+        );
+        let short = indexed("short", "int16");
+        assert!(short.topic(&Token::int(32767)).is_ok());
+        assert!(short.topic(&Token::int(-32768)).is_ok());
+        assert_eq!(
+            short.topic(&Token::int(32768)),
+            out_of_range("short", "int16")
+        );
+        assert_eq!(
+            short.topic(&Token::int(-32769)),
+            out_of_range("short", "int16")
+        );
+        assert!(
+            indexed("big", "uint256")
+                .topic(&Token::Uint([0xff; 32]))
+                .is_ok()
+        );
+        let items = indexed("items", "uint8[]");
+        assert_eq!(
+            items.topic(&Token::Array(vec![Token::uint(1), Token::uint(256)])),
+            out_of_range("items", "uint8[]")
+        );
+
+        let topic = indexed("callback", "function")
+            .topic(&Token::FixedBytes(vec![0x11; 24]))
+            .unwrap();
+        assert_eq!(topic[..24], [0x11; 24]);
+        assert_eq!(topic[24..], [0u8; 8]);
+
+        let topic = indexed("name", "string")
+            .topic(&Token::String("hello".to_string()))
+            .unwrap();
+        assert_eq!(
+            hex::encode(topic),
+            "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8"
+        );
+
+        let mut preimage = [0u8; 64];
+        preimage[31] = 1;
+        preimage[63] = 2;
+        let topic = indexed("ids", "uint256[]")
+            .topic(&Token::Array(vec![Token::uint(1), Token::uint(2)]))
+            .unwrap();
+        assert_eq!(topic, keccak256(&preimage));
+
+        let pair = EventParam {
+            components: Some(vec![
+                Component {
+                    name: "flag".to_string(),
+                    r#type: "bool".to_string(),
+                    components: None,
+                    indexed: None,
+                    internal_type: None,
+                },
+                Component {
+                    name: "data".to_string(),
+                    r#type: "bytes".to_string(),
+                    components: None,
+                    indexed: None,
+                    internal_type: None,
+                },
+            ]),
+            ..indexed("pair", "tuple")
+        };
+        let topic = pair
+            .topic(&Token::Tuple(vec![
+                Token::Bool(true),
+                Token::Bytes(vec![0xab]),
+            ]))
+            .unwrap();
+        let mut preimage = [0u8; 64];
+        preimage[31] = 1;
+        preimage[32] = 0xab;
+        assert_eq!(topic, keccak256(&preimage));
+
+        assert_eq!(
+            pair.topic(&Token::Tuple(vec![Token::Bool(true)])),
+            Err(TopicError::TypeMismatch {
+                param: "pair".to_string(),
+                ty: "tuple".to_string()
+            })
+        );
+        let data = EventParam {
+            indexed: Some(false),
+            ..indexed("amount", "uint256")
+        };
+        assert_eq!(
+            data.topic(&Token::uint(1)),
+            Err(TopicError::NotIndexed("amount".to_string()))
+        );
+    }
+
+    #[test]
+    fn mutability_lattice() {
+        use StateMutability::*;
+        let all = [Pure, View, Nonpayable, Payable];
+        assert!(View.allows(Pure) && !Pure.allows(View) && Payable.allows(Nonpayable));
+        assert_eq!(View.join(Nonpayable), Nonpayable);
+        assert_eq!(View.meet(Nonpayable), View);
+        assert!(!Pure.reads_state() && View.reads_state() && !View.modifies_state());
+
+        let overrides: Vec<(StateMutability, StateMutability)> = all
+            .iter()
+            .flat_map(|&base| all.iter().map(move |&derived| (base, derived)))
+            .filter(|&(base, derived)| StateMutability::is_compatible_override(base, derived))
+            .collect();
+        assert_eq!(
+            overrides,
+            [
+                (Pure, Pure),
+                (View, Pure),
+                (View, View),
+                (Nonpayable, Pure),
+                (Nonpayable, View),
+                (Nonpayable, Nonpayable),
+                (Payable, Payable),
+            ]
+        );
+        assert!(StateMutability::is_compatible_change(Nonpayable, Payable));
+        assert!(StateMutability::is_compatible_change(View, Pure));
+        assert!(!StateMutability::is_compatible_change(View, Nonpayable));
+        assert!(!StateMutability::is_compatible_change(Payable, Nonpayable));
+    }
+
+    #[test]
+    fn display_and_summary() {
+        let abi: Abi = serde_json::from_str(
+            r#"[
+                {"type": "constructor", "inputs": [{"name": "owner", "type": "address"}], "stateMutability": "nonpayable"},
+                {"type": "function", "name": "transfer", "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}], "outputs": [{"name": "", "type": "bool"}], "stateMutability": "nonpayable"},
+                {"type": "function", "name": "balanceOf", "inputs": [{"name": "account", "type": "address"}], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"},
+                {"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"},
+                {"type": "function", "name": "totalSupply", "inputs": [], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"},
+                {"type": "event", "name": "Transfer", "inputs": [{"name": "from", "type": "address", "indexed": true}, {"name": "value", "type": "uint256", "indexed": false}], "anonymous": false},
+                {"type": "error", "name": "Unauthorized", "inputs": [{"name": "caller", "type": "tuple", "components": [{"name": "account", "type": "address"}, {"name": "role", "type": "uint8"}]}]},
+                {"type": "receive", "stateMutability": "payable"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            abi.to_string(),
+            "constructor(address owner)\n\
+             receive() payable\n\
+             \n\
+             function balanceOf(address account) view returns (uint256)\n\
+             function totalSupply() view returns (uint256)\n\
+             \n\
+             function transfer(address to, uint256 amount) returns (bool)\n\
+             \n\
+             function deposit() payable\n\
+             \n\
+             event Transfer(address indexed from, uint256 value)\n\
+             \n\
+             error Unauthorized((address,uint8) caller)"
+        );
+        assert_eq!(Abi::new().to_string(), "");
+
+        let summary = abi.summary();
+        assert_eq!(
+            summary,
+            AbiSummary {
+                functions: 4,
+                events: 1,
+                errors: 1,
+                constructor: true,
+                receive: true,
+                fallback: false,
+            }
+        );
+        assert_eq!(
+            summary.to_string(),
+            "4 functions, 1 event, 1 error, constructor, receive"
+        );
+    }
+
+    #[test]
+    fn decode_reverts() {
+        let unauthorized = Error {
+            name: "Unauthorized".to_string(),
+            inputs: vec![param("caller", "address")],
+        };
+        let abi = Abi::from_items(vec![AbiItem::Error(unauthorized.clone())]);
+        let revert_data = |selector: [u8; 4], params: &[Param], values: &[Token]| {
+            let mut data = selector.to_vec();
+            data.extend(codec::encode(params, values).unwrap());
+            data
+        };
+
+        assert_eq!(abi.decode_revert(&[]), Ok(Revert::Empty));
+        let data = revert_data(
+            [0x08, 0xc3, 0x79, 0xa0],
+            &[param("", "string")],
+            &[Token::String("not owner".to_string())],
+        );
+        assert_eq!(
+            abi.decode_revert(&data),
+            Ok(Revert::Message("not owner".to_string()))
+        );
+        let data = revert_data(
+            [0x4e, 0x48, 0x7b, 0x71],
+            &[param("", "uint256")],
+            &[Token::uint(0x11)],
+        );
+        assert_eq!(abi.decode_revert(&data), Ok(Revert::Panic(0x11)));
+        let data = revert_data(
+            unauthorized.selector(),
+            &unauthorized.inputs,
+            &[Token::Address([0x42; 20])],
+        );
+        assert_eq!(
+            abi.decode_revert(&data),
+            Ok(Revert::Custom {
+                error: &unauthorized,
+                values: vec![Token::Address([0x42; 20])],
+            })
+        );
+
+        assert_eq!(abi.decode_revert(&[1, 2]), Err(RevertError::TooShort(2)));
+        assert_eq!(
+            abi.decode_revert(&[1, 2, 3, 4]),
+            Err(RevertError::UnknownSelector([1, 2, 3, 4]))
+        );
+        assert_eq!(
+            abi.decode_revert(&unauthorized.selector()),
+            Err(RevertError::Decode(codec::DecodeError::UnexpectedEnd {
+                offset: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn parameters() {
+        let json = r#"{
+            "name": "orders",
+            "type": "tuple[]",
+            "components": [
+                {"name": "maker", "type": "address", "internalType": "address"},
+                {
+                    "name": "fee",
+                    "type": "tuple",
+                    "components": [{"name": "bps", "type": "uint16"}],
+                    "internalType": "struct Fee"
+                }
+            ],
+            "internalType": "struct Order[]"
+        }"#;
+        let parameter: Parameter = serde_json::from_str(json).unwrap();
+        assert_eq!(parameter.indexed, None);
+        assert!(!parameter.is_indexed());
+        assert_eq!(parameter.canonical_type(), "(address,(uint16))[]");
+
+        let mut walked = Vec::new();
+        parameter.walk(&mut |parameter, depth| walked.push((parameter.name.clone(), depth)));
+        assert_eq!(
+            walked,
+            [
+                ("orders".to_string(), 0),
+                ("maker".to_string(), 1),
+                ("fee".to_string(), 1),
+                ("bps".to_string(), 2),
+            ]
+        );
+
+        let event_param = EventParam {
+            indexed: Some(true),
+            ..parameter.clone()
+        };
+        assert!(event_param.is_indexed());
+        let json = serde_json::to_value(&event_param).unwrap();
+        assert_eq!(json["indexed"], true);
+        assert_eq!(
+            serde_json::from_value::<EventParam>(json).unwrap(),
+            event_param
+        );
+        assert!(
+            serde_json::to_value(&parameter)
+                .unwrap()
+                .get("indexed")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn flatten_parameters() {
+        let parameter: Parameter = serde_json::from_str(
+            r#"{
+                "name": "order",
+                "type": "tuple",
+                "components": [
+                    {"name": "maker", "type": "address"},
+                    {
+                        "name": "items",
+                        "type": "tuple[2]",
+                        "components": [
+                            {"name": "price", "type": "uint256"},
+                            {"name": "", "type": "bytes32[]"}
+                        ]
+                    },
+                    {
+                        "name": "fees",
+                        "type": "tuple[]",
+                        "components": [{"name": "bps", "type": "uint16", "internalType": "uint16"}]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let leaves: Vec<(String, String)> = parameter
+            .flatten()
+            .into_iter()
+            .map(|leaf| (leaf.path, leaf.r#type))
+            .collect();
+        let leaf = |path: &str, ty: &str| (path.to_string(), ty.to_string());
+        assert_eq!(
+            leaves,
+            [
+                leaf("order.maker", "address"),
+                leaf("order.items[0].price", "uint256"),
+                leaf("order.items[0].1", "bytes32[]"),
+                leaf("order.items[1].price", "uint256"),
+                leaf("order.items[1].1", "bytes32[]"),
+                leaf("order.fees[].bps", "uint16"),
+            ]
+        );
+        assert_eq!(
+            parameter.flatten()[5].internal_type,
+            Some(InternalType::Elementary("uint16".to_string()))
+        );
+
+        let amount = param("amount", "uint256");
+        assert_eq!(
+            amount.flatten(),
+            [FlatParameter {
+                path: "amount".to_string(),
+                r#type: "uint256".to_string(),
+                internal_type: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn decode_logs() {
+        let event_param = |name: &str, ty: &str, indexed: bool| EventParam {
+            name: name.to_string(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: Some(indexed),
+            internal_type: None,
+        };
+        let transfer = Event {
+            name: "Transfer".to_string(),
+            inputs: vec![
+                event_param("from", "address", true),
+                event_param("value", "uint256", false),
+                event_param("to", "address", true),
+            ],
+            anonymous: false,
+        };
+        let from = [0x11; 20];
+        let to = [0x22; 20];
+        let address_topic = |address: [u8; 20]| {
+            let mut topic = [0u8; 32];
+            topic[12..].copy_from_slice(&address);
+            topic
+        };
+        let topics = [transfer.topic0(), address_topic(from), address_topic(to)];
+        let data = codec::encode(
+            &[event_param("value", "uint256", false)],
+            &[Token::uint(1000)],
+        )
+        .unwrap();
+        assert_eq!(
+            transfer.decode_log(&topics, &data).unwrap(),
+            [
+                ("from".to_string(), Token::Address(from)),
+                ("value".to_string(), Token::uint(1000)),
+                ("to".to_string(), Token::Address(to)),
+            ]
+        );
+        assert_eq!(
+            transfer.decode_log(&topics[..2], &data),
+            Err(LogError::TopicCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            transfer.decode_log(&[[0; 32], topics[1], topics[2]], &data),
+            Err(LogError::SignatureMismatch("Transfer".to_string()))
+        );
+
+        let note = Event {
+            name: "Note".to_string(),
+            inputs: vec![
+                event_param("tag", "string", true),
+                event_param("payload", "bytes", false),
+            ],
+            anonymous: true,
+        };
+        let tag = keccak256(b"hello");
+        let data = codec::encode(
+            &[event_param("payload", "bytes", false)],
+            &[Token::Bytes(vec![1, 2, 3])],
+        )
+        .unwrap();
+        assert_eq!(
+            note.decode_log(&[tag], &data).unwrap(),
+            [
+                ("tag".to_string(), Token::FixedBytes(tag.to_vec())),
+                ("payload".to_string(), Token::Bytes(vec![1, 2, 3])),
+            ]
+        );
+        assert_eq!(
+            note.decode_log(&[tag], &[]),
+            Err(LogError::Decode(codec::DecodeError::UnexpectedEnd {
+                offset: 0
+            }))
+        );
+    }
 }
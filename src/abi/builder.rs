@@ -0,0 +1,542 @@
+//! Programmatic construction of ABIs.
+//!
+//! An [`Abi`] is a plain list of items, so nothing stops it from holding two
+//! constructors, a `view` receive function, or a parameter of type `uint7`.
+//! An [`AbiBuilder`] checks each item as it is added and rejects those the
+//! compiler could not have produced, so that code generators and tests build
+//! only ABIs that describe a real contract.
+
+use std::collections::HashSet;
+
+use super::{
+    Abi, AbiItem, Component, Constructor, Error, Event, Fallback, Function, Receive,
+    StateMutability, strip_array_suffix,
+};
+
+/// An error adding an item to an [`AbiBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AbiBuilderError {
+    /// The name of an item or parameter is not a Solidity identifier.
+    #[error("`{0}` is not a valid name")]
+    InvalidName(String),
+
+    /// A parameter type is not a canonical ABI type, or a tuple has no
+    /// components, or a type that is not a tuple has some.
+    #[error("{item} has a parameter of invalid type `{ty}`")]
+    InvalidType { item: String, ty: String },
+
+    /// An item has the signature of an item already added, or is a second
+    /// constructor, receive, or fallback function.
+    #[error("{0} is already defined")]
+    Duplicate(String),
+
+    /// A special function has a mutability it cannot have, such as a
+    /// receive function that is not `payable`.
+    #[error("{item} cannot be {mutability}")]
+    InvalidMutability {
+        item: String,
+        mutability: StateMutability,
+    },
+
+    /// An event has more indexed parameters than fit in the topics of a log:
+    /// three, or four if the event is anonymous.
+    #[error("event {event} has {count} indexed parameters")]
+    TooManyIndexed { event: String, count: usize },
+}
+
+/// Builds an [`Abi`], checking each item as it is added.
+///
+/// ```
+/// use solc::abi::{Abi, Fallback, Function, Param, StateMutability};
+///
+/// let param = |name: &str, ty: &str| Param {
+///     name: name.to_string(),
+///     r#type: ty.to_string(),
+///     components: None,
+///     indexed: None,
+///     internal_type: None,
+/// };
+/// let abi = Abi::builder()
+///     .function(Function {
+///         name: "transfer".to_string(),
+///         inputs: vec![param("to", "address"), param("amount", "uint256")],
+///         outputs: vec![param("", "bool")],
+///         state_mutability: StateMutability::Nonpayable,
+///     })?
+///     .receive()?
+///     .fallback(Fallback::new())?
+///     .build();
+/// assert_eq!(abi.items.len(), 3);
+/// # Ok::<(), solc::abi::AbiBuilderError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AbiBuilder {
+    items: Vec<AbiItem>,
+    signatures: HashSet<String>,
+}
+
+impl Abi {
+    /// Start building an ABI.
+    pub fn builder() -> AbiBuilder {
+        AbiBuilder::new()
+    }
+}
+
+impl Receive {
+    /// A receive function, which is always `payable`.
+    pub fn new() -> Self {
+        Self {
+            state_mutability: StateMutability::Payable,
+        }
+    }
+}
+
+impl Default for Receive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fallback {
+    /// A fallback function that does not accept Ether.
+    pub fn new() -> Self {
+        Self {
+            state_mutability: StateMutability::Nonpayable,
+        }
+    }
+
+    /// A fallback function that accepts Ether.
+    pub fn payable() -> Self {
+        Self {
+            state_mutability: StateMutability::Payable,
+        }
+    }
+}
+
+impl Default for Fallback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AbiBuilder {
+    /// A builder without items.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a function. Overloads are allowed, but not two functions with
+    /// the same signature.
+    pub fn function(self, function: Function) -> Result<Self, AbiBuilderError> {
+        let label = format!("function {}", function.name);
+        check_name(&function.name)?;
+        check_params(
+            &label,
+            function
+                .inputs
+                .iter()
+                .chain(&function.outputs)
+                .map(|p| (p.name.as_str(), p.r#type.as_str(), p.components.as_deref())),
+        )?;
+        let signature = format!("function {}", function.signature());
+        self.push(signature, AbiItem::Function(function))
+    }
+
+    /// Add the constructor, which is `payable` or `nonpayable`.
+    pub fn constructor(self, constructor: Constructor) -> Result<Self, AbiBuilderError> {
+        check_payable_or_not("constructor", constructor.state_mutability)?;
+        check_params(
+            "constructor",
+            constructor
+                .inputs
+                .iter()
+                .map(|p| (p.name.as_str(), p.r#type.as_str(), p.components.as_deref())),
+        )?;
+        self.push("constructor".to_string(), AbiItem::Constructor(constructor))
+    }
+
+    /// Add the receive function.
+    pub fn receive(self) -> Result<Self, AbiBuilderError> {
+        self.push("receive".to_string(), AbiItem::Receive(Receive::new()))
+    }
+
+    /// Add the fallback function, which is `payable` or `nonpayable`.
+    pub fn fallback(self, fallback: Fallback) -> Result<Self, AbiBuilderError> {
+        check_payable_or_not("fallback", fallback.state_mutability)?;
+        self.push("fallback".to_string(), AbiItem::Fallback(fallback))
+    }
+
+    /// Add an event. An anonymous event and one that is not may share a
+    /// signature, since only the latter has it as a topic.
+    pub fn event(self, event: Event) -> Result<Self, AbiBuilderError> {
+        let label = format!("event {}", event.name);
+        check_name(&event.name)?;
+        check_params(
+            &label,
+            event
+                .inputs
+                .iter()
+                .map(|p| (p.name.as_str(), p.r#type.as_str(), p.components.as_deref())),
+        )?;
+        let count = event.inputs.iter().filter(|p| p.is_indexed()).count();
+        if count > if event.anonymous { 4 } else { 3 } {
+            return Err(AbiBuilderError::TooManyIndexed {
+                event: event.name,
+                count,
+            });
+        }
+        let signature = match event.anonymous {
+            true => format!("anonymous event {}", event.signature()),
+            false => format!("event {}", event.signature()),
+        };
+        self.push(signature, AbiItem::Event(event))
+    }
+
+    /// Add a custom error.
+    pub fn error(self, error: Error) -> Result<Self, AbiBuilderError> {
+        let label = format!("error {}", error.name);
+        check_name(&error.name)?;
+        check_params(
+            &label,
+            error
+                .inputs
+                .iter()
+                .map(|p| (p.name.as_str(), p.r#type.as_str(), p.components.as_deref())),
+        )?;
+        let signature = format!("error {}", error.signature());
+        self.push(signature, AbiItem::Error(error))
+    }
+
+    /// Add an item of any kind, with the checks of its kind.
+    pub fn item(self, item: AbiItem) -> Result<Self, AbiBuilderError> {
+        match item {
+            AbiItem::Function(function) => self.function(function),
+            AbiItem::Constructor(constructor) => self.constructor(constructor),
+            AbiItem::Receive(receive) => {
+                if receive.state_mutability != StateMutability::Payable {
+                    return Err(AbiBuilderError::InvalidMutability {
+                        item: "receive".to_string(),
+                        mutability: receive.state_mutability,
+                    });
+                }
+                self.receive()
+            }
+            AbiItem::Fallback(fallback) => self.fallback(fallback),
+            AbiItem::Event(event) => self.event(event),
+            AbiItem::Error(error) => self.error(error),
+        }
+    }
+
+    /// The ABI, with the items in the order they were added.
+    pub fn build(self) -> Abi {
+        Abi::from_items(self.items)
+    }
+
+    fn push(mut self, signature: String, item: AbiItem) -> Result<Self, AbiBuilderError> {
+        if self.signatures.contains(&signature) {
+            return Err(AbiBuilderError::Duplicate(signature));
+        }
+        self.signatures.insert(signature);
+        self.items.push(item);
+        Ok(self)
+    }
+}
+
+fn check_payable_or_not(item: &str, mutability: StateMutability) -> Result<(), AbiBuilderError> {
+    match mutability {
+        StateMutability::Payable | StateMutability::Nonpayable => Ok(()),
+        _ => Err(AbiBuilderError::InvalidMutability {
+            item: item.to_string(),
+            mutability,
+        }),
+    }
+}
+
+/// Check the names and types of parameters, given as name, type, and
+/// components. Parameters may be unnamed.
+fn check_params<'a>(
+    item: &str,
+    params: impl IntoIterator<Item = (&'a str, &'a str, Option<&'a [Component]>)>,
+) -> Result<(), AbiBuilderError> {
+    for (name, ty, components) in params {
+        check_param(item, name, ty, components)?;
+    }
+    Ok(())
+}
+
+/// Check the name and type of one parameter, and those of its components
+/// at every level.
+fn check_param(
+    item: &str,
+    name: &str,
+    ty: &str,
+    components: Option<&[Component]>,
+) -> Result<(), AbiBuilderError> {
+    if !name.is_empty() {
+        check_name(name)?;
+    }
+    if !is_valid_type(ty, components) {
+        return Err(AbiBuilderError::InvalidType {
+            item: item.to_string(),
+            ty: ty.to_string(),
+        });
+    }
+    for component in components.unwrap_or_default() {
+        check_param(
+            item,
+            &component.name,
+            &component.r#type,
+            component.components.as_deref(),
+        )?;
+    }
+    Ok(())
+}
+
+fn check_name(name: &str) -> Result<(), AbiBuilderError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        Ok(())
+    } else {
+        Err(AbiBuilderError::InvalidName(name.to_string()))
+    }
+}
+
+/// Whether a type is a canonical ABI type, with components exactly if it is
+/// a tuple or an array of tuples.
+fn is_valid_type(ty: &str, components: Option<&[Component]>) -> bool {
+    let mut base = ty;
+    while let Some(inner) = strip_array_suffix(base) {
+        let length = &base[inner.len() + 1..base.len() - 1];
+        if !length.is_empty() && !length.parse::<usize>().is_ok_and(|length| length > 0) {
+            return false;
+        }
+        base = inner;
+    }
+    match (base, components) {
+        ("tuple", Some(components)) => components
+            .iter()
+            .all(|component| is_valid_type(&component.r#type, component.components.as_deref())),
+        (_, Some(_)) => false,
+        (base, None) => is_elementary(base),
+    }
+}
+
+/// Whether a type is an elementary ABI type, with explicit sizes.
+fn is_elementary(ty: &str) -> bool {
+    let integer = |bits: &str| {
+        bits.parse::<usize>()
+            .is_ok_and(|bits| (8..=256).contains(&bits) && bits % 8 == 0)
+    };
+    let fixed = |suffix: &str| {
+        suffix.split_once('x').is_some_and(|(bits, decimals)| {
+            integer(bits)
+                && decimals
+                    .parse::<usize>()
+                    .is_ok_and(|decimals| decimals <= 80)
+        })
+    };
+    if matches!(ty, "address" | "bool" | "string" | "bytes" | "function") {
+        return true;
+    }
+    if let Some(size) = ty.strip_prefix("bytes") {
+        return size
+            .parse::<usize>()
+            .is_ok_and(|size| (1..=32).contains(&size));
+    }
+    if let Some(bits) = ty.strip_prefix("uint").or_else(|| ty.strip_prefix("int")) {
+        return integer(bits);
+    }
+    if let Some(suffix) = ty
+        .strip_prefix("ufixed")
+        .or_else(|| ty.strip_prefix("fixed"))
+    {
+        return fixed(suffix);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::{EventParam, Param};
+
+    fn param(name: &str, ty: &str) -> Param {
+        Param {
+            name: name.to_string(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: None,
+            internal_type: None,
+        }
+    }
+
+    fn function(name: &str, inputs: Vec<Param>) -> Function {
+        Function {
+            name: name.to_string(),
+            inputs,
+            outputs: Vec::new(),
+            state_mutability: StateMutability::Nonpayable,
+        }
+    }
+
+    #[test]
+    fn builds_abis() {
+        let pair = Param {
+            components: Some(vec![
+                Component {
+                    name: "token".to_string(),
+                    r#type: "address".to_string(),
+                    components: None,
+                    indexed: None,
+                    internal_type: None,
+                },
+                Component {
+                    name: "amounts".to_string(),
+                    r#type: "uint128[2]".to_string(),
+                    components: None,
+                    indexed: None,
+                    internal_type: None,
+                },
+            ]),
+            ..param("pairs", "tuple[]")
+        };
+        let indexed = |name: &str| EventParam {
+            name: name.to_string(),
+            r#type: "address".to_string(),
+            components: None,
+            indexed: Some(true),
+            internal_type: None,
+        };
+        let abi = Abi::builder()
+            .constructor(Constructor {
+                inputs: vec![param("owner", "address")],
+                state_mutability: StateMutability::Payable,
+            })
+            .unwrap()
+            .function(function("swap", vec![pair]))
+            .unwrap()
+            .function(function("swap", vec![param("amount", "uint256")]))
+            .unwrap()
+            .event(Event {
+                name: "Swap".to_string(),
+                inputs: vec![indexed("from"), indexed("to")],
+                anonymous: false,
+            })
+            .unwrap()
+            .item(AbiItem::Error(Error {
+                name: "Expired".to_string(),
+                inputs: vec![param("deadline", "uint64")],
+            }))
+            .unwrap()
+            .receive()
+            .unwrap()
+            .fallback(Fallback::payable())
+            .unwrap()
+            .build();
+        assert_eq!(
+            abi.summary().to_string(),
+            "2 functions, 1 event, 1 error, constructor, receive, fallback"
+        );
+        assert_eq!(
+            abi,
+            serde_json::from_value(serde_json::to_value(&abi).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_items() {
+        let builder = Abi::builder()
+            .function(function("f", vec![param("a", "uint256")]))
+            .unwrap();
+        assert_eq!(
+            builder
+                .clone()
+                .function(function("f", vec![param("b", "uint256")]))
+                .unwrap_err(),
+            AbiBuilderError::Duplicate("function f(uint256)".to_string())
+        );
+        for ty in [
+            "uint7",
+            "uint",
+            "bytes33",
+            "tuple",
+            "uint256[0]",
+            "fixed128x81",
+            "int256]",
+        ] {
+            assert_eq!(
+                builder
+                    .clone()
+                    .function(function("g", vec![param("a", ty)]))
+                    .unwrap_err(),
+                AbiBuilderError::InvalidType {
+                    item: "function g".to_string(),
+                    ty: ty.to_string()
+                },
+            );
+        }
+        assert_eq!(
+            builder
+                .clone()
+                .function(function("1f", Vec::new()))
+                .unwrap_err(),
+            AbiBuilderError::InvalidName("1f".to_string())
+        );
+        let nested = Param {
+            components: Some(vec![Component {
+                components: Some(vec![param("bad name", "uint256")]),
+                ..param("inner", "tuple")
+            }]),
+            ..param("outer", "tuple")
+        };
+        assert_eq!(
+            builder
+                .clone()
+                .function(function("g", vec![nested]))
+                .unwrap_err(),
+            AbiBuilderError::InvalidName("bad name".to_string())
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .item(AbiItem::Receive(Receive {
+                    state_mutability: StateMutability::View
+                }))
+                .unwrap_err(),
+            AbiBuilderError::InvalidMutability {
+                item: "receive".to_string(),
+                mutability: StateMutability::View
+            }
+        );
+        let builder = builder.receive().unwrap();
+        assert_eq!(
+            builder.clone().receive().unwrap_err(),
+            AbiBuilderError::Duplicate("receive".to_string())
+        );
+        let event = |anonymous| Event {
+            name: "E".to_string(),
+            inputs: (0..4)
+                .map(|i| EventParam {
+                    name: format!("p{}", i),
+                    r#type: "uint256".to_string(),
+                    components: None,
+                    indexed: Some(true),
+                    internal_type: None,
+                })
+                .collect(),
+            anonymous,
+        };
+        assert_eq!(
+            builder.clone().event(event(false)).unwrap_err(),
+            AbiBuilderError::TooManyIndexed {
+                event: "E".to_string(),
+                count: 4
+            }
+        );
+        assert!(builder.event(event(true)).is_ok());
+    }
+}
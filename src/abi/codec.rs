@@ -0,0 +1,680 @@
+//! ABI encoding and decoding of parameter values.
+//!
+//! Values are encoded as a head of 32-byte words, one or more per parameter,
+//! followed by a tail with the contents of dynamic values, which the head
+//! points to by offset. [`encode`] writes [`Token`]s in this encoding for the
+//! parameters they are values of, and [`decode`] reads such an encoding back
+//! into tokens.
+//!
+//! The encoding is described in the [Solidity Contract ABI Specification].
+//!
+//! [Solidity Contract ABI Specification]: https://docs.soliditylang.org/en/latest/abi-spec.html#formal-specification-of-the-encoding
+
+use super::token::pad_right;
+use super::{Component, Param, Token, strip_array_suffix};
+
+/// An error returned when data is not a valid encoding of the parameters.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    /// The data ends before a value that should be at `offset`.
+    #[error("data too short for a value at offset {offset}")]
+    UnexpectedEnd { offset: usize },
+
+    /// The offset or length at `offset` points outside the data.
+    #[error("offset or length at offset {offset} is out of range")]
+    OutOfRange { offset: usize },
+
+    /// A word is not a valid value of its type, such as a `bool` other than
+    /// 0 or 1, or an `address` with bits set above its 20 bytes.
+    #[error("invalid value for type `{0}`")]
+    InvalidValue(String),
+
+    /// A `string` is not valid UTF-8.
+    #[error("string is not valid UTF-8")]
+    InvalidUtf8,
+
+    /// The type is not a known ABI type.
+    #[error("unsupported type `{0}`")]
+    UnsupportedType(String),
+}
+
+/// An error returned when tokens are not values of the parameters.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EncodeError {
+    /// The number of tokens is not the number of parameters.
+    #[error("expected {expected} values, got {found}")]
+    Count { expected: usize, found: usize },
+
+    /// A token is not a value of its type, such as a [`Token::Bool`] for a
+    /// `uint256`, or a fixed-size array of the wrong length.
+    #[error("value does not match type `{0}`")]
+    TypeMismatch(String),
+
+    /// An integer does not fit in the width of its type.
+    #[error("invalid value for type `{0}`")]
+    InvalidValue(String),
+
+    /// The type is not a known ABI type.
+    #[error("unsupported type `{0}`")]
+    UnsupportedType(String),
+}
+
+/// Encode values of `params`, one token per parameter.
+///
+/// Integer tokens are checked against the width of their types, so that the
+/// encoding is one [`decode`] accepts.
+pub fn encode(params: &[Param], tokens: &[Token]) -> Result<Vec<u8>, EncodeError> {
+    if params.len() != tokens.len() {
+        return Err(EncodeError::Count {
+            expected: params.len(),
+            found: tokens.len(),
+        });
+    }
+    let types: Vec<Type> = params
+        .iter()
+        .map(|param| (param.r#type.as_str(), param.components.as_deref()))
+        .collect();
+    let mut out = Vec::new();
+    encode_sequence(&types, tokens, &mut out)?;
+    Ok(out)
+}
+
+/// Decode the ABI encoding of values of `params`.
+///
+/// Returns one token per parameter. Data after the encoding is ignored, as
+/// the EVM does for calldata.
+pub fn decode(params: &[Param], data: &[u8]) -> Result<Vec<Token>, DecodeError> {
+    let types: Vec<(&str, Option<&[Component]>)> = params
+        .iter()
+        .map(|param| (param.r#type.as_str(), param.components.as_deref()))
+        .collect();
+    decode_sequence(&types, data, 0)
+}
+
+/// A type to decode: its canonical name and its components, for tuples.
+type Type<'a> = (&'a str, Option<&'a [Component]>);
+
+/// Decode a sequence of values whose head starts at `base`.
+///
+/// Offsets of dynamic values are relative to `base`.
+fn decode_sequence(types: &[Type], data: &[u8], base: usize) -> Result<Vec<Token>, DecodeError> {
+    let mut tokens = Vec::with_capacity(types.len());
+    let mut head = base;
+    for &(ty, components) in types {
+        if is_dynamic(ty, components) {
+            let offset = read_usize(data, head)?;
+            let start = base
+                .checked_add(offset)
+                .ok_or(DecodeError::OutOfRange { offset: head })?;
+            tokens.push(decode_value(ty, components, data, start)?);
+            head += 32;
+        } else {
+            tokens.push(decode_value(ty, components, data, head)?);
+            head = head
+                .checked_add(static_size(ty, components)?)
+                .ok_or(DecodeError::OutOfRange { offset: head })?;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Decode one value whose encoding starts at `offset`.
+fn decode_value(
+    ty: &str,
+    components: Option<&[Component]>,
+    data: &[u8],
+    offset: usize,
+) -> Result<Token, DecodeError> {
+    if let Some(element) = strip_array_suffix(ty) {
+        let length = &ty[element.len() + 1..ty.len() - 1];
+        let (count, base) = if length.is_empty() {
+            (read_usize(data, offset)?, offset + 32)
+        } else {
+            let count = length
+                .parse()
+                .map_err(|_| DecodeError::UnsupportedType(ty.to_string()))?;
+            (count, offset)
+        };
+        // Every element takes at least one word, so a count larger than the
+        // data is invalid and must not be used to allocate.
+        if count > data.len() / 32 {
+            return Err(DecodeError::OutOfRange { offset });
+        }
+        let types = vec![(element, components); count];
+        let items = decode_sequence(&types, data, base)?;
+        return Ok(if length.is_empty() {
+            Token::Array(items)
+        } else {
+            Token::FixedArray(items)
+        });
+    }
+
+    if ty == "tuple" {
+        let components = components.ok_or_else(|| DecodeError::UnsupportedType(ty.to_string()))?;
+        let types: Vec<Type> = components
+            .iter()
+            .map(|component| (component.r#type.as_str(), component.components.as_deref()))
+            .collect();
+        return decode_sequence(&types, data, offset).map(Token::Tuple);
+    }
+
+    if ty == "bytes" || ty == "string" {
+        let length = read_usize(data, offset)?;
+        let start = offset + 32;
+        let bytes = start
+            .checked_add(length)
+            .and_then(|end| data.get(start..end))
+            .ok_or(DecodeError::OutOfRange { offset })?;
+        return if ty == "bytes" {
+            Ok(Token::Bytes(bytes.to_vec()))
+        } else {
+            String::from_utf8(bytes.to_vec())
+                .map(Token::String)
+                .map_err(|_| DecodeError::InvalidUtf8)
+        };
+    }
+
+    let word = *read_word(data, offset)?;
+    let invalid = || DecodeError::InvalidValue(ty.to_string());
+    match ty {
+        "address" => {
+            if word[..12].iter().any(|byte| *byte != 0) {
+                return Err(invalid());
+            }
+            Ok(Token::Address(word[12..].try_into().unwrap()))
+        }
+        "bool" => match (word[..31].iter().all(|byte| *byte == 0), word[31]) {
+            (true, 0) => Ok(Token::Bool(false)),
+            (true, 1) => Ok(Token::Bool(true)),
+            _ => Err(invalid()),
+        },
+        // An external function is an address followed by a selector.
+        "function" => {
+            if word[24..].iter().any(|byte| *byte != 0) {
+                return Err(invalid());
+            }
+            Ok(Token::FixedBytes(word[..24].to_vec()))
+        }
+        _ if ty.starts_with("uint") && bits(&ty[4..]).is_some() => {
+            let bits = bits(&ty[4..]).unwrap();
+            if !fits_unsigned(&word, bits) {
+                return Err(invalid());
+            }
+            Ok(Token::Uint(word))
+        }
+        _ if ty.starts_with("int") && bits(&ty[3..]).is_some() => {
+            let bits = bits(&ty[3..]).unwrap();
+            if !fits_signed(&word, bits) {
+                return Err(invalid());
+            }
+            Ok(Token::Int(word))
+        }
+        _ if ty.starts_with("bytes") => {
+            let size: usize = ty[5..]
+                .parse()
+                .ok()
+                .filter(|size| (1..=32).contains(size))
+                .ok_or_else(|| DecodeError::UnsupportedType(ty.to_string()))?;
+            if word[size..].iter().any(|byte| *byte != 0) {
+                return Err(invalid());
+            }
+            Ok(Token::FixedBytes(word[..size].to_vec()))
+        }
+        _ => Err(DecodeError::UnsupportedType(ty.to_string())),
+    }
+}
+
+/// Append the encoding of a sequence of values, heads first.
+///
+/// `tokens` must have one token per type. Offsets of dynamic values are
+/// relative to the start of the sequence.
+fn encode_sequence(types: &[Type], tokens: &[Token], out: &mut Vec<u8>) -> Result<(), EncodeError> {
+    let head_size = types.iter().try_fold(0usize, |size, &(ty, components)| {
+        let head = if is_dynamic(ty, components) {
+            Some(32)
+        } else {
+            static_size(ty, components).ok()
+        };
+        head.and_then(|head| size.checked_add(head))
+            .ok_or_else(|| EncodeError::UnsupportedType(ty.to_string()))
+    })?;
+    let mut tail = Vec::new();
+    for (&(ty, components), token) in types.iter().zip(tokens) {
+        if is_dynamic(ty, components) {
+            write_usize(head_size + tail.len(), out);
+            encode_value(ty, components, token, &mut tail)?;
+        } else {
+            encode_value(ty, components, token, out)?;
+        }
+    }
+    out.extend_from_slice(&tail);
+    Ok(())
+}
+
+/// Append the encoding of one value.
+fn encode_value(
+    ty: &str,
+    components: Option<&[Component]>,
+    token: &Token,
+    out: &mut Vec<u8>,
+) -> Result<(), EncodeError> {
+    let mismatch = || EncodeError::TypeMismatch(ty.to_string());
+
+    if let Some(element) = strip_array_suffix(ty) {
+        let length = &ty[element.len() + 1..ty.len() - 1];
+        let items = match (token, length) {
+            (Token::Array(items), "") => {
+                write_usize(items.len(), out);
+                items
+            }
+            (Token::FixedArray(items), length) if length.parse() == Ok(items.len()) => items,
+            _ => return Err(mismatch()),
+        };
+        let types = vec![(element, components); items.len()];
+        return encode_sequence(&types, items, out);
+    }
+
+    match (ty, token) {
+        ("tuple", Token::Tuple(items)) => {
+            let components =
+                components.ok_or_else(|| EncodeError::UnsupportedType(ty.to_string()))?;
+            if components.len() != items.len() {
+                return Err(mismatch());
+            }
+            let types: Vec<Type> = components
+                .iter()
+                .map(|component| (component.r#type.as_str(), component.components.as_deref()))
+                .collect();
+            encode_sequence(&types, items, out)
+        }
+        ("bytes", Token::Bytes(bytes)) => {
+            write_usize(bytes.len(), out);
+            pad_right(bytes, out);
+            Ok(())
+        }
+        ("string", Token::String(string)) => {
+            write_usize(string.len(), out);
+            pad_right(string.as_bytes(), out);
+            Ok(())
+        }
+        ("address", Token::Address(address)) => {
+            out.extend_from_slice(&[0u8; 12]);
+            out.extend_from_slice(address);
+            Ok(())
+        }
+        ("bool", Token::Bool(value)) => {
+            write_usize(usize::from(*value), out);
+            Ok(())
+        }
+        // An external function is an address followed by a selector.
+        ("function", Token::FixedBytes(bytes)) if bytes.len() == 24 => {
+            pad_right(bytes, out);
+            Ok(())
+        }
+        (_, Token::Uint(word)) if ty.starts_with("uint") && bits(&ty[4..]).is_some() => {
+            if !fits_unsigned(word, bits(&ty[4..]).unwrap()) {
+                return Err(EncodeError::InvalidValue(ty.to_string()));
+            }
+            out.extend_from_slice(word);
+            Ok(())
+        }
+        (_, Token::Int(word)) if ty.starts_with("int") && bits(&ty[3..]).is_some() => {
+            if !fits_signed(word, bits(&ty[3..]).unwrap()) {
+                return Err(EncodeError::InvalidValue(ty.to_string()));
+            }
+            out.extend_from_slice(word);
+            Ok(())
+        }
+        (_, Token::FixedBytes(bytes))
+            if ty.strip_prefix("bytes").and_then(|size| size.parse().ok()) == Some(bytes.len())
+                && (1..=32).contains(&bytes.len()) =>
+        {
+            pad_right(bytes, out);
+            Ok(())
+        }
+        _ if token.matches_type(ty, components) => {
+            Err(EncodeError::UnsupportedType(ty.to_string()))
+        }
+        _ => Err(mismatch()),
+    }
+}
+
+/// Append a word holding an offset or a length.
+fn write_usize(value: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&[0u8; 24]);
+    out.extend_from_slice(&(value as u64).to_be_bytes());
+}
+
+/// The width of an integer type from its suffix, such as 256 for `uint256`.
+///
+/// An empty suffix, as in `uint`, is 256 bits.
+pub(super) fn bits(suffix: &str) -> Option<usize> {
+    if suffix.is_empty() {
+        return Some(256);
+    }
+    suffix
+        .parse()
+        .ok()
+        .filter(|bits| (8..=256).contains(bits) && bits % 8 == 0)
+}
+
+/// Whether a word holds an unsigned integer of `bits` bits.
+pub(super) fn fits_unsigned(word: &[u8; 32], bits: usize) -> bool {
+    word[..32 - bits / 8].iter().all(|byte| *byte == 0)
+}
+
+/// Whether a word holds a signed integer of `bits` bits, sign-extended.
+pub(super) fn fits_signed(word: &[u8; 32], bits: usize) -> bool {
+    let start = 32 - bits / 8;
+    let extension = if word[start] & 0x80 == 0 { 0 } else { 0xff };
+    word[..start].iter().all(|byte| *byte == extension)
+}
+
+/// Whether a type is encoded in the tail, with an offset in the head.
+fn is_dynamic(ty: &str, components: Option<&[Component]>) -> bool {
+    if ty == "bytes" || ty == "string" || ty.ends_with("[]") {
+        return true;
+    }
+    if let Some(element) = strip_array_suffix(ty) {
+        return is_dynamic(element, components);
+    }
+    ty == "tuple"
+        && components.is_some_and(|components| {
+            components
+                .iter()
+                .any(|component| is_dynamic(&component.r#type, component.components.as_deref()))
+        })
+}
+
+/// The size of the in-place encoding of a static type.
+///
+/// Fixed-size arrays too large for their size to fit in a `usize` are
+/// unsupported.
+fn static_size(ty: &str, components: Option<&[Component]>) -> Result<usize, DecodeError> {
+    if let Some(element) = strip_array_suffix(ty) {
+        let count: usize = ty[element.len() + 1..ty.len() - 1]
+            .parse()
+            .map_err(|_| DecodeError::UnsupportedType(ty.to_string()))?;
+        return count
+            .checked_mul(static_size(element, components)?)
+            .ok_or_else(|| DecodeError::UnsupportedType(ty.to_string()));
+    }
+    if ty == "tuple" {
+        let components = components.ok_or_else(|| DecodeError::UnsupportedType(ty.to_string()))?;
+        return components.iter().try_fold(0usize, |size, component| {
+            size.checked_add(static_size(
+                &component.r#type,
+                component.components.as_deref(),
+            )?)
+            .ok_or_else(|| DecodeError::UnsupportedType(ty.to_string()))
+        });
+    }
+    Ok(32)
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<&[u8; 32], DecodeError> {
+    offset
+        .checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .map(|word| word.try_into().unwrap())
+        .ok_or(DecodeError::UnexpectedEnd { offset })
+}
+
+/// Read a word holding an offset or a length.
+fn read_usize(data: &[u8], offset: usize) -> Result<usize, DecodeError> {
+    let word = *read_word(data, offset)?;
+    let (high, low) = word.split_at(24);
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(DecodeError::OutOfRange { offset });
+    }
+    let value = u64::from_be_bytes(low.try_into().unwrap());
+    usize::try_from(value)
+        .ok()
+        .filter(|value| *value <= data.len())
+        .ok_or(DecodeError::OutOfRange { offset })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(ty: &str, components: Option<Vec<Component>>) -> Param {
+        Param {
+            name: String::new(),
+            r#type: ty.to_string(),
+            components,
+            indexed: None,
+            internal_type: None,
+        }
+    }
+
+    fn component(ty: &str) -> Component {
+        Component {
+            name: String::new(),
+            r#type: ty.to_string(),
+            components: None,
+            indexed: None,
+            internal_type: None,
+        }
+    }
+
+    fn words(words: &[&str]) -> Vec<u8> {
+        words
+            .iter()
+            .flat_map(|word| hex::decode(format!("{:0>64}", word)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn decode_static_values() {
+        let params = [
+            param("address", None),
+            param("uint256", None),
+            param("int8", None),
+            param("bool", None),
+            param("bytes2", None),
+        ];
+        let data = words(&[
+            "00000000000000000000000000000000000000000000000000000000000000ff",
+            "2a",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "1",
+            "abcd000000000000000000000000000000000000000000000000000000000000",
+        ]);
+        let mut address = [0u8; 20];
+        address[19] = 0xff;
+        assert_eq!(
+            decode(&params, &data).unwrap(),
+            [
+                Token::Address(address),
+                Token::uint(42),
+                Token::int(-1),
+                Token::Bool(true),
+                Token::FixedBytes(vec![0xab, 0xcd]),
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_dynamic_values() {
+        // f(uint256[], (string, uint8)[1], bytes)
+        let params = [
+            param("uint256[]", None),
+            param(
+                "tuple[1]",
+                Some(vec![component("string"), component("uint8")]),
+            ),
+            param("bytes", None),
+        ];
+        let data = words(&[
+            "60",  // offset of the uint256[]
+            "c0",  // offset of the tuple[1]
+            "160", // offset of the bytes
+            "2",
+            "1",
+            "2",  // [1, 2]
+            "20", // offset of the first tuple in the array
+            "40",
+            "7", // ("hi", 7)
+            "2",
+            "6869000000000000000000000000000000000000000000000000000000000000",
+            "1",
+            "ff00000000000000000000000000000000000000000000000000000000000000",
+        ]);
+        assert_eq!(
+            decode(&params, &data).unwrap(),
+            [
+                Token::Array(vec![Token::uint(1), Token::uint(2)]),
+                Token::FixedArray(vec![Token::Tuple(vec![
+                    Token::String("hi".to_string()),
+                    Token::uint(7),
+                ])]),
+                Token::Bytes(vec![0xff]),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_round_trips() {
+        let params = [
+            param("uint256[]", None),
+            param(
+                "tuple[1]",
+                Some(vec![component("string"), component("uint8")]),
+            ),
+            param("bytes", None),
+            param("int16", None),
+            param(
+                "tuple",
+                Some(vec![component("address"), component("bytes4[2]")]),
+            ),
+        ];
+        let tokens = vec![
+            Token::Array(vec![Token::uint(1), Token::uint(2)]),
+            Token::FixedArray(vec![Token::Tuple(vec![
+                Token::String("a string longer than thirty-two bytes".to_string()),
+                Token::uint(7),
+            ])]),
+            Token::Bytes(vec![0xff; 33]),
+            Token::int(-300),
+            Token::Tuple(vec![
+                Token::Address([0x11; 20]),
+                Token::FixedArray(vec![
+                    Token::FixedBytes(vec![1, 2, 3, 4]),
+                    Token::FixedBytes(vec![5, 6, 7, 8]),
+                ]),
+            ]),
+        ];
+        let data = encode(&params, &tokens).unwrap();
+        assert_eq!(decode(&params, &data).unwrap(), tokens);
+
+        // The encoding in `decode_dynamic_values`.
+        let data = encode(
+            &params[..3],
+            &[
+                Token::Array(vec![Token::uint(1), Token::uint(2)]),
+                Token::FixedArray(vec![Token::Tuple(vec![
+                    Token::String("hi".to_string()),
+                    Token::uint(7),
+                ])]),
+                Token::Bytes(vec![0xff]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            words(&[
+                "60",
+                "c0",
+                "160",
+                "2",
+                "1",
+                "2",
+                "20",
+                "40",
+                "7",
+                "2",
+                "6869000000000000000000000000000000000000000000000000000000000000",
+                "1",
+                "ff00000000000000000000000000000000000000000000000000000000000000",
+            ])
+        );
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        assert_eq!(
+            encode(&[param("uint256", None)], &[]),
+            Err(EncodeError::Count {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            encode(&[param("uint256", None)], &[Token::Bool(true)]),
+            Err(EncodeError::TypeMismatch("uint256".to_string()))
+        );
+        assert_eq!(
+            encode(
+                &[param("uint8[2]", None)],
+                &[Token::FixedArray(vec![Token::uint(1)])]
+            ),
+            Err(EncodeError::TypeMismatch("uint8[2]".to_string()))
+        );
+        assert_eq!(
+            encode(&[param("uint8", None)], &[Token::uint(256)]),
+            Err(EncodeError::InvalidValue("uint8".to_string()))
+        );
+        assert_eq!(
+            encode(&[param("int8", None)], &[Token::int(-129)]),
+            Err(EncodeError::InvalidValue("int8".to_string()))
+        );
+        assert_eq!(
+            encode(&[param("uint7", None)], &[Token::uint(1)]),
+            Err(EncodeError::UnsupportedType("uint7".to_string()))
+        );
+
+        let huge = "uint256[1152921504606846976][1152921504606846976]";
+        assert_eq!(
+            encode(&[param(huge, None)], &[Token::FixedArray(vec![])]),
+            Err(EncodeError::UnsupportedType(huge.to_string()))
+        );
+        let tuple = param("tuple", Some(vec![component(huge)]));
+        assert_eq!(
+            encode(&[tuple], &[Token::Tuple(vec![])]),
+            Err(EncodeError::UnsupportedType("tuple".to_string()))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_encodings() {
+        assert_eq!(
+            decode(&[param("uint256", None)], &[0; 31]),
+            Err(DecodeError::UnexpectedEnd { offset: 0 })
+        );
+        assert_eq!(
+            decode(&[param("bool", None)], &words(&["2"])),
+            Err(DecodeError::InvalidValue("bool".to_string()))
+        );
+        assert_eq!(
+            decode(&[param("uint8", None)], &words(&["100"])),
+            Err(DecodeError::InvalidValue("uint8".to_string()))
+        );
+        assert_eq!(
+            decode(&[param("bytes", None)], &words(&["20", "ffff"])),
+            Err(DecodeError::OutOfRange { offset: 32 })
+        );
+        assert_eq!(
+            decode(&[param("bytes", None)], &words(&["20", "2"])),
+            Err(DecodeError::OutOfRange { offset: 32 })
+        );
+        assert_eq!(
+            decode(&[param("function", None)], &words(&["1"])),
+            Err(DecodeError::InvalidValue("function".to_string()))
+        );
+        assert_eq!(
+            decode(&[param("fixed128x18", None)], &words(&["0"])),
+            Err(DecodeError::UnsupportedType("fixed128x18".to_string()))
+        );
+    }
+}
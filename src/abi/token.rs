@@ -0,0 +1,162 @@
+//! Concrete values of ABI types.
+
+use super::Component;
+use super::codec::{bits, fits_signed, fits_unsigned};
+
+/// A value of an ABI type.
+///
+/// Integers are kept as 32-byte big-endian words, the way the ABI encodes
+/// them, so values of any width up to 256 bits can be represented. Signed
+/// integers use two's complement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// An `address`.
+    Address([u8; 20]),
+    /// A `bytes1` to `bytes32` value.
+    FixedBytes(Vec<u8>),
+    /// A dynamic `bytes` value.
+    Bytes(Vec<u8>),
+    /// A signed integer as a two's complement word.
+    Int([u8; 32]),
+    /// An unsigned integer as a word.
+    Uint([u8; 32]),
+    /// A `bool`.
+    Bool(bool),
+    /// A `string`.
+    String(String),
+    /// A fixed-size array `T[k]`.
+    FixedArray(Vec<Token>),
+    /// A dynamic array `T[]`.
+    Array(Vec<Token>),
+    /// A tuple, the ABI type of a struct.
+    Tuple(Vec<Token>),
+}
+
+impl Token {
+    /// An unsigned integer value.
+    pub fn uint(value: u128) -> Self {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        Token::Uint(word)
+    }
+
+    /// A signed integer value, sign-extended to a word.
+    pub fn int(value: i128) -> Self {
+        let mut word = if value < 0 { [0xff; 32] } else { [0u8; 32] };
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        Token::Int(word)
+    }
+
+    /// Whether this value has the canonical ABI type `ty`.
+    ///
+    /// `components` are the tuple components of `ty`, if it is a tuple type.
+    pub(crate) fn matches_type(&self, ty: &str, components: Option<&[Component]>) -> bool {
+        if let Some(element) = super::strip_array_suffix(ty) {
+            let length = &ty[element.len() + 1..ty.len() - 1];
+            let items = match (self, length) {
+                (Token::Array(items), "") => items,
+                (Token::FixedArray(items), length) => {
+                    if length.parse() != Ok(items.len()) {
+                        return false;
+                    }
+                    items
+                }
+                _ => return false,
+            };
+            return items
+                .iter()
+                .all(|item| item.matches_type(element, components));
+        }
+
+        match self {
+            Token::Address(_) => ty == "address",
+            // An external function is an address followed by a selector.
+            Token::FixedBytes(bytes) if ty == "function" => bytes.len() == 24,
+            Token::FixedBytes(bytes) => ty
+                .strip_prefix("bytes")
+                .and_then(|size| size.parse::<usize>().ok())
+                .is_some_and(|size| size == bytes.len() && (1..=32).contains(&size)),
+            Token::Bytes(_) => ty == "bytes",
+            Token::Int(_) => ty.starts_with("int"),
+            Token::Uint(_) => ty.starts_with("uint"),
+            Token::Bool(_) => ty == "bool",
+            Token::String(_) => ty == "string",
+            Token::Tuple(items) => {
+                ty == "tuple"
+                    && components.is_some_and(|components| {
+                        components.len() == items.len()
+                            && items.iter().zip(components).all(|(item, component)| {
+                                item.matches_type(
+                                    &component.r#type,
+                                    component.components.as_deref(),
+                                )
+                            })
+                    })
+            }
+            Token::FixedArray(_) | Token::Array(_) => false,
+        }
+    }
+
+    /// Whether the integers in this value fit the widths of their types,
+    /// which [`matches_type`](Self::matches_type) does not check.
+    ///
+    /// The value must have the type `ty`.
+    pub(crate) fn fits_type(&self, ty: &str, components: Option<&[Component]>) -> bool {
+        if let Some(element) = super::strip_array_suffix(ty) {
+            return match self {
+                Token::Array(items) | Token::FixedArray(items) => {
+                    items.iter().all(|item| item.fits_type(element, components))
+                }
+                _ => false,
+            };
+        }
+
+        match self {
+            Token::Int(word) => ty
+                .strip_prefix("int")
+                .and_then(bits)
+                .is_some_and(|bits| fits_signed(word, bits)),
+            Token::Uint(word) => ty
+                .strip_prefix("uint")
+                .and_then(bits)
+                .is_some_and(|bits| fits_unsigned(word, bits)),
+            Token::Tuple(items) => components.is_some_and(|components| {
+                items.iter().zip(components).all(|(item, component)| {
+                    item.fits_type(&component.r#type, component.components.as_deref())
+                })
+            }),
+            _ => true,
+        }
+    }
+
+    /// Append the in-place encoding used for indexed event parameters.
+    ///
+    /// Every value is padded to a multiple of 32 bytes, including `bytes`
+    /// and `string`, and arrays have no length prefix.
+    pub(crate) fn encode_topic_preimage(&self, out: &mut Vec<u8>) {
+        match self {
+            Token::Address(address) => {
+                out.extend_from_slice(&[0u8; 12]);
+                out.extend_from_slice(address);
+            }
+            Token::FixedBytes(bytes) | Token::Bytes(bytes) => pad_right(bytes, out),
+            Token::String(string) => pad_right(string.as_bytes(), out),
+            Token::Int(word) | Token::Uint(word) => out.extend_from_slice(word),
+            Token::Bool(value) => {
+                out.extend_from_slice(&[0u8; 31]);
+                out.push(u8::from(*value));
+            }
+            Token::FixedArray(items) | Token::Array(items) | Token::Tuple(items) => {
+                for item in items {
+                    item.encode_topic_preimage(out);
+                }
+            }
+        }
+    }
+}
+
+/// Append bytes, padded with zeros on the right to a multiple of 32 bytes.
+pub(super) fn pad_right(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(bytes);
+    out.resize(out.len() + (32 - bytes.len() % 32) % 32, 0);
+}
@@ -0,0 +1,208 @@
+//! EVM assembly output types.
+//!
+//! The compiler reports the assembly of a contract as `evm.assembly`, a
+//! listing meant to be read, and as `evm.legacyAssembly`, the same assembly
+//! as JSON. The JSON form is an [`Assembly`]: the items of the creation code
+//! under `.code`, and under `.data` the assemblies nested in it, such as the
+//! runtime code, along with data stored in the bytecode.
+//!
+//! ```json
+//! {
+//!   ".code": [{"begin": 0, "end": 120, "name": "PUSH", "source": 0, "value": "80"}],
+//!   ".data": {"0": {".auxdata": "a264…", ".code": []}},
+//!   "sourceList": ["A.sol"]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::SourceLocation;
+use crate::opcodes::Opcode;
+
+/// The assembly of a contract, or of code nested in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Assembly {
+    /// The items of the code, in order.
+    #[serde(rename = ".code", default)]
+    pub code: Vec<AssemblyItem>,
+    /// Nested assemblies and data, keyed by the index or hash the code
+    /// refers to them by.
+    #[serde(rename = ".data", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, AssemblyData>,
+    /// The CBOR-encoded metadata appended to the runtime code, as hex.
+    #[serde(rename = ".auxdata", skip_serializing_if = "Option::is_none")]
+    pub auxdata: Option<String>,
+    /// The names of the sources that [`AssemblyItem::source`] indexes, only
+    /// given for the outermost assembly.
+    #[serde(
+        rename = "sourceList",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub source_list: Option<Vec<String>>,
+}
+
+impl Assembly {
+    /// The assembly of the runtime code, which the creation code of a
+    /// contract holds as its first nested assembly.
+    pub fn runtime(&self) -> Option<&Assembly> {
+        match self.data.get("0")? {
+            AssemblyData::Assembly(assembly) => Some(assembly),
+            AssemblyData::Hex(_) => None,
+        }
+    }
+
+    /// The assemblies nested in this one, such as the runtime code and the
+    /// code of contracts it creates, without those nested in them.
+    pub fn sub_assemblies(&self) -> impl Iterator<Item = (&str, &Assembly)> {
+        self.data.iter().filter_map(|(key, data)| match data {
+            AssemblyData::Assembly(assembly) => Some((key.as_str(), &**assembly)),
+            AssemblyData::Hex(_) => None,
+        })
+    }
+}
+
+/// An entry of the `.data` section of an [`Assembly`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AssemblyData {
+    /// Nested code, such as the runtime code or that of a created contract.
+    Assembly(Box<Assembly>),
+    /// Data stored in the bytecode, as hex.
+    Hex(String),
+}
+
+/// An item of assembly code: an instruction, a push of a value the final
+/// layout decides, or a jump destination.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssemblyItem {
+    /// The instruction, such as `MSTORE`, or the kind of item, such as
+    /// `PUSH`, `PUSH [tag]`, `PUSH [$]`, or `tag`.
+    pub name: String,
+    /// The byte offset in the source where the code of the item starts.
+    pub begin: i64,
+    /// The byte offset in the source where the code of the item ends.
+    pub end: i64,
+    /// The index of the source in the source list, or `-1` for none.
+    #[serde(default = "no_source", skip_serializing_if = "is_no_source")]
+    pub source: i64,
+    /// The argument of the item, such as the value pushed or the number
+    /// of a tag, as hex or decimal depending on the kind of item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Whether a jump enters or leaves a function.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jump_type: Option<JumpType>,
+    /// The depth of modifiers the code of the item is in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modifier_depth: Option<u64>,
+}
+
+fn no_source() -> i64 {
+    -1
+}
+
+fn is_no_source(source: &i64) -> bool {
+    *source == -1
+}
+
+impl AssemblyItem {
+    /// Whether the item is a jump destination.
+    pub fn is_tag(&self) -> bool {
+        self.name == "tag"
+    }
+
+    /// The opcode of an item that is a plain instruction, such as `MSTORE`.
+    ///
+    /// Pushes and tags have no opcode, since the size of what they push is
+    /// only known once the code is laid out.
+    pub fn opcode(&self) -> Option<Opcode> {
+        if self.name.starts_with("PUSH") && self.value.is_some() {
+            return None;
+        }
+        Opcode::from_name(&self.name)
+    }
+
+    /// The source range of the item, or `None` if the compiler gave it no
+    /// source.
+    pub fn location(&self) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            offset: self.begin.try_into().ok()?,
+            length: (self.end - self.begin).try_into().ok()?,
+            source_index: self.source.try_into().ok()?,
+        })
+    }
+}
+
+/// Whether a jump enters or leaves a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JumpType {
+    /// A jump into a function.
+    #[serde(rename = "[in]")]
+    In,
+    /// A jump out of a function.
+    #[serde(rename = "[out]")]
+    Out,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_assembly() {
+        let json = r##"{
+            ".code": [
+                {"begin": 60, "end": 200, "name": "PUSH", "source": 0, "value": "80"},
+                {"begin": 60, "end": 200, "name": "MSTORE", "source": 0},
+                {"begin": 60, "end": 200, "name": "PUSH [$]", "source": 0, "value": "0000000000000000000000000000000000000000000000000000000000000000"},
+                {"begin": 60, "end": 200, "name": "CODECOPY", "source": 0},
+                {"begin": -1, "end": -1, "name": "RETURN"}
+            ],
+            ".data": {
+                "0": {
+                    ".auxdata": "a2646970667358221220",
+                    ".code": [
+                        {"begin": 60, "end": 200, "name": "tag", "source": 0, "value": "1"},
+                        {"begin": 60, "end": 200, "name": "JUMPDEST", "source": 0},
+                        {"begin": 100, "end": 120, "name": "JUMP", "source": 0, "jumpType": "[in]", "modifierDepth": 1}
+                    ]
+                },
+                "8d8a5f2b": "48656c6c6f"
+            },
+            "sourceList": ["A.sol", "#utility.yul"]
+        }"##;
+
+        let assembly: Assembly = serde_json::from_str(json).unwrap();
+        assert_eq!(assembly.source_list.as_ref().unwrap()[0], "A.sol");
+        assert_eq!(assembly.code[0].opcode(), None);
+        assert_eq!(assembly.code[1].opcode(), Opcode::from_name("MSTORE"));
+        assert_eq!(
+            assembly.code[1].location(),
+            Some(SourceLocation {
+                offset: 60,
+                length: 140,
+                source_index: 0
+            })
+        );
+        assert_eq!(assembly.code[4].source, -1);
+        assert_eq!(assembly.code[4].location(), None);
+
+        let runtime = assembly.runtime().unwrap();
+        assert_eq!(runtime.auxdata.as_deref(), Some("a2646970667358221220"));
+        assert!(runtime.code[0].is_tag());
+        assert_eq!(runtime.code[2].jump_type, Some(JumpType::In));
+        assert_eq!(runtime.code[2].modifier_depth, Some(1));
+        assert_eq!(assembly.sub_assemblies().count(), 1);
+        assert_eq!(
+            assembly.data["8d8a5f2b"],
+            AssemblyData::Hex("48656c6c6f".to_string())
+        );
+
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_value(&assembly).unwrap(), value);
+    }
+}
@@ -5,10 +5,57 @@
 //! to a Solidity language construct.
 
 use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+pub use completeness::{CompletenessIssue, CompletenessReport, check_completeness};
+pub use coverage::{CoverageError, CoverageReport};
+pub use file_level::{FileLevelUsage, file_level_usage};
+pub use index::{AstIndex, Declaration};
+pub use inheritdoc::inherited_documentation;
+pub use legacy::{from_legacy, parse_legacy};
+pub use lenient::{LenientParse, NodeError, parse_lenient};
+pub use lines::{IndexedSource, LineColumn, SourceIndexer};
+pub use locations::{LocatedNode, LocationIndex};
+pub use navigation::{Hover, Navigator};
+pub use node::Node;
+pub use normalize::normalize;
+pub use overrides::{
+    OverriddenDeclaration, OverrideIssue, OverrideReport, ResolvedOverride, resolve_overrides,
+};
+pub use payability::{EtherTransfer, Payability, TransferKind, payability};
+pub use stats::AstStats;
+pub use type_name::TypeNameError;
+pub use validate::{SelectorError, SelectorMismatch, SelectorReport, validate_selectors};
+pub use visit::Visitor;
+pub use visit_mut::VisitorMut;
+
+mod completeness;
+mod coverage;
+mod file_level;
+pub mod fixtures;
+mod index;
+mod inheritdoc;
+mod interface;
+mod legacy;
+mod lenient;
+mod lines;
+mod locations;
+mod navigation;
+mod node;
+mod normalize;
+mod overrides;
+mod parameters;
+mod payability;
+pub mod printer;
+mod stats;
+mod type_name;
+mod validate;
+pub mod visit;
+pub mod visit_mut;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct SourceUnit {
@@ -34,6 +81,7 @@ pub enum SourceUnitNode {
     UserDefinedValueTypeDefinition(UserDefinedValueTypeDefinition),
     UsingForDirective(UsingForDirective),
     VariableDeclaration(VariableDeclaration),
+    UnparsedNode(UnparsedNode),
 }
 
 impl Default for SourceUnitNode {
@@ -55,7 +103,13 @@ pub struct ImportDirective {
     pub id: i64,
     pub absolute_path: PathBuf,
     pub file: PathBuf,
-    pub unit_alias: String,
+    /// The name of `import "file" as Name;`, which the compiler writes as an
+    /// empty string when there is none.
+    #[serde(
+        deserialize_with = "deserialize_unit_alias",
+        serialize_with = "serialize_unit_alias"
+    )]
+    pub unit_alias: Option<String>,
     pub symbol_aliases: Vec<SymbolAlias>,
     pub scope: i64,
     pub source_unit: i64,
@@ -63,6 +117,58 @@ pub struct ImportDirective {
     pub name_location: String,
 }
 
+impl ImportDirective {
+    /// Whether the whole unit is imported under a name, as in
+    /// `import "file" as Name;` or `import * as Name from "file";`.
+    pub fn is_aliased(&self) -> bool {
+        self.unit_alias.is_some()
+    }
+
+    /// The symbols of `import {A, B as C} from "file";`, as pairs of the
+    /// imported name and the name it is visible under.
+    pub fn imported_symbols(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.symbol_aliases.iter().map(|alias| {
+            let name = alias.foreign.name.as_str();
+            (name, alias.local.as_deref().unwrap_or(name))
+        })
+    }
+
+    /// How the path in the import is resolved.
+    pub fn kind(&self) -> ImportKind {
+        if self.file.starts_with(".") || self.file.starts_with("..") {
+            ImportKind::Relative
+        } else {
+            ImportKind::Direct
+        }
+    }
+}
+
+/// How the compiler resolves the path of an [`ImportDirective`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportKind {
+    /// A path starting with `./` or `../`, resolved against the directory of
+    /// the importing file.
+    Relative,
+    /// Any other path, resolved through the remappings, the base path and the
+    /// include paths.
+    Direct,
+}
+
+fn deserialize_unit_alias<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let alias = String::deserialize(deserializer)?;
+    Ok(Some(alias).filter(|alias| !alias.is_empty()))
+}
+
+fn serialize_unit_alias<S>(alias: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(alias.as_deref().unwrap_or_default())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct SymbolAlias {
@@ -115,6 +221,7 @@ pub enum ContractDefinitionNode {
     UserDefinedValueTypeDefinition(UserDefinedValueTypeDefinition),
     UsingForDirective(UsingForDirective),
     VariableDeclaration(VariableDeclaration),
+    UnparsedNode(UnparsedNode),
 }
 
 impl Default for ContractDefinitionNode {
@@ -128,6 +235,10 @@ impl Default for ContractDefinitionNode {
 pub struct InheritanceSpecifier {
     pub id: i64,
     pub base_name: IdentifierPath,
+    /// The constructor arguments of `is Base(arguments)`, absent when the
+    /// base is named without parentheses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<Box<Expression>>>,
     pub src: SourceLocation,
 }
 
@@ -260,6 +371,10 @@ pub struct ModifierDefinition {
     pub src: SourceLocation,
     pub documentation: Option<Documentation>,
     pub name_location: String,
+    pub overrides: Option<OverrideSpecifier>,
+    /// Present only when the modifier overrides a base modifier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_modifiers: Option<Vec<i64>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -313,6 +428,15 @@ pub struct EnumDefinition {
     pub name_location: String,
 }
 
+impl EnumDefinition {
+    /// The index of the member called `name`, which is the value the member
+    /// has as a `uint8` in the ABI and in storage.
+    pub fn value_index(&self, name: &str) -> Option<u8> {
+        let index = self.members.iter().position(|member| member.name == name)?;
+        u8::try_from(index).ok()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct EnumValue {
@@ -320,6 +444,8 @@ pub struct EnumValue {
     pub name: String,
     pub name_location: String,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -337,12 +463,32 @@ pub struct UserDefinedValueTypeDefinition {
 #[serde(rename_all = "camelCase")]
 pub struct UsingForDirective {
     pub id: i64,
-    pub library_name: IdentifierPath,
+    /// The library of `using L for T`, absent for `using {f} for T`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_name: Option<IdentifierPath>,
+    /// The functions of `using {f, g} for T`, absent for `using L for T`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_list: Option<Vec<UsingForFunction>>,
     pub type_name: Option<TypeName>,
     pub src: SourceLocation,
     pub global: bool,
 }
 
+/// An entry of the function list of `using {f, add as +} for T`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsingForFunction {
+    /// A function attached to the type, as `f` in the example.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<IdentifierPath>,
+    /// A function that defines an operator, as `add` in the example.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<IdentifierPath>,
+    /// The operator `definition` defines, such as `+`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "nodeType")]
 pub enum Statement {
@@ -362,6 +508,33 @@ pub enum Statement {
     UncheckedBlock(UncheckedBlock),
     VariableDeclarationStatement(VariableDeclarationStatement),
     WhileStatement(WhileStatement),
+    UnparsedNode(UnparsedNode),
+}
+
+impl Statement {
+    /// The documentation comment before the statement, which solc keeps for
+    /// statements such as `/// @solidity memory-safe-assembly` blocks.
+    pub fn documentation(&self) -> Option<&Documentation> {
+        match self {
+            Statement::Block(node) => node.documentation.as_ref(),
+            Statement::Break(node) => node.documentation.as_ref(),
+            Statement::Continue(node) => node.documentation.as_ref(),
+            Statement::DoWhileStatement(node) => node.documentation.as_ref(),
+            Statement::EmitStatement(node) => node.documentation.as_ref(),
+            Statement::ExpressionStatement(node) => node.documentation.as_ref(),
+            Statement::ForStatement(node) => node.documentation.as_ref(),
+            Statement::IfStatement(node) => node.documentation.as_ref(),
+            Statement::InlineAssembly(node) => node.documentation.as_ref(),
+            Statement::PlaceholderStatement(node) => node.documentation.as_ref(),
+            Statement::Return(node) => node.documentation.as_ref(),
+            Statement::RevertStatement(node) => node.documentation.as_ref(),
+            Statement::TryStatement(node) => node.documentation.as_ref(),
+            Statement::UncheckedBlock(node) => node.documentation.as_ref(),
+            Statement::VariableDeclarationStatement(node) => node.documentation.as_ref(),
+            Statement::WhileStatement(node) => node.documentation.as_ref(),
+            Statement::UnparsedNode(_) => None,
+        }
+    }
 }
 
 impl Default for Statement {
@@ -376,6 +549,8 @@ pub struct Block {
     pub id: i64,
     pub statements: Vec<Statement>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -384,6 +559,8 @@ pub struct UncheckedBlock {
     pub id: i64,
     pub statements: Vec<Statement>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -394,6 +571,8 @@ pub struct IfStatement {
     pub true_body: Box<Statement>,
     pub false_body: Option<Box<Statement>>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -405,6 +584,8 @@ pub struct ForStatement {
     pub loop_expression: Option<Box<Expression>>,
     pub body: Box<Statement>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
     pub is_simple_counter_loop: Option<bool>,
 }
 
@@ -415,6 +596,8 @@ pub struct WhileStatement {
     pub condition: Box<Expression>,
     pub body: Box<Statement>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -424,18 +607,24 @@ pub struct DoWhileStatement {
     pub condition: Box<Expression>,
     pub body: Box<Statement>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct Continue {
     pub id: i64,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct Break {
     pub id: i64,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -445,6 +634,8 @@ pub struct Return {
     pub function_return_parameters: i64,
     pub expression: Option<Box<Expression>>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -453,6 +644,8 @@ pub struct EmitStatement {
     pub id: i64,
     pub event_call: FunctionCall,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -461,6 +654,8 @@ pub struct RevertStatement {
     pub id: i64,
     pub error_call: FunctionCall,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -470,6 +665,8 @@ pub struct TryStatement {
     pub external_call: Box<Expression>,
     pub clauses: Vec<TryCatchClause>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -488,6 +685,8 @@ pub struct ExpressionStatement {
     pub id: i64,
     pub expression: Box<Expression>,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -505,8 +704,13 @@ pub struct VariableDeclarationStatement {
 #[serde(rename_all = "camelCase")]
 pub struct InlineAssembly {
     pub id: i64,
-    #[serde(rename = "AST")]
-    pub ast: YulBlock,
+    /// The typed Yul block, present since Solidity 0.6.0.
+    #[serde(rename = "AST", default, skip_serializing_if = "Option::is_none")]
+    pub ast: Option<YulBlock>,
+    /// The assembly source, which compilers before 0.6.0 give instead of
+    /// [`ast`](Self::ast).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operations: Option<String>,
     pub external_references: Vec<ExternalReference>,
     pub src: SourceLocation,
     pub documentation: Option<Documentation>,
@@ -528,6 +732,8 @@ pub enum YulStatement {
     YulVariableDeclaration(YulVariableDeclaration),
     YulFunctionDefinition(YulFunctionDefinition),
     YulExpressionStatement(YulExpressionStatement),
+    YulLeave(YulLeave),
+    YulContinue(YulContinue),
 }
 
 impl Default for YulStatement {
@@ -559,7 +765,7 @@ pub struct YulVariableDeclaration {
     pub src: String,
     pub native_src: Option<String>,
     pub variables: Vec<YulTypedName>,
-    pub value: YulExpression,
+    pub value: Option<YulExpression>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -646,7 +852,10 @@ pub struct YulFunctionDefinition {
     pub src: String,
     pub native_src: Option<String>,
     pub name: String,
+    #[serde(default)]
     pub parameters: Vec<YulTypedName>,
+    #[serde(default)]
+    pub return_variables: Vec<YulTypedName>,
     pub body: YulBlock,
 }
 
@@ -657,6 +866,20 @@ pub struct YulBreak {
     pub native_src: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct YulContinue {
+    pub src: String,
+    pub native_src: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct YulLeave {
+    pub src: String,
+    pub native_src: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "nodeType")]
 pub enum YulExpression {
@@ -695,6 +918,10 @@ pub struct ExternalReference {
     pub is_offset: bool,
     pub is_slot: bool,
     pub src: SourceLocation,
+    /// The member accessed in assembly, `slot` or `offset` for storage
+    /// variables and `length` or `offset` for calldata arrays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
     pub value_size: i64,
 }
 
@@ -703,6 +930,8 @@ pub struct ExternalReference {
 pub struct PlaceholderStatement {
     pub id: i64,
     pub src: SourceLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -724,6 +953,7 @@ pub enum Expression {
     UnaryOperation(UnaryOperation),
     VariableDeclarationStatement(VariableDeclarationStatement),
     ExpressionStatement(ExpressionStatement),
+    UnparsedNode(UnparsedNode),
 }
 
 impl Default for Expression {
@@ -732,6 +962,21 @@ impl Default for Expression {
     }
 }
 
+impl Expression {
+    /// The expression inside any parentheses around it, so that `((a))`
+    /// and `a` are handled alike.
+    pub fn without_parentheses(&self) -> &Expression {
+        let mut expression = self;
+        while let Expression::TupleExpression(tuple) = expression
+            && tuple.is_parenthesized()
+            && let Some(Some(inner)) = tuple.components.first()
+        {
+            expression = inner;
+        }
+        expression
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum AssignmentOperator {
     #[default]
@@ -768,6 +1013,7 @@ pub struct Assignment {
     pub is_l_value: bool,
     pub is_pure: bool,
     pub l_value_requested: bool,
+    pub argument_types: Option<Vec<TypeDescriptions>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -965,6 +1211,9 @@ pub struct IndexRangeAccess {
     pub id: i64,
     pub base_expression: Box<Expression>,
     pub start_expression: Option<Box<Expression>>,
+    /// The end of `base[start:end]`, absent for `base[start:]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_expression: Option<Box<Expression>>,
     pub src: SourceLocation,
     pub type_descriptions: TypeDescriptions,
     pub is_constant: bool,
@@ -979,6 +1228,8 @@ pub struct TupleExpression {
     pub id: i64,
     pub components: Vec<Option<Box<Expression>>>,
     pub src: SourceLocation,
+    /// Whether the expression is an array literal such as `[1, 2, 3]`,
+    /// rather than a tuple such as `(a, b)` or a parenthesized expression.
     pub is_inline_array: bool,
     pub type_descriptions: TypeDescriptions,
     pub is_constant: bool,
@@ -987,6 +1238,27 @@ pub struct TupleExpression {
     pub l_value_requested: bool,
 }
 
+impl TupleExpression {
+    /// Whether the expression is a tuple of any number of components, such
+    /// as `(a, b)`, `(, b)`, or `()`, and not an array literal or a single
+    /// expression in parentheses.
+    pub fn is_tuple(&self) -> bool {
+        !self.is_inline_array && !self.is_parenthesized()
+    }
+
+    /// Whether the expression only puts parentheses around another, as in
+    /// `(a + b)`. The compiler has no separate node for parentheses.
+    pub fn is_parenthesized(&self) -> bool {
+        !self.is_inline_array && matches!(self.components.as_slice(), [Some(_)])
+    }
+
+    /// The elements of an array literal, or `None` for a tuple.
+    pub fn inline_array_elements(&self) -> Option<impl Iterator<Item = &Expression>> {
+        self.is_inline_array
+            .then(|| self.components.iter().flatten().map(Box::as_ref))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Identifier {
@@ -1004,11 +1276,26 @@ pub struct Identifier {
 pub struct IdentifierPath {
     pub id: i64,
     pub name: String,
-    pub name_locations: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_locations: Option<Vec<SourceLocation>>,
     pub referenced_declaration: Option<i64>,
     pub src: SourceLocation,
 }
 
+impl IdentifierPath {
+    /// The segments of a dotted path such as `A.B.c`, each with its location.
+    ///
+    /// The locations come from `nameLocations`, which older compilers do not
+    /// emit; the location of every segment is then `None`.
+    pub fn segments(&self) -> impl Iterator<Item = (&str, Option<&SourceLocation>)> {
+        let locations = self.name_locations.as_deref().unwrap_or_default();
+        self.name
+            .split('.')
+            .enumerate()
+            .map(move |(index, segment)| (segment, locations.get(index)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Literal {
@@ -1054,6 +1341,10 @@ pub struct NewExpression {
 #[serde(rename_all = "camelCase")]
 pub struct ElementaryTypeNameExpression {
     pub id: i64,
+    /// The type, which compilers before 0.6.0 emit as a plain string such as
+    /// `"uint256"`. Such a string is read into a node without an id or a
+    /// source location.
+    #[serde(deserialize_with = "deserialize_expression_type_name")]
     pub type_name: ElementaryTypeName,
     pub src: SourceLocation,
     pub type_descriptions: TypeDescriptions,
@@ -1064,6 +1355,36 @@ pub struct ElementaryTypeNameExpression {
     pub l_value_requested: bool,
 }
 
+fn deserialize_expression_type_name<'de, D>(deserializer: D) -> Result<ElementaryTypeName, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum TypeNameOrString {
+        Node(ElementaryTypeName),
+        Name(String),
+    }
+
+    match TypeNameOrString::deserialize(deserializer)? {
+        TypeNameOrString::Node(node) => Ok(node),
+        TypeNameOrString::Name(name) => {
+            let (name, state_mutability) = match name.strip_suffix(" payable") {
+                Some(name) => (name, Some("payable".to_string())),
+                None => (name.as_str(), None),
+            };
+            let name = ElementaryType::deserialize(
+                serde::de::IntoDeserializer::<D::Error>::into_deserializer(name),
+            )?;
+            Ok(ElementaryTypeName {
+                name,
+                state_mutability,
+                ..Default::default()
+            })
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "nodeType")]
 pub enum TypeName {
@@ -1072,6 +1393,7 @@ pub enum TypeName {
     FunctionTypeName(FunctionTypeName),
     Mapping(Mapping),
     UserDefinedTypeName(UserDefinedTypeName),
+    UnparsedNode(UnparsedNode),
 }
 
 impl Default for TypeName {
@@ -1094,12 +1416,42 @@ pub struct ElementaryTypeName {
 #[serde(rename_all = "camelCase")]
 pub struct UserDefinedTypeName {
     pub id: i64,
+    /// The name of the type, emitted by compilers before 0.8.0 instead of
+    /// `pathNode`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_path_node"
+    )]
     pub path_node: Option<IdentifierPath>,
     pub referenced_declaration: Option<i64>,
     pub src: SourceLocation,
     pub type_descriptions: TypeDescriptions,
 }
 
+/// Serialize the path of a [`UserDefinedTypeName`] with its `nodeType`, as
+/// the compiler does.
+fn serialize_path_node<S>(path: &Option<IdentifierPath>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    #[derive(Serialize)]
+    struct Tagged<'a> {
+        #[serde(rename = "nodeType")]
+        node_type: &'static str,
+        #[serde(flatten)]
+        path: &'a IdentifierPath,
+    }
+
+    path.as_ref()
+        .map(|path| Tagged {
+            node_type: "IdentifierPath",
+            path,
+        })
+        .serialize(serializer)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ArrayTypeName {
@@ -1134,8 +1486,8 @@ pub struct FunctionTypeName {
     pub id: i64,
     pub parameter_types: ParameterList,
     pub return_parameter_types: ParameterList,
-    pub visibility: String,
-    pub state_mutability: String,
+    pub visibility: Visibility,
+    pub state_mutability: StateMutability,
     pub src: SourceLocation,
     pub type_descriptions: TypeDescriptions,
 }
@@ -1165,27 +1517,49 @@ impl<'de> Deserialize<'de> for SourceLocation {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for SourceLocation {
+    type Err = SourceLocationError;
+
+    /// Parse a source range written as `offset:length:sourceIndex`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split(':').collect();
         if parts.len() != 3 {
-            return Err(serde::de::Error::custom(format!(
-                "invalid source location: expected 'offset:length:sourceIndex', got '{}'",
-                s
-            )));
+            return Err(SourceLocationError::Format(s.to_string()));
         }
+        let field = |name: &'static str, part: &str| {
+            part.parse()
+                .map_err(|e: std::num::ParseIntError| SourceLocationError::Number {
+                    field: name,
+                    message: e.to_string(),
+                })
+        };
         Ok(SourceLocation {
-            offset: parts[0]
-                .parse()
-                .map_err(|e| serde::de::Error::custom(format!("invalid offset: {}", e)))?,
-            length: parts[1]
-                .parse()
-                .map_err(|e| serde::de::Error::custom(format!("invalid length: {}", e)))?,
-            source_index: parts[2]
-                .parse()
-                .map_err(|e| serde::de::Error::custom(format!("invalid source_index: {}", e)))?,
+            offset: field("offset", parts[0])?,
+            length: field("length", parts[1])?,
+            source_index: field("source_index", parts[2])?,
         })
     }
 }
 
+/// An error returned for text that is not a source range.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SourceLocationError {
+    /// The text is not three fields separated by colons.
+    #[error("invalid source location: expected 'offset:length:sourceIndex', got '{0}'")]
+    Format(String),
+
+    /// A field is not a non-negative integer.
+    #[error("invalid {field}: {message}")]
+    Number {
+        field: &'static str,
+        message: String,
+    },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct TypeDescriptions {
@@ -1202,19 +1576,216 @@ pub struct CommonType {
     pub type_string: String,
 }
 
+/// A built-in Solidity type, such as `uint256` or `bytes32`.
+///
+/// Values built directly may break Solidity's rules on widths, such as
+/// `Uint(7)`; [`ElementaryType::validate`] checks them. Names are checked when
+/// parsed with [`ElementaryType::try_new`] or deserialized.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum ElementaryType {
+    /// `uintN`, with N bits.
     Uint(u16),
+    /// `intN`, with N bits.
     Int(u16),
     #[default]
     Address,
+    /// `address payable`, an address that can receive Ether.
+    ///
+    /// The AST writes this type as an `address` type name with a `payable`
+    /// state mutability; [`ElementaryTypeName::elementary_type`] combines the
+    /// two.
+    AddressPayable,
+    /// `payable`, the name of the conversion `payable(x)` to
+    /// `address payable`.
     Payable,
     Bool,
     String,
     Bytes,
+    /// `bytesN`, with N bytes.
     FixedBytes(u16),
-    Ufixed(u8, u8),
-    Fixed(u8, u8),
+    /// `ufixedMxN`, with M bits and N decimals.
+    Ufixed(u16, u8),
+    /// `fixedMxN`, with M bits and N decimals.
+    Fixed(u16, u8),
+}
+
+/// An error returned for a name or width that is not a valid elementary type.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ElementaryTypeError {
+    /// An integer width that is not a multiple of 8 from 8 to 256.
+    #[error("invalid integer width {0}, expected a multiple of 8 from 8 to 256")]
+    IntegerBits(u16),
+
+    /// A `bytesN` size outside 1 to 32.
+    #[error("invalid bytes size {0}, expected 1 to 32")]
+    BytesSize(u16),
+
+    /// A fixed-point width that is not a multiple of 8 from 8 to 256.
+    #[error("invalid fixed-point width {0}, expected a multiple of 8 from 8 to 256")]
+    FixedBits(u16),
+
+    /// A number of fixed-point decimals above 80.
+    #[error("invalid fixed-point decimals {0}, expected at most 80")]
+    FixedDecimals(u8),
+
+    /// A name that is not an elementary type.
+    #[error("unknown elementary type: {0}")]
+    Unknown(String),
+}
+
+impl ElementaryType {
+    /// Parse the name of an elementary type, such as `uint`, `int8`,
+    /// `bytes32`, or `fixed128x18`, checking Solidity's rules on widths.
+    ///
+    /// `uint`, `int`, `fixed`, and `ufixed` are the aliases the language
+    /// defines, for 256 bits and for 128 bits with 18 decimals, and `byte`
+    /// is the alias of `bytes1` in releases before 0.8.0. Widths are plain
+    /// decimal numbers without a sign or leading zeros.
+    pub fn try_new(name: &str) -> Result<Self, ElementaryTypeError> {
+        let unknown = || ElementaryTypeError::Unknown(name.to_string());
+        let number = |digits: &str| {
+            let canonical = digits.bytes().all(|digit| digit.is_ascii_digit())
+                && (digits == "0" || !digits.starts_with('0'));
+            match canonical {
+                true => digits.parse().map_err(|_| unknown()),
+                false => Err(unknown()),
+            }
+        };
+        let fixed = |suffix: &str| -> Result<(u16, u8), ElementaryTypeError> {
+            if suffix.is_empty() {
+                return Ok((128, 18));
+            }
+            let (bits, decimals) = suffix.split_once('x').ok_or_else(unknown)?;
+            let decimals: u16 = number(decimals)?;
+            Ok((number(bits)?, decimals.try_into().map_err(|_| unknown())?))
+        };
+
+        let ty = match name {
+            "address" => Self::Address,
+            "address payable" => Self::AddressPayable,
+            "payable" => Self::Payable,
+            "bool" => Self::Bool,
+            "string" => Self::String,
+            "bytes" => Self::Bytes,
+            "byte" => Self::FixedBytes(1),
+            "uint" => Self::Uint(256),
+            "int" => Self::Int(256),
+            _ if name.starts_with("uint") => Self::Uint(number(&name[4..])?),
+            _ if name.starts_with("int") => Self::Int(number(&name[3..])?),
+            _ if name.starts_with("bytes") => Self::FixedBytes(number(&name[5..])?),
+            _ if name.starts_with("ufixed") => {
+                let (bits, decimals) = fixed(&name[6..])?;
+                Self::Ufixed(bits, decimals)
+            }
+            _ if name.starts_with("fixed") => {
+                let (bits, decimals) = fixed(&name[5..])?;
+                Self::Fixed(bits, decimals)
+            }
+            _ => return Err(unknown()),
+        };
+        ty.validate()?;
+        Ok(ty)
+    }
+
+    /// Check that the widths of this type follow Solidity's rules.
+    pub fn validate(&self) -> Result<(), ElementaryTypeError> {
+        let valid_bits = |bits: u16| bits.is_multiple_of(8) && (8..=256).contains(&bits);
+        match *self {
+            Self::Uint(bits) | Self::Int(bits) if !valid_bits(bits) => {
+                Err(ElementaryTypeError::IntegerBits(bits))
+            }
+            Self::FixedBytes(size) if !(1..=32).contains(&size) => {
+                Err(ElementaryTypeError::BytesSize(size))
+            }
+            Self::Ufixed(bits, _) | Self::Fixed(bits, _) if !valid_bits(bits) => {
+                Err(ElementaryTypeError::FixedBits(bits))
+            }
+            Self::Ufixed(_, decimals) | Self::Fixed(_, decimals) if decimals > 80 => {
+                Err(ElementaryTypeError::FixedDecimals(decimals))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The canonical name of this type in the ABI, such as `address` for
+    /// `address payable`.
+    pub fn abi_type(&self) -> String {
+        match self {
+            Self::AddressPayable | Self::Payable => "address".to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Whether values of this type are encoded in the tail of ABI encoded
+    /// data, with an offset in the head.
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, Self::String | Self::Bytes)
+    }
+
+    /// The number of bytes a value of this type takes in the ABI encoding,
+    /// or `None` for dynamic types, whose size depends on the value.
+    ///
+    /// Every static elementary type is padded to one 32-byte word.
+    pub fn abi_size(&self) -> Option<usize> {
+        (!self.is_dynamic()).then_some(32)
+    }
+
+    /// The largest value of an integer type, as a big-endian word, or
+    /// `None` for other types and integers of invalid width.
+    pub fn max_value(&self) -> Option<[u8; 32]> {
+        self.validate().ok()?;
+        let mut word = [0; 32];
+        match *self {
+            Self::Uint(bits) => word[32 - usize::from(bits / 8)..].fill(0xff),
+            Self::Int(bits) => {
+                let start = 32 - usize::from(bits / 8);
+                word[start..].fill(0xff);
+                word[start] = 0x7f;
+            }
+            _ => return None,
+        }
+        Some(word)
+    }
+
+    /// The smallest value of an integer type, as a big-endian two's
+    /// complement word, or `None` for other types and integers of invalid
+    /// width.
+    pub fn min_value(&self) -> Option<[u8; 32]> {
+        self.validate().ok()?;
+        let mut word = [0; 32];
+        match *self {
+            Self::Uint(_) => {}
+            Self::Int(bits) => {
+                let start = 32 - usize::from(bits / 8);
+                word[..start].fill(0xff);
+                word[start] = 0x80;
+            }
+            _ => return None,
+        }
+        Some(word)
+    }
+}
+
+impl ElementaryTypeName {
+    /// The type this node names, with `address payable` told apart from
+    /// `address` by the state mutability.
+    pub fn elementary_type(&self) -> ElementaryType {
+        match self.name {
+            ElementaryType::Address if self.state_mutability.as_deref() == Some("payable") => {
+                ElementaryType::AddressPayable
+            }
+            ElementaryType::Payable => ElementaryType::AddressPayable,
+            ref name => name.clone(),
+        }
+    }
+}
+
+impl std::str::FromStr for ElementaryType {
+    type Err = ElementaryTypeError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::try_new(name)
+    }
 }
 
 impl<'de> Deserialize<'de> for ElementaryType {
@@ -1223,44 +1794,24 @@ impl<'de> Deserialize<'de> for ElementaryType {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        match s.as_str() {
-            "address" => Ok(Self::Address),
-            "payable" => Ok(Self::Payable),
-            "bool" => Ok(Self::Bool),
-            "string" => Ok(Self::String),
-            "bytes" => Ok(Self::Bytes),
-            s if s.starts_with("uint") => {
-                let bits = if s.len() == 4 {
-                    256
-                } else {
-                    s[4..].parse::<u16>().map_err(serde::de::Error::custom)?
-                };
-                Ok(Self::Uint(bits))
-            }
-            s if s.starts_with("int") => {
-                let bits = if s.len() == 3 {
-                    256
-                } else {
-                    s[3..].parse::<u16>().map_err(serde::de::Error::custom)?
-                };
-                Ok(Self::Int(bits))
-            }
-            s if s.starts_with("bytes") => {
-                let size = if s.len() == 5 {
-                    0
-                } else {
-                    s[5..].parse::<u16>().map_err(serde::de::Error::custom)?
-                };
-                Ok(if size == 0 {
-                    Self::Bytes
-                } else {
-                    Self::FixedBytes(size)
-                })
-            }
-            _ => Err(serde::de::Error::custom(format!(
-                "unknown elementary type: {}",
-                s
-            ))),
+        Self::try_new(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ElementaryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uint(b) => write!(f, "uint{}", b),
+            Self::Int(b) => write!(f, "int{}", b),
+            Self::Address => f.write_str("address"),
+            Self::AddressPayable => f.write_str("address payable"),
+            Self::Payable => f.write_str("payable"),
+            Self::Bool => f.write_str("bool"),
+            Self::String => f.write_str("string"),
+            Self::Bytes => f.write_str("bytes"),
+            Self::FixedBytes(b) => write!(f, "bytes{}", b),
+            Self::Ufixed(t, d) => write!(f, "ufixed{}x{}", t, d),
+            Self::Fixed(t, d) => write!(f, "fixed{}x{}", t, d),
         }
     }
 }
@@ -1270,19 +1821,7 @@ impl Serialize for ElementaryType {
     where
         S: serde::Serializer,
     {
-        let s = match self {
-            Self::Uint(b) => format!("uint{}", b),
-            Self::Int(b) => format!("int{}", b),
-            Self::Address => "address".into(),
-            Self::Payable => "payable".into(),
-            Self::Bool => "bool".into(),
-            Self::String => "string".into(),
-            Self::Bytes => "bytes".into(),
-            Self::FixedBytes(b) => format!("bytes{}", b),
-            Self::Ufixed(t, f) => format!("ufixed{}x{}", t, f),
-            Self::Fixed(t, f) => format!("fixed{}x{}", t, f),
-        };
-        serializer.serialize_str(&s)
+        serializer.collect_str(self)
     }
 }
 
@@ -1319,6 +1858,42 @@ pub enum Mutability {
     Constant,
 }
 
+/// A placeholder for a node that [`parse_lenient`] could not read, in a
+/// position that holds nodes of several types.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UnparsedNode {
+    /// The id of the node, or 0 if it had none.
+    pub id: i64,
+    /// The source range of the node, or an empty range if it had none.
+    pub src: SourceLocation,
+    /// The node as it was in the JSON.
+    pub raw: serde_json::Value,
+}
+
+impl UnparsedNode {
+    /// A placeholder for a node, taking its id and source range if they
+    /// can be read.
+    pub fn new(raw: serde_json::Value) -> Self {
+        Self {
+            id: raw
+                .get("id")
+                .and_then(serde_json::Value::as_i64)
+                .unwrap_or_default(),
+            src: raw
+                .get("src")
+                .and_then(|src| SourceLocation::deserialize(src).ok())
+                .unwrap_or_default(),
+            raw,
+        }
+    }
+
+    /// The `nodeType` of the node as it was in the JSON.
+    pub fn original_node_type(&self) -> Option<&str> {
+        self.raw.get("nodeType").and_then(serde_json::Value::as_str)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct StructuredDocumentation {
@@ -1327,6 +1902,34 @@ pub struct StructuredDocumentation {
     pub src: SourceLocation,
 }
 
+/// The paths of the AST fixtures, in `fixtures/ast`.
+#[cfg(test)]
+pub(crate) fn fixture_paths() -> Vec<std::path::PathBuf> {
+    walkdir::WalkDir::new("fixtures/ast")
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .map(walkdir::DirEntry::into_path)
+        .collect()
+}
+
+/// Every AST fixture with its path, parsed.
+#[cfg(test)]
+pub(crate) fn all_fixtures() -> Vec<(std::path::PathBuf, SourceUnit)> {
+    use rayon::prelude::*;
+
+    fixture_paths()
+        .into_par_iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(&path).expect("Failed to read fixture file");
+            let source_unit = serde_json::from_str(&content)
+                .unwrap_or_else(|e| panic!("Failed to parse {:?}: {}", path, e));
+            (path, source_unit)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1336,7 +1939,6 @@ mod tests {
     use serde::de::IntoDeserializer;
     use serde_json::Value;
     use serde_path_to_error::deserialize;
-    use walkdir::WalkDir;
 
     fn find_deserialization_error(content: &str) -> String {
         let value: Value = serde_json::from_str(content).expect("Failed to parse JSON");
@@ -1352,10 +1954,8 @@ mod tests {
                 }
             }
 
-            if let Some(node_type) = obj.get("nodeType") {
-                if let Some(type_str) = node_type.as_str() {
-                    return try_parse_node(value, json_path, type_str);
-                }
+            if let Some(type_str) = obj.get("nodeType").and_then(|v| v.as_str()) {
+                return try_parse_node(value, json_path, type_str);
             }
         }
 
@@ -1437,20 +2037,630 @@ mod tests {
     }
 
     #[test]
-    fn fixtures() {
-        let entries: Vec<walkdir::DirEntry> = WalkDir::new("fixtures/ast")
-            .into_iter()
-            .filter_map(Result::ok)
-            .filter(|entry| entry.file_type().is_file())
-            .filter(|entry| entry.path().extension().map_or(false, |e| e == "json"))
+    fn builtin_identifier() {
+        let json = r#"{
+            "id": 12,
+            "name": "require",
+            "nodeType": "Identifier",
+            "overloadedDeclarations": [-18, -18],
+            "src": "100:7:0",
+            "typeDescriptions": {
+                "typeIdentifier": "t_function_require_pure$_t_bool_$returns$__$",
+                "typeString": "function (bool) pure"
+            }
+        }"#;
+        let identifier: Identifier = serde_json::from_str(json).unwrap();
+        assert_eq!(identifier.referenced_declaration, None);
+
+        let mut value: Value = serde_json::from_str(json).unwrap();
+        value["referencedDeclaration"] = Value::Null;
+        let identifier: Identifier = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(identifier.referenced_declaration, None);
+
+        value["referencedDeclaration"] = (-18).into();
+        let identifier: Identifier = serde_json::from_value(value).unwrap();
+        assert_eq!(identifier.referenced_declaration, Some(-18));
+    }
+
+    #[test]
+    fn assignment_argument_types() {
+        let json = r#"{
+            "argumentTypes": [{"typeIdentifier": "t_uint256", "typeString": "uint256"}],
+            "id": 7,
+            "isConstant": false,
+            "isLValue": false,
+            "isPure": false,
+            "lValueRequested": false,
+            "leftHandSide": {
+                "id": 5,
+                "name": "x",
+                "nodeType": "Identifier",
+                "overloadedDeclarations": [],
+                "referencedDeclaration": 3,
+                "src": "50:1:0",
+                "typeDescriptions": {"typeIdentifier": "t_uint256", "typeString": "uint256"}
+            },
+            "nodeType": "Assignment",
+            "operator": "=",
+            "rightHandSide": {
+                "hexValue": "31",
+                "id": 6,
+                "isConstant": false,
+                "isLValue": false,
+                "isPure": true,
+                "kind": "number",
+                "lValueRequested": false,
+                "nodeType": "Literal",
+                "src": "54:1:0",
+                "typeDescriptions": {"typeIdentifier": "t_rational_1_by_1", "typeString": "int_const 1"},
+                "value": "1"
+            },
+            "src": "50:5:0",
+            "typeDescriptions": {"typeIdentifier": "t_uint256", "typeString": "uint256"}
+        }"#;
+        let assignment: Assignment = serde_json::from_str(json).unwrap();
+        let argument_types = assignment.argument_types.as_ref().unwrap();
+        assert_eq!(argument_types[0].type_string.as_deref(), Some("uint256"));
+
+        let value = serde_json::to_value(&assignment).unwrap();
+        assert_eq!(value["argumentTypes"][0]["typeIdentifier"], "t_uint256");
+    }
+
+    #[test]
+    fn function_type_name() {
+        let json = r#"{
+            "id": 9,
+            "nodeType": "FunctionTypeName",
+            "parameterTypes": {
+                "id": 5,
+                "nodeType": "ParameterList",
+                "parameters": [],
+                "src": "40:2:0"
+            },
+            "returnParameterTypes": {
+                "id": 8,
+                "nodeType": "ParameterList",
+                "parameters": [],
+                "src": "0:0:0"
+            },
+            "src": "31:28:0",
+            "stateMutability": "view",
+            "typeDescriptions": {
+                "typeIdentifier": "t_function_external_view$__$returns$__$",
+                "typeString": "function () view external"
+            },
+            "visibility": "external"
+        }"#;
+        let type_name: TypeName = serde_json::from_str(json).unwrap();
+        let TypeName::FunctionTypeName(function) = type_name else {
+            panic!("expected a function type name");
+        };
+        assert_eq!(function.visibility, Visibility::External);
+        assert_eq!(function.state_mutability, StateMutability::View);
+        assert_eq!(function.parameter_types.id, 5);
+    }
+
+    #[test]
+    fn tuple_expressions() {
+        let identifier = |name: &str| {
+            Some(Box::new(Expression::Identifier(Identifier {
+                name: name.to_string(),
+                ..Default::default()
+            })))
+        };
+        let tuple = |components: Vec<Option<Box<Expression>>>, is_inline_array| TupleExpression {
+            components,
+            is_inline_array,
+            ..Default::default()
+        };
+
+        let pair = tuple(vec![None, identifier("b")], false);
+        assert!(pair.is_tuple());
+        assert!(!pair.is_parenthesized());
+        assert!(pair.inline_array_elements().is_none());
+
+        let array = tuple(vec![identifier("a"), identifier("b")], true);
+        assert!(!array.is_tuple());
+        assert_eq!(array.inline_array_elements().unwrap().count(), 2);
+
+        let singleton = tuple(vec![identifier("a")], true);
+        assert!(!singleton.is_parenthesized());
+
+        let parenthesized = Expression::TupleExpression(tuple(
+            vec![Some(Box::new(Expression::TupleExpression(tuple(
+                vec![identifier("a")],
+                false,
+            ))))],
+            false,
+        ));
+        assert!(matches!(
+            parenthesized.without_parentheses(),
+            Expression::Identifier(identifier) if identifier.name == "a"
+        ));
+        let array = Expression::TupleExpression(singleton);
+        assert_eq!(array.without_parentheses(), &array);
+    }
+
+    #[test]
+    fn legacy_elementary_type_name_expression() {
+        let json = r#"{
+            "argumentTypes": null,
+            "id": 7,
+            "isConstant": false,
+            "isLValue": false,
+            "isPure": true,
+            "lValueRequested": false,
+            "nodeType": "ElementaryTypeNameExpression",
+            "src": "120:7:0",
+            "typeDescriptions": {"typeIdentifier": "t_type$_t_address_$", "typeString": "type(address)"},
+            "typeName": "address payable"
+        }"#;
+        let Expression::ElementaryTypeNameExpression(expression) =
+            serde_json::from_str(json).unwrap()
+        else {
+            panic!("expected an elementary type name expression");
+        };
+        assert_eq!(expression.type_name.name, ElementaryType::Address);
+        assert_eq!(
+            expression.type_name.state_mutability.as_deref(),
+            Some("payable")
+        );
+
+        let json = json.replace(r#""address payable""#, r#""uint""#);
+        let expression: ElementaryTypeNameExpression = serde_json::from_str(&json).unwrap();
+        assert_eq!(expression.type_name.name, ElementaryType::Uint(256));
+        assert!(
+            serde_json::from_str::<ElementaryTypeNameExpression>(
+                &json.replace(r#""uint""#, r#""map""#)
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn elementary_type_abi() {
+        let uint8 = ElementaryType::Uint(8);
+        assert_eq!(uint8.abi_size(), Some(32));
+        assert!(!uint8.is_dynamic());
+        let mut max = [0; 32];
+        max[31] = 0xff;
+        assert_eq!(uint8.max_value(), Some(max));
+        assert_eq!(uint8.min_value(), Some([0; 32]));
+
+        let int16 = ElementaryType::Int(16);
+        let mut max = [0; 32];
+        max[30..].copy_from_slice(&[0x7f, 0xff]);
+        assert_eq!(int16.max_value(), Some(max));
+        let mut min = [0xff; 32];
+        min[30..].copy_from_slice(&[0x80, 0x00]);
+        assert_eq!(int16.min_value(), Some(min));
+        assert_eq!(ElementaryType::Int(256).min_value().unwrap()[0], 0x80);
+
+        assert!(ElementaryType::Bytes.is_dynamic());
+        assert_eq!(ElementaryType::String.abi_size(), None);
+        assert_eq!(ElementaryType::Bool.max_value(), None);
+        for invalid in [
+            ElementaryType::Int(0),
+            ElementaryType::Uint(0),
+            ElementaryType::Int(12),
+            ElementaryType::Int(264),
+            ElementaryType::Uint(512),
+        ] {
+            assert_eq!(invalid.max_value(), None, "{invalid:?}");
+            assert_eq!(invalid.min_value(), None, "{invalid:?}");
+        }
+
+        let payable: ElementaryType = "address payable".parse().unwrap();
+        assert_eq!(payable, ElementaryType::AddressPayable);
+        assert_eq!(payable.to_string(), "address payable");
+        assert_eq!(payable.abi_type(), "address");
+        assert_eq!(ElementaryType::Payable.abi_type(), "address");
+
+        let type_name = ElementaryTypeName {
+            name: ElementaryType::Address,
+            state_mutability: Some("payable".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(type_name.elementary_type(), ElementaryType::AddressPayable);
+        let type_name = ElementaryTypeName {
+            state_mutability: Some("nonpayable".to_string()),
+            ..type_name
+        };
+        assert_eq!(type_name.elementary_type(), ElementaryType::Address);
+    }
+
+    #[test]
+    fn elementary_type_widths() {
+        assert_eq!(
+            ElementaryType::try_new("uint"),
+            Ok(ElementaryType::Uint(256))
+        );
+        assert_eq!(ElementaryType::try_new("int8"), Ok(ElementaryType::Int(8)));
+        assert_eq!(
+            ElementaryType::try_new("bytes32"),
+            Ok(ElementaryType::FixedBytes(32))
+        );
+        assert_eq!(
+            ElementaryType::try_new("fixed"),
+            Ok(ElementaryType::Fixed(128, 18))
+        );
+        assert_eq!(
+            ElementaryType::try_new("ufixed256x80"),
+            Ok(ElementaryType::Ufixed(256, 80))
+        );
+        assert_eq!(
+            "ufixed256x80"
+                .parse::<ElementaryType>()
+                .unwrap()
+                .to_string(),
+            "ufixed256x80"
+        );
+
+        assert_eq!(
+            ElementaryType::try_new("uint7"),
+            Err(ElementaryTypeError::IntegerBits(7))
+        );
+        assert_eq!(
+            ElementaryType::try_new("int264"),
+            Err(ElementaryTypeError::IntegerBits(264))
+        );
+        assert_eq!(
+            ElementaryType::try_new("bytes0"),
+            Err(ElementaryTypeError::BytesSize(0))
+        );
+        assert_eq!(
+            ElementaryType::try_new("fixed12x18"),
+            Err(ElementaryTypeError::FixedBits(12))
+        );
+        assert_eq!(
+            ElementaryType::try_new("ufixed128x81"),
+            Err(ElementaryTypeError::FixedDecimals(81))
+        );
+        assert_eq!(
+            ElementaryType::try_new("uintx"),
+            Err(ElementaryTypeError::Unknown("uintx".to_string()))
+        );
+        for name in [
+            "uint08",
+            "uint+8",
+            "int-8",
+            "bytes01",
+            "fixed128x018",
+            "ufixed128x",
+        ] {
+            assert_eq!(
+                ElementaryType::try_new(name),
+                Err(ElementaryTypeError::Unknown(name.to_string()))
+            );
+        }
+        assert_eq!(
+            ElementaryType::try_new("byte"),
+            Ok(ElementaryType::FixedBytes(1))
+        );
+        assert_eq!(
+            ElementaryType::try_new("fixed128x0"),
+            Ok(ElementaryType::Fixed(128, 0))
+        );
+        assert!(serde_json::from_str::<ElementaryType>(r#""uint7""#).is_err());
+        assert!(ElementaryType::Uint(7).validate().is_err());
+    }
+
+    #[test]
+    fn legacy_inline_assembly() {
+        let json = r#"{
+            "evmVersion": "petersburg",
+            "externalReferences": [],
+            "id": 12,
+            "nodeType": "InlineAssembly",
+            "operations": "{ mstore(0, 1) }",
+            "src": "50:30:0"
+        }"#;
+        let statement: Statement = serde_json::from_str(json).unwrap();
+        let Statement::InlineAssembly(assembly) = &statement else {
+            panic!("expected inline assembly");
+        };
+        assert!(assembly.ast.is_none());
+        assert_eq!(assembly.operations.as_deref(), Some("{ mstore(0, 1) }"));
+        let value = serde_json::to_value(&statement).unwrap();
+        assert_eq!(value["operations"], "{ mstore(0, 1) }");
+        assert!(value.get("AST").is_none());
+    }
+
+    #[test]
+    fn import_directive() {
+        let json = r#"{
+            "absolutePath": "lib/token/IERC20.sol",
+            "file": "../token/IERC20.sol",
+            "id": 3,
+            "nameLocation": "-1:-1:-1",
+            "nodeType": "ImportDirective",
+            "scope": 10,
+            "sourceUnit": 20,
+            "src": "0:60:0",
+            "symbolAliases": [
+                {
+                    "foreign": {
+                        "id": 1,
+                        "name": "IERC20",
+                        "nodeType": "Identifier",
+                        "overloadedDeclarations": [],
+                        "src": "8:6:0",
+                        "typeDescriptions": {}
+                    },
+                    "nameLocation": "-1:-1:-1"
+                },
+                {
+                    "foreign": {
+                        "id": 2,
+                        "name": "SafeERC20",
+                        "nodeType": "Identifier",
+                        "overloadedDeclarations": [],
+                        "src": "16:9:0",
+                        "typeDescriptions": {}
+                    },
+                    "local": "Safe",
+                    "nameLocation": "-1:-1:-1"
+                }
+            ],
+            "unitAlias": ""
+        }"#;
+        let import: ImportDirective = serde_json::from_str(json).unwrap();
+        assert!(!import.is_aliased());
+        assert_eq!(import.kind(), ImportKind::Relative);
+        assert_eq!(
+            import.imported_symbols().collect::<Vec<_>>(),
+            [("IERC20", "IERC20"), ("SafeERC20", "Safe")]
+        );
+        let value = serde_json::to_value(&import).unwrap();
+        assert_eq!(value["unitAlias"], "");
+
+        let import = ImportDirective {
+            file: "@openzeppelin/contracts/token/ERC20/IERC20.sol".into(),
+            unit_alias: Some("OZ".to_string()),
+            ..Default::default()
+        };
+        assert!(import.is_aliased());
+        assert_eq!(import.kind(), ImportKind::Direct);
+    }
+
+    #[test]
+    fn parses_source_locations() {
+        assert_eq!(
+            "14:5:2".parse(),
+            Ok(SourceLocation {
+                offset: 14,
+                length: 5,
+                source_index: 2
+            })
+        );
+        assert_eq!(
+            "14:5".parse::<SourceLocation>(),
+            Err(SourceLocationError::Format("14:5".to_string()))
+        );
+        assert!(matches!(
+            "14:x:2".parse::<SourceLocation>(),
+            Err(SourceLocationError::Number {
+                field: "length",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn identifier_path_segments() {
+        let json = r#"{
+            "id": 7,
+            "name": "Lib.Inner.value",
+            "nameLocations": ["10:3:0", "14:5:0", "20:5:0"],
+            "nodeType": "IdentifierPath",
+            "referencedDeclaration": 3,
+            "src": "10:15:0"
+        }"#;
+        let path: IdentifierPath = serde_json::from_str(json).unwrap();
+        let segments: Vec<_> = path.segments().collect();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].0, "Inner");
+        assert_eq!(
+            segments[1].1,
+            Some(&SourceLocation {
+                offset: 14,
+                length: 5,
+                source_index: 0
+            })
+        );
+
+        let legacy = IdentifierPath {
+            name: "Token".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(legacy.segments().collect::<Vec<_>>(), [("Token", None)]);
+    }
+
+    #[test]
+    fn statement_documentation() {
+        let json = r#"{
+            "documentation": "@solidity memory-safe-assembly",
+            "expression": {
+                "id": 1,
+                "name": "x",
+                "nodeType": "Identifier",
+                "overloadedDeclarations": [],
+                "src": "40:1:0",
+                "typeDescriptions": {}
+            },
+            "id": 2,
+            "nodeType": "ExpressionStatement",
+            "src": "40:2:0"
+        }"#;
+        let statement: Statement = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            statement.documentation(),
+            Some(Documentation::String(text)) if text == "@solidity memory-safe-assembly"
+        ));
+        let value = serde_json::to_value(&statement).unwrap();
+        assert_eq!(value["documentation"], "@solidity memory-safe-assembly");
+
+        let statement = Statement::Break(Break::default());
+        assert!(statement.documentation().is_none());
+        let value = serde_json::to_value(&statement).unwrap();
+        assert!(value.get("documentation").is_none());
+    }
+
+    #[test]
+    fn enum_definition() {
+        let json = r#"{
+            "canonicalName": "Status",
+            "id": 4,
+            "members": [
+                {
+                    "id": 1,
+                    "name": "Open",
+                    "nameLocation": "17:4:0",
+                    "nodeType": "EnumValue",
+                    "src": "17:4:0"
+                },
+                {
+                    "documentation": {
+                        "id": 2,
+                        "nodeType": "StructuredDocumentation",
+                        "src": "23:15:0",
+                        "text": "@dev Closed."
+                    },
+                    "id": 3,
+                    "name": "Closed",
+                    "nameLocation": "39:6:0",
+                    "nodeType": "EnumValue",
+                    "src": "39:6:0"
+                }
+            ],
+            "name": "Status",
+            "nameLocation": "5:6:0",
+            "nodeType": "EnumDefinition",
+            "src": "0:47:0"
+        }"#;
+        let definition: EnumDefinition = serde_json::from_str(json).unwrap();
+        assert_eq!(definition.name_location, "5:6:0");
+        assert!(definition.members[0].documentation.is_none());
+        assert!(matches!(
+            &definition.members[1].documentation,
+            Some(Documentation::Structured(doc)) if doc.text == "@dev Closed."
+        ));
+        assert_eq!(definition.value_index("Open"), Some(0));
+        assert_eq!(definition.value_index("Closed"), Some(1));
+        assert_eq!(definition.value_index("Pending"), None);
+
+        let value = serde_json::to_value(&definition.members[0]).unwrap();
+        assert!(value.get("documentation").is_none());
+    }
+
+    #[test]
+    fn contract_user_defined_value_types() {
+        let content = fs::read_to_string(
+            "fixtures/ast/sherlock-2026-01-opencover-insured-vaults/TransientSlot.json",
+        )
+        .unwrap();
+        let mut nodes = Vec::new();
+        collect_nodes(
+            &serde_json::from_str(&content).unwrap(),
+            "UserDefinedValueTypeDefinition",
+            &mut nodes,
+        );
+        assert_eq!(nodes.len(), 5);
+
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let definitions: Vec<&UserDefinedValueTypeDefinition> = source_unit
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                SourceUnitNode::ContractDefinition(contract) => Some(&contract.nodes),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|node| match node {
+                ContractDefinitionNode::UserDefinedValueTypeDefinition(definition) => {
+                    Some(definition)
+                }
+                _ => None,
+            })
             .collect();
+        assert_eq!(definitions.len(), nodes.len());
+        assert_eq!(definitions[0].canonical_name, "TransientSlot.AddressSlot");
+        assert!(matches!(
+            &definitions[0].underlying_type,
+            TypeName::ElementaryTypeName(elementary) if elementary.name == ElementaryType::FixedBytes(32)
+        ));
+        for (definition, node) in definitions.iter().zip(&nodes) {
+            assert_eq!(definition.id, node["id"]);
+            assert_eq!(definition.name, node["name"]);
+            assert_eq!(definition.name_location, node["nameLocation"]);
+        }
+    }
+
+    #[test]
+    fn user_defined_type_name_round_trip() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ISynthToken.json").unwrap();
+        let mut nodes = Vec::new();
+        collect_nodes(
+            &serde_json::from_str(&content).unwrap(),
+            "UserDefinedTypeName",
+            &mut nodes,
+        );
+        assert!(!nodes.is_empty());
+        for node in nodes {
+            let type_name: TypeName = serde_json::from_value(node.clone()).unwrap();
+            let TypeName::UserDefinedTypeName(user_defined) = &type_name else {
+                panic!("expected a user-defined type name");
+            };
+            assert!(user_defined.referenced_declaration.is_some());
+            assert_eq!(serde_json::to_value(&type_name).unwrap(), node);
+        }
+
+        let legacy = serde_json::json!({
+            "contractScope": null,
+            "id": 4,
+            "name": "Token",
+            "nodeType": "UserDefinedTypeName",
+            "referencedDeclaration": 2,
+            "src": "40:5:0",
+            "typeDescriptions": {"typeIdentifier": "t_contract$_Token_$2", "typeString": "contract Token"}
+        });
+        let type_name: TypeName = serde_json::from_value(legacy).unwrap();
+        let TypeName::UserDefinedTypeName(user_defined) = type_name else {
+            panic!("expected a user-defined type name");
+        };
+        assert_eq!(user_defined.name.as_deref(), Some("Token"));
+        assert_eq!(user_defined.referenced_declaration, Some(2));
+        assert!(user_defined.path_node.is_none());
+    }
 
-        entries.par_iter().for_each(|entry| {
-            let content = fs::read_to_string(entry.path()).expect("Failed to read fixture file");
+    fn collect_nodes(value: &Value, node_type: &str, nodes: &mut Vec<Value>) {
+        match value {
+            Value::Object(object) => {
+                if object.get("nodeType").and_then(Value::as_str) == Some(node_type) {
+                    nodes.push(value.clone());
+                }
+                for value in object.values() {
+                    collect_nodes(value, node_type, nodes);
+                }
+            }
+            Value::Array(items) => {
+                for value in items {
+                    collect_nodes(value, node_type, nodes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn fixtures() {
+        fixture_paths().par_iter().for_each(|path| {
+            let content = fs::read_to_string(path).expect("Failed to read fixture file");
             let result: Result<SourceUnit, serde_json::Error> = serde_json::from_str(&content);
-            if let Err(_) = result {
+            if result.is_err() {
                 let error_msg = find_deserialization_error(&content);
-                panic!("Failed to parse {:?}: {}", entry.path(), error_msg);
+                panic!("Failed to parse {:?}: {}", path, error_msg);
             }
         });
     }
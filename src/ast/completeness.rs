@@ -0,0 +1,242 @@
+//! Checks of abstract contracts and interfaces.
+//!
+//! The compiler marks each contract as `fullyImplemented` or not, but does
+//! not say which functions are missing. [`check_completeness`] finds them by
+//! following the inheritance of each contract, and checks that the recorded
+//! flag and the rules for interfaces agree with what it finds.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::*;
+
+/// A contract whose declarations do not match its kind or its
+/// `fullyImplemented` flag.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CompletenessIssue {
+    /// The `fullyImplemented` flag disagrees with the functions found to be
+    /// unimplemented.
+    #[error("contract {contract} is recorded as fullyImplemented={recorded}")]
+    FullyImplementedMismatch { contract: i64, recorded: bool },
+
+    /// A contract that is not abstract has unimplemented functions.
+    #[error("contract {0} is not abstract but has unimplemented functions")]
+    NotAbstract(i64),
+
+    /// An interface declares a function with a body.
+    #[error("interface {contract} implements function {function}")]
+    InterfaceImplementation { contract: i64, function: i64 },
+
+    /// An interface declares a state variable, constructor, or modifier.
+    #[error("interface {contract} declares state or modifier {declaration}")]
+    InterfaceState { contract: i64, declaration: i64 },
+}
+
+/// The result of [`check_completeness`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompletenessReport {
+    /// The ids of the unimplemented functions of each contract that is not an
+    /// interface, by contract id. Contracts without any are left out.
+    pub unimplemented: BTreeMap<i64, Vec<i64>>,
+    /// The contracts whose declarations do not match their kind or flag.
+    pub issues: Vec<CompletenessIssue>,
+}
+
+/// Find the unimplemented functions of contracts and check interfaces.
+///
+/// A function is unimplemented if it has no body and neither a function nor
+/// a public state variable in the contract or its bases overrides it. Only
+/// the most derived declaration of such a function is listed.
+///
+/// Pass every source unit of a compilation, so that node ids are unique.
+/// Contracts whose bases are not all in the source units are not checked
+/// against their `fullyImplemented` flag.
+pub fn check_completeness(source_units: &[SourceUnit]) -> CompletenessReport {
+    let contracts: HashMap<i64, &ContractDefinition> = source_units
+        .iter()
+        .flat_map(|source_unit| &source_unit.nodes)
+        .filter_map(|node| match node {
+            SourceUnitNode::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+        .map(|contract| (contract.id, contract))
+        .collect();
+
+    let mut report = CompletenessReport::default();
+    for source_unit in source_units {
+        for node in &source_unit.nodes {
+            let SourceUnitNode::ContractDefinition(contract) = node else {
+                continue;
+            };
+            if contract.contract_kind == ContractKind::Interface {
+                check_interface(contract, &mut report.issues);
+                continue;
+            }
+
+            let bases: Vec<&ContractDefinition> = contract
+                .linearized_base_contracts
+                .iter()
+                .filter_map(|id| contracts.get(id).copied())
+                .collect();
+            let unimplemented = unimplemented_functions(&bases);
+
+            if contract.contract_kind == ContractKind::Contract
+                && !contract.r#abstract
+                && !unimplemented.is_empty()
+            {
+                report
+                    .issues
+                    .push(CompletenessIssue::NotAbstract(contract.id));
+            }
+            if bases.len() == contract.linearized_base_contracts.len()
+                && contract.fully_implemented != unimplemented.is_empty()
+            {
+                report
+                    .issues
+                    .push(CompletenessIssue::FullyImplementedMismatch {
+                        contract: contract.id,
+                        recorded: contract.fully_implemented,
+                    });
+            }
+            if !unimplemented.is_empty() {
+                report.unimplemented.insert(contract.id, unimplemented);
+            }
+        }
+    }
+    report
+}
+
+/// The functions without a body that nothing in `contracts` overrides.
+fn unimplemented_functions(contracts: &[&ContractDefinition]) -> Vec<i64> {
+    let mut overridden: HashSet<i64> = HashSet::new();
+    let mut declarations = Vec::new();
+    for contract in contracts {
+        for node in &contract.nodes {
+            match node {
+                ContractDefinitionNode::FunctionDefinition(function) => {
+                    overridden.extend(function.base_functions.iter().flatten());
+                    if !function.implemented {
+                        declarations.push(function.id);
+                    }
+                }
+                ContractDefinitionNode::VariableDeclaration(variable) => {
+                    overridden.extend(variable.base_functions.iter().flatten());
+                }
+                _ => {}
+            }
+        }
+    }
+    declarations
+        .into_iter()
+        .filter(|id| !overridden.contains(id))
+        .collect()
+}
+
+fn check_interface(contract: &ContractDefinition, issues: &mut Vec<CompletenessIssue>) {
+    for node in &contract.nodes {
+        match node {
+            ContractDefinitionNode::FunctionDefinition(function)
+                if function.implemented || function.kind == FunctionKind::Constructor =>
+            {
+                issues.push(CompletenessIssue::InterfaceImplementation {
+                    contract: contract.id,
+                    function: function.id,
+                });
+            }
+            ContractDefinitionNode::VariableDeclaration(VariableDeclaration { id, .. })
+            | ContractDefinitionNode::ModifierDefinition(ModifierDefinition { id, .. }) => {
+                issues.push(CompletenessIssue::InterfaceState {
+                    contract: contract.id,
+                    declaration: *id,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn fixtures_are_consistent() {
+        crate::ast::all_fixtures()
+            .into_par_iter()
+            .for_each(|(path, source_unit)| {
+                let report = check_completeness(&[source_unit]);
+                assert!(report.issues.is_empty(), "{:?}: {:?}", path, report.issues);
+            });
+    }
+
+    #[test]
+    fn lists_unimplemented_functions() {
+        let function = |id: i64, implemented: bool, bases: &[i64]| {
+            ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                id,
+                implemented,
+                base_functions: (!bases.is_empty()).then(|| bases.to_vec()),
+                ..Default::default()
+            })
+        };
+        let contract =
+            |id: i64, bases: &[i64], nodes: Vec<ContractDefinitionNode>| ContractDefinition {
+                id,
+                linearized_base_contracts: bases.to_vec(),
+                nodes,
+                ..Default::default()
+            };
+        let source_unit = SourceUnit {
+            nodes: vec![
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    contract_kind: ContractKind::Interface,
+                    ..contract(
+                        10,
+                        &[10],
+                        vec![
+                            function(1, false, &[]),
+                            function(2, false, &[]),
+                            function(3, true, &[]),
+                        ],
+                    )
+                }),
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    r#abstract: true,
+                    ..contract(20, &[20, 10], vec![function(4, true, &[1])])
+                }),
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    fully_implemented: true,
+                    ..contract(
+                        30,
+                        &[30, 20, 10],
+                        vec![ContractDefinitionNode::VariableDeclaration(
+                            VariableDeclaration {
+                                id: 5,
+                                base_functions: Some(vec![2]),
+                                ..Default::default()
+                            },
+                        )],
+                    )
+                }),
+                SourceUnitNode::ContractDefinition(contract(40, &[40, 10], vec![])),
+            ],
+            ..Default::default()
+        };
+        let report = check_completeness(&[source_unit]);
+
+        assert_eq!(report.unimplemented[&20], [2]);
+        assert!(!report.unimplemented.contains_key(&30));
+        assert_eq!(report.unimplemented[&40], [1, 2]);
+        assert_eq!(
+            report.issues,
+            [
+                CompletenessIssue::InterfaceImplementation {
+                    contract: 10,
+                    function: 3
+                },
+                CompletenessIssue::NotAbstract(40),
+            ]
+        );
+    }
+}
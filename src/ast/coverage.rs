@@ -0,0 +1,335 @@
+//! What the typed AST drops from the ASTs of a codebase.
+//!
+//! Deserializing into the typed nodes silently ignores fields they do not
+//! model, and node types they do not know make a whole source unit fail. A
+//! [`CoverageReport`] reads raw AST JSON, reads every node in it as its typed
+//! node and serializes it back, and records the node types and fields that
+//! did not survive, so users can see what the crate loses for their code.
+//!
+//! Nodes are checked innermost first, the way [`parse_lenient`] reads them,
+//! so a node that fails is replaced by a placeholder in its parent and does
+//! not hide what the parent drops.
+//!
+//! [`parse_lenient`]: super::parse_lenient
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::lenient::{prune, reserialize_node};
+
+/// The node types and fields of ASTs that the typed nodes do not keep.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// The number of source units read.
+    pub source_units: usize,
+    /// Node types the typed AST has no node for, with the number of nodes of
+    /// each type.
+    pub unknown_node_types: BTreeMap<String, usize>,
+    /// Node types the typed AST has but failed to read, with the number of
+    /// nodes that failed and the first error.
+    pub failed_nodes: BTreeMap<String, (usize, String)>,
+    /// Fields the typed nodes drop, by node type and then by field, with the
+    /// number of nodes that had the field. Fields of nested objects that are
+    /// not nodes are named by their path, such as `typeDescriptions.typeString`.
+    pub dropped_fields: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// An error reading the ASTs of a directory.
+#[derive(Debug, thiserror::Error)]
+pub enum CoverageError {
+    /// A file or directory could not be read.
+    #[error("failed to read {}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+
+    /// A file is not JSON.
+    #[error("{} is not JSON: {error}", path.display())]
+    Json {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+}
+
+impl CoverageReport {
+    /// Report on every `.json` file below `directory`, each holding the AST
+    /// of one source unit.
+    pub fn from_directory(directory: impl AsRef<Path>) -> Result<Self, CoverageError> {
+        let mut report = Self::default();
+        let mut directories = vec![directory.as_ref().to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            let io_error = |error| CoverageError::Io {
+                path: directory.clone(),
+                error,
+            };
+            let mut entries = fs::read_dir(&directory)
+                .map_err(io_error)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(io_error)?;
+            entries.sort();
+            for path in entries {
+                if path.is_dir() {
+                    directories.push(path);
+                } else if path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+                {
+                    let content = fs::read_to_string(&path).map_err(|error| CoverageError::Io {
+                        path: path.clone(),
+                        error,
+                    })?;
+                    let value = serde_json::from_str(&content)
+                        .map_err(|error| CoverageError::Json { path, error })?;
+                    report.add(&value);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Add the AST of a source unit to the report.
+    pub fn add(&mut self, source_unit: &Value) {
+        self.source_units += 1;
+        // Reading each node on its own reads every subtree once per ancestor,
+        // so the whole tree is read at once unless some node in it fails.
+        if let Some(node_type) = source_unit.get("nodeType").and_then(Value::as_str)
+            && let Some(Ok(typed)) = reserialize_node(node_type, source_unit)
+        {
+            self.compare_node(node_type, source_unit, &typed, true);
+            return;
+        }
+        let mut value = source_unit.clone();
+        prune(&mut value, String::new(), &mut |_, node_type, node| {
+            self.check(node_type, node)
+        });
+    }
+
+    /// Whether the typed AST kept everything it read.
+    pub fn is_complete(&self) -> bool {
+        self.unknown_node_types.is_empty()
+            && self.failed_nodes.is_empty()
+            && self.dropped_fields.is_empty()
+    }
+
+    /// Record what the typed AST loses of a node, but not of the nodes under
+    /// it. Returns whether the node could not be read at all.
+    fn check(&mut self, node_type: &str, raw: &Value) -> bool {
+        match reserialize_node(node_type, raw) {
+            None => {
+                *self
+                    .unknown_node_types
+                    .entry(node_type.to_string())
+                    .or_default() += 1;
+                true
+            }
+            Some(Err(error)) => {
+                self.failed_nodes
+                    .entry(node_type.to_string())
+                    .or_insert_with(|| (0, error.to_string()))
+                    .0 += 1;
+                true
+            }
+            Some(Ok(typed)) => {
+                self.compare_node(node_type, raw, &typed, false);
+                false
+            }
+        }
+    }
+
+    /// Record the fields of a node that its typed form does not have, and
+    /// with `nested`, those of the nodes under it.
+    fn compare_node(&mut self, node_type: &str, raw: &Value, typed: &Value, nested: bool) {
+        let mut dropped = Vec::new();
+        self.compare(raw, typed, "", nested, &mut dropped);
+        for field in dropped {
+            *self
+                .dropped_fields
+                .entry(node_type.to_string())
+                .or_default()
+                .entry(field)
+                .or_default() += 1;
+        }
+    }
+
+    /// Collect the fields of `raw` that `typed` does not have, naming them
+    /// from `prefix`.
+    ///
+    /// Objects that are not nodes are compared field by field. Nodes belong
+    /// to their own node type, so they are compared on their own if `nested`,
+    /// or skipped. Fields that are `null` or empty carry nothing to lose, such
+    /// as the empty `nodes` that Foundry adds to every node.
+    fn compare(
+        &mut self,
+        raw: &Value,
+        typed: &Value,
+        prefix: &str,
+        nested: bool,
+        dropped: &mut Vec<String>,
+    ) {
+        match (raw, typed) {
+            (Value::Object(raw), Value::Object(typed)) => {
+                for (field, value) in raw {
+                    let empty = match value {
+                        Value::Null => true,
+                        Value::Array(items) => items.is_empty(),
+                        Value::Object(object) => object.is_empty(),
+                        _ => false,
+                    };
+                    if field == "nodeType" || empty {
+                        continue;
+                    }
+                    let path = format!("{}{}", prefix, field);
+                    match typed.get(field) {
+                        None => {
+                            if !dropped.contains(&path) {
+                                dropped.push(path);
+                            }
+                        }
+                        Some(typed) => self.compare_value(value, typed, &path, nested, dropped),
+                    }
+                }
+            }
+            (Value::Array(raw), Value::Array(typed)) => {
+                for (raw, typed) in raw.iter().zip(typed) {
+                    self.compare_value(raw, typed, prefix, nested, dropped);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Compare the value of a field, or an item of an array field.
+    fn compare_value(
+        &mut self,
+        raw: &Value,
+        typed: &Value,
+        path: &str,
+        nested: bool,
+        dropped: &mut Vec<String>,
+    ) {
+        match raw.get("nodeType").and_then(Value::as_str) {
+            Some(node_type) if nested => self.compare_node(node_type, raw, typed, true),
+            Some(_) => {}
+            None if raw.is_array() => self.compare(raw, typed, path, nested, dropped),
+            None => self.compare(raw, typed, &format!("{}.", path), nested, dropped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn fixtures_are_covered() {
+        let report = CoverageReport::from_directory(
+            "fixtures/ast/codearena-2026-01-olas-autonolas-governance",
+        )
+        .unwrap();
+        assert!(report.source_units > 0);
+        assert!(report.is_complete(), "{:#?}", report);
+
+        crate::ast::fixture_paths().par_iter().for_each(|path| {
+            let content = fs::read_to_string(path).expect("Failed to read fixture file");
+            let mut report = CoverageReport::default();
+            report.add(&serde_json::from_str(&content).unwrap());
+            assert!(report.is_complete(), "{:?}: {:#?}", path, report);
+        });
+    }
+
+    #[test]
+    fn reports_unknown_nodes_and_fields() {
+        let mut report = CoverageReport::default();
+        report.add(&json!({
+            "absolutePath": "A.sol",
+            "exportedSymbols": {},
+            "id": 3,
+            "nodeType": "SourceUnit",
+            "nodes": [
+                {
+                    "id": 1,
+                    "literals": ["solidity", "^0.8.0"],
+                    "nodeType": "PragmaDirective",
+                    "src": "0:23:0",
+                    "futureField": { "nested": true }
+                },
+                {
+                    "id": 2,
+                    "nodeType": "FutureDefinition",
+                    "src": "24:10:0"
+                },
+                {
+                    "id": 4,
+                    "nodeType": "EnumValue",
+                    "src": "35:1:0"
+                }
+            ],
+            "src": "0:36:0",
+            "experimentalSolidity": false
+        }));
+        assert_eq!(report.source_units, 1);
+        assert_eq!(
+            report.unknown_node_types,
+            BTreeMap::from([("FutureDefinition".to_string(), 1)])
+        );
+        assert_eq!(report.failed_nodes["EnumValue"].0, 1);
+        assert_eq!(
+            report.dropped_fields,
+            BTreeMap::from([
+                (
+                    "PragmaDirective".to_string(),
+                    BTreeMap::from([("futureField".to_string(), 1)])
+                ),
+                (
+                    "SourceUnit".to_string(),
+                    BTreeMap::from([("experimentalSolidity".to_string(), 1)])
+                ),
+            ])
+        );
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    fn reports_dropped_fields_of_nested_nodes() {
+        let pragma = |id: i64| {
+            json!({
+                "id": id,
+                "literals": ["solidity", "^0.8.0"],
+                "nodeType": "PragmaDirective",
+                "src": "0:23:0",
+                "futureField": [1]
+            })
+        };
+        let mut report = CoverageReport::default();
+        report.add(&json!({
+            "absolutePath": "A.sol",
+            "exportedSymbols": {},
+            "id": 3,
+            "nodeType": "SourceUnit",
+            "nodes": [pragma(1), pragma(2)],
+            "src": "0:36:0",
+            "experimentalSolidity": false
+        }));
+        assert!(report.unknown_node_types.is_empty());
+        assert!(report.failed_nodes.is_empty());
+        assert_eq!(
+            report.dropped_fields,
+            BTreeMap::from([
+                (
+                    "PragmaDirective".to_string(),
+                    BTreeMap::from([("futureField".to_string(), 2)])
+                ),
+                (
+                    "SourceUnit".to_string(),
+                    BTreeMap::from([("experimentalSolidity".to_string(), 1)])
+                ),
+            ])
+        );
+    }
+}
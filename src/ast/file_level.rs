@@ -0,0 +1,210 @@
+//! Functions and constants declared outside of contracts.
+//!
+//! Free functions and file-level constants are compiled into every contract
+//! that uses them, directly or through one another. [`file_level_usage`]
+//! finds which contracts those are, for example to know which contracts
+//! change when a shared helper does.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::visit::{self, Visitor};
+use super::*;
+
+impl SourceUnit {
+    /// The functions declared at file level.
+    pub fn free_functions(&self) -> impl Iterator<Item = &FunctionDefinition> {
+        self.nodes.iter().filter_map(|node| match node {
+            SourceUnitNode::FunctionDefinition(function) => Some(function),
+            _ => None,
+        })
+    }
+
+    /// The constants declared at file level.
+    pub fn file_constants(&self) -> impl Iterator<Item = &VariableDeclaration> {
+        self.nodes.iter().filter_map(|node| match node {
+            SourceUnitNode::VariableDeclaration(variable) => Some(variable),
+            _ => None,
+        })
+    }
+}
+
+/// The free functions and file-level constants used by each contract,
+/// returned by [`file_level_usage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileLevelUsage {
+    /// The ids of the free functions used by each contract, by contract id.
+    pub functions: BTreeMap<i64, BTreeSet<i64>>,
+    /// The ids of the file-level constants used by each contract, by
+    /// contract id.
+    pub constants: BTreeMap<i64, BTreeSet<i64>>,
+}
+
+impl FileLevelUsage {
+    /// The ids of the contracts that use a free function or file-level
+    /// constant.
+    pub fn contracts_using(&self, declaration: i64) -> impl Iterator<Item = i64> + '_ {
+        self.functions
+            .iter()
+            .chain(&self.constants)
+            .filter(move |(_, used)| used.contains(&declaration))
+            .map(|(contract, _)| *contract)
+    }
+}
+
+/// Find the free functions and file-level constants that each contract uses.
+///
+/// A contract uses a declaration when it refers to it, or refers to a free
+/// function or constant that uses it in turn. Contracts that use none are
+/// left out.
+///
+/// Pass every source unit of a compilation, so that node ids are unique and
+/// declarations are found wherever they are declared.
+pub fn file_level_usage(source_units: &[SourceUnit]) -> FileLevelUsage {
+    // The declarations each free function and constant refers to.
+    let mut file_level: HashMap<i64, BTreeSet<i64>> = HashMap::new();
+    let mut functions = BTreeSet::new();
+    for source_unit in source_units {
+        for function in source_unit.free_functions() {
+            let mut references = References::default();
+            references.visit_function_definition(function);
+            file_level.insert(function.id, references.0);
+            functions.insert(function.id);
+        }
+        for constant in source_unit.file_constants() {
+            let mut references = References::default();
+            references.visit_variable_declaration(constant);
+            file_level.insert(constant.id, references.0);
+        }
+    }
+
+    let mut usage = FileLevelUsage::default();
+    for source_unit in source_units {
+        for node in &source_unit.nodes {
+            let SourceUnitNode::ContractDefinition(contract) = node else {
+                continue;
+            };
+            let mut references = References::default();
+            references.visit_contract_definition(contract);
+
+            let mut used = BTreeSet::new();
+            let mut pending: Vec<i64> = references.0.into_iter().collect();
+            while let Some(id) = pending.pop() {
+                if let Some(references) = file_level.get(&id)
+                    && used.insert(id)
+                {
+                    pending.extend(references);
+                }
+            }
+
+            let (used_functions, used_constants): (BTreeSet<i64>, BTreeSet<i64>) =
+                used.into_iter().partition(|id| functions.contains(id));
+            if !used_functions.is_empty() {
+                usage.functions.insert(contract.id, used_functions);
+            }
+            if !used_constants.is_empty() {
+                usage.constants.insert(contract.id, used_constants);
+            }
+        }
+    }
+    usage
+}
+
+/// Collects the declarations that a subtree refers to.
+#[derive(Default)]
+struct References(BTreeSet<i64>);
+
+impl Visitor for References {
+    fn visit_identifier(&mut self, node: &Identifier) {
+        self.0.extend(node.referenced_declaration);
+    }
+
+    fn visit_identifier_path(&mut self, node: &IdentifierPath) {
+        self.0.extend(node.referenced_declaration);
+    }
+
+    fn visit_member_access(&mut self, node: &MemberAccess) {
+        self.0.extend(node.referenced_declaration);
+        visit::walk_member_access(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn fixture_free_functions() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/TestUtils.json").unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let free_functions: Vec<&FunctionDefinition> = source_unit.free_functions().collect();
+        assert!(!free_functions.is_empty());
+        assert!(
+            free_functions
+                .iter()
+                .all(|function| function.kind == FunctionKind::FreeFunction)
+        );
+    }
+
+    #[test]
+    fn transitive_usage() {
+        let call = |id: i64| {
+            Box::new(Expression::Identifier(Identifier {
+                referenced_declaration: Some(id),
+                ..Default::default()
+            }))
+        };
+        let body = |id: i64| Block {
+            statements: vec![Statement::Return(Return {
+                expression: Some(call(id)),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let source_unit = SourceUnit {
+            nodes: vec![
+                SourceUnitNode::VariableDeclaration(VariableDeclaration {
+                    id: 1,
+                    constant: true,
+                    ..Default::default()
+                }),
+                SourceUnitNode::FunctionDefinition(FunctionDefinition {
+                    id: 2,
+                    kind: FunctionKind::FreeFunction,
+                    body: Some(body(1)),
+                    ..Default::default()
+                }),
+                SourceUnitNode::FunctionDefinition(FunctionDefinition {
+                    id: 3,
+                    kind: FunctionKind::FreeFunction,
+                    body: Some(body(2)),
+                    ..Default::default()
+                }),
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    id: 10,
+                    nodes: vec![ContractDefinitionNode::FunctionDefinition(
+                        FunctionDefinition {
+                            id: 11,
+                            body: Some(body(3)),
+                            ..Default::default()
+                        },
+                    )],
+                    ..Default::default()
+                }),
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    id: 20,
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let usage = file_level_usage(&[source_unit]);
+
+        assert_eq!(usage.functions[&10], BTreeSet::from([2, 3]));
+        assert_eq!(usage.constants[&10], BTreeSet::from([1]));
+        assert!(!usage.functions.contains_key(&20));
+        assert_eq!(usage.contracts_using(1).collect::<Vec<_>>(), [10]);
+    }
+}
@@ -0,0 +1,251 @@
+//! Generate AST fixtures from Solidity snippets.
+//!
+//! The typed AST is tested against fixture files produced by real compilers.
+//! [`FixtureGenerator`] compiles a corpus of snippets with several compiler
+//! versions and collects one example of every node type each compiler emits,
+//! so node types and fields the typed AST does not cover yet can be found and
+//! turned into fixtures.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::compiler::Compiler;
+use crate::standard_json_input::{OutputSelector, StandardJsonInput};
+
+/// Compiles snippets with several compilers and collects AST nodes by type.
+///
+/// Each compiler is given a label, such as its version, which is recorded
+/// with every fixture it produced.
+pub struct FixtureGenerator<C> {
+    compilers: Vec<(String, C)>,
+    snippets: Vec<(String, String)>,
+}
+
+impl<C: Compiler> FixtureGenerator<C> {
+    /// Create a generator without compilers or snippets.
+    pub fn new() -> Self {
+        Self {
+            compilers: Vec::new(),
+            snippets: Vec::new(),
+        }
+    }
+
+    /// Add a compiler, labelled for example with its version.
+    pub fn compiler(mut self, label: impl Into<String>, compiler: C) -> Self {
+        self.compilers.push((label.into(), compiler));
+        self
+    }
+
+    /// Add a Solidity snippet to compile.
+    ///
+    /// The name is used as the source file name and in fixture file names.
+    pub fn snippet(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.snippets.push((name.into(), source.into()));
+        self
+    }
+
+    /// Compile every snippet with every compiler and collect the fixtures.
+    ///
+    /// Each snippet is compiled on its own, so a snippet that a compiler
+    /// rejects (for example because it uses newer syntax) only loses the
+    /// fixtures of that snippet. Its diagnostics are recorded as a failure.
+    pub fn generate(&self) -> Result<Fixtures, C::Error> {
+        let mut fixtures = Fixtures::default();
+        for (label, compiler) in &self.compilers {
+            for (name, source) in &self.snippets {
+                let mut input = StandardJsonInput::new().add_source(name, source);
+                input.settings.select(OutputSelector::Ast).for_file("*");
+
+                let output = compiler.compile(&input)?;
+                if output.has_errors() {
+                    let message = output
+                        .errors
+                        .iter()
+                        .map(|error| error.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    fixtures.failures.push(Failure {
+                        compiler: label.clone(),
+                        snippet: name.clone(),
+                        message,
+                    });
+                    continue;
+                }
+
+                for source in output.sources.values() {
+                    let Some(ast) = &source.ast else {
+                        continue;
+                    };
+                    if let Err(error) = source.source_unit() {
+                        fixtures.failures.push(Failure {
+                            compiler: label.clone(),
+                            snippet: name.clone(),
+                            message: error.to_string(),
+                        });
+                    }
+                    let mut found = BTreeMap::new();
+                    collect_nodes(ast, &mut found);
+                    for (node_type, node) in found {
+                        fixtures.nodes.entry(node_type).or_default().push(Fixture {
+                            compiler: label.clone(),
+                            snippet: name.clone(),
+                            node: node.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(fixtures)
+    }
+}
+
+impl<C: Compiler> Default for FixtureGenerator<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The fixtures collected by [`FixtureGenerator::generate`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fixtures {
+    /// Example nodes keyed by `nodeType`, one per compiler and snippet.
+    pub nodes: BTreeMap<String, Vec<Fixture>>,
+    /// Snippets that did not compile, or whose AST the typed nodes could not
+    /// parse.
+    pub failures: Vec<Failure>,
+}
+
+impl Fixtures {
+    /// The node types that were emitted by at least one compiler.
+    pub fn node_types(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(String::as_str)
+    }
+
+    /// Write each fixture to `<dir>/<nodeType>/<compiler>/<snippet>.json`.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        for (node_type, fixtures) in &self.nodes {
+            for fixture in fixtures {
+                let dir = dir.as_ref().join(node_type).join(&fixture.compiler);
+                fs::create_dir_all(&dir)?;
+                let json = serde_json::to_string_pretty(&fixture.node)?;
+                fs::write(dir.join(format!("{}.json", fixture.snippet)), json)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An example AST node and where it came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fixture {
+    /// The label of the compiler that emitted the node.
+    pub compiler: String,
+    /// The name of the snippet the node was found in.
+    pub snippet: String,
+    /// The node as raw JSON.
+    pub node: Value,
+}
+
+/// A snippet that did not produce a usable AST with one compiler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The label of the compiler.
+    pub compiler: String,
+    /// The name of the snippet.
+    pub snippet: String,
+    /// The compiler diagnostics or the deserialization error.
+    pub message: String,
+}
+
+/// Collect the first node of each type in the tree.
+fn collect_nodes<'a>(value: &'a Value, found: &mut BTreeMap<String, &'a Value>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(node_type) = object.get("nodeType").and_then(Value::as_str) {
+                found.entry(node_type.to_string()).or_insert(value);
+            }
+            object
+                .values()
+                .for_each(|value| collect_nodes(value, found));
+        }
+        Value::Array(items) => items.iter().for_each(|value| collect_nodes(value, found)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::standard_json_input::SourceContent;
+    use crate::standard_json_output::{Error, Source, StandardJsonOutput};
+
+    /// A compiler that returns a fixed AST, or rejects sources with `error`.
+    struct Canned(Value);
+
+    impl Compiler for Canned {
+        type Error = ();
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            let mut output = StandardJsonOutput::default();
+            for (path, source) in &input.sources {
+                let SourceContent::Content { content } = &source.content else {
+                    continue;
+                };
+                if content.contains("error") {
+                    output.errors.push(Error {
+                        message: "Expected pragma.".to_string(),
+                        ..Default::default()
+                    });
+                    continue;
+                }
+                output.sources.insert(
+                    path.to_string_lossy().into_owned(),
+                    Source {
+                        id: 0,
+                        ast: Some(self.0.clone()),
+                    },
+                );
+            }
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn collects_nodes_by_type() {
+        let ast = json!({
+            "nodeType": "SourceUnit",
+            "id": 1,
+            "absolutePath": "A.sol",
+            "exportedSymbols": {},
+            "src": "0:10:0",
+            "nodes": [
+                {"nodeType": "PragmaDirective", "id": 0, "literals": ["solidity"], "src": "0:5:0"},
+                {"nodeType": "FancyNewDirective", "id": 2, "src": "5:5:0"}
+            ]
+        });
+        let fixtures = FixtureGenerator::new()
+            .compiler("0.8.30", Canned(ast))
+            .snippet("A", "pragma solidity;")
+            .snippet("B", "error")
+            .generate()
+            .unwrap();
+
+        let node_types: Vec<&str> = fixtures.node_types().collect();
+        assert_eq!(
+            node_types,
+            vec!["FancyNewDirective", "PragmaDirective", "SourceUnit"]
+        );
+        assert_eq!(fixtures.nodes["PragmaDirective"][0].compiler, "0.8.30");
+
+        // One failure for the snippet that did not compile, and one for the
+        // node type the typed AST does not know.
+        assert_eq!(fixtures.failures.len(), 2);
+        assert!(fixtures.failures.iter().any(|f| f.snippet == "B"));
+    }
+}
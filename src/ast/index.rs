@@ -0,0 +1,845 @@
+//! Lookup of declarations by node id.
+//!
+//! References in the AST, such as the `referencedDeclaration` of an
+//! [`Identifier`](super::Identifier) or the `baseFunctions` of a function,
+//! hold the id of the node they refer to. An [`AstIndex`] maps the ids of
+//! all declarations of a compilation to the nodes, so that such references
+//! resolve in constant time. It also maps the id of every other node to a
+//! [`Node`], for lookups of nodes that are not declarations.
+//!
+//! Names can also be looked up the way a source sees them, following
+//! `import {A as B}` and `import * as M` back to the original declarations,
+//! so that a path such as `M.A.f` resolves to the function it names.
+
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+/// A node that references can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Declaration<'a> {
+    /// A source unit, which an import directive refers to.
+    SourceUnit(&'a SourceUnit),
+    /// An import directive with a unit alias, as `M` in
+    /// `import * as M from "file";`.
+    ImportDirective(&'a ImportDirective),
+    /// A contract, interface, or library.
+    ContractDefinition(&'a ContractDefinition),
+    /// A function, including constructors, fallback and receive functions.
+    FunctionDefinition(&'a FunctionDefinition),
+    /// A modifier.
+    ModifierDefinition(&'a ModifierDefinition),
+    /// An event.
+    EventDefinition(&'a EventDefinition),
+    /// A custom error.
+    ErrorDefinition(&'a ErrorDefinition),
+    /// A struct.
+    StructDefinition(&'a StructDefinition),
+    /// An enum.
+    EnumDefinition(&'a EnumDefinition),
+    /// A value of an enum.
+    EnumValue(&'a EnumValue),
+    /// A user-defined value type, as in `type Price is uint256;`.
+    UserDefinedValueTypeDefinition(&'a UserDefinedValueTypeDefinition),
+    /// A state variable, constant, local variable, or parameter.
+    VariableDeclaration(&'a VariableDeclaration),
+}
+
+impl<'a> Declaration<'a> {
+    /// The id of the node.
+    pub fn id(&self) -> i64 {
+        match self {
+            Declaration::SourceUnit(node) => node.id,
+            Declaration::ImportDirective(node) => node.id,
+            Declaration::ContractDefinition(node) => node.id,
+            Declaration::FunctionDefinition(node) => node.id,
+            Declaration::ModifierDefinition(node) => node.id,
+            Declaration::EventDefinition(node) => node.id,
+            Declaration::ErrorDefinition(node) => node.id,
+            Declaration::StructDefinition(node) => node.id,
+            Declaration::EnumDefinition(node) => node.id,
+            Declaration::EnumValue(node) => node.id,
+            Declaration::UserDefinedValueTypeDefinition(node) => node.id,
+            Declaration::VariableDeclaration(node) => node.id,
+        }
+    }
+
+    /// The declared name.
+    ///
+    /// Source units have none, and imports only have one when the unit is
+    /// imported under an alias.
+    pub fn name(&self) -> Option<&'a str> {
+        match self {
+            Declaration::SourceUnit(_) => None,
+            Declaration::ImportDirective(node) => node.unit_alias.as_deref(),
+            Declaration::ContractDefinition(node) => Some(&node.name),
+            Declaration::FunctionDefinition(node) => Some(&node.name),
+            Declaration::ModifierDefinition(node) => Some(&node.name),
+            Declaration::EventDefinition(node) => Some(&node.name),
+            Declaration::ErrorDefinition(node) => Some(&node.name),
+            Declaration::StructDefinition(node) => Some(&node.name),
+            Declaration::EnumDefinition(node) => Some(&node.name),
+            Declaration::EnumValue(node) => Some(&node.name),
+            Declaration::UserDefinedValueTypeDefinition(node) => Some(&node.name),
+            Declaration::VariableDeclaration(node) => Some(&node.name),
+        }
+    }
+
+    /// The source range of the declared name, if the compiler reports one.
+    ///
+    /// Source units have none, and neither do imports without an alias.
+    pub fn name_src(&self) -> Option<SourceLocation> {
+        let name_location = match self {
+            Declaration::SourceUnit(_) => return None,
+            Declaration::ImportDirective(node) => &node.name_location,
+            Declaration::ContractDefinition(node) => &node.name_location,
+            Declaration::FunctionDefinition(node) => &node.name_location,
+            Declaration::ModifierDefinition(node) => &node.name_location,
+            Declaration::EventDefinition(node) => &node.name_location,
+            Declaration::ErrorDefinition(node) => &node.name_location,
+            Declaration::StructDefinition(node) => &node.name_location,
+            Declaration::EnumDefinition(node) => &node.name_location,
+            Declaration::EnumValue(node) => &node.name_location,
+            Declaration::UserDefinedValueTypeDefinition(node) => &node.name_location,
+            Declaration::VariableDeclaration(node) => &node.name_location,
+        };
+        name_location.parse().ok()
+    }
+
+    /// The source range of the node.
+    pub fn src(&self) -> &'a SourceLocation {
+        match self {
+            Declaration::SourceUnit(node) => &node.src,
+            Declaration::ImportDirective(node) => &node.src,
+            Declaration::ContractDefinition(node) => &node.src,
+            Declaration::FunctionDefinition(node) => &node.src,
+            Declaration::ModifierDefinition(node) => &node.src,
+            Declaration::EventDefinition(node) => &node.src,
+            Declaration::ErrorDefinition(node) => &node.src,
+            Declaration::StructDefinition(node) => &node.src,
+            Declaration::EnumDefinition(node) => &node.src,
+            Declaration::EnumValue(node) => &node.src,
+            Declaration::UserDefinedValueTypeDefinition(node) => &node.src,
+            Declaration::VariableDeclaration(node) => &node.src,
+        }
+    }
+}
+
+/// The declarations of a compilation, by id.
+#[derive(Debug, Clone, Default)]
+pub struct AstIndex<'a> {
+    declarations: HashMap<i64, Declaration<'a>>,
+    nodes: HashMap<i64, Node<'a>>,
+    /// The id of the source unit of each declaration.
+    source_units: HashMap<i64, i64>,
+}
+
+impl<'a> AstIndex<'a> {
+    /// Index the declarations of source units.
+    ///
+    /// Pass every source unit of a compilation, so that references across
+    /// files resolve. Local variables and the parameters of functions,
+    /// modifiers, events, errors and catch clauses are indexed along with
+    /// the declarations at file and contract level.
+    pub fn new(source_units: &'a [SourceUnit]) -> Self {
+        let mut index = AstIndex::default();
+        for source_unit in source_units {
+            let mut collector = Collector {
+                index: &mut index,
+                source_unit: source_unit.id,
+            };
+            collector.insert(Declaration::SourceUnit(source_unit));
+            for node in &source_unit.nodes {
+                collector.collect_source_unit_node(node);
+            }
+            let root = Node::from(source_unit);
+            index
+                .nodes
+                .extend(root.descendants().into_iter().map(|node| (node.id(), node)));
+        }
+        index
+    }
+
+    /// The declaration with an id.
+    pub fn get(&self, id: i64) -> Option<Declaration<'a>> {
+        self.declarations.get(&id).copied()
+    }
+
+    /// The node with an id, of any type.
+    pub fn node(&self, id: i64) -> Option<Node<'a>> {
+        self.nodes.get(&id).copied()
+    }
+
+    /// The declaration a reference refers to, such as the
+    /// `referencedDeclaration` of an identifier.
+    ///
+    /// References to built-ins, such as `msg`, have negative ids and do not
+    /// resolve.
+    pub fn resolve(&self, reference: Option<i64>) -> Option<Declaration<'a>> {
+        reference.and_then(|id| self.get(id))
+    }
+
+    /// The contract with an id.
+    pub fn contract(&self, id: i64) -> Option<&'a ContractDefinition> {
+        match self.get(id)? {
+            Declaration::ContractDefinition(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The function with an id.
+    pub fn function(&self, id: i64) -> Option<&'a FunctionDefinition> {
+        match self.get(id)? {
+            Declaration::FunctionDefinition(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The variable with an id.
+    pub fn variable(&self, id: i64) -> Option<&'a VariableDeclaration> {
+        match self.get(id)? {
+            Declaration::VariableDeclaration(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The source unit that declares the node with an id.
+    pub fn source_unit(&self, id: i64) -> Option<&'a SourceUnit> {
+        match self.get(*self.source_units.get(&id)?)? {
+            Declaration::SourceUnit(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The declarations a name refers to at file level of a source unit.
+    ///
+    /// Imported names resolve to the declarations they were imported from,
+    /// through any number of files that import and re-export them, and a
+    /// unit alias such as `M` of `import * as M from "file";` resolves to the
+    /// import directive. A name can refer to several declarations when
+    /// functions are overloaded.
+    pub fn lookup(&self, source_unit: i64, name: &str) -> Vec<Declaration<'a>> {
+        let mut found = Vec::new();
+        self.lookup_in(source_unit, name, &mut HashSet::new(), &mut found);
+        dedup(found)
+    }
+
+    /// The declarations a path of names such as `M.A.f` refers to at file
+    /// level of a source unit.
+    ///
+    /// The first name is looked up as with [`AstIndex::lookup`]. Each
+    /// following name is looked up among the file-level declarations of an
+    /// aliased unit, the members of a contract and those it inherits, or the
+    /// values of an enum.
+    pub fn resolve_path(&self, source_unit: i64, path: &str) -> Vec<Declaration<'a>> {
+        let mut names = path.split('.');
+        let Some(first) = names.next() else {
+            return Vec::new();
+        };
+        let mut found = self.lookup(source_unit, first);
+        for name in names {
+            found = dedup(
+                found
+                    .into_iter()
+                    .flat_map(|declaration| self.members(declaration, name))
+                    .collect(),
+            );
+        }
+        found
+    }
+
+    fn lookup_in(
+        &self,
+        source_unit: i64,
+        name: &str,
+        visited: &mut HashSet<(i64, String)>,
+        found: &mut Vec<Declaration<'a>>,
+    ) {
+        if !visited.insert((source_unit, name.to_string())) {
+            return;
+        }
+        let Some(Declaration::SourceUnit(unit)) = self.get(source_unit) else {
+            return;
+        };
+        for node in &unit.nodes {
+            let SourceUnitNode::ImportDirective(import) = node else {
+                found.extend(file_level_declaration(node).filter(|d| d.name() == Some(name)));
+                continue;
+            };
+            if import.unit_alias.is_some() {
+                if import.unit_alias.as_deref() == Some(name) {
+                    found.push(Declaration::ImportDirective(import));
+                }
+            } else if import.symbol_aliases.is_empty() {
+                self.lookup_in(import.source_unit, name, visited, found);
+            } else {
+                for alias in &import.symbol_aliases {
+                    if alias.local.as_deref().unwrap_or(&alias.foreign.name) != name {
+                        continue;
+                    }
+                    match self.resolve(alias.foreign.referenced_declaration) {
+                        Some(declaration) => found.push(declaration),
+                        None => {
+                            self.lookup_in(import.source_unit, &alias.foreign.name, visited, found)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The declarations named `name` inside a declaration.
+    fn members(&self, declaration: Declaration<'a>, name: &str) -> Vec<Declaration<'a>> {
+        match declaration {
+            Declaration::ImportDirective(import) => self.lookup(import.source_unit, name),
+            Declaration::ContractDefinition(contract) => {
+                let bases: Vec<&ContractDefinition> =
+                    match contract.linearized_base_contracts.is_empty() {
+                        true => vec![contract],
+                        false => contract
+                            .linearized_base_contracts
+                            .iter()
+                            .filter_map(|id| self.contract(*id))
+                            .collect(),
+                    };
+                // A declaration hides those of its bases with the same
+                // parameter types, so overloads declared in bases stay
+                // visible.
+                let mut found: Vec<Declaration<'a>> = Vec::new();
+                for base in bases {
+                    let declared: Vec<_> = base
+                        .nodes
+                        .iter()
+                        .filter_map(contract_declaration)
+                        .filter(|d| d.name() == Some(name))
+                        .filter(|d| !found.iter().any(|hiding| hides(*hiding, *d)))
+                        .collect();
+                    found.extend(declared);
+                }
+                found
+            }
+            Declaration::EnumDefinition(definition) => definition
+                .members
+                .iter()
+                .filter(|value| value.name == name)
+                .map(Declaration::EnumValue)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The indexed declarations, in no particular order.
+    pub fn declarations(&self) -> impl Iterator<Item = Declaration<'a>> + '_ {
+        self.declarations.values().copied()
+    }
+
+    /// The number of indexed declarations.
+    pub fn len(&self) -> usize {
+        self.declarations.len()
+    }
+
+    /// Whether no declarations are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+}
+
+/// Remove repeated declarations, keeping the first of each.
+fn dedup(declarations: Vec<Declaration<'_>>) -> Vec<Declaration<'_>> {
+    let mut seen = HashSet::new();
+    declarations
+        .into_iter()
+        .filter(|declaration| seen.insert(declaration.id()))
+        .collect()
+}
+
+fn file_level_declaration(node: &SourceUnitNode) -> Option<Declaration<'_>> {
+    match node {
+        SourceUnitNode::ContractDefinition(node) => Some(Declaration::ContractDefinition(node)),
+        SourceUnitNode::EnumDefinition(node) => Some(Declaration::EnumDefinition(node)),
+        SourceUnitNode::ErrorDefinition(node) => Some(Declaration::ErrorDefinition(node)),
+        SourceUnitNode::EventDefinition(node) => Some(Declaration::EventDefinition(node)),
+        SourceUnitNode::FunctionDefinition(node) => Some(Declaration::FunctionDefinition(node)),
+        SourceUnitNode::ImportDirective(node) => Some(Declaration::ImportDirective(node)),
+        SourceUnitNode::StructDefinition(node) => Some(Declaration::StructDefinition(node)),
+        SourceUnitNode::UserDefinedValueTypeDefinition(node) => {
+            Some(Declaration::UserDefinedValueTypeDefinition(node))
+        }
+        SourceUnitNode::VariableDeclaration(node) => Some(Declaration::VariableDeclaration(node)),
+        SourceUnitNode::PragmaDirective(_)
+        | SourceUnitNode::UsingForDirective(_)
+        | SourceUnitNode::UnparsedNode(_) => None,
+    }
+}
+
+fn contract_declaration(node: &ContractDefinitionNode) -> Option<Declaration<'_>> {
+    match node {
+        ContractDefinitionNode::EnumDefinition(node) => Some(Declaration::EnumDefinition(node)),
+        ContractDefinitionNode::ErrorDefinition(node) => Some(Declaration::ErrorDefinition(node)),
+        ContractDefinitionNode::EventDefinition(node) => Some(Declaration::EventDefinition(node)),
+        ContractDefinitionNode::FunctionDefinition(node) => {
+            Some(Declaration::FunctionDefinition(node))
+        }
+        ContractDefinitionNode::ModifierDefinition(node) => {
+            Some(Declaration::ModifierDefinition(node))
+        }
+        ContractDefinitionNode::StructDefinition(node) => Some(Declaration::StructDefinition(node)),
+        ContractDefinitionNode::UserDefinedValueTypeDefinition(node) => {
+            Some(Declaration::UserDefinedValueTypeDefinition(node))
+        }
+        ContractDefinitionNode::VariableDeclaration(node) => {
+            Some(Declaration::VariableDeclaration(node))
+        }
+        ContractDefinitionNode::UsingForDirective(_) | ContractDefinitionNode::UnparsedNode(_) => {
+            None
+        }
+    }
+}
+
+/// Whether a member of a derived contract hides a member of a base with the
+/// same name.
+///
+/// Functions, events and errors hide those with the same parameter types,
+/// ignoring data locations, since an override may take `memory` parameters
+/// where the base takes `calldata`. Other members hide by name alone.
+fn hides(derived: Declaration<'_>, base: Declaration<'_>) -> bool {
+    match (parameter_types(derived), parameter_types(base)) {
+        (Some(derived), Some(base)) => derived == base,
+        _ => true,
+    }
+}
+
+/// The parameter types of a function, event, or error, without data
+/// locations.
+fn parameter_types(declaration: Declaration<'_>) -> Option<Vec<String>> {
+    let parameters = match declaration {
+        Declaration::FunctionDefinition(node) => &node.parameters,
+        Declaration::EventDefinition(node) => &node.parameters,
+        Declaration::ErrorDefinition(node) => &node.parameters,
+        _ => return None,
+    };
+    let location = ["memory", "calldata", "storage", "ref", "pointer", "slice"];
+    Some(
+        parameters
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let type_string = parameter
+                    .type_descriptions
+                    .type_string
+                    .as_deref()
+                    .unwrap_or_default();
+                let words: Vec<&str> = type_string.split(' ').collect();
+                let end = words
+                    .iter()
+                    .rposition(|word| !location.contains(word))
+                    .map_or(0, |last| last + 1);
+                words[..end].join(" ")
+            })
+            .collect(),
+    )
+}
+
+/// Records the declarations of one source unit.
+struct Collector<'i, 'a> {
+    index: &'i mut AstIndex<'a>,
+    source_unit: i64,
+}
+
+impl<'a> Collector<'_, 'a> {
+    fn insert(&mut self, declaration: Declaration<'a>) {
+        let id = declaration.id();
+        self.index.declarations.insert(id, declaration);
+        self.index.source_units.insert(id, self.source_unit);
+    }
+
+    fn collect_source_unit_node(&mut self, node: &'a SourceUnitNode) {
+        match node {
+            SourceUnitNode::ContractDefinition(contract) => {
+                self.insert(Declaration::ContractDefinition(contract));
+                for node in &contract.nodes {
+                    self.collect_contract_node(node);
+                }
+            }
+            SourceUnitNode::EnumDefinition(node) => self.collect_enum(node),
+            SourceUnitNode::ErrorDefinition(node) => {
+                self.insert(Declaration::ErrorDefinition(node));
+                self.collect_parameters(&node.parameters);
+            }
+            SourceUnitNode::EventDefinition(node) => {
+                self.insert(Declaration::EventDefinition(node));
+                self.collect_parameters(&node.parameters);
+            }
+            SourceUnitNode::FunctionDefinition(node) => self.collect_function(node),
+            SourceUnitNode::ImportDirective(node) => {
+                self.insert(Declaration::ImportDirective(node))
+            }
+            SourceUnitNode::StructDefinition(node) => self.collect_struct(node),
+            SourceUnitNode::UserDefinedValueTypeDefinition(node) => {
+                self.insert(Declaration::UserDefinedValueTypeDefinition(node))
+            }
+            SourceUnitNode::VariableDeclaration(node) => {
+                self.insert(Declaration::VariableDeclaration(node))
+            }
+            SourceUnitNode::PragmaDirective(_)
+            | SourceUnitNode::UsingForDirective(_)
+            | SourceUnitNode::UnparsedNode(_) => {}
+        }
+    }
+
+    fn collect_contract_node(&mut self, node: &'a ContractDefinitionNode) {
+        match node {
+            ContractDefinitionNode::EnumDefinition(node) => self.collect_enum(node),
+            ContractDefinitionNode::ErrorDefinition(node) => {
+                self.insert(Declaration::ErrorDefinition(node));
+                self.collect_parameters(&node.parameters);
+            }
+            ContractDefinitionNode::EventDefinition(node) => {
+                self.insert(Declaration::EventDefinition(node));
+                self.collect_parameters(&node.parameters);
+            }
+            ContractDefinitionNode::FunctionDefinition(node) => self.collect_function(node),
+            ContractDefinitionNode::ModifierDefinition(node) => {
+                self.insert(Declaration::ModifierDefinition(node));
+                self.collect_parameters(&node.parameters);
+                self.collect_statements(&node.body.statements);
+            }
+            ContractDefinitionNode::StructDefinition(node) => self.collect_struct(node),
+            ContractDefinitionNode::UserDefinedValueTypeDefinition(node) => {
+                self.insert(Declaration::UserDefinedValueTypeDefinition(node))
+            }
+            ContractDefinitionNode::VariableDeclaration(node) => {
+                self.insert(Declaration::VariableDeclaration(node))
+            }
+            ContractDefinitionNode::UsingForDirective(_)
+            | ContractDefinitionNode::UnparsedNode(_) => {}
+        }
+    }
+
+    fn collect_enum(&mut self, node: &'a EnumDefinition) {
+        self.insert(Declaration::EnumDefinition(node));
+        for value in &node.members {
+            self.insert(Declaration::EnumValue(value));
+        }
+    }
+
+    fn collect_struct(&mut self, node: &'a StructDefinition) {
+        self.insert(Declaration::StructDefinition(node));
+        for member in &node.members {
+            self.insert(Declaration::VariableDeclaration(member));
+        }
+    }
+
+    fn collect_function(&mut self, node: &'a FunctionDefinition) {
+        self.insert(Declaration::FunctionDefinition(node));
+        self.collect_parameters(&node.parameters);
+        self.collect_parameters(&node.return_parameters);
+        if let Some(body) = &node.body {
+            self.collect_statements(&body.statements);
+        }
+    }
+
+    fn collect_parameters(&mut self, parameters: &'a ParameterList) {
+        for parameter in &parameters.parameters {
+            self.insert(Declaration::VariableDeclaration(parameter));
+        }
+    }
+
+    fn collect_statements(&mut self, statements: &'a [Statement]) {
+        for statement in statements {
+            self.collect_statement(statement);
+        }
+    }
+
+    fn collect_statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::Block(node) => self.collect_statements(&node.statements),
+            Statement::UncheckedBlock(node) => self.collect_statements(&node.statements),
+            Statement::IfStatement(node) => {
+                self.collect_statement(&node.true_body);
+                if let Some(false_body) = &node.false_body {
+                    self.collect_statement(false_body);
+                }
+            }
+            Statement::ForStatement(node) => {
+                if let Some(initialization) = &node.initialization_expression
+                    && let Expression::VariableDeclarationStatement(declaration) =
+                        initialization.as_ref()
+                {
+                    self.collect_variable_declaration_statement(declaration);
+                }
+                self.collect_statement(&node.body);
+            }
+            Statement::WhileStatement(node) => self.collect_statement(&node.body),
+            Statement::DoWhileStatement(node) => self.collect_statement(&node.body),
+            Statement::TryStatement(node) => {
+                for clause in &node.clauses {
+                    if let Some(parameters) = &clause.parameters {
+                        self.collect_parameters(parameters);
+                    }
+                    self.collect_statements(&clause.block.statements);
+                }
+            }
+            Statement::VariableDeclarationStatement(node) => {
+                self.collect_variable_declaration_statement(node)
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_variable_declaration_statement(&mut self, node: &'a VariableDeclarationStatement) {
+        for declaration in node.declarations.iter().flatten() {
+            self.insert(Declaration::VariableDeclaration(declaration));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::ast::visit::Visitor;
+
+    /// Collects the references of identifiers.
+    #[derive(Default)]
+    struct References(Vec<(String, Option<i64>)>);
+
+    impl Visitor for References {
+        fn visit_identifier(&mut self, node: &Identifier) {
+            self.0
+                .push((node.name.clone(), node.referenced_declaration));
+        }
+    }
+
+    #[test]
+    fn fixture_references_resolve() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/StdCheats.json").unwrap();
+        let source_units = vec![serde_json::from_str::<SourceUnit>(&content).unwrap()];
+        let index = AstIndex::new(&source_units);
+        assert!(!index.is_empty());
+
+        let source_unit = &source_units[0];
+        let Some(Declaration::SourceUnit(found)) = index.get(source_unit.id) else {
+            panic!("expected the source unit");
+        };
+        assert!(std::ptr::eq(found, source_unit));
+
+        let mut references = References::default();
+        references.visit_source_unit(source_unit);
+        let mut resolved = 0;
+        for (name, reference) in &references.0 {
+            match reference {
+                // Declarations in other files and built-ins do not resolve.
+                Some(id) if *id >= 0 && index.get(*id).is_none() => {}
+                Some(id) if *id >= 0 => {
+                    let declaration = index.resolve(Some(*id)).unwrap();
+                    assert_eq!(declaration.id(), *id);
+                    assert_eq!(declaration.name(), Some(name.as_str()));
+                    assert_eq!(index.source_unit(*id).unwrap().id, source_unit.id);
+                    resolved += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(resolved > 0);
+    }
+
+    #[test]
+    fn typed_lookups() {
+        let variable = VariableDeclaration {
+            id: 4,
+            name: "total".to_string(),
+            ..Default::default()
+        };
+        let function = FunctionDefinition {
+            id: 3,
+            name: "add".to_string(),
+            body: Some(Block {
+                id: 5,
+                statements: vec![Statement::UncheckedBlock(UncheckedBlock {
+                    id: 6,
+                    statements: vec![Statement::VariableDeclarationStatement(
+                        VariableDeclarationStatement {
+                            id: 7,
+                            declarations: vec![None, Some(variable.clone())],
+                            ..Default::default()
+                        },
+                    )],
+                    ..Default::default()
+                })],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let source_units = vec![SourceUnit {
+            id: 1,
+            nodes: vec![
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    id: 2,
+                    name: "Counter".to_string(),
+                    ..Default::default()
+                }),
+                SourceUnitNode::FunctionDefinition(function),
+            ],
+            ..Default::default()
+        }];
+        let index = AstIndex::new(&source_units);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.contract(2).unwrap().name, "Counter");
+        assert_eq!(index.function(3).unwrap().name, "add");
+        assert_eq!(index.variable(4), Some(&variable));
+        assert_eq!(index.function(2), None);
+        assert_eq!(index.resolve(None), None);
+        assert_eq!(index.resolve(Some(-15)), None);
+        assert_eq!(index.source_unit(4).unwrap().id, 1);
+        assert_eq!(index.node(6).unwrap().node_type(), "UncheckedBlock");
+        assert_eq!(index.node(4), Some(Node::VariableDeclaration(&variable)));
+    }
+
+    #[test]
+    fn resolves_aliases() {
+        let import = |id, source_unit, alias: Option<&str>, symbols: &[(&str, Option<&str>)]| {
+            SourceUnitNode::ImportDirective(ImportDirective {
+                id,
+                source_unit,
+                unit_alias: alias.map(ToString::to_string),
+                symbol_aliases: symbols
+                    .iter()
+                    .map(|(foreign, local)| SymbolAlias {
+                        foreign: Identifier {
+                            name: foreign.to_string(),
+                            ..Default::default()
+                        },
+                        local: local.map(ToString::to_string),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+        };
+        let function = |id, name: &str| {
+            ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                id,
+                name: name.to_string(),
+                ..Default::default()
+            })
+        };
+        let source_units = vec![
+            // Base.sol: contract Base { function f() {} function f(uint) {} }
+            //           contract A is Base { function g() {} }
+            SourceUnit {
+                id: 1,
+                nodes: vec![
+                    SourceUnitNode::ContractDefinition(ContractDefinition {
+                        id: 10,
+                        name: "Base".to_string(),
+                        nodes: vec![function(11, "f"), function(12, "f")],
+                        linearized_base_contracts: vec![10],
+                        ..Default::default()
+                    }),
+                    SourceUnitNode::ContractDefinition(ContractDefinition {
+                        id: 13,
+                        name: "A".to_string(),
+                        nodes: vec![function(14, "g")],
+                        linearized_base_contracts: vec![13, 10],
+                        ..Default::default()
+                    }),
+                ],
+                ..Default::default()
+            },
+            // B.sol: import {A as B} from "Base.sol"; import * as M from "Base.sol";
+            SourceUnit {
+                id: 2,
+                nodes: vec![
+                    import(20, 1, None, &[("A", Some("B"))]),
+                    import(21, 1, Some("M"), &[]),
+                ],
+                ..Default::default()
+            },
+            // C.sol: import "B.sol";
+            SourceUnit {
+                id: 3,
+                nodes: vec![import(30, 2, None, &[])],
+                ..Default::default()
+            },
+        ];
+        let index = AstIndex::new(&source_units);
+        let ids = |declarations: Vec<Declaration>| -> Vec<i64> {
+            declarations.iter().map(Declaration::id).collect()
+        };
+
+        assert_eq!(ids(index.lookup(2, "B")), [13]);
+        assert_eq!(ids(index.lookup(2, "A")), [] as [i64; 0]);
+        assert_eq!(ids(index.lookup(2, "M")), [21]);
+        assert_eq!(ids(index.resolve_path(2, "M.A.g")), [14]);
+        assert_eq!(ids(index.resolve_path(2, "M.A.f")), [11, 12]);
+        assert_eq!(ids(index.resolve_path(2, "B.f")), [11, 12]);
+        assert_eq!(ids(index.resolve_path(3, "B.g")), [14]);
+        assert_eq!(ids(index.resolve_path(3, "M.Base")), [10]);
+        assert_eq!(ids(index.resolve_path(3, "M.Missing")), [] as [i64; 0]);
+    }
+
+    #[test]
+    fn members_hide_by_signature() {
+        let function = |id, types: &[&str]| {
+            ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                id,
+                name: "f".to_string(),
+                parameters: ParameterList {
+                    parameters: types
+                        .iter()
+                        .map(|type_string| VariableDeclaration {
+                            type_descriptions: TypeDescriptions {
+                                type_string: Some(type_string.to_string()),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        };
+        let contract = |id, nodes, bases: &[i64]| {
+            SourceUnitNode::ContractDefinition(ContractDefinition {
+                id,
+                name: format!("C{}", id),
+                nodes,
+                linearized_base_contracts: bases.to_vec(),
+                ..Default::default()
+            })
+        };
+        // contract C3 { function f() {} function f(uint256) {} function f(bytes calldata) {} }
+        // contract C4 is C3 { function f(uint256) {} function f(bytes memory) {} }
+        let source_units = vec![SourceUnit {
+            id: 1,
+            nodes: vec![
+                contract(
+                    3,
+                    vec![
+                        function(10, &[]),
+                        function(11, &["uint256"]),
+                        function(12, &["bytes calldata"]),
+                    ],
+                    &[3],
+                ),
+                contract(
+                    4,
+                    vec![function(20, &["uint256"]), function(21, &["bytes memory"])],
+                    &[4, 3],
+                ),
+            ],
+            ..Default::default()
+        }];
+        let index = AstIndex::new(&source_units);
+        let ids: Vec<i64> = index
+            .resolve_path(1, "C4.f")
+            .iter()
+            .map(Declaration::id)
+            .collect();
+        assert_eq!(ids, [20, 21, 10]);
+    }
+}
@@ -0,0 +1,236 @@
+//! Documentation inherited from overridden functions.
+//!
+//! NatSpec on an overriding function can be copied from a base function,
+//! either explicitly with `@inheritdoc Base`, or implicitly when the
+//! overriding function has no documentation at all. The AST keeps the
+//! comment as written, so [`inherited_documentation`] follows `baseFunctions`
+//! to find the documentation that applies.
+
+use std::collections::HashMap;
+
+use super::*;
+
+impl StructuredDocumentation {
+    /// The contract named by an `@inheritdoc` tag, if any.
+    pub fn inheritdoc(&self) -> Option<&str> {
+        inheritdoc(&self.text)
+    }
+}
+
+impl Documentation {
+    /// The text of the comment, without the comment markers.
+    pub fn text(&self) -> &str {
+        match self {
+            Documentation::String(text) => text,
+            Documentation::Structured(documentation) => &documentation.text,
+        }
+    }
+
+    /// The contract named by an `@inheritdoc` tag, if any.
+    pub fn inheritdoc(&self) -> Option<&str> {
+        inheritdoc(self.text())
+    }
+}
+
+fn inheritdoc(text: &str) -> Option<&str> {
+    let (_, rest) = text.split_once("@inheritdoc")?;
+    rest.split_whitespace().next()
+}
+
+/// Find the documentation that applies to each function and public state
+/// variable that overrides another, keyed by node id.
+///
+/// A declaration with an `@inheritdoc Base` tag gets the documentation of the
+/// base function declared in the contract `Base`. A declaration without
+/// documentation gets that of the first of its base functions that has any.
+/// Documentation is followed through several levels of inheritance. The
+/// result has no entry for declarations that keep their own documentation,
+/// or whose base function is not in `source_units`.
+///
+/// `source_units` must come from one compilation, so that node ids are
+/// unique.
+pub fn inherited_documentation(source_units: &[SourceUnit]) -> HashMap<i64, &Documentation> {
+    let mut collector = Collector::default();
+    for source_unit in source_units {
+        for node in &source_unit.nodes {
+            if let SourceUnitNode::ContractDefinition(contract) = node {
+                collector.collect_contract(contract);
+            }
+        }
+    }
+
+    let mut inherited = HashMap::new();
+    for (&id, declaration) in &collector.declarations {
+        if declaration.base_functions.is_empty() {
+            continue;
+        }
+        let mut current = declaration;
+        // Bound the walk by the number of declarations in case of a cycle.
+        for _ in 0..collector.declarations.len() {
+            let base = match current.documentation.map(Documentation::inheritdoc) {
+                Some(Some(contract)) => current.base_functions.iter().find(|base| {
+                    collector
+                        .declarations
+                        .get(base)
+                        .and_then(|base| collector.contracts.get(&base.scope))
+                        .is_some_and(|name| *name == contract)
+                }),
+                Some(None) => break,
+                None => current.base_functions.iter().find(|base| {
+                    collector
+                        .declarations
+                        .get(base)
+                        .is_some_and(|base| base.documentation.is_some())
+                }),
+            };
+            let Some(base) = base.and_then(|base| collector.declarations.get(base)) else {
+                break;
+            };
+            current = base;
+        }
+        if !std::ptr::eq(current, declaration)
+            && let Some(documentation) = current.documentation
+            && documentation.inheritdoc().is_none()
+        {
+            inherited.insert(id, documentation);
+        }
+    }
+    inherited
+}
+
+/// A function or state variable that can be overridden.
+struct Declaration<'a> {
+    scope: i64,
+    documentation: Option<&'a Documentation>,
+    base_functions: &'a [i64],
+}
+
+#[derive(Default)]
+struct Collector<'a> {
+    contracts: HashMap<i64, &'a str>,
+    declarations: HashMap<i64, Declaration<'a>>,
+}
+
+impl<'a> Collector<'a> {
+    fn declare(
+        &mut self,
+        id: i64,
+        scope: i64,
+        documentation: &'a Option<Documentation>,
+        base_functions: &'a Option<Vec<i64>>,
+    ) {
+        self.declarations.insert(
+            id,
+            Declaration {
+                scope,
+                documentation: documentation.as_ref(),
+                base_functions: base_functions.as_deref().unwrap_or_default(),
+            },
+        );
+    }
+
+    fn collect_contract(&mut self, node: &'a ContractDefinition) {
+        self.contracts.insert(node.id, &node.name);
+        for node in &node.nodes {
+            match node {
+                ContractDefinitionNode::FunctionDefinition(function) => self.declare(
+                    function.id,
+                    function.scope,
+                    &function.documentation,
+                    &function.base_functions,
+                ),
+                ContractDefinitionNode::VariableDeclaration(variable) => self.declare(
+                    variable.id,
+                    variable.scope,
+                    &variable.documentation,
+                    &variable.base_functions,
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(id: i64, name: &str, functions: Vec<FunctionDefinition>) -> SourceUnitNode {
+        SourceUnitNode::ContractDefinition(ContractDefinition {
+            id,
+            name: name.to_string(),
+            nodes: functions
+                .into_iter()
+                .map(|function| {
+                    ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                        scope: id,
+                        ..function
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn function(id: i64, text: Option<&str>, base_functions: &[i64]) -> FunctionDefinition {
+        FunctionDefinition {
+            id,
+            name: "price".to_string(),
+            documentation: text.map(|text| {
+                Documentation::Structured(StructuredDocumentation {
+                    id: id + 100,
+                    text: text.to_string(),
+                    src: SourceLocation::default(),
+                })
+            }),
+            base_functions: (!base_functions.is_empty()).then(|| base_functions.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_inheritdoc_tag() {
+        let documentation =
+            Documentation::String("@inheritdoc IPriceOracle\n @dev Bid only.".to_string());
+        assert_eq!(documentation.inheritdoc(), Some("IPriceOracle"));
+        assert_eq!(
+            Documentation::String("@notice Price.".into()).inheritdoc(),
+            None
+        );
+    }
+
+    #[test]
+    fn follows_base_functions() {
+        let source_unit = SourceUnit {
+            nodes: vec![
+                contract(
+                    10,
+                    "IOracle",
+                    vec![function(1, Some("@notice Price."), &[])],
+                ),
+                contract(
+                    20,
+                    "Oracle",
+                    vec![function(2, Some("@inheritdoc IOracle"), &[1])],
+                ),
+                contract(30, "Derived", vec![function(3, None, &[2])]),
+                contract(40, "Custom", vec![function(4, Some("@notice Own."), &[1])]),
+                contract(
+                    50,
+                    "Wrong",
+                    vec![function(5, Some("@inheritdoc Other"), &[1])],
+                ),
+            ],
+            ..Default::default()
+        };
+        let sources = [source_unit];
+        let inherited = inherited_documentation(&sources);
+
+        let text = |id: i64| inherited.get(&id).map(|doc| doc.text());
+        assert_eq!(text(2), Some("@notice Price."));
+        assert_eq!(text(3), Some("@notice Price."));
+        assert_eq!(text(1), None);
+        assert_eq!(text(4), None);
+        assert_eq!(text(5), None);
+    }
+}
@@ -0,0 +1,315 @@
+//! The ABI of a contract from its AST.
+//!
+//! The compiler derives the ABI of a contract from its declarations and
+//! those of the contracts it inherits from. [`Abi::from_contract_definition`]
+//! does the same from the AST, for compilations that only requested the AST
+//! or that were read from AST files.
+
+use std::collections::HashSet;
+
+use super::parameters::abi_param;
+use super::*;
+use crate::abi::{self, Abi, AbiItem, InternalType, Param};
+
+impl Abi {
+    /// The ABI of a contract, with the members it inherits.
+    ///
+    /// The contracts of `linearizedBaseContracts` are visited from the most
+    /// derived, so an overriding function or state variable replaces the
+    /// function it overrides. Public state variables get their getters, and
+    /// the events and errors the contract uses are included along with those
+    /// it and its bases declare. Abstract contracts have no constructor in
+    /// their ABI. Items are sorted by type and then by name, and overloaded
+    /// functions by selector, as the compiler sorts them.
+    ///
+    /// The index must hold every source unit of the compilation, so that base
+    /// contracts and the types of parameters resolve. Base contracts that are
+    /// not in it are left out. Functions of libraries are described with ABI
+    /// types, while the compiler describes storage parameters of library
+    /// functions with library-specific names.
+    pub fn from_contract_definition(
+        contract: &ContractDefinition,
+        index: &AstIndex<'_>,
+    ) -> Result<Self, TypeNameError> {
+        let bases = contract
+            .linearized_base_contracts
+            .iter()
+            .filter(|id| **id != contract.id)
+            .filter_map(|id| match index.get(*id) {
+                Some(Declaration::ContractDefinition(base)) => Some(base),
+                _ => None,
+            });
+
+        let mut builder = Builder::default();
+        for base in std::iter::once(contract).chain(bases) {
+            for node in &base.nodes {
+                match node {
+                    ContractDefinitionNode::FunctionDefinition(function) => {
+                        let own = base.id == contract.id && !contract.r#abstract;
+                        builder.function(function, own, index)?
+                    }
+                    ContractDefinitionNode::VariableDeclaration(variable)
+                        if variable.visibility == Visibility::Public =>
+                    {
+                        builder.add(AbiItem::Function(getter(variable, index)?))
+                    }
+                    ContractDefinitionNode::EventDefinition(event) => {
+                        builder.event(event, index)?
+                    }
+                    ContractDefinitionNode::ErrorDefinition(error) => {
+                        builder.error(error, index)?
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for id in contract.used_events.iter().flatten() {
+            if let Some(Declaration::EventDefinition(event)) = index.get(*id) {
+                builder.event(event, index)?;
+            }
+        }
+        for id in &contract.used_errors {
+            if let Some(Declaration::ErrorDefinition(error)) = index.get(*id) {
+                builder.error(error, index)?;
+            }
+        }
+
+        let mut items = builder.items;
+        items.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+        Ok(Abi::from_items(items))
+    }
+}
+
+/// Collects the items of an ABI, keeping the first of items with the same
+/// signature.
+#[derive(Default)]
+struct Builder {
+    items: Vec<AbiItem>,
+    signatures: HashSet<String>,
+}
+
+impl Builder {
+    fn add(&mut self, item: AbiItem) {
+        let signature = match &item {
+            AbiItem::Function(function) => format!("function {}", function.signature()),
+            AbiItem::Constructor(_) => "constructor".to_string(),
+            AbiItem::Receive(_) => "receive".to_string(),
+            AbiItem::Fallback(_) => "fallback".to_string(),
+            AbiItem::Event(event) => format!("event {}", event.signature()),
+            AbiItem::Error(error) => format!("error {}", error.signature()),
+        };
+        if self.signatures.insert(signature) {
+            self.items.push(item);
+        }
+    }
+
+    /// Add a function of the contract or of a base. Only the constructor of
+    /// the contract itself is part of its ABI, and only if the contract can
+    /// be deployed.
+    fn function(
+        &mut self,
+        function: &FunctionDefinition,
+        own: bool,
+        index: &AstIndex<'_>,
+    ) -> Result<(), TypeNameError> {
+        let state_mutability = abi::StateMutability::from(&function.state_mutability);
+        let item = match function.kind {
+            FunctionKind::Function
+                if matches!(
+                    function.visibility,
+                    Visibility::Public | Visibility::External
+                ) =>
+            {
+                AbiItem::Function(abi::Function {
+                    name: function.name.clone(),
+                    inputs: function.parameters.abi_params(index)?,
+                    outputs: function.return_parameters.abi_params(index)?,
+                    state_mutability,
+                })
+            }
+            FunctionKind::Constructor if own => AbiItem::Constructor(abi::Constructor {
+                inputs: function.parameters.abi_params(index)?,
+                state_mutability,
+            }),
+            FunctionKind::Receive => AbiItem::Receive(abi::Receive { state_mutability }),
+            FunctionKind::Fallback => AbiItem::Fallback(abi::Fallback { state_mutability }),
+            _ => return Ok(()),
+        };
+        self.add(item);
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        event: &EventDefinition,
+        index: &AstIndex<'_>,
+    ) -> Result<(), TypeNameError> {
+        self.add(AbiItem::Event(abi::Event {
+            name: event.name.clone(),
+            inputs: event.parameters.abi_event_params(index)?,
+            anonymous: event.anonymous,
+        }));
+        Ok(())
+    }
+
+    fn error(
+        &mut self,
+        error: &ErrorDefinition,
+        index: &AstIndex<'_>,
+    ) -> Result<(), TypeNameError> {
+        self.add(AbiItem::Error(abi::Error {
+            name: error.name.clone(),
+            inputs: error.parameters.abi_params(index)?,
+        }));
+        Ok(())
+    }
+}
+
+/// The getter of a public state variable, which takes a key for every
+/// mapping and an index for every array in the type of the variable.
+///
+/// A getter of a struct returns its members, leaving out mappings and
+/// arrays other than `bytes` and `string`.
+fn getter(
+    variable: &VariableDeclaration,
+    index: &AstIndex<'_>,
+) -> Result<abi::Function, TypeNameError> {
+    let mut inputs = Vec::new();
+    let mut value_name = "";
+    let mut type_name = &variable.type_name;
+    loop {
+        match type_name {
+            TypeName::Mapping(mapping) => {
+                inputs.push(abi_param(&mapping.key_name, &mapping.key_type, index)?);
+                value_name = &mapping.value_name;
+                type_name = &mapping.value_type;
+            }
+            TypeName::ArrayTypeName(array) => {
+                inputs.push(Param {
+                    name: String::new(),
+                    r#type: "uint256".to_string(),
+                    components: None,
+                    indexed: None,
+                    internal_type: Some(InternalType::Elementary("uint256".to_string())),
+                });
+                value_name = "";
+                type_name = &array.base_type;
+            }
+            _ => break,
+        }
+    }
+
+    let structure = match type_name {
+        TypeName::UserDefinedTypeName(user_defined) => {
+            match index.resolve(user_defined.referenced_declaration) {
+                Some(Declaration::StructDefinition(structure)) => Some(structure),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let outputs = match structure {
+        Some(structure) => structure
+            .members
+            .iter()
+            .filter(|member| {
+                !matches!(
+                    member.type_name,
+                    TypeName::Mapping(_) | TypeName::ArrayTypeName(_)
+                )
+            })
+            .map(|member| member.abi_param(index))
+            .collect::<Result<_, _>>()?,
+        None => vec![abi_param(value_name, type_name, index)?],
+    };
+
+    Ok(abi::Function {
+        name: variable.name.clone(),
+        inputs,
+        outputs,
+        state_mutability: abi::StateMutability::View,
+    })
+}
+
+/// The type and name the compiler sorts the items of an ABI by, and for
+/// functions the selector, which orders overloads.
+fn sort_key(item: &AbiItem) -> (&'static str, &str, [u8; 4]) {
+    match item {
+        AbiItem::Function(function) => ("function", &function.name, function.selector()),
+        AbiItem::Constructor(_) => ("constructor", "", [0; 4]),
+        AbiItem::Receive(_) => ("receive", "", [0; 4]),
+        AbiItem::Fallback(_) => ("fallback", "", [0; 4]),
+        AbiItem::Event(event) => ("event", &event.name, [0; 4]),
+        AbiItem::Error(error) => ("error", &error.name, [0; 4]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn fixture_abis_match() {
+        let mut checked = 0;
+        for entry in fs::read_dir("fixtures/abi").unwrap() {
+            let project = entry.unwrap().path();
+            if !project.is_dir() {
+                continue;
+            }
+            let directory = Path::new("fixtures/ast").join(project.file_name().unwrap());
+            let Ok(entries) = fs::read_dir(&directory) else {
+                continue;
+            };
+            let source_units: Vec<SourceUnit> = entries
+                .map(|entry| {
+                    let content = fs::read_to_string(entry.unwrap().path()).unwrap();
+                    serde_json::from_str(&content).unwrap()
+                })
+                .collect();
+            let index = AstIndex::new(&source_units);
+
+            for source_unit in &source_units {
+                for node in &source_unit.nodes {
+                    let SourceUnitNode::ContractDefinition(contract) = node else {
+                        continue;
+                    };
+                    if contract.contract_kind == ContractKind::Library {
+                        continue;
+                    }
+                    // The fixtures hold source units of several compilations,
+                    // so ids of different nodes collide and some bases are
+                    // missing. Only contracts whose references and parameter
+                    // types all resolve to the right kind of node are
+                    // compared.
+                    let resolves =
+                        contract.linearized_base_contracts.iter().all(|id| {
+                            matches!(index.get(*id), Some(Declaration::ContractDefinition(_)))
+                        }) && contract.used_errors.iter().all(|id| {
+                            matches!(index.get(*id), Some(Declaration::ErrorDefinition(_)))
+                        }) && contract.used_events.iter().flatten().all(|id| {
+                            matches!(index.get(*id), Some(Declaration::EventDefinition(_)))
+                        });
+                    let stem = source_unit.absolute_path.file_stem();
+                    if !resolves || stem.is_none_or(|stem| *stem != *contract.name) {
+                        continue;
+                    }
+                    let path = project.join(format!("{}.json", contract.name));
+                    let Ok(content) = fs::read_to_string(&path) else {
+                        continue;
+                    };
+                    let mut expected: Abi = serde_json::from_str(&content).unwrap();
+                    expected.items.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+                    let Ok(abi) = Abi::from_contract_definition(contract, &index) else {
+                        continue;
+                    };
+                    assert_eq!(abi, expected, "{}", path.display());
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 100, "{}", checked);
+    }
+}
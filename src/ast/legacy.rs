@@ -0,0 +1,617 @@
+//! Conversion of the legacy AST format.
+//!
+//! Before 0.8.0, solc could emit the AST in a legacy format (`legacyAST`, or
+//! `--ast-json` before 0.5), which explorers still serve for many verified
+//! contracts. In that format every node is an object with the node type in
+//! `name`, its fields in `attributes`, and its child nodes in an unnamed
+//! `children` array:
+//!
+//! ```json
+//! {"id": 3, "name": "Return", "src": "80:12:0",
+//!  "attributes": {"functionReturnParameters": 2},
+//!  "children": [{"id": 4, "name": "Identifier", "src": "87:5:0", "attributes": {"value": "count"}}]}
+//! ```
+//!
+//! [`from_legacy`] turns such a tree into the compact format, by assigning
+//! the children to the fields of their parent, and [`parse_legacy`] then
+//! reads it into a [`SourceUnit`](super::SourceUnit), upgrading it with [`normalize`] first.
+
+use serde_json::{Map, Value};
+
+use super::lenient::{LenientParse, parse_lenient};
+use super::normalize::normalize;
+
+/// Node types that are type names, as held by `typeName` fields.
+const TYPE_NAMES: &[&str] = &[
+    "ArrayTypeName",
+    "ElementaryTypeName",
+    "FunctionTypeName",
+    "Mapping",
+    "UserDefinedTypeName",
+];
+
+/// Node types that are expressions, which have the annotation fields of
+/// [`EXPRESSION_DEFAULTS`].
+const EXPRESSIONS: &[&str] = &[
+    "Assignment",
+    "BinaryOperation",
+    "Conditional",
+    "ElementaryTypeNameExpression",
+    "FunctionCall",
+    "FunctionCallOptions",
+    "Identifier",
+    "IndexAccess",
+    "IndexRangeAccess",
+    "Literal",
+    "MemberAccess",
+    "NewExpression",
+    "TupleExpression",
+    "UnaryOperation",
+];
+
+/// Annotations of expressions that old compilers leave out, with the value
+/// to assume.
+const EXPRESSION_DEFAULTS: &[(&str, bool)] = &[
+    ("isConstant", false),
+    ("isLValue", false),
+    ("isPure", false),
+    ("lValueRequested", false),
+];
+
+/// Convert an AST in the legacy format into the compact format.
+///
+/// Values that are not legacy nodes are returned unchanged. The result has
+/// the shape of the compact AST of the same compiler release, so it may
+/// still need [`normalize`] to be read into the typed nodes.
+pub fn from_legacy(legacy: &Value) -> Value {
+    let Value::Object(object) = legacy else {
+        return legacy.clone();
+    };
+    let Some(node_type) = object.get("name").and_then(Value::as_str) else {
+        return legacy.clone();
+    };
+    if !object.contains_key("attributes") && !object.contains_key("children") {
+        return legacy.clone();
+    }
+
+    let mut node = Map::new();
+    node.insert("nodeType".to_string(), node_type.into());
+    for key in ["id", "src"] {
+        if let Some(value) = object.get(key) {
+            node.insert(key.to_string(), value.clone());
+        }
+    }
+    let attributes = object.get("attributes").and_then(Value::as_object);
+    for (key, value) in attributes.into_iter().flatten() {
+        let value = match value {
+            // Empty lists are written as a list with a single null.
+            Value::Array(items) if items.len() == 1 && items[0].is_null() => {
+                Value::Array(Vec::new())
+            }
+            _ => value.clone(),
+        };
+        node.insert(key.clone(), value);
+    }
+    rename_attributes(node_type, &mut node);
+
+    let children: Vec<Value> = object
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(from_legacy)
+        .collect();
+    assign_children(node_type, children, &mut node);
+
+    if EXPRESSIONS.contains(&node_type) {
+        for (key, value) in EXPRESSION_DEFAULTS {
+            node.entry(*key).or_insert(Value::Bool(*value));
+        }
+    }
+    if EXPRESSIONS.contains(&node_type)
+        || TYPE_NAMES.contains(&node_type)
+        || node_type == "VariableDeclaration"
+    {
+        node.entry("typeDescriptions")
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    Value::Object(node)
+}
+
+/// Read an AST in the legacy format into a source unit.
+///
+/// The AST is converted with [`from_legacy`] and upgraded with
+/// [`normalize`], then read with [`parse_lenient`], so nodes the conversion
+/// cannot fully restore are left as placeholders and reported rather than
+/// failing the whole source unit.
+pub fn parse_legacy(json: &str) -> Result<LenientParse, serde_json::Error> {
+    let legacy: Value = serde_json::from_str(json)?;
+    let mut compact = from_legacy(&legacy);
+    normalize(&mut compact);
+    parse_lenient(&compact.to_string())
+}
+
+/// Give attributes the names and shapes of the compact format.
+fn rename_attributes(node_type: &str, node: &mut Map<String, Value>) {
+    if let Some(type_string) = node.remove("type") {
+        let mut descriptions = Map::new();
+        descriptions.insert("typeString".to_string(), type_string);
+        node.insert("typeDescriptions".to_string(), Value::Object(descriptions));
+    }
+
+    let rename = |node: &mut Map<String, Value>, from: &str, to: &str| {
+        if let Some(value) = node.remove(from) {
+            node.insert(to.to_string(), value);
+        }
+    };
+    match node_type {
+        "Identifier" => rename(node, "value", "name"),
+        "MemberAccess" => rename(node, "member_name", "memberName"),
+        "ElementaryTypeNameExpression" => rename(node, "value", "typeName"),
+        "Literal" => {
+            rename(node, "token", "kind");
+            rename(node, "hexvalue", "hexValue");
+        }
+        "FunctionCall" => {
+            let flag = |node: &mut Map<String, Value>, key: &str| {
+                node.remove(key).and_then(|v| v.as_bool()) == Some(true)
+            };
+            let type_conversion = flag(node, "type_conversion");
+            let struct_constructor = flag(node, "isStructConstructorCall");
+            let kind = if type_conversion {
+                "typeConversion"
+            } else if struct_constructor {
+                "structConstructorCall"
+            } else {
+                "functionCall"
+            };
+            node.entry("kind").or_insert(kind.into());
+        }
+        _ => {}
+    }
+}
+
+/// Assign the converted children of a node to its fields.
+fn assign_children(node_type: &str, children: Vec<Value>, node: &mut Map<String, Value>) {
+    let child_type = |child: &Value| {
+        child
+            .get("nodeType")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let mut children = children.into_iter();
+    let set = |node: &mut Map<String, Value>, key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            node.insert(key.to_string(), value);
+        }
+    };
+
+    match node_type {
+        "SourceUnit" => {
+            node.insert("nodes".to_string(), children.collect());
+        }
+        "ContractDefinition" => {
+            let mut bases = Vec::new();
+            let mut members = Vec::new();
+            for child in children {
+                match child_type(&child).as_str() {
+                    "InheritanceSpecifier" => bases.push(child),
+                    "StructuredDocumentation" => set(node, "documentation", Some(child)),
+                    _ => members.push(child),
+                }
+            }
+            node.insert("baseContracts".to_string(), bases.into());
+            node.insert("nodes".to_string(), members.into());
+        }
+        "FunctionDefinition" | "ModifierDefinition" | "EventDefinition" | "ErrorDefinition" => {
+            let mut parameter_lists = 0;
+            let mut modifiers = Vec::new();
+            for child in children {
+                match child_type(&child).as_str() {
+                    "ParameterList" => {
+                        let key = if parameter_lists == 0 {
+                            "parameters"
+                        } else {
+                            "returnParameters"
+                        };
+                        parameter_lists += 1;
+                        set(node, key, Some(child));
+                    }
+                    "ModifierInvocation" => modifiers.push(child),
+                    "OverrideSpecifier" => set(node, "overrides", Some(child)),
+                    "StructuredDocumentation" => set(node, "documentation", Some(child)),
+                    "Block" => set(node, "body", Some(child)),
+                    _ => {}
+                }
+            }
+            if node_type == "FunctionDefinition" {
+                node.insert("modifiers".to_string(), modifiers.into());
+            }
+        }
+        "VariableDeclaration" => {
+            for child in children {
+                let child_type = child_type(&child);
+                if TYPE_NAMES.contains(&child_type.as_str()) && !node.contains_key("typeName") {
+                    set(node, "typeName", Some(child));
+                } else if child_type == "OverrideSpecifier" {
+                    set(node, "overrides", Some(child));
+                } else if child_type == "StructuredDocumentation" {
+                    set(node, "documentation", Some(child));
+                } else {
+                    set(node, "value", Some(child));
+                }
+            }
+        }
+        "VariableDeclarationStatement" => {
+            let mut declarations = Vec::new();
+            for child in children {
+                if child_type(&child) == "VariableDeclaration" {
+                    declarations.push(child);
+                } else {
+                    set(node, "initialValue", Some(child));
+                }
+            }
+            node.insert("declarations".to_string(), declarations.into());
+        }
+        "TryStatement" => {
+            set(node, "externalCall", children.next());
+            node.insert("clauses".to_string(), children.collect());
+        }
+        "TryCatchClause" => {
+            for child in children {
+                match child_type(&child).as_str() {
+                    "ParameterList" => set(node, "parameters", Some(child)),
+                    _ => set(node, "block", Some(child)),
+                }
+            }
+        }
+        "ElementaryTypeNameExpression" => set(node, "typeName", children.next()),
+        _ => {
+            let (fields, rest) = positional_fields(node_type);
+            for field in fields {
+                // Optional children that are absent are listed as null
+                // attributes, and take no place in the children.
+                if node.get(*field).is_some_and(Value::is_null) {
+                    continue;
+                }
+                set(node, field, children.next());
+            }
+            if let Some(rest) = rest {
+                node.insert(rest.to_string(), children.collect());
+            }
+        }
+    }
+}
+
+/// The fields that hold the children of a node, in order, and the field
+/// that holds any remaining children.
+fn positional_fields(node_type: &str) -> (&'static [&'static str], Option<&'static str>) {
+    match node_type {
+        "ParameterList" => (&[], Some("parameters")),
+        "Block" | "UncheckedBlock" => (&[], Some("statements")),
+        "StructDefinition" | "EnumDefinition" => (&[], Some("members")),
+        "OverrideSpecifier" => (&[], Some("overrides")),
+        "TupleExpression" => (&[], Some("components")),
+        "InheritanceSpecifier" => (&["baseName"], Some("arguments")),
+        "ModifierInvocation" => (&["modifierName"], Some("arguments")),
+        "FunctionCall" => (&["expression"], Some("arguments")),
+        "FunctionCallOptions" => (&["expression"], Some("options")),
+        "UsingForDirective" => (&["libraryName", "typeName"], None),
+        "UserDefinedTypeName" => (&["pathNode"], None),
+        "Mapping" => (&["keyType", "valueType"], None),
+        "ArrayTypeName" => (&["baseType", "length"], None),
+        "FunctionTypeName" => (&["parameterTypes", "returnParameterTypes"], None),
+        "ExpressionStatement" => (&["expression"], None),
+        "EmitStatement" => (&["eventCall"], None),
+        "RevertStatement" => (&["errorCall"], None),
+        "Return" => (&["expression"], None),
+        "IfStatement" => (&["condition", "trueBody", "falseBody"], None),
+        "WhileStatement" | "DoWhileStatement" => (&["condition", "body"], None),
+        "ForStatement" => (
+            &[
+                "initializationExpression",
+                "condition",
+                "loopExpression",
+                "body",
+            ],
+            None,
+        ),
+        "Assignment" => (&["leftHandSide", "rightHandSide"], None),
+        "BinaryOperation" => (&["leftExpression", "rightExpression"], None),
+        "UnaryOperation" => (&["subExpression"], None),
+        "Conditional" => (&["condition", "trueExpression", "falseExpression"], None),
+        "MemberAccess" => (&["expression"], None),
+        "IndexAccess" => (&["baseExpression", "indexExpression"], None),
+        "IndexRangeAccess" => (
+            &["baseExpression", "startExpression", "endExpression"],
+            None,
+        ),
+        "NewExpression" => (&["typeName"], None),
+        _ => (&[], None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::ast::{
+        ContractDefinitionNode, Expression, FunctionKind, SourceUnitNode, StateMutability,
+        Statement,
+    };
+
+    fn node(id: i64, name: &str, attributes: Value, children: Vec<Value>) -> Value {
+        let mut node = json!({
+            "id": id,
+            "name": name,
+            "src": format!("{}:1:0", id),
+            "attributes": attributes,
+        });
+        if !children.is_empty() {
+            node["children"] = children.into();
+        }
+        node
+    }
+
+    fn identifier(id: i64, name: &str, declaration: i64, ty: &str) -> Value {
+        node(
+            id,
+            "Identifier",
+            json!({
+                "argumentTypes": null,
+                "overloadedDeclarations": [null],
+                "referencedDeclaration": declaration,
+                "type": ty,
+                "value": name
+            }),
+            vec![],
+        )
+    }
+
+    fn variable(id: i64, name: &str, ty: &str, state: bool) -> Value {
+        node(
+            id,
+            "VariableDeclaration",
+            json!({
+                "constant": false,
+                "name": name,
+                "scope": 30,
+                "stateVariable": state,
+                "storageLocation": "default",
+                "type": ty,
+                "value": null,
+                "visibility": if state { "public" } else { "internal" }
+            }),
+            vec![node(
+                id + 100,
+                "ElementaryTypeName",
+                json!({"name": ty, "type": ty}),
+                vec![],
+            )],
+        )
+    }
+
+    /// The legacy AST solc 0.4.24 emits for:
+    ///
+    /// ```solidity
+    /// pragma solidity ^0.4.24;
+    /// contract Counter {
+    ///     uint256 public count;
+    ///     event Incremented(address indexed by, uint256 value);
+    ///     function increment(uint256 by) public returns (uint256) {
+    ///         count += by;
+    ///         emit Incremented(msg.sender, count);
+    ///         return count;
+    ///     }
+    /// }
+    /// ```
+    fn counter() -> Value {
+        let function = node(
+            29,
+            "FunctionDefinition",
+            json!({
+                "constant": false,
+                "documentation": null,
+                "implemented": true,
+                "isConstructor": false,
+                "modifiers": [null],
+                "name": "increment",
+                "payable": false,
+                "scope": 30,
+                "stateMutability": "nonpayable",
+                "superFunction": null,
+                "visibility": "public"
+            }),
+            vec![
+                node(
+                    11,
+                    "ParameterList",
+                    json!({"parameters": [null]}),
+                    vec![variable(10, "by", "uint256", false)],
+                ),
+                node(
+                    14,
+                    "ParameterList",
+                    json!({"parameters": [null]}),
+                    vec![variable(13, "", "uint256", false)],
+                ),
+                node(
+                    28,
+                    "Block",
+                    json!({}),
+                    vec![
+                        node(
+                            18,
+                            "ExpressionStatement",
+                            json!({}),
+                            vec![node(
+                                17,
+                                "Assignment",
+                                json!({
+                                    "argumentTypes": null,
+                                    "isConstant": false,
+                                    "isLValue": false,
+                                    "isPure": false,
+                                    "lValueRequested": false,
+                                    "operator": "+=",
+                                    "type": "uint256"
+                                }),
+                                vec![
+                                    identifier(15, "count", 3, "uint256"),
+                                    identifier(16, "by", 10, "uint256"),
+                                ],
+                            )],
+                        ),
+                        node(
+                            24,
+                            "EmitStatement",
+                            json!({}),
+                            vec![node(
+                                23,
+                                "FunctionCall",
+                                json!({
+                                    "argumentTypes": null,
+                                    "isConstant": false,
+                                    "isLValue": false,
+                                    "isPure": false,
+                                    "isStructConstructorCall": false,
+                                    "lValueRequested": false,
+                                    "names": [null],
+                                    "type": "tuple()",
+                                    "type_conversion": false
+                                }),
+                                vec![
+                                    identifier(19, "Incremented", 9, "function (address,uint256)"),
+                                    node(
+                                        21,
+                                        "MemberAccess",
+                                        json!({
+                                            "argumentTypes": null,
+                                            "isConstant": false,
+                                            "isLValue": false,
+                                            "isPure": false,
+                                            "lValueRequested": false,
+                                            "member_name": "sender",
+                                            "referencedDeclaration": null,
+                                            "type": "address"
+                                        }),
+                                        vec![identifier(20, "msg", -15, "msg")],
+                                    ),
+                                    identifier(22, "count", 3, "uint256"),
+                                ],
+                            )],
+                        ),
+                        node(
+                            27,
+                            "Return",
+                            json!({"functionReturnParameters": 14}),
+                            vec![identifier(26, "count", 3, "uint256")],
+                        ),
+                    ],
+                ),
+            ],
+        );
+        let event = node(
+            9,
+            "EventDefinition",
+            json!({"anonymous": false, "documentation": null, "name": "Incremented"}),
+            vec![node(
+                8,
+                "ParameterList",
+                json!({}),
+                vec![
+                    variable(5, "by", "address", false),
+                    variable(7, "value", "uint256", false),
+                ],
+            )],
+        );
+        node(
+            31,
+            "SourceUnit",
+            json!({"absolutePath": "Counter.sol", "exportedSymbols": {"Counter": [30]}}),
+            vec![
+                node(
+                    1,
+                    "PragmaDirective",
+                    json!({"literals": ["solidity", "^", "0.4", ".24"]}),
+                    vec![],
+                ),
+                node(
+                    30,
+                    "ContractDefinition",
+                    json!({
+                        "baseContracts": [null],
+                        "contractDependencies": [null],
+                        "contractKind": "contract",
+                        "documentation": null,
+                        "fullyImplemented": true,
+                        "linearizedBaseContracts": [30],
+                        "name": "Counter",
+                        "scope": 31
+                    }),
+                    vec![variable(3, "count", "uint256", true), event, function],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn converts_children_to_fields() {
+        let compact = from_legacy(&counter());
+        assert_eq!(compact["nodeType"], "SourceUnit");
+        let contract = &compact["nodes"][1];
+        assert_eq!(contract["baseContracts"], json!([]));
+        let function = &contract["nodes"][2];
+        assert_eq!(function["parameters"]["parameters"][0]["name"], "by");
+        assert_eq!(function["returnParameters"]["id"], 14);
+        let assignment = &function["body"]["statements"][0]["expression"];
+        assert_eq!(assignment["leftHandSide"]["name"], "count");
+        assert_eq!(assignment["typeDescriptions"]["typeString"], "uint256");
+        let call = &function["body"]["statements"][1]["eventCall"];
+        assert_eq!(call["kind"], "functionCall");
+        assert_eq!(call["arguments"][0]["memberName"], "sender");
+
+        assert_eq!(from_legacy(&json!({"name": "x"})), json!({"name": "x"}));
+    }
+
+    #[test]
+    fn parses_legacy_source_unit() {
+        let parsed = parse_legacy(&counter().to_string()).unwrap();
+        // Old compilers do not record event selectors, which normalization
+        // does not compute, so the event is left unparsed.
+        assert_eq!(parsed.errors.len(), 1, "{:#?}", parsed.errors);
+        assert_eq!(parsed.errors[0].node_type, "EventDefinition");
+        let source_unit = parsed.source_unit;
+        assert_eq!(source_unit.id, 31);
+        let SourceUnitNode::ContractDefinition(contract) = &source_unit.nodes[1] else {
+            panic!("expected a contract definition");
+        };
+        assert_eq!(contract.name, "Counter");
+        assert!(contract.nodes.iter().any(|node| matches!(
+            node,
+            ContractDefinitionNode::UnparsedNode(node)
+                if node.original_node_type() == Some("EventDefinition")
+        )));
+        let Some(ContractDefinitionNode::FunctionDefinition(function)) = contract
+            .nodes
+            .iter()
+            .find(|node| matches!(node, ContractDefinitionNode::FunctionDefinition(_)))
+        else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(function.kind, FunctionKind::Function);
+        assert_eq!(function.state_mutability, StateMutability::Nonpayable);
+        let statements = &function.body.as_ref().unwrap().statements;
+        assert_eq!(statements.len(), 3);
+        let Statement::EmitStatement(emit) = &statements[1] else {
+            panic!("expected an emit statement");
+        };
+        assert_eq!(emit.event_call.kind, "functionCall");
+        assert!(matches!(
+            *emit.event_call.arguments[0],
+            Expression::MemberAccess(_)
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+//! Best-effort parsing of ASTs the typed nodes do not fully support.
+//!
+//! A single node that the typed AST cannot represent, such as a node type
+//! added by a newer compiler, makes deserializing the whole [`SourceUnit`]
+//! fail. [`parse_lenient`] instead puts an [`UnparsedNode`] holding the raw
+//! JSON in place of each node that fails and keeps the rest of the tree, so
+//! tools keep working on partially supported compiler versions and report
+//! what they could not read.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::*;
+
+/// The result of [`parse_lenient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LenientParse {
+    /// The source unit, with placeholders for the nodes that failed to parse.
+    pub source_unit: SourceUnit,
+    /// The nodes that failed to parse, innermost first.
+    pub errors: Vec<NodeError>,
+}
+
+/// A node that failed to parse and was replaced by [`parse_lenient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeError {
+    /// Where the node was in the JSON, such as `nodes[2].body.statements[0]`.
+    pub path: String,
+    /// The `nodeType` of the node.
+    pub node_type: String,
+    /// Why the node failed to parse.
+    pub message: String,
+    /// The node as it was in the JSON.
+    pub raw: Value,
+}
+
+/// Parse a source unit, replacing the nodes that fail to parse with
+/// placeholders.
+///
+/// Nodes are checked innermost first. A node that fails is replaced by an
+/// [`UnparsedNode`] holding its raw JSON, so its siblings and parents are
+/// kept. Placeholders fit where a node can be of several types: a source
+/// unit or contract member, a statement, an expression, or a type name. A
+/// node elsewhere, such as a parameter in a parameter list, is replaced
+/// along with the nearest enclosing node that can be, and both are reported.
+///
+/// Returns an error only if the input is not JSON, or if the source unit
+/// itself cannot be parsed even with placeholders for its failing nodes.
+pub fn parse_lenient(json: &str) -> Result<LenientParse, serde_json::Error> {
+    let mut value: Value = serde_json::from_str(json)?;
+    let mut errors = Vec::new();
+    let mut check = |path, node_type: &str, node: &Value| match check_node(node_type, node) {
+        Ok(()) => false,
+        Err(error) => {
+            let mut raw = node.clone();
+            restore_placeholders(&mut raw);
+            errors.push(NodeError {
+                path,
+                node_type: node_type.to_string(),
+                message: error.to_string(),
+                raw,
+            });
+            true
+        }
+    };
+    if let Value::Object(object) = &mut value {
+        for (field, child) in object.iter_mut() {
+            prune(child, field.clone(), &mut check);
+        }
+    }
+    let source_unit = SourceUnit::deserialize(&value)?;
+    Ok(LenientParse {
+        source_unit,
+        errors,
+    })
+}
+
+/// Replace the failing nodes under `value` with placeholders. Returns
+/// whether `value` itself is a node that failed.
+///
+/// `check` is called with the path, node type, and JSON of every node, after
+/// the failing nodes under it were replaced, and returns whether it failed.
+pub(super) fn prune(
+    value: &mut Value,
+    path: String,
+    check: &mut impl FnMut(String, &str, &Value) -> bool,
+) -> bool {
+    match value {
+        Value::Object(object) => {
+            for (field, child) in object.iter_mut() {
+                if prune(child, format!("{}.{}", path, field), check) {
+                    replace_with_placeholder(child);
+                }
+            }
+            let Some(node_type) = object.get("nodeType").and_then(Value::as_str) else {
+                return false;
+            };
+            let node_type = node_type.to_string();
+            check(path, &node_type, value)
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                if prune(item, format!("{}[{}]", path, index), check) {
+                    replace_with_placeholder(item);
+                }
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+/// Replace a node with an [`UnparsedNode`] holding it as it was in the JSON.
+fn replace_with_placeholder(node: &mut Value) {
+    let mut raw = node.take();
+    restore_placeholders(&mut raw);
+    *node = serde_json::to_value(Statement::UnparsedNode(UnparsedNode::new(raw)))
+        .expect("AST nodes serialize to JSON");
+}
+
+/// Put the nodes held by the placeholders under `value` back in their place.
+fn restore_placeholders(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if object.get("nodeType").and_then(Value::as_str) == Some("UnparsedNode")
+                && let Some(raw) = object.get_mut("raw")
+            {
+                *value = raw.take();
+                return;
+            }
+            object.values_mut().for_each(restore_placeholders);
+        }
+        Value::Array(items) => items.iter_mut().for_each(restore_placeholders),
+        _ => {}
+    }
+}
+
+/// Deserialize a node as the type named by its `nodeType`.
+fn check_node(node_type: &str, value: &Value) -> Result<(), serde_json::Error> {
+    match reserialize_node(node_type, value) {
+        Some(result) => result.map(drop),
+        None => Err(serde::de::Error::custom(format!(
+            "unknown node type `{}`",
+            node_type
+        ))),
+    }
+}
+
+/// Deserialize a node as the type named by its `nodeType` and serialize it
+/// back, which leaves out whatever the typed node does not keep.
+///
+/// Returns `None` if the typed AST has no node of that type.
+pub(super) fn reserialize_node(
+    node_type: &str,
+    value: &Value,
+) -> Option<Result<Value, serde_json::Error>> {
+    macro_rules! check {
+        ($($ty:ident),* $(,)?) => {
+            match node_type {
+                $(stringify!($ty) => Some($ty::deserialize(value).map(|node| {
+                    serde_json::to_value(node).expect("AST nodes serialize to JSON")
+                })),)*
+                _ => None,
+            }
+        };
+    }
+
+    check!(
+        SourceUnit,
+        PragmaDirective,
+        ImportDirective,
+        ContractDefinition,
+        InheritanceSpecifier,
+        VariableDeclaration,
+        OverrideSpecifier,
+        FunctionDefinition,
+        ModifierInvocation,
+        ParameterList,
+        ModifierDefinition,
+        EventDefinition,
+        ErrorDefinition,
+        StructDefinition,
+        EnumDefinition,
+        EnumValue,
+        UserDefinedValueTypeDefinition,
+        UsingForDirective,
+        Block,
+        UncheckedBlock,
+        IfStatement,
+        ForStatement,
+        WhileStatement,
+        DoWhileStatement,
+        Continue,
+        Break,
+        Return,
+        EmitStatement,
+        RevertStatement,
+        TryStatement,
+        TryCatchClause,
+        ExpressionStatement,
+        VariableDeclarationStatement,
+        InlineAssembly,
+        PlaceholderStatement,
+        YulBlock,
+        YulAssignment,
+        YulVariableDeclaration,
+        YulTypedName,
+        YulExpressionStatement,
+        YulIf,
+        YulForLoop,
+        YulSwitch,
+        YulCase,
+        YulLiteral,
+        YulFunctionDefinition,
+        YulBreak,
+        YulContinue,
+        YulLeave,
+        YulFunctionCall,
+        YulIdentifier,
+        Assignment,
+        BinaryOperation,
+        Conditional,
+        UnaryOperation,
+        FunctionCall,
+        FunctionCallOptions,
+        MemberAccess,
+        IndexAccess,
+        IndexRangeAccess,
+        TupleExpression,
+        Identifier,
+        IdentifierPath,
+        Literal,
+        NewExpression,
+        ElementaryTypeNameExpression,
+        ElementaryTypeName,
+        UserDefinedTypeName,
+        ArrayTypeName,
+        Mapping,
+        FunctionTypeName,
+        StructuredDocumentation,
+        UnparsedNode,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn fixture() -> Value {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ArrayHelpers.json")
+                .unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    fn member(source_unit: &SourceUnit, index: usize) -> &ContractDefinitionNode {
+        let SourceUnitNode::ContractDefinition(contract) = &source_unit.nodes[1] else {
+            panic!("expected a contract definition");
+        };
+        &contract.nodes[index]
+    }
+
+    #[test]
+    fn replaces_failing_nodes() {
+        let mut value = fixture();
+        let function_json = &mut value["nodes"][1]["nodes"][0];
+        assert_eq!(function_json["nodeType"], "FunctionDefinition");
+        let statements = function_json["body"]["statements"].as_array_mut().unwrap();
+        let count = statements.len();
+        let future = serde_json::json!({"nodeType": "FutureStatement", "id": 1000, "src": "1:2:0"});
+        statements[0] = future.clone();
+        statements[1]["id"] = "not an id".into();
+        let bad_id = statements[1].clone();
+
+        assert!(serde_json::from_value::<SourceUnit>(value.clone()).is_err());
+        let parsed = parse_lenient(&value.to_string()).unwrap();
+
+        let node_types: Vec<&str> = parsed
+            .errors
+            .iter()
+            .map(|error| error.node_type.as_str())
+            .collect();
+        assert_eq!(node_types.len(), 2);
+        assert_eq!(node_types[0], "FutureStatement");
+        assert_eq!(
+            parsed.errors[0].path,
+            "nodes[1].nodes[0].body.statements[0]"
+        );
+        assert!(parsed.errors[0].message.contains("unknown node type"));
+        assert_eq!(parsed.errors[0].raw, future);
+        assert_eq!(
+            parsed.errors[1].path,
+            "nodes[1].nodes[0].body.statements[1]"
+        );
+
+        let ContractDefinitionNode::FunctionDefinition(function) = member(&parsed.source_unit, 0)
+        else {
+            panic!("expected a function definition");
+        };
+        let statements = &function.body.as_ref().unwrap().statements;
+        assert_eq!(statements.len(), count);
+        let Statement::UnparsedNode(placeholder) = &statements[0] else {
+            panic!("expected a placeholder");
+        };
+        assert_eq!(placeholder.id, 1000);
+        assert_eq!(placeholder.src.length, 2);
+        assert_eq!(placeholder.original_node_type(), Some("FutureStatement"));
+        let Statement::UnparsedNode(placeholder) = &statements[1] else {
+            panic!("expected a placeholder");
+        };
+        assert_eq!(placeholder.raw, bad_id);
+        assert!(!matches!(statements[2], Statement::UnparsedNode(_)));
+    }
+
+    #[test]
+    fn keeps_parameters_of_unparsed_types() {
+        let mut value = fixture();
+        let parameters = &mut value["nodes"][1]["nodes"][1]["parameters"]["parameters"];
+        let count = parameters.as_array().unwrap().len();
+        parameters[0]["typeName"] =
+            serde_json::json!({"nodeType": "FutureTypeName", "id": 1000, "src": "0:0:0"});
+
+        let parsed = parse_lenient(&value.to_string()).unwrap();
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(
+            parsed.errors[0].path,
+            "nodes[1].nodes[1].parameters.parameters[0].typeName"
+        );
+        let ContractDefinitionNode::FunctionDefinition(function) = member(&parsed.source_unit, 1)
+        else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(function.parameters.parameters.len(), count);
+        assert!(matches!(
+            function.parameters.parameters[0].type_name,
+            TypeName::UnparsedNode(_)
+        ));
+
+        // A parameter cannot be a placeholder itself, so a parameter that
+        // fails is replaced along with its function.
+        let mut value = fixture();
+        let original = value["nodes"][1]["nodes"][2].clone();
+        value["nodes"][1]["nodes"][2]["parameters"]["parameters"][0]["id"] = "not an id".into();
+        let parsed = parse_lenient(&value.to_string()).unwrap();
+        let node_types: Vec<&str> = parsed
+            .errors
+            .iter()
+            .map(|error| error.node_type.as_str())
+            .collect();
+        assert_eq!(
+            node_types,
+            ["VariableDeclaration", "ParameterList", "FunctionDefinition"]
+        );
+        let ContractDefinitionNode::UnparsedNode(placeholder) = member(&parsed.source_unit, 2)
+        else {
+            panic!("expected a placeholder");
+        };
+        assert_eq!(placeholder.raw["id"], original["id"]);
+        assert_eq!(
+            placeholder.raw["parameters"]["parameters"][0]["id"],
+            "not an id"
+        );
+        assert!(matches!(
+            member(&parsed.source_unit, 3),
+            ContractDefinitionNode::FunctionDefinition(_)
+        ));
+    }
+
+    #[test]
+    fn valid_ast_has_no_errors() {
+        let content = fixture().to_string();
+        let parsed = parse_lenient(&content).unwrap();
+        assert!(parsed.errors.is_empty());
+        assert_eq!(
+            parsed.source_unit,
+            serde_json::from_str::<SourceUnit>(&content).unwrap()
+        );
+    }
+}
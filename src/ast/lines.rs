@@ -0,0 +1,251 @@
+//! Conversion between byte offsets and lines and columns.
+//!
+//! Source locations in the AST, source maps, and diagnostics are byte
+//! offsets, while people and editors count lines and columns. An
+//! [`IndexedSource`] records where each line of a source starts, so that
+//! offsets convert to a [`LineColumn`] and back without rescanning the
+//! source, and a [`SourceIndexer`] holds one for each source of a
+//! compilation, by the source index that [`SourceLocation`] refers to.
+
+use std::collections::HashMap;
+
+use super::SourceLocation;
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+use crate::standard_json_output::StandardJsonOutput;
+
+/// A position in a source, as a 1-based line and a 1-based column counted
+/// in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineColumn {
+    /// The line, counting from 1. Lines end at `\n`.
+    pub line: usize,
+    /// The column, counting from 1, in Unicode scalar values from the start
+    /// of the line: neither bytes nor the UTF-16 units some editors use.
+    pub column: usize,
+}
+
+/// A source with the offset each of its lines starts at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSource {
+    content: String,
+    line_starts: Vec<usize>,
+}
+
+impl IndexedSource {
+    /// Index the lines of a source.
+    pub fn new(content: impl Into<String>) -> Self {
+        let content = content.into();
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            content,
+            line_starts,
+        }
+    }
+
+    /// The content of the source.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// The number of lines, counting the empty line after a final newline.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The position of a byte offset, or `None` if the offset is past the
+    /// end of the source or inside a character. The end of the source is a
+    /// valid position.
+    pub fn position(&self, offset: usize) -> Option<LineColumn> {
+        if !self.content.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.content[self.line_starts[line]..offset].chars().count() + 1;
+        Some(LineColumn {
+            line: line + 1,
+            column,
+        })
+    }
+
+    /// The byte offset of a position, or `None` if the line does not exist
+    /// or the column is past its end. The column just after the last
+    /// character of a line is valid.
+    pub fn offset(&self, position: LineColumn) -> Option<usize> {
+        let line = self.line(position.line)?;
+        let start = self.line_starts[position.line - 1];
+        let column = position.column.checked_sub(1)?;
+        match line.char_indices().nth(column) {
+            Some((index, _)) => Some(start + index),
+            None if column == line.chars().count() => Some(start + line.len()),
+            None => None,
+        }
+    }
+
+    /// The text of a 1-based line, without its line ending.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.content.len(), |next| next - 1);
+        Some(self.content[start..end].trim_end_matches('\r'))
+    }
+
+    /// The positions of the start and the end of a byte range.
+    pub fn range(&self, offset: usize, length: usize) -> Option<(LineColumn, LineColumn)> {
+        Some((
+            self.position(offset)?,
+            self.position(offset.checked_add(length)?)?,
+        ))
+    }
+
+    /// The text of a byte range, or `None` if it is out of range or splits
+    /// a character.
+    pub fn slice(&self, offset: usize, length: usize) -> Option<&str> {
+        self.content.get(offset..offset.checked_add(length)?)
+    }
+}
+
+/// The sources of a compilation, indexed by line, by source index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceIndexer {
+    sources: HashMap<usize, IndexedSource>,
+}
+
+impl SourceIndexer {
+    /// An indexer without sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sources of a compilation, with the source indices the compiler
+    /// gave them in its output. Sources given by URL, and sources the output
+    /// has no id for, are left out.
+    pub fn from_compilation(input: &StandardJsonInput, output: &StandardJsonOutput) -> Self {
+        let mut indexer = Self::new();
+        for (name, source) in &input.sources {
+            let SourceContent::Content { content } = &source.content else {
+                continue;
+            };
+            if let Some(output) = output.sources.get(name.to_string_lossy().as_ref()) {
+                indexer = indexer.source(output.id, content.clone());
+            }
+        }
+        indexer
+    }
+
+    /// Add a source with its source index.
+    pub fn source(mut self, source_index: usize, content: impl Into<String>) -> Self {
+        self.sources
+            .insert(source_index, IndexedSource::new(content));
+        self
+    }
+
+    /// The source with a source index.
+    pub fn get(&self, source_index: usize) -> Option<&IndexedSource> {
+        self.sources.get(&source_index)
+    }
+
+    /// The positions of the start and the end of a location.
+    pub fn range(&self, location: &SourceLocation) -> Option<(LineColumn, LineColumn)> {
+        self.get(location.source_index)?
+            .range(location.offset, location.length)
+    }
+
+    /// The location between two positions in a source.
+    pub fn location(
+        &self,
+        source_index: usize,
+        start: LineColumn,
+        end: LineColumn,
+    ) -> Option<SourceLocation> {
+        let source = self.get(source_index)?;
+        let offset = source.offset(start)?;
+        let length = source.offset(end)?.checked_sub(offset)?;
+        Some(SourceLocation {
+            offset,
+            length,
+            source_index,
+        })
+    }
+
+    /// The source text of a location.
+    pub fn snippet(&self, location: &SourceLocation) -> Option<&str> {
+        self.get(location.source_index)?
+            .slice(location.offset, location.length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(line: usize, column: usize) -> LineColumn {
+        LineColumn { line, column }
+    }
+
+    #[test]
+    fn converts_offsets_and_positions() {
+        let source = IndexedSource::new("contract A {\r\n    string s = \"é\";\r\n}\n");
+        assert_eq!(source.line_count(), 4);
+        assert_eq!(source.line(2), Some("    string s = \"é\";"));
+        assert_eq!(source.line(4), Some(""));
+        assert_eq!(source.line(0), None);
+        assert_eq!(source.line(5), None);
+
+        assert_eq!(source.position(0), Some(position(1, 1)));
+        assert_eq!(source.position(14), Some(position(2, 1)));
+        let e = source.content().find('é').unwrap();
+        assert_eq!(source.position(e), Some(position(2, 17)));
+        assert_eq!(source.position(e + 1), None);
+        assert_eq!(source.position(e + 2), Some(position(2, 18)));
+        assert_eq!(
+            source.position(source.content().len()),
+            Some(position(4, 1))
+        );
+        assert_eq!(source.position(source.content().len() + 1), None);
+
+        for offset in [0, 14, e, e + 2, 36] {
+            assert_eq!(
+                source.offset(source.position(offset).unwrap()),
+                Some(offset)
+            );
+        }
+        assert_eq!(source.offset(position(1, 13)), Some(12));
+        assert_eq!(source.offset(position(1, 14)), None);
+        assert_eq!(source.offset(position(1, 0)), None);
+        assert_eq!(source.offset(position(9, 1)), None);
+
+        assert_eq!(
+            source.range(e - 1, 4),
+            Some((position(2, 16), position(2, 19)))
+        );
+        assert_eq!(source.slice(e - 1, 4), Some("\"é\""));
+        assert_eq!(source.slice(e - 1, 2), None);
+    }
+
+    #[test]
+    fn indexes_sources_of_a_compilation() {
+        let input = StandardJsonInput::new()
+            .add_source("A.sol", "contract A {}\n")
+            .add_source("B.sol", "import \"A.sol\";\ncontract B is A {}\n");
+        let output: StandardJsonOutput =
+            serde_json::from_str(r#"{"sources": {"A.sol": {"id": 0}, "B.sol": {"id": 1}}}"#)
+                .unwrap();
+        let indexer = SourceIndexer::from_compilation(&input, &output);
+
+        let location = SourceLocation {
+            offset: 16,
+            length: 18,
+            source_index: 1,
+        };
+        assert_eq!(indexer.snippet(&location), Some("contract B is A {}"));
+        let (start, end) = indexer.range(&location).unwrap();
+        assert_eq!((start, end), (position(2, 1), position(2, 19)));
+        assert_eq!(indexer.location(1, start, end), Some(location));
+        assert_eq!(indexer.location(0, end, start), None);
+        assert_eq!(indexer.get(2), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Lookup of AST nodes by source location.
+//!
+//! Diagnostics and source maps refer to code by byte range rather than by
+//! node. A [`LocationIndex`] records the range of every node with an id, so
+//! that such a range can be traced back to the node it covers.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::stats::untagged_node_type;
+use super::{SourceLocation, SourceUnit};
+
+/// A node with an id and its source range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocatedNode {
+    /// The id of the node.
+    pub id: i64,
+    /// The `nodeType` of the node, such as `FunctionCall`.
+    pub node_type: String,
+    /// The source range of the node.
+    pub src: SourceLocation,
+}
+
+/// The source ranges of the nodes of a compilation.
+#[derive(Debug, Clone, Default)]
+pub struct LocationIndex {
+    /// The nodes of each source, in pre-order, by source index.
+    nodes: HashMap<usize, Vec<LocatedNode>>,
+    /// The source index of each source path.
+    paths: HashMap<PathBuf, usize>,
+}
+
+impl LocationIndex {
+    /// Index the nodes of source units.
+    ///
+    /// Pass every source unit of a compilation, so that source indices are
+    /// unique. Yul nodes have no id and are not indexed.
+    pub fn new(source_units: &[SourceUnit]) -> Self {
+        let mut index = LocationIndex::default();
+        for source_unit in source_units {
+            index.paths.insert(
+                source_unit.absolute_path.clone(),
+                source_unit.src.source_index,
+            );
+            let value = serde_json::to_value(source_unit).expect("AST nodes serialize to JSON");
+            collect(&value, "SourceUnit", "", &mut index.nodes);
+        }
+        index
+    }
+
+    /// The source index of the source at `path`, as used in source ranges.
+    pub fn source_index(&self, path: impl AsRef<Path>) -> Option<usize> {
+        self.paths.get(path.as_ref()).copied()
+    }
+
+    /// The nodes of a source, in pre-order.
+    pub fn nodes(&self, source_index: usize) -> &[LocatedNode] {
+        self.nodes.get(&source_index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Find the node that a source range refers to.
+    ///
+    /// This is the innermost node with exactly this range if there is one,
+    /// and otherwise the smallest node that contains the range.
+    pub fn node_at(&self, location: &SourceLocation) -> Option<&LocatedNode> {
+        let nodes = self.nodes(location.source_index);
+        let exact = nodes.iter().rev().find(|node| node.src == *location);
+        exact.or_else(|| {
+            nodes
+                .iter()
+                .rev()
+                .filter(|node| contains(&node.src, location))
+                .min_by_key(|node| node.src.length)
+        })
+    }
+
+    /// The nodes whose range contains a byte offset, outermost first.
+    pub fn nodes_containing(&self, source_index: usize, offset: usize) -> Vec<&LocatedNode> {
+        let location = SourceLocation {
+            offset,
+            length: 0,
+            source_index,
+        };
+        self.nodes(source_index)
+            .iter()
+            .filter(|node| contains(&node.src, &location))
+            .collect()
+    }
+}
+
+/// Whether the range `outer` contains the range `inner`.
+fn contains(outer: &SourceLocation, inner: &SourceLocation) -> bool {
+    outer.offset <= inner.offset && inner.offset + inner.length <= outer.offset + outer.length
+}
+
+/// Record the serialized nodes in `value`, held by `field` of `parent`.
+fn collect(value: &Value, parent: &str, field: &str, nodes: &mut HashMap<usize, Vec<LocatedNode>>) {
+    match value {
+        Value::Object(object) => {
+            let node_type = object
+                .get("nodeType")
+                .and_then(Value::as_str)
+                .or_else(|| untagged_node_type(parent, field))
+                .unwrap_or(parent);
+            let id = object.get("id").and_then(Value::as_i64);
+            let src = object
+                .get("src")
+                .cloned()
+                .and_then(|src| serde_json::from_value::<SourceLocation>(src).ok());
+            if let (Some(id), Some(src)) = (id, src) {
+                nodes
+                    .entry(src.source_index)
+                    .or_default()
+                    .push(LocatedNode {
+                        id,
+                        node_type: node_type.to_string(),
+                        src,
+                    });
+            }
+            for (field, value) in object {
+                collect(value, node_type, field, nodes);
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|value| collect(value, parent, field, nodes)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::ast::SourceUnitNode;
+
+    #[test]
+    fn fixture_locations() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/StdCheats.json").unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let index = LocationIndex::new(std::slice::from_ref(&source_unit));
+        let source_index = index.source_index(&source_unit.absolute_path).unwrap();
+        assert_eq!(source_index, source_unit.src.source_index);
+        assert_eq!(index.nodes(source_index)[0].id, source_unit.id);
+
+        let SourceUnitNode::ContractDefinition(contract) = source_unit
+            .nodes
+            .iter()
+            .find(|node| matches!(node, SourceUnitNode::ContractDefinition(_)))
+            .unwrap()
+        else {
+            unreachable!();
+        };
+        let node = index.node_at(&contract.src).unwrap();
+        assert_eq!(node.id, contract.id);
+        assert_eq!(node.node_type, "ContractDefinition");
+
+        let containing = index.nodes_containing(source_index, contract.src.offset + 1);
+        assert_eq!(containing[0].node_type, "SourceUnit");
+        assert_eq!(containing[1].id, contract.id);
+
+        // A range inside a node but matching none is traced to the smallest
+        // node around it.
+        let inner = SourceLocation {
+            offset: contract.src.offset + 1,
+            length: 0,
+            ..contract.src.clone()
+        };
+        let node = index.node_at(&inner).unwrap();
+        assert!(contains(&node.src, &inner));
+        assert!(node.src.length <= contract.src.length);
+    }
+}
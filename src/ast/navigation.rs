@@ -0,0 +1,215 @@
+//! What the code under a cursor is and where it is declared.
+//!
+//! Editors ask two questions about a position in a source: what is here,
+//! and where is it defined. A [`Navigator`] answers both from the AST of a
+//! compilation, finding the node at a byte offset with a
+//! [`LocationIndex`], following its reference with an [`AstIndex`], and
+//! reading its type from the type descriptions the compiler wrote.
+
+use super::*;
+
+/// The node under a cursor, with the declaration it refers to and its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hover<'a> {
+    /// The innermost node at the offset.
+    pub node: Node<'a>,
+    /// The declaration the node refers to, or the node itself if it is a
+    /// declaration. `None` for built-ins such as `msg` and for nodes that
+    /// refer to nothing, such as literals.
+    pub declaration: Option<Declaration<'a>>,
+    /// The type of the node as the compiler writes it, such as
+    /// `uint256` or `function (address) view returns (uint256)`, or the
+    /// type of the variable it declares or refers to.
+    pub type_string: Option<&'a str>,
+}
+
+/// Answers hover and go-to-definition queries for a compilation.
+#[derive(Debug, Clone)]
+pub struct Navigator<'a> {
+    index: AstIndex<'a>,
+    locations: LocationIndex,
+}
+
+impl<'a> Navigator<'a> {
+    /// Index source units for queries.
+    ///
+    /// Pass every source unit of a compilation, so that references across
+    /// files resolve.
+    pub fn new(source_units: &'a [SourceUnit]) -> Self {
+        Self {
+            index: AstIndex::new(source_units),
+            locations: LocationIndex::new(source_units),
+        }
+    }
+
+    /// The declarations of the compilation.
+    pub fn index(&self) -> &AstIndex<'a> {
+        &self.index
+    }
+
+    /// The source ranges of the nodes of the compilation.
+    pub fn locations(&self) -> &LocationIndex {
+        &self.locations
+    }
+
+    /// The innermost node at a byte offset of the source with an index.
+    ///
+    /// A node that starts at the offset is preferred over one that ends
+    /// there.
+    pub fn node_at(&self, source_index: usize, offset: usize) -> Option<Node<'a>> {
+        let innermost = self
+            .locations
+            .nodes_containing(source_index, offset)
+            .iter()
+            .rev()
+            .min_by_key(|node| (node.src.offset + node.src.length == offset, node.src.length))?
+            .id;
+        self.index.node(innermost)
+    }
+
+    /// What the node at a byte offset is, or `None` if no node is there.
+    ///
+    /// On the name of a declaration, the node is the declaration, even when
+    /// the compiler reports a type name spanning the name, as it does for
+    /// variables of function type.
+    pub fn hover(&self, source_index: usize, offset: usize) -> Option<Hover<'a>> {
+        let node = self
+            .declaration_named_at(source_index, offset)
+            .or_else(|| self.node_at(source_index, offset))?;
+        let declaration = match referenced_declaration(node) {
+            Some(reference) => self.index.resolve(Some(reference)),
+            None => self.index.get(node.id()),
+        };
+        let type_descriptions = type_descriptions(node).or(match declaration {
+            Some(Declaration::VariableDeclaration(variable)) => Some(&variable.type_descriptions),
+            _ => None,
+        });
+        Some(Hover {
+            node,
+            declaration,
+            type_string: type_descriptions.and_then(|types| types.type_string.as_deref()),
+        })
+    }
+
+    /// The innermost declaration whose name is at a byte offset.
+    fn declaration_named_at(&self, source_index: usize, offset: usize) -> Option<Node<'a>> {
+        self.locations
+            .nodes_containing(source_index, offset)
+            .iter()
+            .rev()
+            .filter_map(|node| self.index.get(node.id))
+            .find(|declaration| {
+                declaration.name_src().is_some_and(|name| {
+                    name.offset <= offset && offset <= name.offset + name.length
+                })
+            })
+            .and_then(|declaration| self.index.node(declaration.id()))
+    }
+
+    /// Where the node at a byte offset is declared: the range of the
+    /// declared name, or of the whole declaration if it has no name.
+    pub fn definition(&self, source_index: usize, offset: usize) -> Option<SourceLocation> {
+        let declaration = self.hover(source_index, offset)?.declaration?;
+        Some(
+            declaration
+                .name_src()
+                .unwrap_or_else(|| declaration.src().clone()),
+        )
+    }
+}
+
+/// The id of the declaration a node refers to.
+fn referenced_declaration(node: Node<'_>) -> Option<i64> {
+    match node {
+        Node::Identifier(node) => node.referenced_declaration,
+        Node::IdentifierPath(node) => node.referenced_declaration,
+        Node::MemberAccess(node) => node.referenced_declaration,
+        Node::UserDefinedTypeName(node) => node.referenced_declaration,
+        _ => None,
+    }
+}
+
+/// The types the compiler wrote for a node.
+fn type_descriptions(node: Node<'_>) -> Option<&TypeDescriptions> {
+    match node {
+        Node::VariableDeclaration(node) => Some(&node.type_descriptions),
+        Node::Assignment(node) => Some(&node.type_descriptions),
+        Node::BinaryOperation(node) => Some(&node.type_descriptions),
+        Node::Conditional(node) => Some(&node.type_descriptions),
+        Node::UnaryOperation(node) => Some(&node.type_descriptions),
+        Node::FunctionCall(node) => Some(&node.type_descriptions),
+        Node::FunctionCallOptions(node) => Some(&node.type_descriptions),
+        Node::MemberAccess(node) => Some(&node.type_descriptions),
+        Node::IndexAccess(node) => Some(&node.type_descriptions),
+        Node::IndexRangeAccess(node) => Some(&node.type_descriptions),
+        Node::TupleExpression(node) => Some(&node.type_descriptions),
+        Node::Identifier(node) => Some(&node.type_descriptions),
+        Node::Literal(node) => Some(&node.type_descriptions),
+        Node::NewExpression(node) => Some(&node.type_descriptions),
+        Node::ElementaryTypeNameExpression(node) => Some(&node.type_descriptions),
+        Node::ElementaryTypeName(node) => Some(&node.type_descriptions),
+        Node::UserDefinedTypeName(node) => Some(&node.type_descriptions),
+        Node::ArrayTypeName(node) => Some(&node.type_descriptions),
+        Node::Mapping(node) => Some(&node.type_descriptions),
+        Node::FunctionTypeName(node) => Some(&node.type_descriptions),
+        _ => None,
+    }
+    .filter(|types| types.type_string.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::ast::visit::Visitor;
+
+    /// Collects the identifiers of a source unit.
+    #[derive(Default)]
+    struct Identifiers(Vec<Identifier>);
+
+    impl Visitor for Identifiers {
+        fn visit_identifier(&mut self, node: &Identifier) {
+            self.0.push(node.clone());
+        }
+    }
+
+    #[test]
+    fn hover_and_definition() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/StdCheats.json").unwrap();
+        let source_units = vec![serde_json::from_str::<SourceUnit>(&content).unwrap()];
+        let navigator = Navigator::new(&source_units);
+
+        let mut identifiers = Identifiers::default();
+        identifiers.visit_source_unit(&source_units[0]);
+        let mut resolved = 0;
+        for identifier in &identifiers.0 {
+            let src = &identifier.src;
+            let hover = navigator.hover(src.source_index, src.offset).unwrap();
+            assert_eq!(hover.node.id(), identifier.id);
+            assert_eq!(
+                hover.type_string,
+                identifier.type_descriptions.type_string.as_deref()
+            );
+            let Some(declaration) = hover.declaration else {
+                continue;
+            };
+            assert_eq!(Some(declaration.id()), identifier.referenced_declaration);
+
+            let definition = navigator.definition(src.source_index, src.offset).unwrap();
+            assert_eq!(definition, declaration.name_src().unwrap());
+            let at_definition = navigator
+                .hover(definition.source_index, definition.offset)
+                .unwrap();
+            assert_eq!(at_definition.declaration, Some(declaration));
+            resolved += 1;
+        }
+        assert!(resolved > 100);
+
+        let source_unit = &source_units[0];
+        let end = source_unit.src.offset + source_unit.src.length;
+        assert_eq!(navigator.hover(source_unit.src.source_index, end + 1), None);
+        assert_eq!(navigator.hover(source_unit.src.source_index + 1, 0), None);
+    }
+}
@@ -0,0 +1,537 @@
+//! A reference to any AST node.
+//!
+//! The AST is a tree of distinct node structs held in per-position enums
+//! such as [`Statement`] and [`Expression`]. A [`Node`] refers to a node of
+//! any type, so that code which only needs ids, source ranges, or the shape
+//! of the tree can handle every node type in one place.
+//!
+//! Yul nodes have no ids, and are not represented: an [`InlineAssembly`]
+//! node has no children other than its documentation.
+
+use super::*;
+
+/// Define [`Node`] with a variant and a `From` impl for each node type.
+macro_rules! nodes {
+    ($($name:ident),* $(,)?) => {
+        /// A reference to a Solidity AST node of any type.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Node<'a> {
+            $($name(&'a $name),)*
+        }
+
+        $(
+            impl<'a> From<&'a $name> for Node<'a> {
+                fn from(node: &'a $name) -> Self {
+                    Node::$name(node)
+                }
+            }
+        )*
+
+        impl<'a> Node<'a> {
+            /// The id of the node.
+            pub fn id(&self) -> i64 {
+                match self {
+                    $(Node::$name(node) => node.id,)*
+                }
+            }
+
+            /// The source range of the node.
+            pub fn src(&self) -> &'a SourceLocation {
+                match *self {
+                    $(Node::$name(node) => &node.src,)*
+                }
+            }
+
+            /// The `nodeType` of the node, such as `FunctionCall`.
+            pub fn node_type(&self) -> &'static str {
+                match self {
+                    $(Node::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+nodes!(
+    SourceUnit,
+    PragmaDirective,
+    ImportDirective,
+    ContractDefinition,
+    InheritanceSpecifier,
+    VariableDeclaration,
+    OverrideSpecifier,
+    FunctionDefinition,
+    ModifierInvocation,
+    ParameterList,
+    ModifierDefinition,
+    EventDefinition,
+    ErrorDefinition,
+    StructDefinition,
+    EnumDefinition,
+    EnumValue,
+    UserDefinedValueTypeDefinition,
+    UsingForDirective,
+    Block,
+    UncheckedBlock,
+    IfStatement,
+    ForStatement,
+    WhileStatement,
+    DoWhileStatement,
+    Continue,
+    Break,
+    Return,
+    EmitStatement,
+    RevertStatement,
+    TryStatement,
+    TryCatchClause,
+    ExpressionStatement,
+    VariableDeclarationStatement,
+    InlineAssembly,
+    PlaceholderStatement,
+    Assignment,
+    BinaryOperation,
+    Conditional,
+    UnaryOperation,
+    FunctionCall,
+    FunctionCallOptions,
+    MemberAccess,
+    IndexAccess,
+    IndexRangeAccess,
+    TupleExpression,
+    Identifier,
+    IdentifierPath,
+    Literal,
+    NewExpression,
+    ElementaryTypeNameExpression,
+    ElementaryTypeName,
+    UserDefinedTypeName,
+    ArrayTypeName,
+    Mapping,
+    FunctionTypeName,
+    StructuredDocumentation,
+    UnparsedNode,
+);
+
+impl<'a> Node<'a> {
+    /// The direct children of the node, in the order the
+    /// [`Visitor`](super::Visitor) visits them.
+    pub fn children(&self) -> Vec<Node<'a>> {
+        let mut children = Vec::new();
+        match *self {
+            Node::SourceUnit(node) => children.extend(node.nodes.iter().map(Node::from)),
+            Node::ImportDirective(node) => children.extend(
+                node.symbol_aliases
+                    .iter()
+                    .map(|alias| Node::from(&alias.foreign)),
+            ),
+            Node::ContractDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.base_contracts.iter().map(Node::from));
+                children.extend(node.nodes.iter().map(Node::from));
+            }
+            Node::InheritanceSpecifier(node) => {
+                children.push(Node::from(&node.base_name));
+                children.extend(
+                    node.arguments
+                        .iter()
+                        .flatten()
+                        .map(Box::as_ref)
+                        .map(Node::from),
+                );
+            }
+            Node::VariableDeclaration(node) => {
+                children.extend(documentation(&node.documentation));
+                children.push(Node::from(&node.type_name));
+                children.extend(node.overrides.as_ref().map(Node::from));
+                children.extend(node.value.as_deref().map(Node::from));
+            }
+            Node::OverrideSpecifier(node) => children.extend(node.overrides.iter().map(Node::from)),
+            Node::FunctionDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.overrides.as_ref().map(Node::from));
+                children.push(Node::from(&node.parameters));
+                children.push(Node::from(&node.return_parameters));
+                children.extend(node.modifiers.iter().map(Node::from));
+                children.extend(node.body.as_ref().map(Node::from));
+            }
+            Node::ModifierInvocation(node) => {
+                children.push(Node::from(&node.modifier_name));
+                children.extend(
+                    node.arguments
+                        .iter()
+                        .flatten()
+                        .map(Box::as_ref)
+                        .map(Node::from),
+                );
+            }
+            Node::ParameterList(node) => children.extend(node.parameters.iter().map(Node::from)),
+            Node::ModifierDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.overrides.as_ref().map(Node::from));
+                children.push(Node::from(&node.parameters));
+                children.push(Node::from(&node.body));
+            }
+            Node::EventDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.push(Node::from(&node.parameters));
+            }
+            Node::ErrorDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.push(Node::from(&node.parameters));
+            }
+            Node::StructDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.members.iter().map(Node::from));
+            }
+            Node::EnumDefinition(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.members.iter().map(Node::from));
+            }
+            Node::EnumValue(node) => children.extend(documentation(&node.documentation)),
+            Node::UserDefinedValueTypeDefinition(node) => {
+                children.push(Node::from(&node.underlying_type))
+            }
+            Node::UsingForDirective(node) => {
+                children.extend(node.library_name.as_ref().map(Node::from));
+                for function in node.function_list.iter().flatten() {
+                    children.extend(function.function.as_ref().map(Node::from));
+                    children.extend(function.definition.as_ref().map(Node::from));
+                }
+                children.extend(node.type_name.as_ref().map(Node::from));
+            }
+            Node::Block(node) => children.extend(node.statements.iter().map(Node::from)),
+            Node::UncheckedBlock(node) => children.extend(node.statements.iter().map(Node::from)),
+            Node::IfStatement(node) => {
+                children.push(Node::from(&*node.condition));
+                children.push(Node::from(&*node.true_body));
+                children.extend(node.false_body.as_deref().map(Node::from));
+            }
+            Node::ForStatement(node) => {
+                children.extend(
+                    node.initialization_expression
+                        .iter()
+                        .map(Box::as_ref)
+                        .map(Node::from),
+                );
+                children.push(Node::from(&*node.condition));
+                children.extend(node.loop_expression.iter().map(Box::as_ref).map(Node::from));
+                children.push(Node::from(&*node.body));
+            }
+            Node::WhileStatement(node) => {
+                children.push(Node::from(&*node.condition));
+                children.push(Node::from(&*node.body));
+            }
+            Node::DoWhileStatement(node) => {
+                children.push(Node::from(&*node.body));
+                children.push(Node::from(&*node.condition));
+            }
+            Node::Return(node) => {
+                children.extend(node.expression.iter().map(Box::as_ref).map(Node::from))
+            }
+            Node::EmitStatement(node) => children.push(Node::from(&node.event_call)),
+            Node::RevertStatement(node) => children.push(Node::from(&node.error_call)),
+            Node::TryStatement(node) => {
+                children.push(Node::from(&*node.external_call));
+                children.extend(node.clauses.iter().map(Node::from));
+            }
+            Node::TryCatchClause(node) => {
+                children.extend(node.parameters.as_ref().map(Node::from));
+                children.push(Node::from(&node.block));
+            }
+            Node::ExpressionStatement(node) => children.push(Node::from(&*node.expression)),
+            Node::VariableDeclarationStatement(node) => {
+                children.extend(documentation(&node.documentation));
+                children.extend(node.declarations.iter().flatten().map(Node::from));
+                children.extend(node.initial_value.iter().map(Box::as_ref).map(Node::from));
+            }
+            Node::InlineAssembly(node) => children.extend(documentation(&node.documentation)),
+            Node::Assignment(node) => {
+                children.push(Node::from(&*node.left_hand_side));
+                children.push(Node::from(&*node.right_hand_side));
+            }
+            Node::BinaryOperation(node) => {
+                children.push(Node::from(&*node.left_expression));
+                children.push(Node::from(&*node.right_expression));
+            }
+            Node::Conditional(node) => {
+                children.push(Node::from(&*node.condition));
+                children.push(Node::from(&*node.true_expression));
+                children.push(Node::from(&*node.false_expression));
+            }
+            Node::UnaryOperation(node) => children.push(Node::from(&*node.sub_expression)),
+            Node::FunctionCall(node) => {
+                children.push(Node::from(&*node.expression));
+                children.extend(node.arguments.iter().map(Box::as_ref).map(Node::from));
+            }
+            Node::FunctionCallOptions(node) => {
+                children.push(Node::from(&*node.expression));
+                children.extend(node.options.iter().map(Box::as_ref).map(Node::from));
+            }
+            Node::MemberAccess(node) => children.push(Node::from(&*node.expression)),
+            Node::IndexAccess(node) => {
+                children.push(Node::from(&*node.base_expression));
+                children.extend(
+                    node.index_expression
+                        .iter()
+                        .map(Box::as_ref)
+                        .map(Node::from),
+                );
+            }
+            Node::IndexRangeAccess(node) => {
+                children.push(Node::from(&*node.base_expression));
+                children.extend(
+                    node.start_expression
+                        .iter()
+                        .chain(&node.end_expression)
+                        .map(Box::as_ref)
+                        .map(Node::from),
+                );
+            }
+            Node::TupleExpression(node) => children.extend(
+                node.components
+                    .iter()
+                    .flatten()
+                    .map(Box::as_ref)
+                    .map(Node::from),
+            ),
+            Node::NewExpression(node) => children.push(Node::from(&node.type_name)),
+            Node::ElementaryTypeNameExpression(node) => children.push(Node::from(&node.type_name)),
+            Node::UserDefinedTypeName(node) => {
+                children.extend(node.path_node.as_ref().map(Node::from))
+            }
+            Node::ArrayTypeName(node) => {
+                children.push(Node::from(&*node.base_type));
+                children.extend(node.length.iter().map(Box::as_ref).map(Node::from));
+            }
+            Node::Mapping(node) => {
+                children.push(Node::from(&*node.key_type));
+                children.push(Node::from(&*node.value_type));
+            }
+            Node::FunctionTypeName(node) => {
+                children.push(Node::from(&node.parameter_types));
+                children.push(Node::from(&node.return_parameter_types));
+            }
+            Node::PragmaDirective(_)
+            | Node::Continue(_)
+            | Node::Break(_)
+            | Node::PlaceholderStatement(_)
+            | Node::Identifier(_)
+            | Node::IdentifierPath(_)
+            | Node::Literal(_)
+            | Node::ElementaryTypeName(_)
+            | Node::StructuredDocumentation(_)
+            | Node::UnparsedNode(_) => {}
+        }
+        children
+    }
+
+    /// The node and all nodes below it, in pre-order.
+    pub fn descendants(&self) -> Vec<Node<'a>> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![*self];
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            stack.extend(node.children().into_iter().rev());
+        }
+        nodes
+    }
+}
+
+/// The node of structured documentation, which is the only kind with an id.
+fn documentation(documentation: &Option<Documentation>) -> Option<Node<'_>> {
+    match documentation {
+        Some(Documentation::Structured(documentation)) => Some(Node::from(documentation)),
+        _ => None,
+    }
+}
+
+impl<'a> From<&'a SourceUnitNode> for Node<'a> {
+    fn from(node: &'a SourceUnitNode) -> Self {
+        match node {
+            SourceUnitNode::ContractDefinition(node) => node.into(),
+            SourceUnitNode::EnumDefinition(node) => node.into(),
+            SourceUnitNode::ErrorDefinition(node) => node.into(),
+            SourceUnitNode::EventDefinition(node) => node.into(),
+            SourceUnitNode::FunctionDefinition(node) => node.into(),
+            SourceUnitNode::ImportDirective(node) => node.into(),
+            SourceUnitNode::PragmaDirective(node) => node.into(),
+            SourceUnitNode::StructDefinition(node) => node.into(),
+            SourceUnitNode::UserDefinedValueTypeDefinition(node) => node.into(),
+            SourceUnitNode::UsingForDirective(node) => node.into(),
+            SourceUnitNode::VariableDeclaration(node) => node.into(),
+            SourceUnitNode::UnparsedNode(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a ContractDefinitionNode> for Node<'a> {
+    fn from(node: &'a ContractDefinitionNode) -> Self {
+        match node {
+            ContractDefinitionNode::EnumDefinition(node) => node.into(),
+            ContractDefinitionNode::ErrorDefinition(node) => node.into(),
+            ContractDefinitionNode::EventDefinition(node) => node.into(),
+            ContractDefinitionNode::FunctionDefinition(node) => node.into(),
+            ContractDefinitionNode::ModifierDefinition(node) => node.into(),
+            ContractDefinitionNode::StructDefinition(node) => node.into(),
+            ContractDefinitionNode::UserDefinedValueTypeDefinition(node) => node.into(),
+            ContractDefinitionNode::UsingForDirective(node) => node.into(),
+            ContractDefinitionNode::VariableDeclaration(node) => node.into(),
+            ContractDefinitionNode::UnparsedNode(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a Statement> for Node<'a> {
+    fn from(node: &'a Statement) -> Self {
+        match node {
+            Statement::Block(node) => node.into(),
+            Statement::Break(node) => node.into(),
+            Statement::Continue(node) => node.into(),
+            Statement::DoWhileStatement(node) => node.into(),
+            Statement::EmitStatement(node) => node.into(),
+            Statement::ExpressionStatement(node) => node.into(),
+            Statement::ForStatement(node) => node.into(),
+            Statement::IfStatement(node) => node.into(),
+            Statement::InlineAssembly(node) => node.into(),
+            Statement::PlaceholderStatement(node) => node.into(),
+            Statement::Return(node) => node.into(),
+            Statement::RevertStatement(node) => node.into(),
+            Statement::TryStatement(node) => node.into(),
+            Statement::UncheckedBlock(node) => node.into(),
+            Statement::VariableDeclarationStatement(node) => node.into(),
+            Statement::WhileStatement(node) => node.into(),
+            Statement::UnparsedNode(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a Expression> for Node<'a> {
+    fn from(node: &'a Expression) -> Self {
+        match node {
+            Expression::Assignment(node) => node.into(),
+            Expression::BinaryOperation(node) => node.into(),
+            Expression::Conditional(node) => node.into(),
+            Expression::ElementaryTypeNameExpression(node) => node.into(),
+            Expression::FunctionCall(node) => node.into(),
+            Expression::Identifier(node) => node.into(),
+            Expression::IndexAccess(node) => node.into(),
+            Expression::IndexRangeAccess(node) => node.into(),
+            Expression::Literal(node) => node.into(),
+            Expression::MemberAccess(node) => node.into(),
+            Expression::NewExpression(node) => node.into(),
+            Expression::TupleExpression(node) => node.into(),
+            Expression::UnaryOperation(node) => node.into(),
+            Expression::VariableDeclarationStatement(node) => node.into(),
+            Expression::ExpressionStatement(node) => node.into(),
+            Expression::UnparsedNode(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a FunctionCallExpression> for Node<'a> {
+    fn from(node: &'a FunctionCallExpression) -> Self {
+        match node {
+            FunctionCallExpression::ElementaryTypeNameExpression(node) => node.into(),
+            FunctionCallExpression::FunctionCall(node) => node.into(),
+            FunctionCallExpression::FunctionCallOptions(node) => node.into(),
+            FunctionCallExpression::Identifier(node) => node.into(),
+            FunctionCallExpression::MemberAccess(node) => node.into(),
+            FunctionCallExpression::NewExpression(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a TypeName> for Node<'a> {
+    fn from(node: &'a TypeName) -> Self {
+        match node {
+            TypeName::ArrayTypeName(node) => node.into(),
+            TypeName::ElementaryTypeName(node) => node.into(),
+            TypeName::FunctionTypeName(node) => node.into(),
+            TypeName::Mapping(node) => node.into(),
+            TypeName::UserDefinedTypeName(node) => node.into(),
+            TypeName::UnparsedNode(node) => node.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    use super::*;
+    use crate::ast::visit::{self, Visitor};
+
+    /// Counts the nodes of a few types, as the visitor reaches them.
+    #[derive(Default)]
+    struct Counter(BTreeMap<&'static str, usize>);
+
+    impl Visitor for Counter {
+        fn visit_identifier(&mut self, _node: &Identifier) {
+            *self.0.entry("Identifier").or_default() += 1;
+        }
+        fn visit_function_call(&mut self, node: &FunctionCall) {
+            *self.0.entry("FunctionCall").or_default() += 1;
+            visit::walk_function_call(self, node);
+        }
+        fn visit_variable_declaration(&mut self, node: &VariableDeclaration) {
+            *self.0.entry("VariableDeclaration").or_default() += 1;
+            visit::walk_variable_declaration(self, node);
+        }
+        fn visit_block(&mut self, node: &Block) {
+            *self.0.entry("Block").or_default() += 1;
+            visit::walk_block(self, node);
+        }
+    }
+
+    #[test]
+    fn descendants_match_visitor() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/StdCheats.json").unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+
+        let mut counter = Counter::default();
+        counter.visit_source_unit(&source_unit);
+        let root = Node::from(&source_unit);
+        let descendants = root.descendants();
+        let mut counts = BTreeMap::new();
+        for node in &descendants {
+            if counter.0.contains_key(node.node_type()) {
+                *counts.entry(node.node_type()).or_default() += 1;
+            }
+        }
+        assert_eq!(counts, counter.0);
+        assert_eq!(counts.len(), 4);
+
+        assert_eq!(descendants[0], root);
+        assert_eq!(root.id(), source_unit.id);
+        assert_eq!(root.src(), &source_unit.src);
+        assert_eq!(root.children().len(), source_unit.nodes.len());
+
+        // Ids are unique.
+        let mut ids: Vec<_> = descendants.iter().map(Node::id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), descendants.len());
+    }
+
+    #[test]
+    fn children_in_order() {
+        let statement = Statement::DoWhileStatement(DoWhileStatement {
+            id: 1,
+            condition: Box::new(Expression::Identifier(Identifier {
+                id: 2,
+                ..Default::default()
+            })),
+            body: Box::new(Statement::Break(Break {
+                id: 3,
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+        let node = Node::from(&statement);
+        assert_eq!(node.node_type(), "DoWhileStatement");
+        let children: Vec<_> = node.children().iter().map(Node::id).collect();
+        assert_eq!(children, [3, 2]);
+    }
+}
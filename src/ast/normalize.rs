@@ -0,0 +1,216 @@
+//! Upgrade ASTs from older compiler releases to the current shape.
+//!
+//! The AST format changes between solc releases. Fields are added (such as
+//! `usedEvents` in 0.8.20 or `nameLocation` in 0.8.2), and some flags are
+//! replaced (such as `isConstructor`, `constant`, and `payable` on functions,
+//! which became `kind` and `stateMutability` in 0.5). The node types in
+//! [`crate::ast`] follow the current format, so ASTs from older releases fail
+//! to deserialize or differ from a newer AST of the same code only in these
+//! details.
+//!
+//! [`normalize`] rewrites the JSON of an AST in place so that it has the
+//! current shape. Missing fields are filled in with the value a current
+//! compiler would emit where it can be derived from the node itself, and with
+//! an empty or "unknown" value otherwise. Values that need information from
+//! elsewhere in the tree, such as `eventSelector`, are not filled in.
+
+use serde_json::{Map, Value};
+
+/// The location used by the compiler when a node has no source location.
+const UNKNOWN_LOCATION: &str = "-1:-1:-1";
+
+/// Upgrade the JSON of an AST to the current shape, in place.
+///
+/// Every node in the tree is visited, so this works on a whole source unit
+/// or on any node within one. Normalizing an AST that already has the current
+/// shape leaves it unchanged.
+pub fn normalize(ast: &mut Value) {
+    match ast {
+        Value::Object(node) => {
+            if let Some(node_type) = node.get("nodeType").and_then(Value::as_str) {
+                let node_type = node_type.to_string();
+                normalize_node(&node_type, node);
+            }
+            node.values_mut().for_each(normalize);
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+fn normalize_node(node_type: &str, node: &mut Map<String, Value>) {
+    match node_type {
+        "ContractDefinition" => {
+            let name = node.get("name").cloned().unwrap_or_default();
+            default_field(node, "canonicalName", name);
+            default_field(node, "abstract", Value::Bool(false));
+            default_field(node, "contractDependencies", Value::Array(Vec::new()));
+            default_field(node, "usedErrors", Value::Array(Vec::new()));
+            default_field(node, "usedEvents", Value::Array(Vec::new()));
+            default_field(node, "nameLocation", UNKNOWN_LOCATION.into());
+        }
+        "FunctionDefinition" => {
+            normalize_function_kind(node);
+            normalize_state_mutability(node);
+            let implemented = node.get("body").is_some_and(|body| !body.is_null());
+            default_field(node, "implemented", Value::Bool(implemented));
+            default_field(node, "virtual", Value::Bool(false));
+            default_field(node, "modifiers", Value::Array(Vec::new()));
+            default_field(node, "nameLocation", UNKNOWN_LOCATION.into());
+        }
+        "VariableDeclaration" => {
+            let constant = node.get("constant").and_then(Value::as_bool);
+            let mutability = if constant == Some(true) {
+                "constant"
+            } else {
+                "mutable"
+            };
+            default_field(node, "mutability", mutability.into());
+            default_field(node, "constant", Value::Bool(false));
+            default_field(node, "nameLocation", UNKNOWN_LOCATION.into());
+        }
+        "ModifierDefinition" => {
+            default_field(node, "virtual", Value::Bool(false));
+            default_field(node, "nameLocation", UNKNOWN_LOCATION.into());
+        }
+        "ModifierInvocation" => {
+            default_field(node, "kind", "modifierInvocation".into());
+        }
+        "EventDefinition"
+        | "ErrorDefinition"
+        | "StructDefinition"
+        | "EnumDefinition"
+        | "EnumValue"
+        | "UserDefinedValueTypeDefinition" => {
+            default_field(node, "nameLocation", UNKNOWN_LOCATION.into());
+        }
+        "FunctionCall" => {
+            default_field(node, "tryCall", Value::Bool(false));
+        }
+        _ => {}
+    }
+}
+
+/// Derive `kind` from the pre-0.5 `isConstructor` flag and function name.
+fn normalize_function_kind(node: &mut Map<String, Value>) {
+    let is_constructor = node.remove("isConstructor").and_then(|v| v.as_bool());
+    if node.contains_key("kind") {
+        return;
+    }
+    let unnamed = node
+        .get("name")
+        .and_then(Value::as_str)
+        .is_none_or(str::is_empty);
+    let kind = if is_constructor == Some(true) {
+        "constructor"
+    } else if unnamed {
+        "fallback"
+    } else {
+        "function"
+    };
+    node.insert("kind".to_string(), kind.into());
+}
+
+/// Derive `stateMutability` from the pre-0.5 `constant` and `payable` flags.
+fn normalize_state_mutability(node: &mut Map<String, Value>) {
+    let constant = node.remove("constant").and_then(|v| v.as_bool());
+    let payable = node.remove("payable").and_then(|v| v.as_bool());
+    if node.contains_key("stateMutability") {
+        return;
+    }
+    let state_mutability = if payable == Some(true) {
+        "payable"
+    } else if constant == Some(true) {
+        "view"
+    } else {
+        "nonpayable"
+    };
+    node.insert("stateMutability".to_string(), state_mutability.into());
+}
+
+fn default_field(node: &mut Map<String, Value>, key: &str, value: Value) {
+    if !node.contains_key(key) {
+        node.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rayon::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+    use crate::ast::{
+        ContractDefinitionNode, FunctionKind, SourceUnit, SourceUnitNode, StateMutability,
+    };
+
+    #[test]
+    fn upgrades_old_ast() {
+        let mut ast = json!({
+            "nodeType": "SourceUnit",
+            "id": 5,
+            "absolutePath": "Old.sol",
+            "exportedSymbols": {"Old": [4]},
+            "src": "0:60:0",
+            "nodes": [{
+                "nodeType": "ContractDefinition",
+                "id": 4,
+                "name": "Old",
+                "baseContracts": [],
+                "contractKind": "contract",
+                "fullyImplemented": true,
+                "linearizedBaseContracts": [4],
+                "scope": 5,
+                "src": "0:60:0",
+                "nodes": [{
+                    "nodeType": "FunctionDefinition",
+                    "id": 3,
+                    "name": "",
+                    "isConstructor": false,
+                    "constant": false,
+                    "payable": true,
+                    "visibility": "public",
+                    "body": {"nodeType": "Block", "id": 2, "src": "40:2:0", "statements": []},
+                    "parameters": {"id": 0, "parameters": [], "src": "28:2:0"},
+                    "returnParameters": {"id": 1, "parameters": [], "src": "40:0:0"},
+                    "scope": 4,
+                    "src": "20:22:0"
+                }]
+            }]
+        });
+
+        normalize(&mut ast);
+        let function = &ast["nodes"][0]["nodes"][0];
+        assert!(function.get("payable").is_none());
+        assert!(function.get("isConstructor").is_none());
+
+        let source_unit: SourceUnit = serde_json::from_value(ast).unwrap();
+        let SourceUnitNode::ContractDefinition(contract) = &source_unit.nodes[0] else {
+            panic!("expected a contract definition");
+        };
+        assert_eq!(contract.canonical_name, "Old");
+        assert_eq!(contract.used_events, Some(Vec::new()));
+        let ContractDefinitionNode::FunctionDefinition(function) = &contract.nodes[0] else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(function.kind, FunctionKind::Fallback);
+        assert_eq!(function.state_mutability, StateMutability::Payable);
+        assert!(function.implemented);
+    }
+
+    #[test]
+    fn fixtures_stay_valid() {
+        crate::ast::fixture_paths().par_iter().for_each(|path| {
+            let content = fs::read_to_string(path).expect("Failed to read fixture file");
+            let mut normalized: Value = serde_json::from_str(&content).unwrap();
+            normalize(&mut normalized);
+            let once = normalized.clone();
+            normalize(&mut normalized);
+            assert!(once == normalized, "normalizing {:?} twice", path);
+            serde_json::from_value::<SourceUnit>(normalized)
+                .unwrap_or_else(|e| panic!("Failed to parse {:?}: {}", path, e));
+        });
+    }
+}
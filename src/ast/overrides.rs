@@ -0,0 +1,400 @@
+//! Resolution of `override` specifiers.
+//!
+//! An override specifier such as `override(A, B)` names base contracts, while
+//! the compiler records the overridden functions and modifiers themselves in
+//! `baseFunctions` and `baseModifiers`. [`resolve_overrides`] pairs the two,
+//! so that each entry of the list leads to the declaration it overrides, and
+//! reports lists that do not match the declarations being overridden, and
+//! functions whose state mutability may not override that of their bases.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::*;
+
+/// A function or modifier that is overridden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverriddenDeclaration<'a> {
+    Function(&'a FunctionDefinition),
+    Modifier(&'a ModifierDefinition),
+}
+
+impl OverriddenDeclaration<'_> {
+    /// The id of the declaration.
+    pub fn id(&self) -> i64 {
+        match self {
+            OverriddenDeclaration::Function(function) => function.id,
+            OverriddenDeclaration::Modifier(modifier) => modifier.id,
+        }
+    }
+}
+
+/// An entry of an override list, with the declaration it overrides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedOverride<'a> {
+    /// The id of the overriding function, modifier, or state variable.
+    pub declaration: i64,
+    /// The entry of the override list, which names a base contract.
+    pub entry: &'a IdentifierPath,
+    /// The declaration of that base contract which is overridden.
+    pub base: OverriddenDeclaration<'a>,
+}
+
+/// An override specifier that does not match the overridden declarations.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum OverrideIssue {
+    /// A declaration overrides base declarations without `override`, which
+    /// is only allowed when they are all in interfaces.
+    #[error("declaration {0} overrides a base declaration without `override`")]
+    MissingOverride(i64),
+
+    /// A declaration is marked `override` but overrides nothing.
+    #[error("declaration {0} is marked `override` but overrides nothing")]
+    OverridesNothing(i64),
+
+    /// A base contract with an overridden declaration is missing from the
+    /// override list. The list can only be left out when all overridden
+    /// declarations are in one contract.
+    #[error("declaration {declaration} overrides contract {contract}, which is not listed")]
+    MissingBase { declaration: i64, contract: i64 },
+
+    /// An entry of the override list names a contract without an overridden
+    /// declaration.
+    #[error("declaration {declaration} lists {entry}, which it does not override")]
+    ExtraBase { declaration: i64, entry: String },
+
+    /// A function changes the state mutability of a function it overrides
+    /// in a way overriding does not allow, such as `view` to `nonpayable`.
+    #[error(
+        "function {declaration} cannot be {derived}, overriding function {base} which is {base_mutability}"
+    )]
+    IncompatibleMutability {
+        declaration: i64,
+        derived: crate::abi::StateMutability,
+        base: i64,
+        base_mutability: crate::abi::StateMutability,
+    },
+}
+
+/// The result of [`resolve_overrides`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OverrideReport<'a> {
+    /// The entries of override lists, with the declarations they override.
+    pub resolved: Vec<ResolvedOverride<'a>>,
+    /// The override specifiers that do not match the overridden declarations.
+    pub issues: Vec<OverrideIssue>,
+}
+
+/// Resolve the override specifiers of functions, modifiers, and state
+/// variables in the source units.
+///
+/// Pass every source unit of a compilation, so that node ids are unique and
+/// base contracts are found wherever they are declared. Overridden
+/// declarations that are not in the source units are ignored.
+pub fn resolve_overrides(source_units: &[SourceUnit]) -> OverrideReport<'_> {
+    let mut index = Index::default();
+    for source_unit in source_units {
+        for node in &source_unit.nodes {
+            if let SourceUnitNode::ContractDefinition(contract) = node {
+                index.add(contract);
+            }
+        }
+    }
+
+    let mut report = OverrideReport::default();
+    for overriding in &index.overriding {
+        index.resolve(overriding, &mut report);
+    }
+    report
+}
+
+/// A function, modifier, or state variable that can override.
+struct Overriding<'a> {
+    id: i64,
+    /// The state mutability of a function.
+    mutability: Option<&'a StateMutability>,
+    overrides: Option<&'a OverrideSpecifier>,
+    bases: &'a [i64],
+}
+
+#[derive(Default)]
+struct Index<'a> {
+    contracts: HashMap<i64, &'a ContractDefinition>,
+    /// Overridable declarations, with the id of their contract.
+    declarations: HashMap<i64, (i64, OverriddenDeclaration<'a>)>,
+    overriding: Vec<Overriding<'a>>,
+}
+
+impl<'a> Index<'a> {
+    fn add(&mut self, contract: &'a ContractDefinition) {
+        self.contracts.insert(contract.id, contract);
+        for node in &contract.nodes {
+            match node {
+                ContractDefinitionNode::FunctionDefinition(function) => {
+                    self.declarations.insert(
+                        function.id,
+                        (contract.id, OverriddenDeclaration::Function(function)),
+                    );
+                    self.overriding.push(Overriding {
+                        id: function.id,
+                        mutability: Some(&function.state_mutability),
+                        overrides: function.overrides.as_ref(),
+                        bases: function.base_functions.as_deref().unwrap_or_default(),
+                    });
+                }
+                ContractDefinitionNode::ModifierDefinition(modifier) => {
+                    self.declarations.insert(
+                        modifier.id,
+                        (contract.id, OverriddenDeclaration::Modifier(modifier)),
+                    );
+                    self.overriding.push(Overriding {
+                        id: modifier.id,
+                        mutability: None,
+                        overrides: modifier.overrides.as_ref(),
+                        bases: modifier.base_modifiers.as_deref().unwrap_or_default(),
+                    });
+                }
+                ContractDefinitionNode::VariableDeclaration(variable) => {
+                    self.overriding.push(Overriding {
+                        id: variable.id,
+                        mutability: None,
+                        overrides: variable.overrides.as_ref(),
+                        bases: variable.base_functions.as_deref().unwrap_or_default(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn resolve(&self, overriding: &Overriding<'a>, report: &mut OverrideReport<'a>) {
+        let bases: Vec<(i64, OverriddenDeclaration<'a>)> = overriding
+            .bases
+            .iter()
+            .filter_map(|base| self.declarations.get(base).copied())
+            .collect();
+        if let Some(mutability) = overriding.mutability {
+            check_mutability(overriding.id, mutability, &bases, report);
+        }
+
+        let Some(overrides) = overriding.overrides else {
+            let in_interfaces = bases.iter().all(|(contract, _)| {
+                self.contracts
+                    .get(contract)
+                    .is_some_and(|contract| contract.contract_kind == ContractKind::Interface)
+            });
+            if !in_interfaces {
+                report
+                    .issues
+                    .push(OverrideIssue::MissingOverride(overriding.id));
+            }
+            return;
+        };
+        if overriding.bases.is_empty() {
+            report
+                .issues
+                .push(OverrideIssue::OverridesNothing(overriding.id));
+            return;
+        }
+
+        let mut listed = BTreeSet::new();
+        for entry in &overrides.overrides {
+            let contract = entry.referenced_declaration;
+            if !contract.is_some_and(|contract| self.contracts.contains_key(&contract)) {
+                continue;
+            }
+            let base = bases
+                .iter()
+                .find(|(scope, _)| Some(*scope) == contract)
+                .map(|(_, base)| *base);
+            match base {
+                Some(base) => {
+                    listed.extend(contract);
+                    report.resolved.push(ResolvedOverride {
+                        declaration: overriding.id,
+                        entry,
+                        base,
+                    });
+                }
+                None => report.issues.push(OverrideIssue::ExtraBase {
+                    declaration: overriding.id,
+                    entry: entry.name.clone(),
+                }),
+            }
+        }
+
+        let contracts: BTreeSet<i64> = bases.iter().map(|(contract, _)| *contract).collect();
+        if overrides.overrides.is_empty() && contracts.len() <= 1 {
+            return;
+        }
+        for contract in contracts.difference(&listed) {
+            report.issues.push(OverrideIssue::MissingBase {
+                declaration: overriding.id,
+                contract: *contract,
+            });
+        }
+    }
+}
+
+/// Report the bases of a function whose state mutability it may not take
+/// over.
+fn check_mutability(
+    id: i64,
+    mutability: &StateMutability,
+    bases: &[(i64, OverriddenDeclaration<'_>)],
+    report: &mut OverrideReport<'_>,
+) {
+    let derived = crate::abi::StateMutability::from(mutability);
+    for (_, base) in bases {
+        let OverriddenDeclaration::Function(function) = base else {
+            continue;
+        };
+        let base_mutability = crate::abi::StateMutability::from(&function.state_mutability);
+        if !crate::abi::StateMutability::is_compatible_override(base_mutability, derived) {
+            report.issues.push(OverrideIssue::IncompatibleMutability {
+                declaration: id,
+                derived,
+                base: function.id,
+                base_mutability,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn contract(id: i64, kind: ContractKind, nodes: Vec<ContractDefinitionNode>) -> SourceUnitNode {
+        SourceUnitNode::ContractDefinition(ContractDefinition {
+            id,
+            name: format!("C{}", id),
+            contract_kind: kind,
+            nodes,
+            ..Default::default()
+        })
+    }
+
+    fn function(id: i64, overrides: Option<&[i64]>, bases: &[i64]) -> ContractDefinitionNode {
+        ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+            id,
+            overrides: overrides.map(|contracts| OverrideSpecifier {
+                overrides: contracts
+                    .iter()
+                    .map(|contract| IdentifierPath {
+                        name: format!("C{}", contract),
+                        referenced_declaration: Some(*contract),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            base_functions: (!bases.is_empty()).then(|| bases.to_vec()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn resolves_entries_and_reports_issues() {
+        let source_unit = SourceUnit {
+            nodes: vec![
+                contract(10, ContractKind::Interface, vec![function(1, None, &[])]),
+                contract(20, ContractKind::Contract, vec![function(2, None, &[])]),
+                contract(30, ContractKind::Contract, vec![function(3, None, &[])]),
+                contract(
+                    40,
+                    ContractKind::Contract,
+                    vec![
+                        function(4, Some(&[20, 30]), &[2, 3]),
+                        function(5, Some(&[20]), &[2, 3]),
+                        function(6, Some(&[20, 10]), &[2]),
+                        function(7, None, &[1]),
+                        function(8, None, &[2]),
+                        function(9, Some(&[]), &[]),
+                        function(11, Some(&[]), &[2]),
+                    ],
+                ),
+            ],
+            ..Default::default()
+        };
+        let sources = [source_unit];
+        let report = resolve_overrides(&sources);
+
+        let resolved: Vec<(i64, i64)> = report
+            .resolved
+            .iter()
+            .map(|resolved| (resolved.declaration, resolved.base.id()))
+            .collect();
+        assert_eq!(resolved, [(4, 2), (4, 3), (5, 2), (6, 2)]);
+        assert_eq!(
+            report.issues,
+            [
+                OverrideIssue::MissingBase {
+                    declaration: 5,
+                    contract: 30
+                },
+                OverrideIssue::ExtraBase {
+                    declaration: 6,
+                    entry: "C10".to_string()
+                },
+                OverrideIssue::MissingOverride(8),
+                OverrideIssue::OverridesNothing(9),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_incompatible_mutability() {
+        let with_mutability = |id, mutability, bases: &[i64]| {
+            let overrides: Option<&[i64]> = (!bases.is_empty()).then_some(&[]);
+            let mut function = function(id, overrides, bases);
+            if let ContractDefinitionNode::FunctionDefinition(function) = &mut function {
+                function.state_mutability = mutability;
+            }
+            function
+        };
+        let source_unit = SourceUnit {
+            nodes: vec![
+                contract(
+                    10,
+                    ContractKind::Contract,
+                    vec![
+                        with_mutability(1, StateMutability::View, &[]),
+                        with_mutability(2, StateMutability::Nonpayable, &[]),
+                    ],
+                ),
+                contract(
+                    20,
+                    ContractKind::Contract,
+                    vec![
+                        with_mutability(3, StateMutability::Pure, &[1]),
+                        with_mutability(4, StateMutability::Payable, &[2]),
+                    ],
+                ),
+            ],
+            ..Default::default()
+        };
+        let sources = [source_unit];
+        let report = resolve_overrides(&sources);
+        assert_eq!(
+            report.issues,
+            [OverrideIssue::IncompatibleMutability {
+                declaration: 4,
+                derived: crate::abi::StateMutability::Payable,
+                base: 2,
+                base_mutability: crate::abi::StateMutability::Nonpayable,
+            }]
+        );
+    }
+
+    #[test]
+    fn fixtures_are_consistent() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/BaseAdapter.json").unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let sources = [source_unit];
+        let report = resolve_overrides(&sources);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+}
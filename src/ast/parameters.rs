@@ -0,0 +1,226 @@
+//! ABI parameters of AST declarations.
+//!
+//! The ABI describes a struct as a `tuple` with a component for every
+//! member, and keeps the Solidity type in `internalType`. The conversions
+//! here build [`Param`]s and [`EventParam`]s from parameter lists the same
+//! way the compiler does, resolving struct members through an [`AstIndex`].
+//! The same types give the signature and selector of a function, for when
+//! only the AST of a compilation was requested.
+
+use super::type_name::{array_suffix, enter_struct};
+use super::*;
+use crate::abi::{Component, EventParam, Param, keccak256};
+
+impl ParameterList {
+    /// The ABI parameters of a function, constructor, or error.
+    pub fn abi_params(&self, index: &AstIndex<'_>) -> Result<Vec<Param>, TypeNameError> {
+        self.parameters
+            .iter()
+            .map(|parameter| parameter.abi_param(index))
+            .collect()
+    }
+
+    /// The ABI parameters of an event, with their `indexed` flags.
+    pub fn abi_event_params(&self, index: &AstIndex<'_>) -> Result<Vec<EventParam>, TypeNameError> {
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                let (r#type, components) =
+                    abi_type_and_components(&parameter.type_name, index, &mut Vec::new())?;
+                Ok(EventParam {
+                    name: parameter.name.clone(),
+                    r#type,
+                    components,
+                    indexed: Some(parameter.indexed.unwrap_or_default()),
+                    internal_type: Some(parameter.type_name.internal_type(index)?),
+                })
+            })
+            .collect()
+    }
+}
+
+impl VariableDeclaration {
+    /// The ABI parameter of a function, constructor, or error parameter.
+    pub fn abi_param(&self, index: &AstIndex<'_>) -> Result<Param, TypeNameError> {
+        abi_param(&self.name, &self.type_name, index)
+    }
+}
+
+impl FunctionDefinition {
+    /// The canonical signature of the function, such as
+    /// `deposit((address,uint256),address)`.
+    ///
+    /// Contracts are named `address`, enums `uint8`, user-defined value types
+    /// by their underlying type, and structs as tuples of their members. The
+    /// type descriptions in the AST do not spell out the last two, so they
+    /// are resolved through the index, which must hold the source units
+    /// declaring them. Functions of libraries are called with selectors of
+    /// library-specific type names, which this does not produce.
+    pub fn signature(&self, index: &AstIndex<'_>) -> Result<String, TypeNameError> {
+        let types = self
+            .parameters
+            .parameters
+            .iter()
+            .map(|parameter| parameter.type_name.abi_type(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}({})", self.name, types.join(",")))
+    }
+
+    /// The selector of the function: the first four bytes of the Keccak-256
+    /// hash of its [signature](Self::signature).
+    pub fn selector(&self, index: &AstIndex<'_>) -> Result<[u8; 4], TypeNameError> {
+        let hash = keccak256(self.signature(index)?.as_bytes());
+        Ok([hash[0], hash[1], hash[2], hash[3]])
+    }
+}
+
+/// The ABI parameter of a value of a type, under a name.
+pub(super) fn abi_param(
+    name: &str,
+    type_name: &TypeName,
+    index: &AstIndex<'_>,
+) -> Result<Param, TypeNameError> {
+    let (r#type, components) = abi_type_and_components(type_name, index, &mut Vec::new())?;
+    Ok(Param {
+        name: name.to_string(),
+        r#type,
+        components,
+        indexed: None,
+        internal_type: Some(type_name.internal_type(index)?),
+    })
+}
+
+/// The ABI type of a type name, with `tuple` for structs, and the
+/// components of the struct if it is one, inside the structs with ids
+/// `structs`.
+fn abi_type_and_components(
+    type_name: &TypeName,
+    index: &AstIndex<'_>,
+    structs: &mut Vec<i64>,
+) -> Result<(String, Option<Vec<Component>>), TypeNameError> {
+    match type_name {
+        TypeName::ArrayTypeName(array) => {
+            let (base, components) = abi_type_and_components(&array.base_type, index, structs)?;
+            Ok((format!("{}{}", base, array_suffix(array)?), components))
+        }
+        TypeName::UserDefinedTypeName(user_defined) => {
+            let declaration = user_defined
+                .referenced_declaration
+                .and_then(|id| index.get(id));
+            match declaration {
+                Some(Declaration::StructDefinition(definition)) => {
+                    enter_struct(definition, structs)?;
+                    let components = definition
+                        .members
+                        .iter()
+                        .map(|member| {
+                            let (r#type, components) =
+                                abi_type_and_components(&member.type_name, index, structs)?;
+                            Ok(Component {
+                                name: member.name.clone(),
+                                r#type,
+                                components,
+                                indexed: None,
+                                internal_type: Some(member.type_name.internal_type(index)?),
+                            })
+                        })
+                        .collect::<Result<_, TypeNameError>>()?;
+                    structs.pop();
+                    Ok(("tuple".to_string(), Some(components)))
+                }
+                _ => Ok((type_name.abi_type(index)?, None)),
+            }
+        }
+        _ => Ok((type_name.abi_type(index)?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::abi::{Abi, AbiItem};
+
+    #[test]
+    fn fixture_params_match_abi() {
+        let project = "codearena-2025-10-covenant";
+        let mut source_units = Vec::new();
+        for entry in fs::read_dir(Path::new("fixtures/ast").join(project)).unwrap() {
+            let path = entry.unwrap().path();
+            let content = fs::read_to_string(&path).unwrap();
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            source_units.push((name, serde_json::from_str::<SourceUnit>(&content).unwrap()));
+        }
+        let (names, source_units): (Vec<_>, Vec<_>) = source_units.into_iter().unzip();
+        let index = AstIndex::new(&source_units);
+
+        let mut matched = 0;
+        let mut tuples = 0;
+        for (name, source_unit) in names.iter().zip(&source_units) {
+            let path = Path::new("fixtures/abi")
+                .join(project)
+                .join(format!("{}.json", name));
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let abi: Abi = serde_json::from_str(&content).unwrap();
+            let contract = source_unit.nodes.iter().find_map(|node| match node {
+                SourceUnitNode::ContractDefinition(contract) if contract.name == *name => {
+                    Some(contract)
+                }
+                _ => None,
+            });
+            // Library functions may take storage references, which have no
+            // ABI type.
+            let Some(contract) = contract.filter(|c| c.contract_kind != ContractKind::Library)
+            else {
+                continue;
+            };
+            for node in &contract.nodes {
+                match node {
+                    ContractDefinitionNode::FunctionDefinition(function)
+                        if function.kind == FunctionKind::Function
+                            && matches!(
+                                function.visibility,
+                                Visibility::Public | Visibility::External
+                            ) =>
+                    {
+                        let selector = hex::encode(function.selector(&index).unwrap());
+                        assert_eq!(function.function_selector.as_deref(), Some(&*selector));
+                        let inputs = function.parameters.abi_params(&index).unwrap();
+                        let outputs = function.return_parameters.abi_params(&index).unwrap();
+                        let found = abi.items.iter().any(|item| match item {
+                            AbiItem::Function(abi) => {
+                                abi.name == function.name
+                                    && abi.inputs == inputs
+                                    && abi.outputs == outputs
+                            }
+                            _ => false,
+                        });
+                        assert!(found, "{}.{}: {:#?}", name, function.name, inputs);
+                        tuples += inputs
+                            .iter()
+                            .chain(&outputs)
+                            .filter(|param| param.components.is_some())
+                            .count();
+                        matched += 1;
+                    }
+                    ContractDefinitionNode::EventDefinition(event) => {
+                        let inputs = event.parameters.abi_event_params(&index).unwrap();
+                        let found = abi.items.iter().any(|item| match item {
+                            AbiItem::Event(abi) => abi.name == event.name && abi.inputs == inputs,
+                            _ => false,
+                        });
+                        assert!(found, "{}.{}: {:#?}", name, event.name, inputs);
+                        matched += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        assert!(matched > 100, "{}", matched);
+        assert!(tuples > 0);
+    }
+}
@@ -0,0 +1,299 @@
+//! How a contract receives and sends Ether.
+//!
+//! A contract accepts plain Ether transfers only through a payable `receive`
+//! function, or a payable `fallback` function when it has no `receive`
+//! function. [`payability`] finds those, possibly inherited from a base, and
+//! the places in the contract's code that send Ether out.
+
+use std::collections::HashMap;
+
+use super::visit::{self, Visitor};
+use super::*;
+
+/// How a contract receives and sends Ether, returned by [`payability`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Payability {
+    /// The id of the `receive` function, if the contract has one.
+    pub receive: Option<i64>,
+    /// The id of the `fallback` function, if the contract has one.
+    pub fallback: Option<i64>,
+    /// Whether plain Ether transfers, with empty calldata, are accepted.
+    pub accepts_plain_transfers: bool,
+    /// Whether the fallback function takes the calldata as a parameter and
+    /// returns data, as in `fallback(bytes calldata) returns (bytes memory)`.
+    pub fallback_has_parameters: bool,
+    /// The places that send Ether out of the contract.
+    pub transfers: Vec<EtherTransfer>,
+}
+
+/// A call that sends Ether out of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EtherTransfer {
+    /// The id of the function or modifier the call is in.
+    pub function: i64,
+    /// The id of the call.
+    pub call: i64,
+    /// Where the call is in the source.
+    pub src: SourceLocation,
+    /// How the Ether is sent.
+    pub kind: TransferKind,
+}
+
+/// How an [`EtherTransfer`] sends Ether.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransferKind {
+    /// `address.transfer(amount)`.
+    Transfer,
+    /// `address.send(amount)`.
+    Send,
+    /// A low-level call with a value, such as `address.call{value: amount}("")`.
+    Call,
+    /// A call of an external function with a value.
+    ExternalCall,
+    /// The creation of a contract with a value, as in `new C{value: amount}()`.
+    Create,
+    /// `selfdestruct(recipient)`, which sends the whole balance.
+    Selfdestruct,
+}
+
+/// Find how a contract receives and sends Ether.
+///
+/// The `receive` and `fallback` functions may be inherited, and transfers
+/// are collected from the functions and modifiers of the contract and all
+/// of its bases, including those that are overridden. Returns `None` if the
+/// contract is not in the source units.
+///
+/// Pass every source unit of a compilation, so that node ids are unique and
+/// base contracts are found wherever they are declared.
+pub fn payability(source_units: &[SourceUnit], contract: i64) -> Option<Payability> {
+    let contracts: HashMap<i64, &ContractDefinition> = source_units
+        .iter()
+        .flat_map(|source_unit| &source_unit.nodes)
+        .filter_map(|node| match node {
+            SourceUnitNode::ContractDefinition(contract) => Some(contract),
+            _ => None,
+        })
+        .map(|contract| (contract.id, contract))
+        .collect();
+    let contract = contracts.get(&contract)?;
+
+    let mut payability = Payability::default();
+    let mut receive: Option<&FunctionDefinition> = None;
+    let mut fallback: Option<&FunctionDefinition> = None;
+    let mut transfers = Transfers::default();
+    // Linearized bases are listed from the most derived, so the first
+    // receive and fallback functions found are the ones that are used.
+    for base in &contract.linearized_base_contracts {
+        let Some(base) = contracts.get(base) else {
+            continue;
+        };
+        for node in &base.nodes {
+            if let ContractDefinitionNode::FunctionDefinition(function) = node {
+                match function.kind {
+                    FunctionKind::Receive if function.implemented => {
+                        receive.get_or_insert(function);
+                    }
+                    FunctionKind::Fallback if function.implemented => {
+                        fallback.get_or_insert(function);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        transfers.visit_contract_definition(base);
+    }
+
+    let is_payable =
+        |function: &FunctionDefinition| function.state_mutability == StateMutability::Payable;
+    payability.receive = receive.map(|function| function.id);
+    payability.fallback = fallback.map(|function| function.id);
+    payability.accepts_plain_transfers = match (receive, fallback) {
+        (Some(receive), _) => is_payable(receive),
+        (None, Some(fallback)) => is_payable(fallback),
+        (None, None) => false,
+    };
+    payability.fallback_has_parameters =
+        fallback.is_some_and(|fallback| !fallback.parameters.parameters.is_empty());
+    payability.transfers = transfers.transfers;
+    Some(payability)
+}
+
+/// Collects the calls that send Ether.
+#[derive(Default)]
+struct Transfers {
+    function: i64,
+    transfers: Vec<EtherTransfer>,
+}
+
+impl Transfers {
+    fn push(&mut self, call: i64, src: &SourceLocation, kind: TransferKind) {
+        self.transfers.push(EtherTransfer {
+            function: self.function,
+            call,
+            src: src.clone(),
+            kind,
+        });
+    }
+}
+
+impl Visitor for Transfers {
+    fn visit_function_definition(&mut self, node: &FunctionDefinition) {
+        self.function = node.id;
+        visit::walk_function_definition(self, node);
+    }
+
+    fn visit_modifier_definition(&mut self, node: &ModifierDefinition) {
+        self.function = node.id;
+        visit::walk_modifier_definition(self, node);
+    }
+
+    fn visit_function_call(&mut self, node: &FunctionCall) {
+        let kind = match &*node.expression {
+            FunctionCallExpression::MemberAccess(member) => {
+                let identifier = member.type_descriptions.type_identifier.as_deref();
+                match identifier.unwrap_or_default() {
+                    id if id.starts_with("t_function_transfer_") => Some(TransferKind::Transfer),
+                    id if id.starts_with("t_function_send_") => Some(TransferKind::Send),
+                    _ => None,
+                }
+            }
+            FunctionCallExpression::Identifier(identifier) => identifier
+                .type_descriptions
+                .type_identifier
+                .as_deref()
+                .filter(|id| id.starts_with("t_function_selfdestruct_"))
+                .map(|_| TransferKind::Selfdestruct),
+            FunctionCallExpression::FunctionCallOptions(options)
+                if options.names.iter().any(|name| name == "value") =>
+            {
+                match &*options.expression {
+                    Expression::NewExpression(_) => Some(TransferKind::Create),
+                    Expression::MemberAccess(member)
+                        if member
+                            .type_descriptions
+                            .type_identifier
+                            .as_deref()
+                            .is_some_and(|id| id.starts_with("t_function_barecall_")) =>
+                    {
+                        Some(TransferKind::Call)
+                    }
+                    _ => Some(TransferKind::ExternalCall),
+                }
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.push(node.id, &node.src, kind);
+        }
+        visit::walk_function_call(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn fixture_transfers() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/StdCheats.json").unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let contracts: Vec<i64> = source_unit
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                SourceUnitNode::ContractDefinition(contract) => Some(contract.id),
+                _ => None,
+            })
+            .collect();
+        let sources = [source_unit];
+        let transfers: Vec<EtherTransfer> = contracts
+            .iter()
+            .flat_map(|contract| payability(&sources, *contract).unwrap().transfers)
+            .collect();
+        assert!(
+            transfers
+                .iter()
+                .any(|transfer| transfer.kind == TransferKind::Call)
+        );
+    }
+
+    #[test]
+    fn receive_and_fallback() {
+        let function = |id: i64, kind: FunctionKind, mutability: StateMutability| {
+            ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                id,
+                kind,
+                state_mutability: mutability,
+                implemented: true,
+                ..Default::default()
+            })
+        };
+        let transfer = Statement::ExpressionStatement(ExpressionStatement {
+            expression: Box::new(Expression::FunctionCall(FunctionCall {
+                id: 9,
+                expression: Box::new(FunctionCallExpression::MemberAccess(MemberAccess {
+                    member_name: "transfer".to_string(),
+                    type_descriptions: TypeDescriptions {
+                        type_identifier: Some(
+                            "t_function_transfer_nonpayable$_t_uint256_$returns$__$".to_string(),
+                        ),
+                        type_string: None,
+                    },
+                    ..Default::default()
+                })),
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+        let source_unit = SourceUnit {
+            nodes: vec![
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    id: 10,
+                    linearized_base_contracts: vec![10],
+                    nodes: vec![
+                        function(1, FunctionKind::Fallback, StateMutability::Payable),
+                        ContractDefinitionNode::FunctionDefinition(FunctionDefinition {
+                            id: 2,
+                            body: Some(Block {
+                                statements: vec![transfer],
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }),
+                    ],
+                    ..Default::default()
+                }),
+                SourceUnitNode::ContractDefinition(ContractDefinition {
+                    id: 20,
+                    linearized_base_contracts: vec![20, 10],
+                    nodes: vec![function(
+                        3,
+                        FunctionKind::Receive,
+                        StateMutability::Nonpayable,
+                    )],
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let sources = [source_unit];
+
+        let base = payability(&sources, 10).unwrap();
+        assert!(base.accepts_plain_transfers);
+        assert_eq!(base.fallback, Some(1));
+        assert!(!base.fallback_has_parameters);
+        assert_eq!(base.transfers.len(), 1);
+        assert_eq!(base.transfers[0].function, 2);
+        assert_eq!(base.transfers[0].kind, TransferKind::Transfer);
+
+        let derived = payability(&sources, 20).unwrap();
+        assert_eq!(derived.receive, Some(3));
+        assert_eq!(derived.fallback, Some(1));
+        assert!(!derived.accepts_plain_transfers);
+        assert_eq!(derived.transfers, base.transfers);
+        assert!(payability(&sources, 30).is_none());
+    }
+}
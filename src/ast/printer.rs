@@ -0,0 +1,1259 @@
+//! Solidity source printed from the AST.
+//!
+//! [`print`] writes a source unit, or any node within one, back out as
+//! formatted Solidity, so that a tree can be deserialized, transformed, and
+//! emitted again. The layout is normalized: blocks are indented by four
+//! spaces, each statement and declaration is on its own line, and the
+//! parentheses of the source come back from the tuple expressions the
+//! compiler keeps for them. NatSpec comments are printed as `///` lines;
+//! other comments are not in the AST and are lost.
+
+use serde::Serialize;
+
+use super::*;
+
+/// The indentation of one level of nesting.
+const INDENT: &str = "    ";
+
+/// Print a node as Solidity source.
+///
+/// Declarations and statements end with a newline, while expressions, type
+/// names, and other fragments are printed on their own without one.
+pub fn print<'a>(node: impl Into<Node<'a>>) -> String {
+    let mut printer = Printer::default();
+    printer.node(node.into());
+    printer.out
+}
+
+/// Writes declarations and statements line by line.
+#[derive(Default)]
+struct Printer {
+    out: String,
+    depth: usize,
+    /// Whether the next line continues the last one, as `else` continues
+    /// the `}` of an `if`.
+    continued: bool,
+}
+
+impl Printer {
+    fn node(&mut self, node: Node<'_>) {
+        self.documentation(statement_documentation(node));
+        match node {
+            Node::SourceUnit(node) => self.source_unit(node),
+            Node::PragmaDirective(node) => self.line(&pragma(node)),
+            Node::ImportDirective(node) => self.line(&import(node)),
+            Node::ContractDefinition(node) => self.contract(node),
+            Node::InheritanceSpecifier(node) => self.out.push_str(&inheritance_specifier(node)),
+            Node::VariableDeclaration(node) if node.state_variable => self.state_variable(node),
+            Node::VariableDeclaration(node) => self.out.push_str(&variable(node)),
+            Node::OverrideSpecifier(node) => self.out.push_str(&override_specifier(node)),
+            Node::FunctionDefinition(node) => self.function(node),
+            Node::ModifierInvocation(node) => self.out.push_str(&modifier_invocation(node)),
+            Node::ParameterList(node) => self.out.push_str(&format!("({})", parameters(node))),
+            Node::ModifierDefinition(node) => self.modifier(node),
+            Node::EventDefinition(node) => {
+                self.documentation(node.documentation.as_ref());
+                self.line(&event(node));
+            }
+            Node::ErrorDefinition(node) => {
+                self.documentation(node.documentation.as_ref());
+                self.line(&format!(
+                    "error {}({});",
+                    node.name,
+                    parameters(&node.parameters)
+                ));
+            }
+            Node::StructDefinition(node) => self.struct_definition(node),
+            Node::EnumDefinition(node) => {
+                self.documentation(node.documentation.as_ref());
+                self.line(&enum_definition(node));
+            }
+            Node::EnumValue(node) => self.out.push_str(&node.name),
+            Node::UserDefinedValueTypeDefinition(node) => self.line(&format!(
+                "type {} is {};",
+                node.name,
+                type_name(&node.underlying_type)
+            )),
+            Node::UsingForDirective(node) => self.line(&using_for(node)),
+            Node::Block(node) => self.block("", &node.statements),
+            Node::UncheckedBlock(node) => self.block("unchecked", &node.statements),
+            Node::IfStatement(node) => self.if_statement(node),
+            Node::ForStatement(node) => self.for_statement(node),
+            Node::WhileStatement(node) => self.body(
+                &format!("while ({})", expression(&node.condition)),
+                &node.body,
+            ),
+            Node::DoWhileStatement(node) => self.do_while_statement(node),
+            Node::Continue(_) => self.line("continue;"),
+            Node::Break(_) => self.line("break;"),
+            Node::Return(node) => self.line(&match &node.expression {
+                Some(expression_) => format!("return {};", expression(expression_)),
+                None => "return;".to_string(),
+            }),
+            Node::EmitStatement(node) => {
+                self.line(&format!("emit {};", function_call(&node.event_call)))
+            }
+            Node::RevertStatement(node) => {
+                self.line(&format!("revert {};", function_call(&node.error_call)))
+            }
+            Node::TryStatement(node) => self.try_statement(node),
+            Node::TryCatchClause(node) => self.catch_clause(node),
+            Node::ExpressionStatement(node) => {
+                self.line(&format!("{};", expression(&node.expression)))
+            }
+            Node::VariableDeclarationStatement(node) => {
+                self.line(&format!("{};", variable_declaration_statement(node)))
+            }
+            Node::InlineAssembly(node) => self.inline_assembly(node),
+            Node::PlaceholderStatement(_) => self.line("_;"),
+            Node::Assignment(node) => self.out.push_str(&assignment(node)),
+            Node::BinaryOperation(node) => self.out.push_str(&binary_operation(node)),
+            Node::Conditional(node) => self.out.push_str(&conditional(node)),
+            Node::UnaryOperation(node) => self.out.push_str(&unary_operation(node)),
+            Node::FunctionCall(node) => self.out.push_str(&function_call(node)),
+            Node::FunctionCallOptions(node) => self.out.push_str(&function_call_options(node)),
+            Node::MemberAccess(node) => self.out.push_str(&member_access(node)),
+            Node::IndexAccess(node) => self.out.push_str(&index_access(node)),
+            Node::IndexRangeAccess(node) => self.out.push_str(&index_range_access(node)),
+            Node::TupleExpression(node) => self.out.push_str(&tuple_expression(node)),
+            Node::Identifier(node) => self.out.push_str(&node.name),
+            Node::IdentifierPath(node) => self.out.push_str(&node.name),
+            Node::Literal(node) => self.out.push_str(&literal(node)),
+            Node::NewExpression(node) => self
+                .out
+                .push_str(&format!("new {}", type_name(&node.type_name))),
+            Node::ElementaryTypeNameExpression(node) => {
+                self.out.push_str(&elementary_type_name(&node.type_name))
+            }
+            Node::ElementaryTypeName(node) => self.out.push_str(&elementary_type_name(node)),
+            Node::UserDefinedTypeName(node) => self.out.push_str(&user_defined_type_name(node)),
+            Node::ArrayTypeName(node) => self.out.push_str(&array_type_name(node)),
+            Node::Mapping(node) => self.out.push_str(&mapping(node)),
+            Node::FunctionTypeName(node) => self.out.push_str(&function_type_name(node)),
+            Node::StructuredDocumentation(node) => self.comment(&node.text),
+            Node::UnparsedNode(node) => self.out.push_str(&unparsed(node)),
+        }
+    }
+
+    /// Write a line at the current indentation.
+    fn line(&mut self, text: &str) {
+        self.indent();
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Indent the start of a line, unless it continues the last one.
+    fn indent(&mut self) {
+        if !std::mem::take(&mut self.continued) {
+            for _ in 0..self.depth {
+                self.out.push_str(INDENT);
+            }
+        }
+    }
+
+    /// Continue the last line, which ends with `}`, with the next one.
+    fn rejoin(&mut self) {
+        if self.out.ends_with('\n') {
+            self.out.pop();
+        }
+        self.out.push(' ');
+        self.continued = true;
+    }
+
+    /// Open a block after `head`, which may be empty.
+    fn open(&mut self, head: &str) {
+        self.indent();
+        self.out.push_str(head);
+        if !head.is_empty() {
+            self.out.push(' ');
+        }
+        self.out.push_str("{\n");
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    /// Write members separated by blank lines, except between consecutive
+    /// one-line members of the same type, such as state variables or events.
+    fn members<'a>(&mut self, nodes: impl IntoIterator<Item = Node<'a>>) {
+        let mut previous: Option<Node<'a>> = None;
+        for node in nodes {
+            if let Some(previous) = previous {
+                let multiline = |node: Node<'_>| {
+                    matches!(
+                        node,
+                        Node::ContractDefinition(_)
+                            | Node::FunctionDefinition(_)
+                            | Node::ModifierDefinition(_)
+                            | Node::StructDefinition(_)
+                    )
+                };
+                if previous.node_type() != node.node_type() || multiline(node) {
+                    self.out.push('\n');
+                }
+            }
+            self.node(node);
+            previous = Some(node);
+        }
+    }
+
+    fn documentation(&mut self, documentation: Option<&Documentation>) {
+        match documentation {
+            Some(Documentation::String(text)) => self.comment(text),
+            Some(Documentation::Structured(documentation)) => self.comment(&documentation.text),
+            None => {}
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        for line in text.lines() {
+            match line.trim() {
+                "" => self.line("///"),
+                line => self.line(&format!("/// {}", line)),
+            }
+        }
+    }
+
+    fn source_unit(&mut self, source_unit: &SourceUnit) {
+        if let Some(license) = &source_unit.license {
+            self.line(&format!("// SPDX-License-Identifier: {}", license));
+        }
+        self.members(source_unit.nodes.iter().map(Node::from));
+    }
+
+    fn contract(&mut self, contract: &ContractDefinition) {
+        self.documentation(contract.documentation.as_ref());
+        let mut head = String::new();
+        if contract.r#abstract {
+            head.push_str("abstract ");
+        }
+        head.push_str(&format!(
+            "{} {}",
+            keyword(&contract.contract_kind),
+            contract.name
+        ));
+        if !contract.base_contracts.is_empty() {
+            let bases: Vec<_> = contract
+                .base_contracts
+                .iter()
+                .map(inheritance_specifier)
+                .collect();
+            head.push_str(&format!(" is {}", bases.join(", ")));
+        }
+        self.open(&head);
+        self.members(contract.nodes.iter().map(Node::from));
+        self.close();
+    }
+
+    fn state_variable(&mut self, variable_: &VariableDeclaration) {
+        self.documentation(variable_.documentation.as_ref());
+        let mut line = variable(variable_);
+        if let Some(value) = &variable_.value {
+            line.push_str(&format!(" = {}", expression(value)));
+        }
+        line.push(';');
+        self.line(&line);
+    }
+
+    fn function(&mut self, function: &FunctionDefinition) {
+        self.documentation(function.documentation.as_ref());
+        let mut head = match function.kind {
+            FunctionKind::Constructor => "constructor".to_string(),
+            FunctionKind::Receive => "receive".to_string(),
+            FunctionKind::Fallback => "fallback".to_string(),
+            FunctionKind::Function | FunctionKind::FreeFunction => {
+                format!("function {}", function.name)
+            }
+        };
+        head.push_str(&format!("({})", parameters(&function.parameters)));
+        // Constructors and free functions take no visibility.
+        if !matches!(
+            function.kind,
+            FunctionKind::Constructor | FunctionKind::FreeFunction
+        ) {
+            head.push_str(&format!(" {}", keyword(&function.visibility)));
+        }
+        if function.state_mutability != StateMutability::Nonpayable {
+            head.push_str(&format!(" {}", keyword(&function.state_mutability)));
+        }
+        if function.r#virtual {
+            head.push_str(" virtual");
+        }
+        if let Some(overrides) = &function.overrides {
+            head.push_str(&format!(" {}", override_specifier(overrides)));
+        }
+        for modifier in &function.modifiers {
+            head.push_str(&format!(" {}", modifier_invocation(modifier)));
+        }
+        if !function.return_parameters.parameters.is_empty() {
+            head.push_str(&format!(
+                " returns ({})",
+                parameters(&function.return_parameters)
+            ));
+        }
+        match &function.body {
+            Some(body) => self.block(&head, &body.statements),
+            None => self.line(&format!("{};", head)),
+        }
+    }
+
+    fn modifier(&mut self, modifier: &ModifierDefinition) {
+        self.documentation(modifier.documentation.as_ref());
+        let mut head = format!(
+            "modifier {}({})",
+            modifier.name,
+            parameters(&modifier.parameters)
+        );
+        if modifier.r#virtual {
+            head.push_str(" virtual");
+        }
+        if let Some(overrides) = &modifier.overrides {
+            head.push_str(&format!(" {}", override_specifier(overrides)));
+        }
+        self.block(&head, &modifier.body.statements);
+    }
+
+    fn struct_definition(&mut self, definition: &StructDefinition) {
+        self.documentation(definition.documentation.as_ref());
+        self.open(&format!("struct {}", definition.name));
+        for member in &definition.members {
+            self.line(&format!("{};", variable(member)));
+        }
+        self.close();
+    }
+
+    fn block(&mut self, head: &str, statements: &[Statement]) {
+        self.open(head);
+        for statement in statements {
+            self.node(statement.into());
+        }
+        self.close();
+    }
+
+    /// Write the body of a control statement after its `head`, on the next
+    /// line if it is not a block.
+    fn body(&mut self, head: &str, body: &Statement) {
+        match body {
+            Statement::Block(block) => self.block(head, &block.statements),
+            body => {
+                self.line(head);
+                self.depth += 1;
+                self.node(body.into());
+                self.depth -= 1;
+            }
+        }
+    }
+
+    fn if_statement(&mut self, statement: &IfStatement) {
+        self.body(
+            &format!("if ({})", expression(&statement.condition)),
+            &statement.true_body,
+        );
+        let Some(false_body) = &statement.false_body else {
+            return;
+        };
+        if matches!(*statement.true_body, Statement::Block(_)) {
+            self.rejoin();
+        }
+        match false_body.as_ref() {
+            Statement::IfStatement(nested) => {
+                self.indent();
+                self.out.push_str("else ");
+                self.continued = true;
+                self.if_statement(nested);
+            }
+            body => self.body("else", body),
+        }
+    }
+
+    fn for_statement(&mut self, statement: &ForStatement) {
+        let part =
+            |expression_: Option<&Expression>| expression_.map(expression).unwrap_or_default();
+        let head = format!(
+            "for ({}; {}; {})",
+            part(statement.initialization_expression.as_deref()),
+            expression(&statement.condition),
+            part(statement.loop_expression.as_deref()),
+        );
+        self.body(&head, &statement.body);
+    }
+
+    fn do_while_statement(&mut self, statement: &DoWhileStatement) {
+        self.body("do", &statement.body);
+        if matches!(*statement.body, Statement::Block(_)) {
+            self.rejoin();
+        }
+        self.line(&format!("while ({});", expression(&statement.condition)));
+    }
+
+    fn try_statement(&mut self, statement: &TryStatement) {
+        let mut clauses = statement.clauses.iter();
+        let mut head = format!("try {}", expression(&statement.external_call));
+        let Some(success) = clauses.next() else {
+            self.line(&head);
+            return;
+        };
+        if let Some(returns) = success
+            .parameters
+            .as_ref()
+            .filter(|returns| !returns.parameters.is_empty())
+        {
+            head.push_str(&format!(" returns ({})", parameters(returns)));
+        }
+        self.block(&head, &success.block.statements);
+        for clause in clauses {
+            self.rejoin();
+            self.catch_clause(clause);
+        }
+    }
+
+    fn catch_clause(&mut self, clause: &TryCatchClause) {
+        let mut head = "catch".to_string();
+        let name = clause.error_name.as_deref().unwrap_or_default();
+        if !name.is_empty() {
+            head.push_str(&format!(" {}", name));
+        }
+        if let Some(list) = &clause.parameters {
+            if name.is_empty() {
+                head.push(' ');
+            }
+            head.push_str(&format!("({})", parameters(list)));
+        }
+        self.block(&head, &clause.block.statements);
+    }
+
+    fn inline_assembly(&mut self, assembly: &InlineAssembly) {
+        let mut head = "assembly".to_string();
+        if let Some(flags) = assembly.flags.as_ref().filter(|flags| !flags.is_empty()) {
+            let flags: Vec<_> = flags.iter().map(|flag| format!("\"{}\"", flag)).collect();
+            head.push_str(&format!(" ({})", flags.join(", ")));
+        }
+        match (&assembly.ast, &assembly.operations) {
+            (Some(block), _) => {
+                self.open(&head);
+                self.yul_statements(&block.statements);
+                self.close();
+            }
+            (None, Some(operations)) => self.line(&format!("{} {}", head, operations.trim())),
+            (None, None) => self.line(&format!("{} {{}}", head)),
+        }
+    }
+
+    fn yul_statements(&mut self, statements: &[YulStatement]) {
+        for statement in statements {
+            self.yul_statement(statement);
+        }
+    }
+
+    fn yul_block(&mut self, head: &str, block: &YulBlock) {
+        self.open(head);
+        self.yul_statements(&block.statements);
+        self.close();
+    }
+
+    fn yul_statement(&mut self, statement: &YulStatement) {
+        match statement {
+            YulStatement::YulBlock(block) => self.yul_block("", block),
+            YulStatement::YulAssignment(assignment) => {
+                let names: Vec<_> = assignment
+                    .variable_names
+                    .iter()
+                    .map(|name| name.name.as_str())
+                    .collect();
+                self.line(&format!(
+                    "{} := {}",
+                    names.join(", "),
+                    yul_expression(&assignment.value)
+                ));
+            }
+            YulStatement::YulFunctionCall(call) => self.line(&yul_function_call(call)),
+            YulStatement::YulExpressionStatement(statement) => {
+                self.line(&yul_expression(&statement.expression))
+            }
+            YulStatement::YulIf(statement) => self.yul_block(
+                &format!("if {}", yul_expression(&statement.condition)),
+                &statement.body,
+            ),
+            YulStatement::YulForLoop(statement) => {
+                let head = format!(
+                    "for {} {} {}",
+                    yul_inline_block(&statement.pre),
+                    yul_expression(&statement.condition),
+                    yul_inline_block(&statement.post)
+                );
+                self.yul_block(&head, &statement.body);
+            }
+            YulStatement::YulSwitch(statement) => {
+                self.line(&format!("switch {}", yul_expression(&statement.expression)));
+                for case in &statement.cases {
+                    let head = match &case.value {
+                        YulCaseValue::Literal(literal) => {
+                            format!("case {}", yul_literal(literal))
+                        }
+                        YulCaseValue::String(_) => "default".to_string(),
+                    };
+                    self.yul_block(&head, &case.body);
+                }
+            }
+            YulStatement::YulBreak(_) => self.line("break"),
+            YulStatement::YulContinue(_) => self.line("continue"),
+            YulStatement::YulLeave(_) => self.line("leave"),
+            YulStatement::YulVariableDeclaration(declaration) => {
+                let mut line = format!("let {}", yul_typed_names(&declaration.variables));
+                if let Some(value) = &declaration.value {
+                    line.push_str(&format!(" := {}", yul_expression(value)));
+                }
+                self.line(&line);
+            }
+            YulStatement::YulFunctionDefinition(definition) => {
+                let mut head = format!(
+                    "function {}({})",
+                    definition.name,
+                    yul_typed_names(&definition.parameters)
+                );
+                if !definition.return_variables.is_empty() {
+                    head.push_str(&format!(
+                        " -> {}",
+                        yul_typed_names(&definition.return_variables)
+                    ));
+                }
+                self.yul_block(&head, &definition.body);
+            }
+        }
+    }
+}
+
+/// The source form of a keyword enum, such as `external` or `view`, which
+/// is the same as its JSON form.
+/// The documentation of a statement node, printed before it.
+fn statement_documentation(node: Node<'_>) -> Option<&Documentation> {
+    match node {
+        Node::Block(node) => node.documentation.as_ref(),
+        Node::UncheckedBlock(node) => node.documentation.as_ref(),
+        Node::IfStatement(node) => node.documentation.as_ref(),
+        Node::ForStatement(node) => node.documentation.as_ref(),
+        Node::WhileStatement(node) => node.documentation.as_ref(),
+        Node::DoWhileStatement(node) => node.documentation.as_ref(),
+        Node::Continue(node) => node.documentation.as_ref(),
+        Node::Break(node) => node.documentation.as_ref(),
+        Node::Return(node) => node.documentation.as_ref(),
+        Node::EmitStatement(node) => node.documentation.as_ref(),
+        Node::RevertStatement(node) => node.documentation.as_ref(),
+        Node::TryStatement(node) => node.documentation.as_ref(),
+        Node::ExpressionStatement(node) => node.documentation.as_ref(),
+        Node::VariableDeclarationStatement(node) => node.documentation.as_ref(),
+        Node::InlineAssembly(node) => node.documentation.as_ref(),
+        Node::PlaceholderStatement(node) => node.documentation.as_ref(),
+        _ => None,
+    }
+}
+
+fn keyword<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn pragma(directive: &PragmaDirective) -> String {
+    let mut literals = directive.literals.iter();
+    let mut line = format!("pragma {}", literals.next().map_or("", String::as_str));
+    let mut previous: Option<&str> = None;
+    for literal in literals {
+        // The literals split the version at dots, as in `0.8` `.0`, and do
+        // not say where the source had spaces, which a version range needs
+        // between its bounds.
+        let space = match previous {
+            None => true,
+            Some(previous) => {
+                ["||", "-"].contains(&previous)
+                    || ["||", "-"].contains(&literal.as_str())
+                    || (previous.ends_with(|c: char| c.is_ascii_alphanumeric())
+                        && !literal.starts_with('.'))
+            }
+        };
+        if space {
+            line.push(' ');
+        }
+        line.push_str(literal);
+        previous = Some(literal);
+    }
+    line.push(';');
+    line
+}
+
+fn import(directive: &ImportDirective) -> String {
+    let file = directive.file.display();
+    if !directive.symbol_aliases.is_empty() {
+        let symbols: Vec<_> = directive
+            .symbol_aliases
+            .iter()
+            .map(|alias| match &alias.local {
+                Some(local) if *local != alias.foreign.name => {
+                    format!("{} as {}", alias.foreign.name, local)
+                }
+                _ => alias.foreign.name.clone(),
+            })
+            .collect();
+        format!("import {{{}}} from \"{}\";", symbols.join(", "), file)
+    } else if let Some(alias) = &directive.unit_alias {
+        format!("import \"{}\" as {};", file, alias)
+    } else {
+        format!("import \"{}\";", file)
+    }
+}
+
+fn event(definition: &EventDefinition) -> String {
+    let mut line = format!(
+        "event {}({})",
+        definition.name,
+        parameters(&definition.parameters)
+    );
+    if definition.anonymous {
+        line.push_str(" anonymous");
+    }
+    line.push(';');
+    line
+}
+
+fn enum_definition(definition: &EnumDefinition) -> String {
+    let members: Vec<_> = definition
+        .members
+        .iter()
+        .map(|member| member.name.as_str())
+        .collect();
+    format!("enum {} {{ {} }}", definition.name, members.join(", "))
+}
+
+fn inheritance_specifier(specifier: &InheritanceSpecifier) -> String {
+    match &specifier.arguments {
+        Some(arguments) => format!("{}({})", specifier.base_name.name, expressions(arguments)),
+        None => specifier.base_name.name.clone(),
+    }
+}
+
+fn using_for(directive: &UsingForDirective) -> String {
+    let target = directive
+        .type_name
+        .as_ref()
+        .map_or_else(|| "*".to_string(), type_name);
+    let attached = match (&directive.library_name, &directive.function_list) {
+        (Some(library), _) => library.name.clone(),
+        (None, functions) => {
+            let functions: Vec<_> = functions
+                .iter()
+                .flatten()
+                .filter_map(
+                    |function| match (&function.function, &function.definition) {
+                        (Some(path), _) => Some(path.name.clone()),
+                        (None, Some(path)) => Some(format!(
+                            "{} as {}",
+                            path.name,
+                            function.operator.as_deref().unwrap_or_default()
+                        )),
+                        (None, None) => None,
+                    },
+                )
+                .collect();
+            format!("{{{}}}", functions.join(", "))
+        }
+    };
+    let mut line = format!("using {} for {}", attached, target);
+    if directive.global {
+        line.push_str(" global");
+    }
+    line.push(';');
+    line
+}
+
+fn override_specifier(specifier: &OverrideSpecifier) -> String {
+    if specifier.overrides.is_empty() {
+        return "override".to_string();
+    }
+    let names: Vec<_> = specifier
+        .overrides
+        .iter()
+        .map(|path| path.name.as_str())
+        .collect();
+    format!("override({})", names.join(", "))
+}
+
+fn modifier_invocation(invocation: &ModifierInvocation) -> String {
+    match &invocation.arguments {
+        Some(arguments) => format!(
+            "{}({})",
+            invocation.modifier_name.name,
+            expressions(arguments)
+        ),
+        None => invocation.modifier_name.name.clone(),
+    }
+}
+
+fn parameters(list: &ParameterList) -> String {
+    let parameters: Vec<_> = list.parameters.iter().map(variable).collect();
+    parameters.join(", ")
+}
+
+/// A variable declaration without its value, such as
+/// `uint256 public constant MAX` or `bytes calldata data`.
+fn variable(declaration: &VariableDeclaration) -> String {
+    let mut parts = vec![type_name(&declaration.type_name)];
+    if declaration.indexed == Some(true) {
+        parts.push("indexed".to_string());
+    }
+    if declaration.storage_location != StorageLocation::Default {
+        parts.push(keyword(&declaration.storage_location));
+    }
+    if declaration.state_variable {
+        if declaration.visibility != Visibility::Internal {
+            parts.push(keyword(&declaration.visibility));
+        }
+        if declaration.constant || declaration.mutability == Mutability::Constant {
+            parts.push("constant".to_string());
+        } else if declaration.mutability == Mutability::Immutable {
+            parts.push("immutable".to_string());
+        }
+        if let Some(overrides) = &declaration.overrides {
+            parts.push(override_specifier(overrides));
+        }
+    }
+    if !declaration.name.is_empty() {
+        parts.push(declaration.name.clone());
+    }
+    parts.join(" ")
+}
+
+/// A variable declaration statement without its `;`, as it also appears in
+/// the head of a `for` loop.
+fn variable_declaration_statement(statement: &VariableDeclarationStatement) -> String {
+    let mut text = match statement.declarations.as_slice() {
+        [Some(declaration)] => variable(declaration),
+        declarations => {
+            let declarations: Vec<_> = declarations
+                .iter()
+                .map(|declaration| declaration.as_ref().map(variable).unwrap_or_default())
+                .collect();
+            format!("({})", declarations.join(", "))
+        }
+    };
+    if let Some(value) = &statement.initial_value {
+        text.push_str(&format!(" = {}", expression(value)));
+    }
+    text
+}
+
+fn expression(expression: &Expression) -> String {
+    match expression {
+        Expression::Assignment(node) => assignment(node),
+        Expression::BinaryOperation(node) => binary_operation(node),
+        Expression::Conditional(node) => conditional(node),
+        Expression::ElementaryTypeNameExpression(node) => elementary_type_name(&node.type_name),
+        Expression::FunctionCall(node) => function_call(node),
+        Expression::Identifier(node) => node.name.clone(),
+        Expression::IndexAccess(node) => index_access(node),
+        Expression::IndexRangeAccess(node) => index_range_access(node),
+        Expression::Literal(node) => literal(node),
+        Expression::MemberAccess(node) => member_access(node),
+        Expression::NewExpression(node) => format!("new {}", type_name(&node.type_name)),
+        Expression::TupleExpression(node) => tuple_expression(node),
+        Expression::UnaryOperation(node) => unary_operation(node),
+        Expression::VariableDeclarationStatement(node) => variable_declaration_statement(node),
+        Expression::UnparsedNode(node) => unparsed(node),
+        Expression::ExpressionStatement(node) => self::expression(&node.expression),
+    }
+}
+
+fn expressions(expressions: &[Box<Expression>]) -> String {
+    let expressions: Vec<_> = expressions
+        .iter()
+        .map(|expression_| expression(expression_))
+        .collect();
+    expressions.join(", ")
+}
+
+/// Named arguments or call options, such as `{value: amount, gas: 5000}`.
+fn named(names: &[String], values: &[Box<Expression>]) -> String {
+    let pairs: Vec<_> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}: {}", name, expression(value)))
+        .collect();
+    format!("{{{}}}", pairs.join(", "))
+}
+
+fn assignment(assignment: &Assignment) -> String {
+    format!(
+        "{} {} {}",
+        expression(&assignment.left_hand_side),
+        keyword(&assignment.operator),
+        expression(&assignment.right_hand_side)
+    )
+}
+
+fn binary_operation(operation: &BinaryOperation) -> String {
+    format!(
+        "{} {} {}",
+        expression(&operation.left_expression),
+        keyword(&operation.operator),
+        expression(&operation.right_expression)
+    )
+}
+
+fn conditional(conditional: &Conditional) -> String {
+    format!(
+        "{} ? {} : {}",
+        expression(&conditional.condition),
+        expression(&conditional.true_expression),
+        expression(&conditional.false_expression)
+    )
+}
+
+fn unary_operation(operation: &UnaryOperation) -> String {
+    let operand = expression(&operation.sub_expression);
+    match (&operation.operator, operation.prefix) {
+        (UnaryOperator::Delete, _) => format!("delete {}", operand),
+        (operator, true) => format!("{}{}", keyword(operator), operand),
+        (operator, false) => format!("{}{}", operand, keyword(operator)),
+    }
+}
+
+fn function_call(call: &FunctionCall) -> String {
+    let callee = match call.expression.as_ref() {
+        FunctionCallExpression::ElementaryTypeNameExpression(node) => {
+            elementary_type_name(&node.type_name)
+        }
+        FunctionCallExpression::FunctionCall(node) => function_call(node),
+        FunctionCallExpression::FunctionCallOptions(node) => function_call_options(node),
+        FunctionCallExpression::Identifier(node) => node.name.clone(),
+        FunctionCallExpression::MemberAccess(node) => member_access(node),
+        FunctionCallExpression::NewExpression(node) => {
+            format!("new {}", type_name(&node.type_name))
+        }
+    };
+    if call.names.is_empty() {
+        format!("{}({})", callee, expressions(&call.arguments))
+    } else {
+        format!("{}({})", callee, named(&call.names, &call.arguments))
+    }
+}
+
+fn function_call_options(options: &FunctionCallOptions) -> String {
+    format!(
+        "{}{}",
+        expression(&options.expression),
+        named(&options.names, &options.options)
+    )
+}
+
+fn member_access(access: &MemberAccess) -> String {
+    format!("{}.{}", expression(&access.expression), access.member_name)
+}
+
+fn index_access(access: &IndexAccess) -> String {
+    let index = access.index_expression.as_deref().map(expression);
+    format!(
+        "{}[{}]",
+        expression(&access.base_expression),
+        index.unwrap_or_default()
+    )
+}
+
+/// A comment in place of a node that could not be read, which has no
+/// source to print.
+fn unparsed(node: &UnparsedNode) -> String {
+    format!(
+        "/* unparsed {} */",
+        node.original_node_type().unwrap_or("node")
+    )
+}
+
+fn index_range_access(access: &IndexRangeAccess) -> String {
+    let start = access.start_expression.as_deref().map(expression);
+    let end = access.end_expression.as_deref().map(expression);
+    format!(
+        "{}[{}:{}]",
+        expression(&access.base_expression),
+        start.unwrap_or_default(),
+        end.unwrap_or_default()
+    )
+}
+
+fn tuple_expression(tuple: &TupleExpression) -> String {
+    let components: Vec<_> = tuple
+        .components
+        .iter()
+        .map(|component| component.as_deref().map(expression).unwrap_or_default())
+        .collect();
+    if tuple.is_inline_array {
+        format!("[{}]", components.join(", "))
+    } else {
+        format!("({})", components.join(", "))
+    }
+}
+
+fn literal(literal: &Literal) -> String {
+    match literal.kind {
+        LiteralKind::Bool => literal.value.clone(),
+        LiteralKind::Number => match &literal.subdenomination {
+            Some(subdenomination) => format!("{} {}", literal.value, subdenomination),
+            None => literal.value.clone(),
+        },
+        LiteralKind::String => {
+            // The hex value keeps the bytes of strings that are not valid
+            // UTF-8.
+            let bytes = literal
+                .hex_value
+                .as_deref()
+                .and_then(|hex_value| hex::decode(hex_value).ok())
+                .unwrap_or_else(|| literal.value.as_bytes().to_vec());
+            let escaped: String = bytes
+                .iter()
+                .map(|&byte| match byte {
+                    b'"' => "\\\"".to_string(),
+                    b'\\' => "\\\\".to_string(),
+                    b'\n' => "\\n".to_string(),
+                    b'\r' => "\\r".to_string(),
+                    b'\t' => "\\t".to_string(),
+                    0x20..=0x7e => char::from(byte).to_string(),
+                    _ => format!("\\x{:02x}", byte),
+                })
+                .collect();
+            format!("\"{}\"", escaped)
+        }
+        LiteralKind::HexString => format!(
+            "hex\"{}\"",
+            literal.hex_value.as_deref().unwrap_or_default()
+        ),
+        LiteralKind::UnicodeString => format!("unicode\"{}\"", escape(&literal.value)),
+    }
+}
+
+/// Escape the quotes, backslashes, and control characters of a string.
+fn escape(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if c.is_control() => format!("\\x{:02x}", u32::from(c)),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn type_name(type_name: &TypeName) -> String {
+    match type_name {
+        TypeName::ArrayTypeName(node) => array_type_name(node),
+        TypeName::ElementaryTypeName(node) => elementary_type_name(node),
+        TypeName::FunctionTypeName(node) => function_type_name(node),
+        TypeName::Mapping(node) => mapping(node),
+        TypeName::UserDefinedTypeName(node) => user_defined_type_name(node),
+        TypeName::UnparsedNode(node) => unparsed(node),
+    }
+}
+
+fn elementary_type_name(type_name: &ElementaryTypeName) -> String {
+    match type_name.name {
+        // The `payable(x)` conversion, rather than the type.
+        ElementaryType::Payable => "payable".to_string(),
+        _ => type_name.elementary_type().to_string(),
+    }
+}
+
+fn user_defined_type_name(type_name: &UserDefinedTypeName) -> String {
+    match (&type_name.path_node, &type_name.name) {
+        (Some(path), _) => path.name.clone(),
+        (None, Some(name)) => name.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+fn array_type_name(array: &ArrayTypeName) -> String {
+    let length = array.length.as_deref().map(expression);
+    format!(
+        "{}[{}]",
+        type_name(&array.base_type),
+        length.unwrap_or_default()
+    )
+}
+
+fn mapping(mapping: &Mapping) -> String {
+    let named = |type_name_: &TypeName, name: &str| match name {
+        "" => type_name(type_name_),
+        name => format!("{} {}", type_name(type_name_), name),
+    };
+    format!(
+        "mapping({} => {})",
+        named(&mapping.key_type, &mapping.key_name),
+        named(&mapping.value_type, &mapping.value_name)
+    )
+}
+
+fn function_type_name(function: &FunctionTypeName) -> String {
+    let mut text = format!("function ({})", parameters(&function.parameter_types));
+    if function.visibility == Visibility::External {
+        text.push_str(" external");
+    }
+    if function.state_mutability != StateMutability::Nonpayable {
+        text.push_str(&format!(" {}", keyword(&function.state_mutability)));
+    }
+    if !function.return_parameter_types.parameters.is_empty() {
+        text.push_str(&format!(
+            " returns ({})",
+            parameters(&function.return_parameter_types)
+        ));
+    }
+    text
+}
+
+/// A Yul block on one line, as in the head of a `for` loop.
+fn yul_inline_block(block: &YulBlock) -> String {
+    if block.statements.is_empty() {
+        return "{ }".to_string();
+    }
+    let mut printer = Printer::default();
+    printer.yul_statements(&block.statements);
+    let lines: Vec<_> = printer.out.lines().map(str::trim).collect();
+    format!("{{ {} }}", lines.join(" "))
+}
+
+fn yul_typed_names(names: &[YulTypedName]) -> String {
+    let names: Vec<_> = names
+        .iter()
+        .map(|name| match name.r#type.as_str() {
+            "" => name.name.clone(),
+            r#type => format!("{}:{}", name.name, r#type),
+        })
+        .collect();
+    names.join(", ")
+}
+
+fn yul_expression(expression: &YulExpression) -> String {
+    match expression {
+        YulExpression::YulIdentifier(identifier) => identifier.name.clone(),
+        YulExpression::YulLiteral(literal) => yul_literal(literal),
+        YulExpression::YulFunctionCall(call) => yul_function_call(call),
+    }
+}
+
+fn yul_function_call(call: &YulFunctionCall) -> String {
+    let arguments: Vec<_> = call.arguments.iter().map(yul_expression).collect();
+    format!(
+        "{}({})",
+        yul_expression(&call.function_name),
+        arguments.join(", ")
+    )
+}
+
+fn yul_literal(literal: &YulLiteral) -> String {
+    let mut text = match literal.kind.as_str() {
+        "string" => format!("\"{}\"", escape(&literal.value)),
+        _ => literal.value.clone(),
+    };
+    if !literal.r#type.is_empty() {
+        text.push_str(&format!(":{}", literal.r#type));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn fixture(name: &str) -> SourceUnit {
+        let path = format!("fixtures/ast/codearena-2025-10-covenant/{}.json", name);
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn prints_fixture_declarations() {
+        let source = print(&fixture("IERC20"));
+        assert!(source.starts_with("// SPDX-License-Identifier: MIT\npragma solidity ^0.8.20;\n"));
+        assert!(source.contains("\ninterface IERC20 {\n"));
+        assert!(source.contains(
+            "\n    event Transfer(address indexed from, address indexed to, uint256 value);\n"
+        ));
+        assert!(source.contains(
+            "\n    function transfer(address to, uint256 value) external returns (bool);\n"
+        ));
+        assert!(source.ends_with("\n}\n"));
+    }
+
+    #[test]
+    fn prints_fixture_statements() {
+        let source = print(&fixture("Covenant"));
+        assert!(source.contains(
+            "\n        if (amountRequested == 0)\n            revert Errors.E_ZeroAmount();\n"
+        ));
+        assert!(source.contains("\n        } else if (swapParams.assetOut == AssetType.BASE) {\n"));
+        assert!(source.contains(
+            "\n        ms.baseSupply = (localBaseSupply + mintParams.baseAmountIn) - protocolFees;\n"
+        ));
+        assert!(source.contains("ILiquidExchangeModel(mp.lex).mint{value: mintParams.msgValue}("));
+
+        let source = print(&fixture("FixedPointMathLib"));
+        assert!(
+            source.contains(
+                "\n        assembly {\n            z := div(mul(x, y), WAD)\n        }\n"
+            )
+        );
+    }
+
+    #[test]
+    fn prints_pragma_ranges() {
+        let pragma = |literals: &[&str]| {
+            print(&PragmaDirective {
+                literals: literals.iter().map(|literal| literal.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        assert_eq!(
+            pragma(&["solidity", ">=", "0.6", ".2", "<", "0.9", ".0"]),
+            "pragma solidity >=0.6.2 <0.9.0;\n"
+        );
+        assert_eq!(
+            pragma(&["solidity", "0.8", ".0", "||", "^", "0.7", ".0"]),
+            "pragma solidity 0.8.0 || ^0.7.0;\n"
+        );
+        assert_eq!(pragma(&["abicoder", "v2"]), "pragma abicoder v2;\n");
+    }
+
+    #[test]
+    fn escapes_string_literals() {
+        let literal = Literal {
+            kind: LiteralKind::String,
+            value: "\u{19}Ethereum Signed Message:\n32".to_string(),
+            hex_value: Some(hex::encode("\u{19}Ethereum Signed Message:\n32")),
+            ..Default::default()
+        };
+        assert_eq!(print(&literal), "\"\\x19Ethereum Signed Message:\\n32\"");
+
+        let literal = Literal {
+            kind: LiteralKind::UnicodeString,
+            value: "say \"hi\" 👋".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(print(&literal), "unicode\"say \\\"hi\\\" 👋\"");
+    }
+
+    #[test]
+    fn prints_statement_documentation() {
+        let block = Block {
+            statements: vec![
+                Statement::ExpressionStatement(ExpressionStatement {
+                    expression: Box::new(Expression::Identifier(Identifier {
+                        name: "x".to_string(),
+                        ..Default::default()
+                    })),
+                    documentation: Some(Documentation::String("@dev Touch x.".to_string())),
+                    ..Default::default()
+                }),
+                Statement::Return(Return::default()),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            print(&block),
+            "{\n    /// @dev Touch x.\n    x;\n    return;\n}\n"
+        );
+    }
+    #[test]
+    fn round_trips_ranges_arguments_and_function_lists() {
+        /// Serialize a node, read it back, and print it.
+        fn round_trip<T>(node: &T) -> (serde_json::Value, T)
+        where
+            T: Serialize + serde::de::DeserializeOwned,
+        {
+            let json = serde_json::to_value(node).unwrap();
+            (json.clone(), serde_json::from_value(json).unwrap())
+        }
+        let identifier = |name: &str| {
+            Box::new(Expression::Identifier(Identifier {
+                name: name.to_string(),
+                ..Default::default()
+            }))
+        };
+        let path = |name: &str| IdentifierPath {
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        let range = IndexRangeAccess {
+            base_expression: identifier("data"),
+            start_expression: Some(identifier("a")),
+            end_expression: Some(identifier("b")),
+            ..Default::default()
+        };
+        let (json, read) = round_trip(&range);
+        assert!(json.get("endExpression").is_some());
+        assert_eq!(read, range);
+        assert_eq!(print(&read), "data[a:b]");
+        let open_start = IndexRangeAccess {
+            start_expression: None,
+            ..range
+        };
+        assert_eq!(print(&round_trip(&open_start).1), "data[:b]");
+
+        let contract = ContractDefinition {
+            name: "Token".to_string(),
+            base_contracts: vec![
+                InheritanceSpecifier {
+                    base_name: path("ERC20"),
+                    arguments: Some(vec![identifier("name"), identifier("symbol")]),
+                    ..Default::default()
+                },
+                InheritanceSpecifier {
+                    base_name: path("Ownable"),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let (json, read) = round_trip(&contract);
+        assert!(json["baseContracts"][0].get("arguments").is_some());
+        assert!(json["baseContracts"][1].get("arguments").is_none());
+        assert_eq!(read, contract);
+        assert!(print(&read).starts_with("contract Token is ERC20(name, symbol), Ownable {\n"));
+
+        let using = UsingForDirective {
+            function_list: Some(vec![
+                UsingForFunction {
+                    function: Some(path("f")),
+                    ..Default::default()
+                },
+                UsingForFunction {
+                    definition: Some(path("add")),
+                    operator: Some("+".to_string()),
+                    ..Default::default()
+                },
+            ]),
+            type_name: Some(TypeName::UserDefinedTypeName(UserDefinedTypeName {
+                path_node: Some(path("Fixed")),
+                ..Default::default()
+            })),
+            global: true,
+            ..Default::default()
+        };
+        let (json, read) = round_trip(&using);
+        assert!(json.get("libraryName").is_none());
+        assert_eq!(read, using);
+        assert_eq!(print(&read), "using {f, add as +} for Fixed global;\n");
+        let library = UsingForDirective {
+            library_name: Some(path("Math")),
+            function_list: None,
+            ..using
+        };
+        assert_eq!(
+            print(&round_trip(&library).1),
+            "using Math for Fixed global;\n"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Size and shape statistics of an AST.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::{ContractDefinitionNode, SourceUnit, SourceUnitNode};
+
+/// Node types that are Solidity expressions, for the expression depth.
+const EXPRESSION_NODE_TYPES: &[&str] = &[
+    "Assignment",
+    "BinaryOperation",
+    "Conditional",
+    "ElementaryTypeNameExpression",
+    "FunctionCall",
+    "FunctionCallOptions",
+    "Identifier",
+    "IndexAccess",
+    "IndexRangeAccess",
+    "Literal",
+    "MemberAccess",
+    "NewExpression",
+    "TupleExpression",
+    "UnaryOperation",
+];
+
+/// Statistics about a source unit, returned by [`SourceUnit::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AstStats {
+    /// The number of nodes of each `nodeType`, including the source unit.
+    pub node_counts: BTreeMap<String, usize>,
+    /// The deepest nesting of Solidity expressions, such as 3 for `a + (b * c)`.
+    pub max_expression_depth: usize,
+    /// The number of functions, including constructors and special functions,
+    /// in each contract, interface, and library.
+    pub functions_per_contract: BTreeMap<String, usize>,
+}
+
+impl AstStats {
+    /// The total number of nodes.
+    pub fn total_nodes(&self) -> usize {
+        self.node_counts.values().sum()
+    }
+}
+
+impl SourceUnit {
+    /// Count the nodes of this source unit and measure its nesting.
+    ///
+    /// This is meant for profiling and for spotting pathological inputs,
+    /// such as generated code with very deep expressions, and walks the whole
+    /// tree.
+    pub fn stats(&self) -> AstStats {
+        let mut stats = AstStats::default();
+        let value = serde_json::to_value(self).expect("AST nodes serialize to JSON");
+        let Value::Object(object) = &value else {
+            unreachable!("a source unit serializes to an object");
+        };
+        stats.node_counts.insert("SourceUnit".to_string(), 1);
+        for (field, value) in object {
+            collect(value, "SourceUnit", field, 0, &mut stats);
+        }
+
+        for node in &self.nodes {
+            if let SourceUnitNode::ContractDefinition(contract) = node {
+                let functions = contract
+                    .nodes
+                    .iter()
+                    .filter(|node| matches!(node, ContractDefinitionNode::FunctionDefinition(_)))
+                    .count();
+                stats
+                    .functions_per_contract
+                    .insert(contract.name.clone(), functions);
+            }
+        }
+        stats
+    }
+}
+
+/// The node type of a field that holds a node struct directly.
+///
+/// Nodes held by a field of a concrete struct type, rather than of one of the
+/// node enums, are serialized without their `nodeType`, so it is recovered
+/// from the type of the parent node and the field name.
+pub(super) fn untagged_node_type(parent: &str, field: &str) -> Option<&'static str> {
+    Some(match (parent, field) {
+        ("ImportDirective", "foreign") => "Identifier",
+        ("ContractDefinition", "baseContracts") => "InheritanceSpecifier",
+        ("InheritanceSpecifier" | "ModifierInvocation" | "UserDefinedTypeName", "baseName")
+        | ("ModifierInvocation", "modifierName")
+        | ("UsingForDirective", "libraryName")
+        | ("UserDefinedTypeName", "pathNode")
+        | ("OverrideSpecifier", "overrides") => "IdentifierPath",
+        ("VariableDeclaration" | "FunctionDefinition", "overrides") => "OverrideSpecifier",
+        ("FunctionDefinition" | "ModifierDefinition", "body") | ("TryCatchClause", "block") => {
+            "Block"
+        }
+        (
+            "FunctionDefinition" | "ModifierDefinition" | "EventDefinition" | "ErrorDefinition"
+            | "TryCatchClause",
+            "parameters",
+        )
+        | ("FunctionDefinition", "returnParameters")
+        | ("FunctionTypeName", "parameterTypes" | "returnParameterTypes") => "ParameterList",
+        ("FunctionDefinition", "modifiers") => "ModifierInvocation",
+        ("ParameterList", "parameters")
+        | ("StructDefinition", "members")
+        | ("VariableDeclarationStatement", "declarations") => "VariableDeclaration",
+        ("EnumDefinition", "members") => "EnumValue",
+        ("EmitStatement", "eventCall") | ("RevertStatement", "errorCall") => "FunctionCall",
+        ("TryStatement", "clauses") => "TryCatchClause",
+        ("InlineAssembly", "AST")
+        | ("YulIf" | "YulCase" | "YulFunctionDefinition", "body")
+        | ("YulForLoop", "pre" | "post" | "body") => "YulBlock",
+        ("YulAssignment", "variableNames") => "YulIdentifier",
+        ("YulVariableDeclaration", "variables")
+        | ("YulFunctionDefinition", "parameters" | "returnVariables") => "YulTypedName",
+        ("YulSwitch", "cases") => "YulCase",
+        ("YulCase", "value") => "YulLiteral",
+        ("ElementaryTypeNameExpression", "typeName") => "ElementaryTypeName",
+        (_, "documentation") => "StructuredDocumentation",
+        _ => return None,
+    })
+}
+
+/// Walk a serialized node, or an array of nodes, held by `field` of `parent`.
+fn collect(
+    value: &Value,
+    parent: &str,
+    field: &str,
+    expression_depth: usize,
+    stats: &mut AstStats,
+) {
+    match value {
+        Value::Object(object) => {
+            let node_type = object
+                .get("nodeType")
+                .and_then(Value::as_str)
+                .or_else(|| untagged_node_type(parent, field));
+            // Objects that are not nodes, such as the symbol aliases of an
+            // import, are looked through as part of their parent node.
+            let Some(node_type) = node_type else {
+                for (field, value) in object {
+                    collect(value, parent, field, expression_depth, stats);
+                }
+                return;
+            };
+
+            *stats.node_counts.entry(node_type.to_string()).or_default() += 1;
+            let depth = if EXPRESSION_NODE_TYPES.contains(&node_type) {
+                expression_depth + 1
+            } else {
+                0
+            };
+            stats.max_expression_depth = stats.max_expression_depth.max(depth);
+            for (field, value) in object {
+                collect(value, node_type, field, depth, stats);
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|value| collect(value, parent, field, expression_depth, stats)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn fixture_stats() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ArrayHelpers.json")
+                .unwrap();
+        let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        let stats = source_unit.stats();
+
+        assert_eq!(stats.node_counts["SourceUnit"], 1);
+        assert_eq!(stats.functions_per_contract["ArrayHelpers"], 15);
+        assert!(stats.max_expression_depth > 0);
+    }
+
+    #[test]
+    fn fixtures_count_every_node() {
+        crate::ast::fixture_paths().par_iter().for_each(|path| {
+            let content = fs::read_to_string(path).expect("Failed to read fixture file");
+            let source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+            let raw: Value = serde_json::from_str(&content).unwrap();
+            let mut raw_counts = BTreeMap::new();
+            count_raw(&raw, &mut raw_counts);
+            assert_eq!(
+                source_unit.stats().node_counts,
+                raw_counts,
+                "node counts of {:?}",
+                path
+            );
+        });
+    }
+
+    fn count_raw(value: &Value, counts: &mut BTreeMap<String, usize>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(node_type) = object.get("nodeType").and_then(Value::as_str) {
+                    *counts.entry(node_type.to_string()).or_default() += 1;
+                }
+                object.values().for_each(|value| count_raw(value, counts));
+            }
+            Value::Array(items) => items.iter().for_each(|value| count_raw(value, counts)),
+            _ => {}
+        }
+    }
+}
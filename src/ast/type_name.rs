@@ -0,0 +1,415 @@
+//! Names of the types that type names refer to.
+//!
+//! A [`TypeName`] node spells a type the way the source does, so a
+//! user-defined type is only a reference to its declaration. The methods
+//! here resolve those references through an [`AstIndex`] to build the
+//! canonical Solidity name of the type, its ABI type for signatures, or its
+//! `internalType` in an ABI.
+
+use super::*;
+use crate::abi::InternalType;
+
+/// An error returned when the type of a type name cannot be named.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TypeNameError {
+    /// A user-defined type does not say which declaration it refers to.
+    #[error("type name {0} has no referenced declaration")]
+    UnresolvedType(i64),
+
+    /// A user-defined type refers to a declaration that is not in the index,
+    /// usually because a source unit it imports was not passed.
+    #[error("type name {node} refers to declaration {declaration}, which was not found")]
+    UnknownDeclaration { node: i64, declaration: i64 },
+
+    /// A type that has no name in this form, such as a mapping in the ABI,
+    /// or an array whose length was not evaluated.
+    #[error("type name {0} cannot be named")]
+    UnsupportedType(i64),
+
+    /// The type name is a placeholder for a node that could not be read.
+    #[error("type name {0} could not be parsed")]
+    Unparsed(i64),
+
+    /// A struct contains itself, through arrays, so it has no ABI type.
+    #[error("struct {0} is recursive")]
+    RecursiveStruct(i64),
+}
+
+impl TypeName {
+    /// The canonical Solidity name of the type, such as `uint256[2]`,
+    /// `mapping(address => Vault.Position)`, or `address payable`.
+    ///
+    /// User-defined types are named by the canonical name of their
+    /// declaration, which includes the contract they are declared in.
+    pub fn canonical_string(&self, index: &AstIndex<'_>) -> Result<String, TypeNameError> {
+        match self {
+            TypeName::ElementaryTypeName(elementary) => {
+                Ok(elementary.elementary_type().to_string())
+            }
+            TypeName::UserDefinedTypeName(user_defined) => match resolve(user_defined, index)? {
+                Declaration::ContractDefinition(node) => Ok(node.canonical_name.clone()),
+                Declaration::StructDefinition(node) => Ok(node.canonical_name.clone()),
+                Declaration::EnumDefinition(node) => Ok(node.canonical_name.clone()),
+                Declaration::UserDefinedValueTypeDefinition(node) => {
+                    Ok(node.canonical_name.clone())
+                }
+                _ => Err(TypeNameError::UnsupportedType(user_defined.id)),
+            },
+            TypeName::ArrayTypeName(array) => Ok(format!(
+                "{}{}",
+                array.base_type.canonical_string(index)?,
+                array_suffix(array)?
+            )),
+            TypeName::Mapping(mapping) => Ok(format!(
+                "mapping({} => {})",
+                mapping.key_type.canonical_string(index)?,
+                mapping.value_type.canonical_string(index)?
+            )),
+            TypeName::FunctionTypeName(function) => {
+                let list = |parameters: &ParameterList| {
+                    parameters
+                        .parameters
+                        .iter()
+                        .map(|parameter| parameter.type_name.canonical_string(index))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(|types| types.join(","))
+                };
+                let mut name = format!("function ({})", list(&function.parameter_types)?);
+                if function.state_mutability != StateMutability::Nonpayable {
+                    let mutability = serde_json::to_value(&function.state_mutability)
+                        .expect("state mutability serializes to JSON");
+                    name.push(' ');
+                    name.push_str(mutability.as_str().unwrap_or_default());
+                }
+                if function.visibility == Visibility::External {
+                    name.push_str(" external");
+                }
+                if !function.return_parameter_types.parameters.is_empty() {
+                    name.push_str(&format!(
+                        " returns ({})",
+                        list(&function.return_parameter_types)?
+                    ));
+                }
+                Ok(name)
+            }
+            TypeName::UnparsedNode(node) => Err(TypeNameError::Unparsed(node.id)),
+        }
+    }
+
+    /// The canonical ABI type of the type, as used in signatures, such as
+    /// `address` for a contract or `(uint256,bool)[]` for an array of
+    /// structs.
+    ///
+    /// Mappings and recursive structs have no ABI type.
+    pub fn abi_type(&self, index: &AstIndex<'_>) -> Result<String, TypeNameError> {
+        self.abi_type_within(index, &mut Vec::new())
+    }
+
+    /// The ABI type of the type inside the structs with ids `structs`.
+    fn abi_type_within(
+        &self,
+        index: &AstIndex<'_>,
+        structs: &mut Vec<i64>,
+    ) -> Result<String, TypeNameError> {
+        match self {
+            TypeName::ElementaryTypeName(elementary) => Ok(elementary.name.abi_type()),
+            TypeName::UserDefinedTypeName(user_defined) => match resolve(user_defined, index)? {
+                Declaration::ContractDefinition(_) => Ok("address".to_string()),
+                Declaration::EnumDefinition(_) => Ok("uint8".to_string()),
+                Declaration::StructDefinition(definition) => {
+                    enter_struct(definition, structs)?;
+                    let members = definition
+                        .members
+                        .iter()
+                        .map(|member| member.type_name.abi_type_within(index, structs))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    structs.pop();
+                    Ok(format!("({})", members.join(",")))
+                }
+                Declaration::UserDefinedValueTypeDefinition(definition) => {
+                    definition.underlying_type.abi_type(index)
+                }
+                _ => Err(TypeNameError::UnsupportedType(user_defined.id)),
+            },
+            TypeName::ArrayTypeName(array) => Ok(format!(
+                "{}{}",
+                array.base_type.abi_type_within(index, structs)?,
+                array_suffix(array)?
+            )),
+            TypeName::FunctionTypeName(_) => Ok("function".to_string()),
+            TypeName::Mapping(mapping) => Err(TypeNameError::UnsupportedType(mapping.id)),
+            TypeName::UnparsedNode(node) => Err(TypeNameError::Unparsed(node.id)),
+        }
+    }
+
+    /// The `internalType` of the type in an ABI, such as
+    /// `struct Vault.Position[]` or `contract IERC20`.
+    pub fn internal_type(&self, index: &AstIndex<'_>) -> Result<InternalType, TypeNameError> {
+        let qualified = |canonical_name: &str| match canonical_name.rsplit_once('.') {
+            Some((contract, name)) => (Some(contract.to_string()), name.to_string()),
+            None => (None, canonical_name.to_string()),
+        };
+        match self {
+            TypeName::ElementaryTypeName(elementary) => Ok(InternalType::Elementary(
+                elementary.elementary_type().to_string(),
+            )),
+            TypeName::UserDefinedTypeName(user_defined) => match resolve(user_defined, index)? {
+                Declaration::ContractDefinition(node) => {
+                    Ok(InternalType::Contract(node.canonical_name.clone()))
+                }
+                Declaration::StructDefinition(node) => {
+                    let (contract, name) = qualified(&node.canonical_name);
+                    Ok(InternalType::Struct { contract, name })
+                }
+                Declaration::EnumDefinition(node) => {
+                    let (contract, name) = qualified(&node.canonical_name);
+                    Ok(InternalType::Enum { contract, name })
+                }
+                Declaration::UserDefinedValueTypeDefinition(node) => {
+                    let (contract, name) = qualified(&node.canonical_name);
+                    Ok(InternalType::UserDefinedValueType { contract, name })
+                }
+                _ => Err(TypeNameError::UnsupportedType(user_defined.id)),
+            },
+            TypeName::ArrayTypeName(array) => {
+                let length = array_length(array)?
+                    .map(|length| length.parse())
+                    .transpose()
+                    .map_err(|_| TypeNameError::UnsupportedType(array.id))?;
+                Ok(InternalType::Array {
+                    base: Box::new(array.base_type.internal_type(index)?),
+                    length,
+                })
+            }
+            TypeName::FunctionTypeName(_) => {
+                Ok(InternalType::Function(self.canonical_string(index)?))
+            }
+            TypeName::Mapping(mapping) => Err(TypeNameError::UnsupportedType(mapping.id)),
+            TypeName::UnparsedNode(node) => Err(TypeNameError::Unparsed(node.id)),
+        }
+    }
+}
+
+/// The declaration a user-defined type name refers to.
+fn resolve<'a>(
+    user_defined: &UserDefinedTypeName,
+    index: &AstIndex<'a>,
+) -> Result<Declaration<'a>, TypeNameError> {
+    let declaration = user_defined
+        .referenced_declaration
+        .ok_or(TypeNameError::UnresolvedType(user_defined.id))?;
+    index
+        .get(declaration)
+        .ok_or(TypeNameError::UnknownDeclaration {
+            node: user_defined.id,
+            declaration,
+        })
+}
+
+/// Record that a struct is being named, failing if it already is, which
+/// means the struct contains itself.
+pub(super) fn enter_struct(
+    definition: &StructDefinition,
+    structs: &mut Vec<i64>,
+) -> Result<(), TypeNameError> {
+    if structs.contains(&definition.id) {
+        return Err(TypeNameError::RecursiveStruct(definition.id));
+    }
+    structs.push(definition.id);
+    Ok(())
+}
+
+/// The `[]` or `[N]` that follows the base type of an array.
+pub(super) fn array_suffix(array: &ArrayTypeName) -> Result<String, TypeNameError> {
+    Ok(format!("[{}]", array_length(array)?.unwrap_or_default()))
+}
+
+/// The length of an array, or `None` for a dynamic array.
+fn array_length(array: &ArrayTypeName) -> Result<Option<&str>, TypeNameError> {
+    let Some(length) = &array.length else {
+        return Ok(None);
+    };
+    // The length may be a constant expression, which the type string has
+    // already evaluated.
+    let type_string = array.type_descriptions.type_string.as_deref();
+    let evaluated = type_string.and_then(|type_string| {
+        let start = type_string.rfind('[')?;
+        let end = type_string.rfind(']')?;
+        type_string.get(start + 1..end)
+    });
+    match (evaluated, length.as_ref()) {
+        (Some(length), _) => Ok(Some(length)),
+        (None, Expression::Literal(literal)) => Ok(Some(&literal.value)),
+        (None, _) => Err(TypeNameError::UnsupportedType(array.id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elementary(name: &str) -> TypeName {
+        TypeName::ElementaryTypeName(ElementaryTypeName {
+            name: name.parse().unwrap(),
+            ..Default::default()
+        })
+    }
+
+    fn user_defined(id: i64, declaration: i64) -> TypeName {
+        TypeName::UserDefinedTypeName(UserDefinedTypeName {
+            id,
+            referenced_declaration: Some(declaration),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn rejects_recursive_structs() {
+        // struct Node { uint256 value; Node[] children; }
+        let children = TypeName::ArrayTypeName(ArrayTypeName {
+            base_type: Box::new(user_defined(21, 20)),
+            ..Default::default()
+        });
+        let source_units = vec![SourceUnit {
+            nodes: vec![SourceUnitNode::StructDefinition(StructDefinition {
+                id: 20,
+                name: "Node".to_string(),
+                canonical_name: "Node".to_string(),
+                members: vec![
+                    VariableDeclaration {
+                        type_name: elementary("uint256"),
+                        ..Default::default()
+                    },
+                    VariableDeclaration {
+                        type_name: children,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            })],
+            ..Default::default()
+        }];
+        let index = AstIndex::new(&source_units);
+
+        let node = user_defined(22, 20);
+        assert_eq!(node.canonical_string(&index).unwrap(), "Node");
+        assert_eq!(
+            node.abi_type(&index),
+            Err(TypeNameError::RecursiveStruct(20))
+        );
+        let parameter = VariableDeclaration {
+            name: "root".to_string(),
+            type_name: node,
+            ..Default::default()
+        };
+        assert_eq!(
+            parameter.abi_param(&index),
+            Err(TypeNameError::RecursiveStruct(20))
+        );
+    }
+
+    #[test]
+    fn names_resolved_types() {
+        let position = StructDefinition {
+            id: 2,
+            name: "Position".to_string(),
+            canonical_name: "Vault.Position".to_string(),
+            members: vec![
+                VariableDeclaration {
+                    type_name: elementary("uint256"),
+                    ..Default::default()
+                },
+                VariableDeclaration {
+                    type_name: user_defined(10, 3),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let source_units = vec![SourceUnit {
+            nodes: vec![SourceUnitNode::ContractDefinition(ContractDefinition {
+                id: 1,
+                name: "Vault".to_string(),
+                canonical_name: "Vault".to_string(),
+                nodes: vec![
+                    ContractDefinitionNode::StructDefinition(position),
+                    ContractDefinitionNode::EnumDefinition(EnumDefinition {
+                        id: 3,
+                        name: "Status".to_string(),
+                        canonical_name: "Vault.Status".to_string(),
+                        ..Default::default()
+                    }),
+                ],
+                ..Default::default()
+            })],
+            ..Default::default()
+        }];
+        let index = AstIndex::new(&source_units);
+
+        let positions = TypeName::ArrayTypeName(ArrayTypeName {
+            base_type: Box::new(user_defined(11, 2)),
+            length: Some(Box::new(Expression::Literal(Literal {
+                value: "2".to_string(),
+                ..Default::default()
+            }))),
+            ..Default::default()
+        });
+        assert_eq!(
+            positions.canonical_string(&index).unwrap(),
+            "Vault.Position[2]"
+        );
+        assert_eq!(positions.abi_type(&index).unwrap(), "(uint256,uint8)[2]");
+
+        let mapping = TypeName::Mapping(Mapping {
+            id: 12,
+            key_type: Box::new(TypeName::ElementaryTypeName(ElementaryTypeName {
+                name: ElementaryType::Address,
+                state_mutability: Some("payable".to_string()),
+                ..Default::default()
+            })),
+            value_type: Box::new(user_defined(13, 1)),
+            ..Default::default()
+        });
+        assert_eq!(
+            mapping.canonical_string(&index).unwrap(),
+            "mapping(address payable => Vault)"
+        );
+        assert_eq!(
+            mapping.abi_type(&index),
+            Err(TypeNameError::UnsupportedType(12))
+        );
+
+        let function = TypeName::FunctionTypeName(FunctionTypeName {
+            parameter_types: ParameterList {
+                parameters: vec![VariableDeclaration {
+                    type_name: elementary("uint256"),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            return_parameter_types: ParameterList {
+                parameters: vec![VariableDeclaration {
+                    type_name: elementary("bool"),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            visibility: Visibility::External,
+            state_mutability: StateMutability::View,
+            ..Default::default()
+        });
+        assert_eq!(
+            function.canonical_string(&index).unwrap(),
+            "function (uint256) view external returns (bool)"
+        );
+        assert_eq!(function.abi_type(&index).unwrap(), "function");
+
+        assert_eq!(
+            user_defined(14, 99).abi_type(&index),
+            Err(TypeNameError::UnknownDeclaration {
+                node: 14,
+                declaration: 99
+            })
+        );
+    }
+}
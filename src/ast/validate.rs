@@ -0,0 +1,321 @@
+//! Check the selectors recorded in an AST against its declarations.
+//!
+//! The compiler records the selector of every public function, public state
+//! variable getter, event, and error in the AST. [`validate_selectors`]
+//! recomputes them from the declared parameter types, which catches ASTs that
+//! were corrupted or edited by hand before they reach other tools.
+
+use super::{
+    AstIndex, ContractDefinition, ContractDefinitionNode, ContractKind, ErrorDefinition,
+    EventDefinition, FunctionDefinition, FunctionKind, ParameterList, SourceUnit, SourceUnitNode,
+    TypeName, TypeNameError, VariableDeclaration, Visibility,
+};
+use crate::selectors::{Signature, SignatureKind};
+
+/// A selector in the AST that does not match the declaration it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectorMismatch {
+    /// The id of the declaration.
+    pub id: i64,
+    /// The signature computed from the declaration.
+    pub signature: Signature,
+    /// The selector recorded in the AST, as hex without a `0x` prefix.
+    pub recorded: String,
+    /// The selector of the computed signature, in the same format.
+    pub computed: String,
+}
+
+/// An error that prevented [`validate_selectors`] from computing a signature.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SelectorError {
+    /// A user-defined type does not say which declaration it refers to.
+    #[error("type name {0} has no referenced declaration")]
+    UnresolvedType(i64),
+
+    /// A user-defined type refers to a declaration in none of the source
+    /// units, usually because a source unit it imports was not passed.
+    #[error("type name {node} refers to declaration {declaration}, which was not found")]
+    UnknownDeclaration { node: i64, declaration: i64 },
+
+    /// A type that cannot appear in a signature, such as a mapping.
+    #[error("type name {0} cannot appear in a signature")]
+    UnsupportedType(i64),
+
+    /// The type name is a placeholder for a node that could not be read.
+    #[error("type name {0} could not be parsed")]
+    Unparsed(i64),
+
+    /// A struct contains itself, through arrays, so it has no ABI type.
+    #[error("struct {0} is recursive")]
+    RecursiveStruct(i64),
+}
+
+impl From<TypeNameError> for SelectorError {
+    fn from(error: TypeNameError) -> Self {
+        match error {
+            TypeNameError::UnresolvedType(node) => SelectorError::UnresolvedType(node),
+            TypeNameError::UnknownDeclaration { node, declaration } => {
+                SelectorError::UnknownDeclaration { node, declaration }
+            }
+            TypeNameError::UnsupportedType(node) => SelectorError::UnsupportedType(node),
+            TypeNameError::Unparsed(node) => SelectorError::Unparsed(node),
+            TypeNameError::RecursiveStruct(id) => SelectorError::RecursiveStruct(id),
+        }
+    }
+}
+
+/// The result of [`validate_selectors`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectorReport {
+    /// The number of selectors that were recomputed.
+    pub checked: usize,
+    /// The recorded selectors that differ from the computed ones.
+    pub mismatches: Vec<SelectorMismatch>,
+    /// Why the selectors of the remaining declarations could not be computed.
+    pub errors: Vec<SelectorError>,
+}
+
+impl SelectorReport {
+    /// Whether every selector was computed and matched.
+    pub fn is_valid(&self) -> bool {
+        self.mismatches.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Recompute the selectors of the declarations in the source units.
+///
+/// Pass every source unit of a compilation, so that structs, enums,
+/// contracts, and user-defined value types used as parameters can be
+/// resolved wherever they are declared. A declaration whose parameter types
+/// cannot be resolved is reported as an error and does not stop the others
+/// from being checked. Functions of libraries are skipped, because their
+/// selectors are computed from library-specific type names rather than ABI
+/// types.
+pub fn validate_selectors(source_units: &[SourceUnit]) -> SelectorReport {
+    let declarations = Declarations::new(source_units);
+    let mut report = SelectorReport::default();
+    for source_unit in source_units {
+        for node in &source_unit.nodes {
+            match node {
+                SourceUnitNode::ContractDefinition(contract) => {
+                    declarations.check_contract(contract, &mut report)
+                }
+                SourceUnitNode::EventDefinition(event) => {
+                    declarations.check_event(event, &mut report)
+                }
+                SourceUnitNode::ErrorDefinition(error) => {
+                    declarations.check_error(error, &mut report)
+                }
+                _ => {}
+            }
+        }
+    }
+    report
+}
+
+/// Computes the signatures of declarations, resolving types through an
+/// index of the compilation.
+struct Declarations<'a> {
+    index: AstIndex<'a>,
+}
+
+impl<'a> Declarations<'a> {
+    fn new(source_units: &'a [SourceUnit]) -> Self {
+        Self {
+            index: AstIndex::new(source_units),
+        }
+    }
+
+    fn check_contract(&self, contract: &ContractDefinition, report: &mut SelectorReport) {
+        let library = contract.contract_kind == ContractKind::Library;
+        for node in &contract.nodes {
+            match node {
+                ContractDefinitionNode::FunctionDefinition(function) if !library => {
+                    self.check_function(function, report)
+                }
+                ContractDefinitionNode::VariableDeclaration(variable) if !library => {
+                    self.check_getter(variable, report)
+                }
+                ContractDefinitionNode::EventDefinition(event) => self.check_event(event, report),
+                ContractDefinitionNode::ErrorDefinition(error) => self.check_error(error, report),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_function(&self, function: &FunctionDefinition, report: &mut SelectorReport) {
+        let Some(recorded) = &function.function_selector else {
+            return;
+        };
+        if function.kind != FunctionKind::Function {
+            return;
+        }
+        let text = function.signature(&self.index).map_err(SelectorError::from);
+        check(function.id, SignatureKind::Function, text, recorded, report);
+    }
+
+    /// Check the getter of a public state variable, which takes a key for
+    /// every mapping and an index for every array in the variable's type.
+    fn check_getter(&self, variable: &VariableDeclaration, report: &mut SelectorReport) {
+        let Some(recorded) = &variable.function_selector else {
+            return;
+        };
+        if variable.visibility != Visibility::Public {
+            return;
+        }
+        let mut parameters = Vec::new();
+        let mut type_name = &variable.type_name;
+        loop {
+            match type_name {
+                TypeName::Mapping(mapping) => {
+                    parameters.push(self.abi_type(&mapping.key_type));
+                    type_name = &mapping.value_type;
+                }
+                TypeName::ArrayTypeName(array) => {
+                    parameters.push(Ok("uint256".to_string()));
+                    type_name = &array.base_type;
+                }
+                _ => break,
+            }
+        }
+        let text = parameters
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map(|types| format!("{}({})", variable.name, types.join(",")));
+        check(variable.id, SignatureKind::Function, text, recorded, report);
+    }
+
+    fn check_event(&self, event: &EventDefinition, report: &mut SelectorReport) {
+        let text = self
+            .parameter_types(&event.parameters)
+            .map(|types| format!("{}({})", event.name, types.join(",")));
+        check(
+            event.id,
+            SignatureKind::Event,
+            text,
+            &event.event_selector,
+            report,
+        );
+    }
+
+    fn check_error(&self, error: &ErrorDefinition, report: &mut SelectorReport) {
+        let text = self
+            .parameter_types(&error.parameters)
+            .map(|types| format!("{}({})", error.name, types.join(",")));
+        check(
+            error.id,
+            SignatureKind::Error,
+            text,
+            &error.error_selector,
+            report,
+        );
+    }
+
+    fn parameter_types(&self, parameters: &ParameterList) -> Result<Vec<String>, SelectorError> {
+        parameters
+            .parameters
+            .iter()
+            .map(|parameter| self.abi_type(&parameter.type_name))
+            .collect()
+    }
+
+    /// The canonical ABI type of a type name, with structs as tuples.
+    fn abi_type(&self, type_name: &TypeName) -> Result<String, SelectorError> {
+        type_name.abi_type(&self.index).map_err(SelectorError::from)
+    }
+}
+
+/// Record the outcome of checking `recorded` against the selector of `text`.
+fn check(
+    id: i64,
+    kind: SignatureKind,
+    text: Result<String, SelectorError>,
+    recorded: &str,
+    report: &mut SelectorReport,
+) {
+    let text = match text {
+        Ok(text) => text,
+        Err(error) => {
+            report.errors.push(error);
+            return;
+        }
+    };
+    report.checked += 1;
+    let signature = Signature::new(kind, text);
+    let computed = hex::encode(signature.selector());
+    if !computed.eq_ignore_ascii_case(recorded) {
+        report.mismatches.push(SelectorMismatch {
+            id,
+            signature,
+            recorded: recorded.to_string(),
+            computed,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn fixture_selectors_match() {
+        // Each fixture is checked on its own, so types imported from other
+        // files cannot be resolved and are reported as errors.
+        let checked = AtomicUsize::new(0);
+        crate::ast::all_fixtures()
+            .into_par_iter()
+            .for_each(|(path, source_unit)| {
+                let report = validate_selectors(&[source_unit]);
+                assert!(
+                    report.mismatches.is_empty(),
+                    "{:?}: {:#?}",
+                    path,
+                    report.mismatches
+                );
+                assert!(
+                    report
+                        .errors
+                        .iter()
+                        .all(|error| matches!(error, SelectorError::UnknownDeclaration { .. })),
+                    "{:?}: {:#?}",
+                    path,
+                    report.errors
+                );
+                checked.fetch_add(report.checked, Ordering::Relaxed);
+            });
+        assert!(checked.into_inner() > 1000);
+    }
+
+    #[test]
+    fn reports_corrupted_selector() {
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ISynthToken.json").unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let contract = value["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|node| node["nodeType"] == "ContractDefinition")
+            .unwrap();
+        let function = contract["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|node| node["functionSelector"].is_string())
+            .unwrap();
+        let recorded = function["functionSelector"].as_str().unwrap().to_string();
+        function["functionSelector"] = "deadbeef".into();
+        let id = function["id"].as_i64().unwrap();
+
+        let report = validate_selectors(&[serde_json::from_value(value).unwrap()]);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].id, id);
+        assert_eq!(report.mismatches[0].recorded, "deadbeef");
+        assert_eq!(report.mismatches[0].computed, recorded);
+    }
+}
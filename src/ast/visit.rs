@@ -0,0 +1,1140 @@
+//! Traversal of the AST with visitors.
+//!
+//! A [`Visitor`] has a `visit_*` method for every node type. The default
+//! implementation of each method calls the matching `walk_*` function, which
+//! visits the children of the node. An implementation overrides the methods
+//! for the nodes it is interested in and calls the `walk_*` function from its
+//! override to keep descending, or leaves it out to skip the subtree.
+//!
+//! [`VisitorMut`](super::visit_mut::VisitorMut) in [`visit_mut`](super::visit_mut)
+//! is the same with mutable references, for passes that rewrite the tree.
+//!
+//! ```
+//! use solc::ast::{FunctionCall, SourceUnit};
+//! use solc::ast::visit::{self, Visitor};
+//!
+//! #[derive(Default)]
+//! struct CountCalls(usize);
+//!
+//! impl Visitor for CountCalls {
+//!     fn visit_function_call(&mut self, node: &FunctionCall) {
+//!         self.0 += 1;
+//!         visit::walk_function_call(self, node);
+//!     }
+//! }
+//!
+//! let mut counter = CountCalls::default();
+//! counter.visit_source_unit(&SourceUnit::default());
+//! assert_eq!(counter.0, 0);
+//! ```
+
+use super::*;
+
+/// Define a visitor trait and its `walk_*` functions, over shared or mutable
+/// references.
+macro_rules! visitor {
+    ($visitor:ident $(, $mut:ident)?) => {
+        /// Visits the nodes of an AST.
+        ///
+        /// See the [module documentation](self) for how the methods and the
+        /// `walk_*` functions work together.
+        pub trait $visitor {
+            /// Visit a [`SourceUnit`] and, by default, its children.
+            fn visit_source_unit(&mut self, node: &$($mut)? SourceUnit) {
+                walk_source_unit(self, node)
+            }
+            /// Visit a [`PragmaDirective`], which has no children to walk.
+            fn visit_pragma_directive(&mut self, _node: &$($mut)? PragmaDirective) {}
+            /// Visit an [`ImportDirective`] and, by default, its children.
+            fn visit_import_directive(&mut self, node: &$($mut)? ImportDirective) {
+                walk_import_directive(self, node)
+            }
+            /// Visit a [`ContractDefinition`] and, by default, its children.
+            fn visit_contract_definition(&mut self, node: &$($mut)? ContractDefinition) {
+                walk_contract_definition(self, node)
+            }
+            /// Visit an [`InheritanceSpecifier`] and, by default, its children.
+            fn visit_inheritance_specifier(&mut self, node: &$($mut)? InheritanceSpecifier) {
+                walk_inheritance_specifier(self, node)
+            }
+            /// Visit a [`VariableDeclaration`] and, by default, its children.
+            fn visit_variable_declaration(&mut self, node: &$($mut)? VariableDeclaration) {
+                walk_variable_declaration(self, node)
+            }
+            /// Visit an [`OverrideSpecifier`] and, by default, its children.
+            fn visit_override_specifier(&mut self, node: &$($mut)? OverrideSpecifier) {
+                walk_override_specifier(self, node)
+            }
+            /// Visit a [`FunctionDefinition`] and, by default, its children.
+            fn visit_function_definition(&mut self, node: &$($mut)? FunctionDefinition) {
+                walk_function_definition(self, node)
+            }
+            /// Visit a [`ModifierInvocation`] and, by default, its children.
+            fn visit_modifier_invocation(&mut self, node: &$($mut)? ModifierInvocation) {
+                walk_modifier_invocation(self, node)
+            }
+            /// Visit a [`ParameterList`] and, by default, its children.
+            fn visit_parameter_list(&mut self, node: &$($mut)? ParameterList) {
+                walk_parameter_list(self, node)
+            }
+            /// Visit a [`ModifierDefinition`] and, by default, its children.
+            fn visit_modifier_definition(&mut self, node: &$($mut)? ModifierDefinition) {
+                walk_modifier_definition(self, node)
+            }
+            /// Visit an [`EventDefinition`] and, by default, its children.
+            fn visit_event_definition(&mut self, node: &$($mut)? EventDefinition) {
+                walk_event_definition(self, node)
+            }
+            /// Visit an [`ErrorDefinition`] and, by default, its children.
+            fn visit_error_definition(&mut self, node: &$($mut)? ErrorDefinition) {
+                walk_error_definition(self, node)
+            }
+            /// Visit a [`StructDefinition`] and, by default, its children.
+            fn visit_struct_definition(&mut self, node: &$($mut)? StructDefinition) {
+                walk_struct_definition(self, node)
+            }
+            /// Visit an [`EnumDefinition`] and, by default, its children.
+            fn visit_enum_definition(&mut self, node: &$($mut)? EnumDefinition) {
+                walk_enum_definition(self, node)
+            }
+            /// Visit an [`EnumValue`] and, by default, its children.
+            fn visit_enum_value(&mut self, node: &$($mut)? EnumValue) {
+                walk_enum_value(self, node)
+            }
+            /// Visit a [`UserDefinedValueTypeDefinition`] and, by default, its children.
+            fn visit_user_defined_value_type_definition(
+                &mut self,
+                node: &$($mut)? UserDefinedValueTypeDefinition,
+            ) {
+                walk_user_defined_value_type_definition(self, node)
+            }
+            /// Visit a [`UsingForDirective`] and, by default, its children.
+            fn visit_using_for_directive(&mut self, node: &$($mut)? UsingForDirective) {
+                walk_using_for_directive(self, node)
+            }
+            /// Visit a [`StructuredDocumentation`], which has no children to walk.
+            fn visit_structured_documentation(
+                &mut self,
+                _node: &$($mut)? StructuredDocumentation,
+            ) {
+            }
+            /// Visit an [`UnparsedNode`], which has no children to walk.
+            fn visit_unparsed_node(&mut self, _node: &$($mut)? UnparsedNode) {}
+
+            /// Visit a statement, by default through the method for its kind.
+            fn visit_statement(&mut self, node: &$($mut)? Statement) {
+                walk_statement(self, node)
+            }
+            /// Visit a [`Block`] and, by default, its children.
+            fn visit_block(&mut self, node: &$($mut)? Block) {
+                walk_block(self, node)
+            }
+            /// Visit an [`UncheckedBlock`] and, by default, its children.
+            fn visit_unchecked_block(&mut self, node: &$($mut)? UncheckedBlock) {
+                walk_unchecked_block(self, node)
+            }
+            /// Visit an [`IfStatement`] and, by default, its children.
+            fn visit_if_statement(&mut self, node: &$($mut)? IfStatement) {
+                walk_if_statement(self, node)
+            }
+            /// Visit a [`ForStatement`] and, by default, its children.
+            fn visit_for_statement(&mut self, node: &$($mut)? ForStatement) {
+                walk_for_statement(self, node)
+            }
+            /// Visit a [`WhileStatement`] and, by default, its children.
+            fn visit_while_statement(&mut self, node: &$($mut)? WhileStatement) {
+                walk_while_statement(self, node)
+            }
+            /// Visit a [`DoWhileStatement`] and, by default, its children.
+            fn visit_do_while_statement(&mut self, node: &$($mut)? DoWhileStatement) {
+                walk_do_while_statement(self, node)
+            }
+            /// Visit a [`Continue`], which has no children to walk.
+            fn visit_continue(&mut self, _node: &$($mut)? Continue) {}
+            /// Visit a [`Break`], which has no children to walk.
+            fn visit_break(&mut self, _node: &$($mut)? Break) {}
+            /// Visit a [`Return`] and, by default, its children.
+            fn visit_return(&mut self, node: &$($mut)? Return) {
+                walk_return(self, node)
+            }
+            /// Visit an [`EmitStatement`] and, by default, its children.
+            fn visit_emit_statement(&mut self, node: &$($mut)? EmitStatement) {
+                walk_emit_statement(self, node)
+            }
+            /// Visit a [`RevertStatement`] and, by default, its children.
+            fn visit_revert_statement(&mut self, node: &$($mut)? RevertStatement) {
+                walk_revert_statement(self, node)
+            }
+            /// Visit a [`TryStatement`] and, by default, its children.
+            fn visit_try_statement(&mut self, node: &$($mut)? TryStatement) {
+                walk_try_statement(self, node)
+            }
+            /// Visit a [`TryCatchClause`] and, by default, its children.
+            fn visit_try_catch_clause(&mut self, node: &$($mut)? TryCatchClause) {
+                walk_try_catch_clause(self, node)
+            }
+            /// Visit an [`ExpressionStatement`] and, by default, its children.
+            fn visit_expression_statement(&mut self, node: &$($mut)? ExpressionStatement) {
+                walk_expression_statement(self, node)
+            }
+            /// Visit a [`VariableDeclarationStatement`] and, by default, its children.
+            fn visit_variable_declaration_statement(
+                &mut self,
+                node: &$($mut)? VariableDeclarationStatement,
+            ) {
+                walk_variable_declaration_statement(self, node)
+            }
+            /// Visit an [`InlineAssembly`] and, by default, its children.
+            fn visit_inline_assembly(&mut self, node: &$($mut)? InlineAssembly) {
+                walk_inline_assembly(self, node)
+            }
+            /// Visit a [`PlaceholderStatement`], which has no children to walk.
+            fn visit_placeholder_statement(&mut self, _node: &$($mut)? PlaceholderStatement) {}
+
+            /// Visit an expression, by default through the method for its kind.
+            fn visit_expression(&mut self, node: &$($mut)? Expression) {
+                walk_expression(self, node)
+            }
+            /// Visit an [`Assignment`] and, by default, its children.
+            fn visit_assignment(&mut self, node: &$($mut)? Assignment) {
+                walk_assignment(self, node)
+            }
+            /// Visit a [`BinaryOperation`] and, by default, its children.
+            fn visit_binary_operation(&mut self, node: &$($mut)? BinaryOperation) {
+                walk_binary_operation(self, node)
+            }
+            /// Visit a [`Conditional`] and, by default, its children.
+            fn visit_conditional(&mut self, node: &$($mut)? Conditional) {
+                walk_conditional(self, node)
+            }
+            /// Visit an [`ElementaryTypeNameExpression`] and, by default, its children.
+            fn visit_elementary_type_name_expression(
+                &mut self,
+                node: &$($mut)? ElementaryTypeNameExpression,
+            ) {
+                walk_elementary_type_name_expression(self, node)
+            }
+            /// Visit a [`FunctionCall`] and, by default, its children.
+            fn visit_function_call(&mut self, node: &$($mut)? FunctionCall) {
+                walk_function_call(self, node)
+            }
+            /// Visit a [`FunctionCallOptions`] and, by default, its children.
+            fn visit_function_call_options(&mut self, node: &$($mut)? FunctionCallOptions) {
+                walk_function_call_options(self, node)
+            }
+            /// Visit an [`Identifier`], which has no children to walk.
+            fn visit_identifier(&mut self, _node: &$($mut)? Identifier) {}
+            /// Visit an [`IndexAccess`] and, by default, its children.
+            fn visit_index_access(&mut self, node: &$($mut)? IndexAccess) {
+                walk_index_access(self, node)
+            }
+            /// Visit an [`IndexRangeAccess`] and, by default, its children.
+            fn visit_index_range_access(&mut self, node: &$($mut)? IndexRangeAccess) {
+                walk_index_range_access(self, node)
+            }
+            /// Visit a [`Literal`], which has no children to walk.
+            fn visit_literal(&mut self, _node: &$($mut)? Literal) {}
+            /// Visit a [`MemberAccess`] and, by default, its children.
+            fn visit_member_access(&mut self, node: &$($mut)? MemberAccess) {
+                walk_member_access(self, node)
+            }
+            /// Visit a [`NewExpression`] and, by default, its children.
+            fn visit_new_expression(&mut self, node: &$($mut)? NewExpression) {
+                walk_new_expression(self, node)
+            }
+            /// Visit a [`TupleExpression`] and, by default, its children.
+            fn visit_tuple_expression(&mut self, node: &$($mut)? TupleExpression) {
+                walk_tuple_expression(self, node)
+            }
+            /// Visit a [`UnaryOperation`] and, by default, its children.
+            fn visit_unary_operation(&mut self, node: &$($mut)? UnaryOperation) {
+                walk_unary_operation(self, node)
+            }
+            /// Visit an [`IdentifierPath`], which has no children to walk.
+            fn visit_identifier_path(&mut self, _node: &$($mut)? IdentifierPath) {}
+
+            /// Visit a type name, by default through the method for its kind.
+            fn visit_type_name(&mut self, node: &$($mut)? TypeName) {
+                walk_type_name(self, node)
+            }
+            /// Visit an [`ElementaryTypeName`], which has no children to walk.
+            fn visit_elementary_type_name(&mut self, _node: &$($mut)? ElementaryTypeName) {}
+            /// Visit a [`UserDefinedTypeName`] and, by default, its children.
+            fn visit_user_defined_type_name(&mut self, node: &$($mut)? UserDefinedTypeName) {
+                walk_user_defined_type_name(self, node)
+            }
+            /// Visit an [`ArrayTypeName`] and, by default, its children.
+            fn visit_array_type_name(&mut self, node: &$($mut)? ArrayTypeName) {
+                walk_array_type_name(self, node)
+            }
+            /// Visit a [`Mapping`] and, by default, its children.
+            fn visit_mapping(&mut self, node: &$($mut)? Mapping) {
+                walk_mapping(self, node)
+            }
+            /// Visit a [`FunctionTypeName`] and, by default, its children.
+            fn visit_function_type_name(&mut self, node: &$($mut)? FunctionTypeName) {
+                walk_function_type_name(self, node)
+            }
+
+            /// Visit a [`YulBlock`] and, by default, its children.
+            fn visit_yul_block(&mut self, node: &$($mut)? YulBlock) {
+                walk_yul_block(self, node)
+            }
+            /// Visit a Yul statement, by default through the method for its kind.
+            fn visit_yul_statement(&mut self, node: &$($mut)? YulStatement) {
+                walk_yul_statement(self, node)
+            }
+            /// Visit a [`YulAssignment`] and, by default, its children.
+            fn visit_yul_assignment(&mut self, node: &$($mut)? YulAssignment) {
+                walk_yul_assignment(self, node)
+            }
+            /// Visit a [`YulVariableDeclaration`] and, by default, its children.
+            fn visit_yul_variable_declaration(&mut self, node: &$($mut)? YulVariableDeclaration) {
+                walk_yul_variable_declaration(self, node)
+            }
+            /// Visit a [`YulTypedName`], which has no children to walk.
+            fn visit_yul_typed_name(&mut self, _node: &$($mut)? YulTypedName) {}
+            /// Visit a [`YulExpressionStatement`] and, by default, its children.
+            fn visit_yul_expression_statement(&mut self, node: &$($mut)? YulExpressionStatement) {
+                walk_yul_expression_statement(self, node)
+            }
+            /// Visit a [`YulIf`] and, by default, its children.
+            fn visit_yul_if(&mut self, node: &$($mut)? YulIf) {
+                walk_yul_if(self, node)
+            }
+            /// Visit a [`YulForLoop`] and, by default, its children.
+            fn visit_yul_for_loop(&mut self, node: &$($mut)? YulForLoop) {
+                walk_yul_for_loop(self, node)
+            }
+            /// Visit a [`YulSwitch`] and, by default, its children.
+            fn visit_yul_switch(&mut self, node: &$($mut)? YulSwitch) {
+                walk_yul_switch(self, node)
+            }
+            /// Visit a [`YulCase`] and, by default, its children.
+            fn visit_yul_case(&mut self, node: &$($mut)? YulCase) {
+                walk_yul_case(self, node)
+            }
+            /// Visit a [`YulFunctionDefinition`] and, by default, its children.
+            fn visit_yul_function_definition(&mut self, node: &$($mut)? YulFunctionDefinition) {
+                walk_yul_function_definition(self, node)
+            }
+            /// Visit a [`YulBreak`], which has no children to walk.
+            fn visit_yul_break(&mut self, _node: &$($mut)? YulBreak) {}
+            /// Visit a [`YulContinue`], which has no children to walk.
+            fn visit_yul_continue(&mut self, _node: &$($mut)? YulContinue) {}
+            /// Visit a [`YulLeave`], which has no children to walk.
+            fn visit_yul_leave(&mut self, _node: &$($mut)? YulLeave) {}
+            /// Visit a Yul expression, by default through the method for its kind.
+            fn visit_yul_expression(&mut self, node: &$($mut)? YulExpression) {
+                walk_yul_expression(self, node)
+            }
+            /// Visit a [`YulFunctionCall`] and, by default, its children.
+            fn visit_yul_function_call(&mut self, node: &$($mut)? YulFunctionCall) {
+                walk_yul_function_call(self, node)
+            }
+            /// Visit a [`YulIdentifier`], which has no children to walk.
+            fn visit_yul_identifier(&mut self, _node: &$($mut)? YulIdentifier) {}
+            /// Visit a [`YulLiteral`], which has no children to walk.
+            fn visit_yul_literal(&mut self, _node: &$($mut)? YulLiteral) {}
+        }
+
+        fn walk_documentation<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            documentation: &$($mut)? Option<Documentation>,
+        ) {
+            if let Some(Documentation::Structured(documentation)) = documentation {
+                visitor.visit_structured_documentation(documentation);
+            }
+        }
+
+        pub fn walk_source_unit<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? SourceUnit) {
+            for node in &$($mut)? node.nodes {
+                match node {
+                    SourceUnitNode::ContractDefinition(node) => visitor.visit_contract_definition(node),
+                    SourceUnitNode::EnumDefinition(node) => visitor.visit_enum_definition(node),
+                    SourceUnitNode::ErrorDefinition(node) => visitor.visit_error_definition(node),
+                    SourceUnitNode::EventDefinition(node) => visitor.visit_event_definition(node),
+                    SourceUnitNode::FunctionDefinition(node) => visitor.visit_function_definition(node),
+                    SourceUnitNode::ImportDirective(node) => visitor.visit_import_directive(node),
+                    SourceUnitNode::PragmaDirective(node) => visitor.visit_pragma_directive(node),
+                    SourceUnitNode::StructDefinition(node) => visitor.visit_struct_definition(node),
+                    SourceUnitNode::UserDefinedValueTypeDefinition(node) => {
+                        visitor.visit_user_defined_value_type_definition(node)
+                    }
+                    SourceUnitNode::UsingForDirective(node) => visitor.visit_using_for_directive(node),
+                    SourceUnitNode::VariableDeclaration(node) => visitor.visit_variable_declaration(node),
+                    SourceUnitNode::UnparsedNode(node) => visitor.visit_unparsed_node(node),
+                }
+            }
+        }
+
+        pub fn walk_import_directive<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ImportDirective,
+        ) {
+            for alias in &$($mut)? node.symbol_aliases {
+                visitor.visit_identifier(&$($mut)? alias.foreign);
+            }
+        }
+
+        pub fn walk_contract_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ContractDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            for base in &$($mut)? node.base_contracts {
+                visitor.visit_inheritance_specifier(base);
+            }
+            for node in &$($mut)? node.nodes {
+                match node {
+                    ContractDefinitionNode::EnumDefinition(node) => visitor.visit_enum_definition(node),
+                    ContractDefinitionNode::ErrorDefinition(node) => visitor.visit_error_definition(node),
+                    ContractDefinitionNode::EventDefinition(node) => visitor.visit_event_definition(node),
+                    ContractDefinitionNode::FunctionDefinition(node) => {
+                        visitor.visit_function_definition(node)
+                    }
+                    ContractDefinitionNode::ModifierDefinition(node) => {
+                        visitor.visit_modifier_definition(node)
+                    }
+                    ContractDefinitionNode::StructDefinition(node) => visitor.visit_struct_definition(node),
+                    ContractDefinitionNode::UserDefinedValueTypeDefinition(node) => {
+                        visitor.visit_user_defined_value_type_definition(node)
+                    }
+                    ContractDefinitionNode::UsingForDirective(node) => {
+                        visitor.visit_using_for_directive(node)
+                    }
+                    ContractDefinitionNode::VariableDeclaration(node) => {
+                        visitor.visit_variable_declaration(node)
+                    }
+                    ContractDefinitionNode::UnparsedNode(node) => visitor.visit_unparsed_node(node),
+                }
+            }
+        }
+
+        pub fn walk_inheritance_specifier<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? InheritanceSpecifier,
+        ) {
+            visitor.visit_identifier_path(&$($mut)? node.base_name);
+            for argument in (&$($mut)? node.arguments).into_iter().flatten() {
+                visitor.visit_expression(argument);
+            }
+        }
+
+        pub fn walk_variable_declaration<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? VariableDeclaration,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            visitor.visit_type_name(&$($mut)? node.type_name);
+            if let Some(overrides) = &$($mut)? node.overrides {
+                visitor.visit_override_specifier(overrides);
+            }
+            if let Some(value) = &$($mut)? node.value {
+                visitor.visit_expression(value);
+            }
+        }
+
+        pub fn walk_override_specifier<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? OverrideSpecifier,
+        ) {
+            for path in &$($mut)? node.overrides {
+                visitor.visit_identifier_path(path);
+            }
+        }
+
+        pub fn walk_function_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? FunctionDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            if let Some(overrides) = &$($mut)? node.overrides {
+                visitor.visit_override_specifier(overrides);
+            }
+            visitor.visit_parameter_list(&$($mut)? node.parameters);
+            visitor.visit_parameter_list(&$($mut)? node.return_parameters);
+            for modifier in &$($mut)? node.modifiers {
+                visitor.visit_modifier_invocation(modifier);
+            }
+            if let Some(body) = &$($mut)? node.body {
+                visitor.visit_block(body);
+            }
+        }
+
+        pub fn walk_modifier_invocation<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ModifierInvocation,
+        ) {
+            visitor.visit_identifier_path(&$($mut)? node.modifier_name);
+            for argument in (&$($mut)? node.arguments).into_iter().flatten() {
+                visitor.visit_expression(argument);
+            }
+        }
+
+        pub fn walk_parameter_list<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ParameterList,
+        ) {
+            for parameter in &$($mut)? node.parameters {
+                visitor.visit_variable_declaration(parameter);
+            }
+        }
+
+        pub fn walk_modifier_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ModifierDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            if let Some(overrides) = &$($mut)? node.overrides {
+                visitor.visit_override_specifier(overrides);
+            }
+            visitor.visit_parameter_list(&$($mut)? node.parameters);
+            visitor.visit_block(&$($mut)? node.body);
+        }
+
+        pub fn walk_event_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? EventDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            visitor.visit_parameter_list(&$($mut)? node.parameters);
+        }
+
+        pub fn walk_error_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ErrorDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            visitor.visit_parameter_list(&$($mut)? node.parameters);
+        }
+
+        pub fn walk_struct_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? StructDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            for member in &$($mut)? node.members {
+                visitor.visit_variable_declaration(member);
+            }
+        }
+
+        pub fn walk_enum_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? EnumDefinition,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            for member in &$($mut)? node.members {
+                visitor.visit_enum_value(member);
+            }
+        }
+
+        pub fn walk_enum_value<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? EnumValue) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+        }
+
+        pub fn walk_user_defined_value_type_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? UserDefinedValueTypeDefinition,
+        ) {
+            visitor.visit_type_name(&$($mut)? node.underlying_type);
+        }
+
+        pub fn walk_using_for_directive<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? UsingForDirective,
+        ) {
+            if let Some(library_name) = &$($mut)? node.library_name {
+                visitor.visit_identifier_path(library_name);
+            }
+            for function in (&$($mut)? node.function_list).into_iter().flatten() {
+                if let Some(path) = &$($mut)? function.function {
+                    visitor.visit_identifier_path(path);
+                }
+                if let Some(path) = &$($mut)? function.definition {
+                    visitor.visit_identifier_path(path);
+                }
+            }
+            if let Some(type_name) = &$($mut)? node.type_name {
+                visitor.visit_type_name(type_name);
+            }
+        }
+
+        pub fn walk_statement<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Statement) {
+            match node {
+                Statement::Block(node) => visitor.visit_block(node),
+                Statement::Break(node) => visitor.visit_break(node),
+                Statement::Continue(node) => visitor.visit_continue(node),
+                Statement::DoWhileStatement(node) => visitor.visit_do_while_statement(node),
+                Statement::EmitStatement(node) => visitor.visit_emit_statement(node),
+                Statement::ExpressionStatement(node) => visitor.visit_expression_statement(node),
+                Statement::ForStatement(node) => visitor.visit_for_statement(node),
+                Statement::IfStatement(node) => visitor.visit_if_statement(node),
+                Statement::InlineAssembly(node) => visitor.visit_inline_assembly(node),
+                Statement::PlaceholderStatement(node) => visitor.visit_placeholder_statement(node),
+                Statement::Return(node) => visitor.visit_return(node),
+                Statement::RevertStatement(node) => visitor.visit_revert_statement(node),
+                Statement::TryStatement(node) => visitor.visit_try_statement(node),
+                Statement::UncheckedBlock(node) => visitor.visit_unchecked_block(node),
+                Statement::VariableDeclarationStatement(node) => {
+                    visitor.visit_variable_declaration_statement(node)
+                }
+                Statement::WhileStatement(node) => visitor.visit_while_statement(node),
+                Statement::UnparsedNode(node) => visitor.visit_unparsed_node(node),
+            }
+        }
+
+        pub fn walk_block<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Block) {
+            for statement in &$($mut)? node.statements {
+                visitor.visit_statement(statement);
+            }
+        }
+
+        pub fn walk_unchecked_block<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? UncheckedBlock,
+        ) {
+            for statement in &$($mut)? node.statements {
+                visitor.visit_statement(statement);
+            }
+        }
+
+        pub fn walk_if_statement<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? IfStatement) {
+            visitor.visit_expression(&$($mut)? node.condition);
+            visitor.visit_statement(&$($mut)? node.true_body);
+            if let Some(false_body) = &$($mut)? node.false_body {
+                visitor.visit_statement(false_body);
+            }
+        }
+
+        pub fn walk_for_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ForStatement,
+        ) {
+            if let Some(initialization) = &$($mut)? node.initialization_expression {
+                visitor.visit_expression(initialization);
+            }
+            visitor.visit_expression(&$($mut)? node.condition);
+            if let Some(loop_expression) = &$($mut)? node.loop_expression {
+                visitor.visit_expression(loop_expression);
+            }
+            visitor.visit_statement(&$($mut)? node.body);
+        }
+
+        pub fn walk_while_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? WhileStatement,
+        ) {
+            visitor.visit_expression(&$($mut)? node.condition);
+            visitor.visit_statement(&$($mut)? node.body);
+        }
+
+        pub fn walk_do_while_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? DoWhileStatement,
+        ) {
+            visitor.visit_statement(&$($mut)? node.body);
+            visitor.visit_expression(&$($mut)? node.condition);
+        }
+
+        pub fn walk_return<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Return) {
+            if let Some(expression) = &$($mut)? node.expression {
+                visitor.visit_expression(expression);
+            }
+        }
+
+        pub fn walk_emit_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? EmitStatement,
+        ) {
+            visitor.visit_function_call(&$($mut)? node.event_call);
+        }
+
+        pub fn walk_revert_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? RevertStatement,
+        ) {
+            visitor.visit_function_call(&$($mut)? node.error_call);
+        }
+
+        pub fn walk_try_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? TryStatement,
+        ) {
+            visitor.visit_expression(&$($mut)? node.external_call);
+            for clause in &$($mut)? node.clauses {
+                visitor.visit_try_catch_clause(clause);
+            }
+        }
+
+        pub fn walk_try_catch_clause<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? TryCatchClause,
+        ) {
+            if let Some(parameters) = &$($mut)? node.parameters {
+                visitor.visit_parameter_list(parameters);
+            }
+            visitor.visit_block(&$($mut)? node.block);
+        }
+
+        pub fn walk_expression_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ExpressionStatement,
+        ) {
+            visitor.visit_expression(&$($mut)? node.expression);
+        }
+
+        pub fn walk_variable_declaration_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? VariableDeclarationStatement,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            for declaration in (&$($mut)? node.declarations).into_iter().flatten() {
+                visitor.visit_variable_declaration(declaration);
+            }
+            if let Some(initial_value) = &$($mut)? node.initial_value {
+                visitor.visit_expression(initial_value);
+            }
+        }
+
+        pub fn walk_inline_assembly<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? InlineAssembly,
+        ) {
+            walk_documentation(visitor, &$($mut)? node.documentation);
+            if let Some(ast) = &$($mut)? node.ast {
+                visitor.visit_yul_block(ast);
+            }
+        }
+
+        pub fn walk_expression<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Expression) {
+            match node {
+                Expression::Assignment(node) => visitor.visit_assignment(node),
+                Expression::BinaryOperation(node) => visitor.visit_binary_operation(node),
+                Expression::Conditional(node) => visitor.visit_conditional(node),
+                Expression::ElementaryTypeNameExpression(node) => {
+                    visitor.visit_elementary_type_name_expression(node)
+                }
+                Expression::FunctionCall(node) => visitor.visit_function_call(node),
+                Expression::Identifier(node) => visitor.visit_identifier(node),
+                Expression::IndexAccess(node) => visitor.visit_index_access(node),
+                Expression::IndexRangeAccess(node) => visitor.visit_index_range_access(node),
+                Expression::Literal(node) => visitor.visit_literal(node),
+                Expression::MemberAccess(node) => visitor.visit_member_access(node),
+                Expression::NewExpression(node) => visitor.visit_new_expression(node),
+                Expression::TupleExpression(node) => visitor.visit_tuple_expression(node),
+                Expression::UnaryOperation(node) => visitor.visit_unary_operation(node),
+                Expression::VariableDeclarationStatement(node) => {
+                    visitor.visit_variable_declaration_statement(node)
+                }
+                Expression::ExpressionStatement(node) => visitor.visit_expression_statement(node),
+                Expression::UnparsedNode(node) => visitor.visit_unparsed_node(node),
+            }
+        }
+
+        pub fn walk_assignment<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Assignment) {
+            visitor.visit_expression(&$($mut)? node.left_hand_side);
+            visitor.visit_expression(&$($mut)? node.right_hand_side);
+        }
+
+        pub fn walk_binary_operation<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? BinaryOperation,
+        ) {
+            visitor.visit_expression(&$($mut)? node.left_expression);
+            visitor.visit_expression(&$($mut)? node.right_expression);
+        }
+
+        pub fn walk_conditional<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Conditional) {
+            visitor.visit_expression(&$($mut)? node.condition);
+            visitor.visit_expression(&$($mut)? node.true_expression);
+            visitor.visit_expression(&$($mut)? node.false_expression);
+        }
+
+        pub fn walk_elementary_type_name_expression<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ElementaryTypeNameExpression,
+        ) {
+            visitor.visit_elementary_type_name(&$($mut)? node.type_name);
+        }
+
+        pub fn walk_function_call<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? FunctionCall,
+        ) {
+            match &$($mut)? *node.expression {
+                FunctionCallExpression::ElementaryTypeNameExpression(node) => {
+                    visitor.visit_elementary_type_name_expression(node)
+                }
+                FunctionCallExpression::FunctionCall(node) => visitor.visit_function_call(node),
+                FunctionCallExpression::FunctionCallOptions(node) => {
+                    visitor.visit_function_call_options(node)
+                }
+                FunctionCallExpression::Identifier(node) => visitor.visit_identifier(node),
+                FunctionCallExpression::MemberAccess(node) => visitor.visit_member_access(node),
+                FunctionCallExpression::NewExpression(node) => visitor.visit_new_expression(node),
+            }
+            for argument in &$($mut)? node.arguments {
+                visitor.visit_expression(argument);
+            }
+        }
+
+        pub fn walk_function_call_options<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? FunctionCallOptions,
+        ) {
+            visitor.visit_expression(&$($mut)? node.expression);
+            for option in &$($mut)? node.options {
+                visitor.visit_expression(option);
+            }
+        }
+
+        pub fn walk_index_access<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? IndexAccess) {
+            visitor.visit_expression(&$($mut)? node.base_expression);
+            if let Some(index) = &$($mut)? node.index_expression {
+                visitor.visit_expression(index);
+            }
+        }
+
+        pub fn walk_index_range_access<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? IndexRangeAccess,
+        ) {
+            visitor.visit_expression(&$($mut)? node.base_expression);
+            if let Some(start) = &$($mut)? node.start_expression {
+                visitor.visit_expression(start);
+            }
+            if let Some(end) = &$($mut)? node.end_expression {
+                visitor.visit_expression(end);
+            }
+        }
+
+        pub fn walk_member_access<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? MemberAccess,
+        ) {
+            visitor.visit_expression(&$($mut)? node.expression);
+        }
+
+        pub fn walk_new_expression<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? NewExpression,
+        ) {
+            visitor.visit_type_name(&$($mut)? node.type_name);
+        }
+
+        pub fn walk_tuple_expression<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? TupleExpression,
+        ) {
+            for component in (&$($mut)? node.components).into_iter().flatten() {
+                visitor.visit_expression(component);
+            }
+        }
+
+        pub fn walk_unary_operation<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? UnaryOperation,
+        ) {
+            visitor.visit_expression(&$($mut)? node.sub_expression);
+        }
+
+        pub fn walk_type_name<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? TypeName) {
+            match node {
+                TypeName::ArrayTypeName(node) => visitor.visit_array_type_name(node),
+                TypeName::ElementaryTypeName(node) => visitor.visit_elementary_type_name(node),
+                TypeName::FunctionTypeName(node) => visitor.visit_function_type_name(node),
+                TypeName::Mapping(node) => visitor.visit_mapping(node),
+                TypeName::UserDefinedTypeName(node) => visitor.visit_user_defined_type_name(node),
+                TypeName::UnparsedNode(node) => visitor.visit_unparsed_node(node),
+            }
+        }
+
+        pub fn walk_user_defined_type_name<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? UserDefinedTypeName,
+        ) {
+            if let Some(path) = &$($mut)? node.path_node {
+                visitor.visit_identifier_path(path);
+            }
+        }
+
+        pub fn walk_array_type_name<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? ArrayTypeName,
+        ) {
+            visitor.visit_type_name(&$($mut)? node.base_type);
+            if let Some(length) = &$($mut)? node.length {
+                visitor.visit_expression(length);
+            }
+        }
+
+        pub fn walk_mapping<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? Mapping) {
+            visitor.visit_type_name(&$($mut)? node.key_type);
+            visitor.visit_type_name(&$($mut)? node.value_type);
+        }
+
+        pub fn walk_function_type_name<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? FunctionTypeName,
+        ) {
+            visitor.visit_parameter_list(&$($mut)? node.parameter_types);
+            visitor.visit_parameter_list(&$($mut)? node.return_parameter_types);
+        }
+
+        pub fn walk_yul_block<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? YulBlock) {
+            for statement in &$($mut)? node.statements {
+                visitor.visit_yul_statement(statement);
+            }
+        }
+
+        pub fn walk_yul_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulStatement,
+        ) {
+            match node {
+                YulStatement::YulBlock(node) => visitor.visit_yul_block(node),
+                YulStatement::YulAssignment(node) => visitor.visit_yul_assignment(node),
+                YulStatement::YulFunctionCall(node) => visitor.visit_yul_function_call(node),
+                YulStatement::YulIf(node) => visitor.visit_yul_if(node),
+                YulStatement::YulForLoop(node) => visitor.visit_yul_for_loop(node),
+                YulStatement::YulSwitch(node) => visitor.visit_yul_switch(node),
+                YulStatement::YulBreak(node) => visitor.visit_yul_break(node),
+                YulStatement::YulVariableDeclaration(node) => {
+                    visitor.visit_yul_variable_declaration(node)
+                }
+                YulStatement::YulFunctionDefinition(node) => {
+                    visitor.visit_yul_function_definition(node)
+                }
+                YulStatement::YulExpressionStatement(node) => {
+                    visitor.visit_yul_expression_statement(node)
+                }
+                YulStatement::YulLeave(node) => visitor.visit_yul_leave(node),
+                YulStatement::YulContinue(node) => visitor.visit_yul_continue(node),
+            }
+        }
+
+        pub fn walk_yul_assignment<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulAssignment,
+        ) {
+            for name in &$($mut)? node.variable_names {
+                visitor.visit_yul_identifier(name);
+            }
+            visitor.visit_yul_expression(&$($mut)? node.value);
+        }
+
+        pub fn walk_yul_variable_declaration<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulVariableDeclaration,
+        ) {
+            for variable in &$($mut)? node.variables {
+                visitor.visit_yul_typed_name(variable);
+            }
+            if let Some(value) = &$($mut)? node.value {
+                visitor.visit_yul_expression(value);
+            }
+        }
+
+        pub fn walk_yul_expression_statement<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulExpressionStatement,
+        ) {
+            visitor.visit_yul_expression(&$($mut)? node.expression);
+        }
+
+        pub fn walk_yul_if<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? YulIf) {
+            visitor.visit_yul_expression(&$($mut)? node.condition);
+            visitor.visit_yul_block(&$($mut)? node.body);
+        }
+
+        pub fn walk_yul_for_loop<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? YulForLoop) {
+            visitor.visit_yul_block(&$($mut)? node.pre);
+            visitor.visit_yul_expression(&$($mut)? node.condition);
+            visitor.visit_yul_block(&$($mut)? node.post);
+            visitor.visit_yul_block(&$($mut)? node.body);
+        }
+
+        pub fn walk_yul_switch<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? YulSwitch) {
+            visitor.visit_yul_expression(&$($mut)? node.expression);
+            for case in &$($mut)? node.cases {
+                visitor.visit_yul_case(case);
+            }
+        }
+
+        pub fn walk_yul_case<V: $visitor + ?Sized>(visitor: &mut V, node: &$($mut)? YulCase) {
+            if let YulCaseValue::Literal(value) = &$($mut)? node.value {
+                visitor.visit_yul_literal(value);
+            }
+            visitor.visit_yul_block(&$($mut)? node.body);
+        }
+
+        pub fn walk_yul_function_definition<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulFunctionDefinition,
+        ) {
+            for parameter in &$($mut)? node.parameters {
+                visitor.visit_yul_typed_name(parameter);
+            }
+            for variable in &$($mut)? node.return_variables {
+                visitor.visit_yul_typed_name(variable);
+            }
+            visitor.visit_yul_block(&$($mut)? node.body);
+        }
+
+        pub fn walk_yul_expression<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulExpression,
+        ) {
+            match node {
+                YulExpression::YulIdentifier(node) => visitor.visit_yul_identifier(node),
+                YulExpression::YulLiteral(node) => visitor.visit_yul_literal(node),
+                YulExpression::YulFunctionCall(node) => visitor.visit_yul_function_call(node),
+            }
+        }
+
+        pub fn walk_yul_function_call<V: $visitor + ?Sized>(
+            visitor: &mut V,
+            node: &$($mut)? YulFunctionCall,
+        ) {
+            visitor.visit_yul_expression(&$($mut)? node.function_name);
+            for argument in &$($mut)? node.arguments {
+                visitor.visit_yul_expression(argument);
+            }
+        }
+    };
+}
+
+pub(super) use visitor;
+
+visitor!(Visitor);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    use rayon::prelude::*;
+
+    use super::*;
+    use crate::ast::visit_mut::{self, VisitorMut};
+
+    const NODE_TYPES: &[&str] = &[
+        "Identifier",
+        "IdentifierPath",
+        "Literal",
+        "ElementaryTypeName",
+        "StructuredDocumentation",
+        "VariableDeclaration",
+        "FunctionCall",
+        "Block",
+        "YulIdentifier",
+        "YulLiteral",
+    ];
+
+    /// Counts a sample of node types, from leaves to statements.
+    #[derive(Default)]
+    struct Counter(BTreeMap<&'static str, usize>);
+
+    impl Counter {
+        fn count(&mut self, node_type: &'static str) {
+            *self.0.entry(node_type).or_default() += 1;
+        }
+    }
+
+    impl Visitor for Counter {
+        fn visit_identifier(&mut self, _node: &Identifier) {
+            self.count("Identifier");
+        }
+        fn visit_identifier_path(&mut self, _node: &IdentifierPath) {
+            self.count("IdentifierPath");
+        }
+        fn visit_literal(&mut self, _node: &Literal) {
+            self.count("Literal");
+        }
+        fn visit_elementary_type_name(&mut self, _node: &ElementaryTypeName) {
+            self.count("ElementaryTypeName");
+        }
+        fn visit_structured_documentation(&mut self, _node: &StructuredDocumentation) {
+            self.count("StructuredDocumentation");
+        }
+        fn visit_variable_declaration(&mut self, node: &VariableDeclaration) {
+            self.count("VariableDeclaration");
+            walk_variable_declaration(self, node);
+        }
+        fn visit_function_call(&mut self, node: &FunctionCall) {
+            self.count("FunctionCall");
+            walk_function_call(self, node);
+        }
+        fn visit_block(&mut self, node: &Block) {
+            self.count("Block");
+            walk_block(self, node);
+        }
+        fn visit_yul_identifier(&mut self, _node: &YulIdentifier) {
+            self.count("YulIdentifier");
+        }
+        fn visit_yul_literal(&mut self, _node: &YulLiteral) {
+            self.count("YulLiteral");
+        }
+    }
+
+    #[test]
+    fn fixtures_visit_every_node() {
+        crate::ast::all_fixtures()
+            .into_par_iter()
+            .for_each(|(path, source_unit)| {
+                let mut counter = Counter::default();
+                counter.visit_source_unit(&source_unit);
+
+                let stats = source_unit.stats();
+                for node_type in NODE_TYPES {
+                    assert_eq!(
+                        counter.0.get(node_type),
+                        stats.node_counts.get(*node_type),
+                        "{} in {:?}",
+                        node_type,
+                        path
+                    );
+                }
+            });
+    }
+
+    #[test]
+    fn rewrite_identifiers() {
+        struct Rename;
+
+        impl VisitorMut for Rename {
+            fn visit_identifier(&mut self, node: &mut Identifier) {
+                node.name = node.name.to_uppercase();
+            }
+            fn visit_yul_identifier(&mut self, node: &mut YulIdentifier) {
+                node.name = node.name.to_uppercase();
+            }
+            fn visit_yul_block(&mut self, node: &mut YulBlock) {
+                visit_mut::walk_yul_block(self, node);
+            }
+        }
+
+        struct Names(Vec<String>);
+
+        impl Visitor for Names {
+            fn visit_identifier(&mut self, node: &Identifier) {
+                self.0.push(node.name.clone());
+            }
+            fn visit_yul_identifier(&mut self, node: &YulIdentifier) {
+                self.0.push(node.name.clone());
+            }
+        }
+
+        let content =
+            fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ArrayHelpers.json")
+                .unwrap();
+        let mut source_unit: SourceUnit = serde_json::from_str(&content).unwrap();
+        Rename.visit_source_unit(&mut source_unit);
+
+        let mut names = Names(Vec::new());
+        names.visit_source_unit(&source_unit);
+        assert!(!names.0.is_empty());
+        assert!(names.0.iter().all(|name| *name == name.to_uppercase()));
+    }
+}
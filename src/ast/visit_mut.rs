@@ -0,0 +1,10 @@
+//! Traversal of the AST with mutable visitors.
+//!
+//! [`VisitorMut`] and the `walk_*` functions here mirror those of
+//! [`visit`](super::visit), but take mutable references so that a visitor can
+//! rewrite the nodes it visits.
+
+use super::visit::visitor;
+use super::*;
+
+visitor!(VisitorMut, mut);
@@ -0,0 +1,252 @@
+//! A cache of compiler outputs on disk.
+//!
+//! Compiling the same sources with the same settings and compiler release
+//! gives the same output, so repeated builds of unchanged inputs can read it
+//! back instead of running the compiler again. A [`Fingerprint`] identifies
+//! a compilation by the keccak256 hashes of its sources, its settings, and
+//! the compiler release, and a [`CompilationCache`] stores outputs under
+//! their fingerprint in a directory. [`Solc::compile_cached`] uses both.
+//!
+//! [`Solc::compile_cached`]: crate::compiler::Solc::compile_cached
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::abi::keccak256;
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+use crate::standard_json_output::StandardJsonOutput;
+
+/// The identity of a compilation: a hash of its sources, settings and
+/// compiler release.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint([u8; 32]);
+
+impl Fingerprint {
+    /// The fingerprint of compiling an input with a compiler release, such
+    /// as `0.8.30` or the full version `solc --version` prints.
+    ///
+    /// Sources are identified by the keccak256 hash of their content, or by
+    /// their URLs and declared hash when they are given as URLs. Returns
+    /// `None` if a source is given as URLs without a hash, since the content
+    /// behind the URLs can change while the input stays the same.
+    pub fn new(input: &StandardJsonInput, compiler: &str) -> Option<Self> {
+        let sources = input
+            .sources
+            .iter()
+            .map(|(path, source)| {
+                let hash = match &source.content {
+                    SourceContent::Content { content } => {
+                        hex::encode(keccak256(content.as_bytes()))
+                    }
+                    SourceContent::Urls { urls } => {
+                        format!("{}:{}", source.keccak256.as_deref()?, urls.join(","))
+                    }
+                };
+                Some((path.as_path(), hash))
+            })
+            .collect::<Option<BTreeMap<&Path, String>>>()?;
+        let key = serde_json::json!({
+            "compiler": compiler,
+            "language": input.language,
+            "sources": sources,
+            "settings": input.settings,
+        });
+        Some(Self(keccak256(key.to_string().as_bytes())))
+    }
+
+    /// The hash.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// How a [`CompilationCache`] writes outputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheFormat {
+    /// JSON without whitespace, the smallest on disk.
+    #[default]
+    Json,
+    /// Indented JSON, to inspect cached outputs.
+    PrettyJson,
+}
+
+/// Compiler outputs stored in a directory, one file per [`Fingerprint`].
+#[derive(Clone, Debug)]
+pub struct CompilationCache {
+    dir: PathBuf,
+    format: CacheFormat,
+}
+
+impl CompilationCache {
+    /// A cache in a directory, which is created when the first output is
+    /// stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            format: CacheFormat::default(),
+        }
+    }
+
+    /// Write outputs in a format. Outputs are read back in either format.
+    pub fn format(mut self, format: CacheFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path of the file that holds the output of a compilation.
+    pub fn path(&self, fingerprint: &Fingerprint) -> PathBuf {
+        self.dir.join(format!("{}.json", fingerprint))
+    }
+
+    /// The stored output of a compilation, or `None` if it was not stored.
+    ///
+    /// A file that cannot be parsed, such as one written by an older
+    /// version of this crate, counts as not stored.
+    pub fn get(&self, fingerprint: &Fingerprint) -> Result<Option<StandardJsonOutput>, CacheError> {
+        let content = match fs::read(self.path(fingerprint)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(serde_json::from_slice(&content).ok())
+    }
+
+    /// Store the output of a compilation.
+    ///
+    /// The file is written in full before it replaces an earlier one, so
+    /// concurrent builds never read a partial output.
+    pub fn put(
+        &self,
+        fingerprint: &Fingerprint,
+        output: &StandardJsonOutput,
+    ) -> Result<(), CacheError> {
+        let content = match self.format {
+            CacheFormat::Json => serde_json::to_vec(output)?,
+            CacheFormat::PrettyJson => serde_json::to_vec_pretty(output)?,
+        };
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(fingerprint);
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, content)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Remove the stored output of a compilation, if there is one.
+    pub fn remove(&self, fingerprint: &Fingerprint) -> Result<(), CacheError> {
+        match fs::remove_file(self.path(fingerprint)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove every stored output.
+    pub fn clear(&self) -> Result<(), CacheError> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An error reading or writing a [`CompilationCache`].
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    /// The cache directory or a file in it could not be accessed.
+    #[error("failed to access compilation cache: {0}")]
+    Io(#[from] io::Error),
+
+    /// An output could not be serialized.
+    #[error("failed to serialize compiler output: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::standard_json_input::EvmVersion;
+
+    #[test]
+    fn fingerprints_inputs() {
+        let input = StandardJsonInput::new().add_source("A.sol", "contract A {}");
+        let fingerprint = Fingerprint::new(&input, "0.8.30").unwrap();
+        assert_eq!(
+            fingerprint,
+            Fingerprint::new(&input.clone(), "0.8.30").unwrap()
+        );
+        assert_eq!(fingerprint.to_string().len(), 64);
+
+        let changed = [
+            Fingerprint::new(&input, "0.8.29").unwrap(),
+            Fingerprint::new(
+                &StandardJsonInput::new().add_source("A.sol", "contract A { }"),
+                "0.8.30",
+            )
+            .unwrap(),
+            Fingerprint::new(
+                &StandardJsonInput::new().add_source("B.sol", "contract A {}"),
+                "0.8.30",
+            )
+            .unwrap(),
+            Fingerprint::new(&input.clone().optimizer(200), "0.8.30").unwrap(),
+            Fingerprint::new(&input.clone().evm_version(EvmVersion::Paris), "0.8.30").unwrap(),
+        ];
+        for other in changed {
+            assert_ne!(fingerprint, other);
+        }
+
+        let urls = vec!["contracts/A.sol".to_string()];
+        let hashed = StandardJsonInput::new().add_source_urls(
+            "A.sol",
+            urls.clone(),
+            Some("0x12".to_string()),
+        );
+        assert!(Fingerprint::new(&hashed, "0.8.30").is_some());
+        let unhashed = StandardJsonInput::new().add_source_urls("A.sol", urls, None);
+        assert_eq!(Fingerprint::new(&unhashed, "0.8.30"), None);
+    }
+
+    #[test]
+    fn stores_outputs() {
+        let dir = env::temp_dir().join(format!("solc-rs-cache-{}", std::process::id()));
+        let cache = CompilationCache::new(&dir).format(CacheFormat::PrettyJson);
+        cache.clear().unwrap();
+
+        let fingerprint = Fingerprint::new(&StandardJsonInput::new(), "0.8.30").unwrap();
+        assert_eq!(cache.get(&fingerprint).unwrap(), None);
+
+        let output: StandardJsonOutput = serde_json::from_str(
+            r#"{"sources": {"A.sol": {"id": 0}}, "contracts": {"A.sol": {"A": {"abi": []}}}}"#,
+        )
+        .unwrap();
+        cache.put(&fingerprint, &output).unwrap();
+        assert_eq!(cache.get(&fingerprint).unwrap(), Some(output.clone()));
+        let written = fs::read_to_string(cache.path(&fingerprint)).unwrap();
+        assert!(written.contains("\n  "));
+
+        fs::write(cache.path(&fingerprint), "{").unwrap();
+        assert_eq!(cache.get(&fingerprint).unwrap(), None);
+
+        cache.put(&fingerprint, &output).unwrap();
+        cache.remove(&fingerprint).unwrap();
+        assert_eq!(cache.get(&fingerprint).unwrap(), None);
+        cache.clear().unwrap();
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,1287 @@
+//! Compiler backends and compilation helpers.
+//!
+//! This module defines the [`Compiler`] trait implemented by every way of
+//! running solc, the [`Solc`] backend that runs a local `solc` executable, and
+//! helpers built on top of them that work with any backend.
+//!
+//! With the `tokio` feature, [`Solc`] can also compile without blocking an
+//! async runtime. With the `wasm` feature, builds for `wasm32` targets also get `SolcWasm`,
+//! a backend that runs the soljson build of the compiler through solc-js.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use semver::Version;
+
+use crate::abi::Abi;
+use crate::ast::{LenientParse, NodeError, SourceUnit, parse_lenient};
+use crate::cache::{CacheError, CompilationCache, Fingerprint};
+use crate::standard_json_input::{
+    Optimizer, OutputSelector, StandardJsonInput, StopAfter, UnsupportedEvmVersion,
+};
+use crate::standard_json_output::{self, Bytecode, StandardJsonOutput};
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub use wasm::{SolcWasm, SolcWasmError};
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm;
+
+/// A backend that compiles Standard JSON input into Standard JSON output.
+///
+/// The error type covers failures to run the compiler at all. Compilation
+/// errors in the sources are reported inside the returned output, not as an
+/// `Err`.
+pub trait Compiler {
+    type Error;
+
+    /// Compile the input and return the parsed compiler output.
+    fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, Self::Error>;
+
+    /// A name for the backend in reports such as [`CompileTimings`], which
+    /// is the name of its type unless the backend says otherwise.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+}
+
+impl<C: Compiler + ?Sized> Compiler for &C {
+    type Error = C::Error;
+
+    fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, Self::Error> {
+        (**self).compile(input)
+    }
+
+    fn name(&self) -> String {
+        (**self).name()
+    }
+}
+
+/// A backend that runs a local `solc` executable.
+///
+/// The input is written to the process on stdin and the output is read from
+/// stdout, using the compiler's `--standard-json` mode.
+#[derive(Clone, Debug)]
+pub struct Solc {
+    path: PathBuf,
+    timeout: Option<Duration>,
+    version: Option<Version>,
+}
+
+impl Solc {
+    /// Create a backend that runs the executable at `path`.
+    ///
+    /// A bare name such as `solc` is looked up in `PATH`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            timeout: None,
+            version: None,
+        }
+    }
+
+    /// Locate a `solc` executable.
+    ///
+    /// The `SOLC` environment variable wins when it is set. Otherwise the
+    /// directories in `PATH` are searched in order. Returns `None` if no
+    /// executable is found.
+    pub fn find() -> Option<Self> {
+        if let Some(path) = env::var_os("SOLC") {
+            return Some(Self::new(path));
+        }
+        let name = format!("solc{}", env::consts::EXE_SUFFIX);
+        env::split_paths(&env::var_os("PATH")?)
+            .map(|dir| dir.join(&name))
+            .find(|path| path.is_file())
+            .map(Self::new)
+    }
+
+    /// Kill the compiler if it runs longer than `timeout`.
+    ///
+    /// There is no timeout by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Declare the release of the executable, as `VersionManager` does for
+    /// the compilers it installs.
+    ///
+    /// Inputs are then checked against the release before the compiler
+    /// runs, so an EVM version it does not know is reported as
+    /// [`SolcError::UnsupportedEvmVersion`] instead of a compiler error.
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// The release of the executable, if it was declared with
+    /// [`with_version`](Self::with_version).
+    pub fn known_version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// The path of the executable this backend runs.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The version the executable reports, such as `0.8.28+commit.7893614a`.
+    pub fn version(&self) -> Result<String, SolcError> {
+        let output = Command::new(&self.path).arg("--version").output()?;
+        if !output.status.success() {
+            return Err(SolcError::Failed {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("Version:"))
+            .map(|version| version.trim().to_string())
+            .ok_or_else(|| SolcError::UnknownVersion(stdout.into_owned()))
+    }
+
+    /// Compile the input, or read the output back from a cache if the same
+    /// sources were compiled with the same settings and compiler release.
+    ///
+    /// The release is the one declared with
+    /// [`with_version`](Self::with_version), or else the version the
+    /// executable reports. Outputs with errors are not stored, so failed
+    /// compilations run again. Inputs with URL sources that declare no
+    /// keccak256 hash are always compiled, since the content behind the
+    /// URLs may have changed.
+    pub fn compile_cached(
+        &self,
+        input: &StandardJsonInput,
+        cache: &CompilationCache,
+    ) -> Result<StandardJsonOutput, SolcError> {
+        let release = match &self.version {
+            Some(version) => version.to_string(),
+            None => self.version()?,
+        };
+        let Some(fingerprint) = Fingerprint::new(input, &release) else {
+            return self.compile(input);
+        };
+        if let Some(output) = cache.get(&fingerprint)? {
+            return Ok(output);
+        }
+        let output = self.compile(input)?;
+        if !output.has_errors() {
+            cache.put(&fingerprint, &output)?;
+        }
+        Ok(output)
+    }
+
+    /// Compile the input and also return what the compiler printed on stderr.
+    ///
+    /// The compiler reports diagnostics in the output, but some problems,
+    /// such as unreadable remapped files or internal errors, are only
+    /// printed on stderr.
+    pub fn compile_with_stderr(&self, input: &StandardJsonInput) -> Result<SolcRun, SolcError> {
+        if let (Some(version), Some(evm_version)) = (&self.version, input.settings.evm_version) {
+            evm_version.check(version)?;
+        }
+        let input = serde_json::to_vec(input)?;
+        let start = Instant::now();
+        let mut child = Command::new(&self.path)
+            .arg("--standard-json")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Write and read on separate threads, so a large input or output
+        // cannot fill a pipe and block both processes.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let stdout = read_to_end(child.stdout.take().expect("stdout is piped"));
+        let stderr = read_to_end(child.stderr.take().expect("stderr is piped"));
+
+        let status = match self.timeout {
+            Some(timeout) => wait_timeout(&mut child, timeout)?,
+            None => child.wait()?,
+        };
+        let duration = start.elapsed();
+        let written = writer.join().expect("stdin writer panicked");
+        let stdout = stdout.join().expect("stdout reader panicked")?;
+        let stderr = stderr.join().expect("stderr reader panicked")?;
+        let stderr = String::from_utf8_lossy(&stderr).into_owned();
+
+        if !status.success() {
+            return Err(SolcError::Failed { status, stderr });
+        }
+        written?;
+        Ok(SolcRun {
+            output: serde_json::from_slice(&stdout)?,
+            stderr,
+            duration,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Solc {
+    /// Compile the input without blocking the async runtime.
+    ///
+    /// See [`Solc::compile_async_with_stderr`].
+    pub async fn compile_async(
+        &self,
+        input: &StandardJsonInput,
+    ) -> Result<StandardJsonOutput, SolcError> {
+        self.compile_async_with_stderr(input)
+            .await
+            .map(|run| run.output)
+    }
+
+    /// Compile the input without blocking the async runtime, and also
+    /// return what the compiler printed on stderr.
+    ///
+    /// The process is killed when the returned future is dropped, so a run
+    /// is cancelled by dropping it, for example when it loses a
+    /// `tokio::select!`. The timeout set with [`Solc::timeout`] applies as
+    /// it does to [`Solc::compile_with_stderr`].
+    pub async fn compile_async_with_stderr(
+        &self,
+        input: &StandardJsonInput,
+    ) -> Result<SolcRun, SolcError> {
+        use tokio::io::AsyncWriteExt;
+
+        if let (Some(version), Some(evm_version)) = (&self.version, input.settings.evm_version) {
+            evm_version.check(version)?;
+        }
+        let input = serde_json::to_vec(input)?;
+        let start = Instant::now();
+        let mut child = tokio::process::Command::new(&self.path)
+            .arg("--standard-json")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Write while reading, so a large input or output cannot fill a pipe
+        // and block both processes. Stdin is closed once written.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let write = async move { stdin.write_all(&input).await };
+        let run = async { tokio::join!(write, child.wait_with_output()) };
+        let (written, output) = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, run)
+                .await
+                .map_err(|_| SolcError::Timeout(timeout))?,
+            None => run.await,
+        };
+        let duration = start.elapsed();
+        let output = output?;
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        if !output.status.success() {
+            return Err(SolcError::Failed {
+                status: output.status,
+                stderr,
+            });
+        }
+        written?;
+        Ok(SolcRun {
+            output: serde_json::from_slice(&output.stdout)?,
+            stderr,
+            duration,
+        })
+    }
+}
+
+impl Default for Solc {
+    /// Run the `solc` executable found in `PATH`.
+    fn default() -> Self {
+        Self::new("solc")
+    }
+}
+
+impl Compiler for Solc {
+    type Error = SolcError;
+
+    fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, SolcError> {
+        self.compile_with_stderr(input).map(|run| run.output)
+    }
+
+    /// The path of the executable, so that runs of different versions are
+    /// told apart.
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+/// The result of [`Solc::compile_with_stderr`].
+#[derive(Clone, Debug)]
+pub struct SolcRun {
+    /// The parsed compiler output.
+    pub output: StandardJsonOutput,
+    /// Everything the compiler printed on stderr, usually empty.
+    pub stderr: String,
+    /// How long the compiler ran, from starting the process until it
+    /// exited.
+    pub duration: Duration,
+}
+
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        pipe.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+/// Wait for the child to exit, killing it once `timeout` has passed.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Result<ExitStatus, SolcError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(SolcError::Timeout(timeout));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// An error that prevented [`Solc`] from producing an output.
+#[derive(Debug, thiserror::Error)]
+pub enum SolcError {
+    /// The executable could not be started or its pipes failed.
+    #[error("failed to run solc: {0}")]
+    Io(#[from] io::Error),
+
+    /// The input could not be serialized or the output could not be parsed.
+    #[error("invalid standard JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The process exited with a failure status.
+    #[error("solc exited with {status}: {stderr}")]
+    Failed { status: ExitStatus, stderr: String },
+
+    /// The process ran longer than the timeout and was killed.
+    #[error("solc did not finish within {0:?}")]
+    Timeout(Duration),
+
+    /// The output of `solc --version` has no version line.
+    #[error("unrecognized solc version output: {0}")]
+    UnknownVersion(String),
+
+    /// The input selects an EVM version the compiler release does not know.
+    #[error(transparent)]
+    UnsupportedEvmVersion(#[from] UnsupportedEvmVersion),
+
+    /// The compilation cache could not be read or written.
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+}
+
+/// A contract compiled by [`compile_source`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledContract {
+    /// The contract name.
+    pub name: String,
+    /// The contract ABI.
+    pub abi: Abi,
+    /// The creation bytecode.
+    pub bytecode: Bytecode,
+    /// Warnings and informational messages reported by the compiler.
+    pub warnings: Vec<standard_json_output::Error>,
+}
+
+/// An error returned by [`compile_source`].
+#[derive(Debug, thiserror::Error)]
+pub enum CompileError<E> {
+    /// The backend failed to run.
+    #[error(transparent)]
+    Compiler(E),
+
+    /// The source has errors. All diagnostics are included.
+    #[error("compilation failed: {}", first_error(.0))]
+    Compilation(Vec<standard_json_output::Error>),
+
+    /// The source does not define any deployable contract.
+    #[error("no contract found in source")]
+    NoContract,
+
+    /// The source defines more than one deployable contract or library.
+    #[error("source defines several contracts: {}", .0.join(", "))]
+    MultipleContracts(Vec<String>),
+}
+
+fn first_error(errors: &[standard_json_output::Error]) -> &str {
+    errors
+        .iter()
+        .find(|error| error.severity == standard_json_output::Severity::Error)
+        .map_or("", |error| error.message.as_str())
+}
+
+/// Compile a single source file with the `solc` in `PATH`.
+///
+/// This is the shortest path from Solidity source to an ABI and bytecode. The
+/// source is compiled on its own with default settings, and it must define
+/// exactly one deployable contract. Interfaces and abstract contracts, which
+/// have no bytecode, are ignored. Use [`compile_source_with`] to pick the backend, or
+/// build a [`StandardJsonInput`] for anything more involved.
+pub fn compile_source(
+    name: &str,
+    source: &str,
+) -> Result<CompiledContract, CompileError<SolcError>> {
+    compile_source_with(&Solc::default(), name, source)
+}
+
+/// Compile a single source file with the given backend.
+///
+/// See [`compile_source`].
+pub fn compile_source_with<C: Compiler>(
+    compiler: &C,
+    name: &str,
+    source: &str,
+) -> Result<CompiledContract, CompileError<C::Error>> {
+    let mut input = StandardJsonInput::new().add_source(name, source);
+    input
+        .settings
+        .select(OutputSelector::Abi)
+        .and(OutputSelector::EvmBytecodeObject)
+        .for_contract("*");
+
+    let output = compiler.compile(&input).map_err(CompileError::Compiler)?;
+    if output.has_errors() {
+        return Err(CompileError::Compilation(output.errors));
+    }
+
+    // Interfaces and abstract contracts have no bytecode and cannot be the
+    // contract the caller wants to deploy.
+    let mut contracts: Vec<_> = output
+        .contracts
+        .into_values()
+        .flatten()
+        .filter(|(_, contract)| {
+            contract
+                .evm
+                .as_ref()
+                .and_then(|evm| evm.bytecode.as_ref())
+                .is_some_and(|bytecode| !bytecode.object.is_empty())
+        })
+        .collect();
+    if contracts.len() > 1 {
+        let names = contracts.into_iter().map(|(name, _)| name).collect();
+        return Err(CompileError::MultipleContracts(names));
+    }
+    let (name, contract) = contracts.pop().ok_or(CompileError::NoContract)?;
+    Ok(CompiledContract {
+        name,
+        abi: contract.abi.unwrap_or_default(),
+        bytecode: contract
+            .evm
+            .and_then(|evm| evm.bytecode)
+            .unwrap_or_default(),
+        warnings: output.errors,
+    })
+}
+
+/// The ASTs returned by [`parse_with_solc`].
+#[derive(Clone, Debug, Default)]
+pub struct ParsedSources {
+    /// The AST of each source that parsed, in the order of their source ids.
+    pub source_units: Vec<SourceUnit>,
+    /// Nodes the typed AST could not represent, which are placeholders in
+    /// `source_units`.
+    pub unparsed: Vec<NodeError>,
+    /// The syntax errors and other diagnostics reported by the compiler.
+    pub errors: Vec<standard_json_output::Error>,
+}
+
+/// An error returned by [`parse_with_solc`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError<E> {
+    /// The backend failed to run.
+    #[error(transparent)]
+    Compiler(E),
+
+    /// The AST of a source is not a source unit.
+    #[error("invalid AST for {file}: {error}")]
+    Ast {
+        file: String,
+        error: serde_json::Error,
+    },
+}
+
+/// Parse sources with the `solc` in `PATH`, without analyzing them.
+///
+/// The compiler stops after parsing, so the ASTs are returned even for code
+/// with type errors or unresolved imports. Names are not resolved in such
+/// ASTs, so fields like `referencedDeclaration` and `typeDescriptions` are
+/// empty. A source with syntax errors has no AST, and its errors are in
+/// [`ParsedSources::errors`]. This suits editors and linters, which have to
+/// work on code that does not compile yet. Use [`parse_with`] to pick the
+/// backend.
+pub fn parse_with_solc<P, S>(
+    sources: impl IntoIterator<Item = (P, S)>,
+) -> Result<ParsedSources, ParseError<SolcError>>
+where
+    P: Into<PathBuf>,
+    S: Into<String>,
+{
+    parse_with(&Solc::default(), sources)
+}
+
+/// Parse sources with the given backend, without analyzing them.
+///
+/// See [`parse_with_solc`].
+pub fn parse_with<C: Compiler, P, S>(
+    compiler: &C,
+    sources: impl IntoIterator<Item = (P, S)>,
+) -> Result<ParsedSources, ParseError<C::Error>>
+where
+    P: Into<PathBuf>,
+    S: Into<String>,
+{
+    let mut input = sources
+        .into_iter()
+        .fold(StandardJsonInput::new(), |input, (path, content)| {
+            input.add_source(path, content)
+        });
+    input.settings.stop_after = Some(StopAfter::Parsing);
+    input.settings.select(OutputSelector::Ast).for_file("*");
+
+    let output = compiler.compile(&input).map_err(ParseError::Compiler)?;
+    read_source_units(output)
+}
+
+/// Read the ASTs of an output leniently, in the order of their source ids.
+fn read_source_units<E>(output: StandardJsonOutput) -> Result<ParsedSources, ParseError<E>> {
+    let mut sources: Vec<_> = output
+        .sources
+        .into_iter()
+        .filter_map(|(file, source)| source.ast.map(|ast| (source.id, file, ast)))
+        .collect();
+    sources.sort_by_key(|(id, _, _)| *id);
+
+    let mut parsed = ParsedSources {
+        errors: output.errors,
+        ..Default::default()
+    };
+    for (_, file, ast) in sources {
+        let LenientParse {
+            source_unit,
+            errors,
+        } = parse_lenient(&ast.to_string()).map_err(|error| ParseError::Ast { file, error })?;
+        parsed.source_units.push(source_unit);
+        parsed.unparsed.extend(errors);
+    }
+    Ok(parsed)
+}
+
+/// The result of [`analyze_with_solc`].
+#[derive(Clone, Debug, Default)]
+pub struct ProjectAnalysis {
+    /// The ASTs and diagnostics of the project.
+    pub sources: ParsedSources,
+    /// Whether the ASTs are fully analyzed, with names resolved and types
+    /// annotated. If not, they are the ASTs of [`parse_with_solc`].
+    pub analyzed: bool,
+}
+
+/// Analyze a project with the `solc` in `PATH`, returning whatever ASTs it
+/// can even if some files do not compile.
+///
+/// The sources are first analyzed in full. If that fails, for example on a
+/// type error in one file, the compiler returns no ASTs at all, so the
+/// sources are parsed again without analysis, as in [`parse_with_solc`].
+/// The diagnostics of both runs are returned, without duplicates, and
+/// [`ProjectAnalysis::analyzed`] tells which ASTs were returned. Use
+/// [`analyze_with`] to pick the backend.
+pub fn analyze_with_solc<P, S>(
+    sources: impl IntoIterator<Item = (P, S)>,
+) -> Result<ProjectAnalysis, ParseError<SolcError>>
+where
+    P: Into<PathBuf>,
+    S: Into<String>,
+{
+    analyze_with(&Solc::default(), sources)
+}
+
+/// Analyze a project with the given backend, returning whatever ASTs it can
+/// even if some files do not compile.
+///
+/// See [`analyze_with_solc`].
+pub fn analyze_with<C: Compiler, P, S>(
+    compiler: &C,
+    sources: impl IntoIterator<Item = (P, S)>,
+) -> Result<ProjectAnalysis, ParseError<C::Error>>
+where
+    P: Into<PathBuf>,
+    S: Into<String>,
+{
+    let sources: Vec<(PathBuf, String)> = sources
+        .into_iter()
+        .map(|(path, content)| (path.into(), content.into()))
+        .collect();
+    let mut input = sources
+        .iter()
+        .fold(StandardJsonInput::new(), |input, (path, content)| {
+            input.add_source(path, content.as_str())
+        });
+    input.settings.select(OutputSelector::Ast).for_file("*");
+
+    let output = compiler.compile(&input).map_err(ParseError::Compiler)?;
+    if !output.has_errors() {
+        return Ok(ProjectAnalysis {
+            sources: read_source_units(output)?,
+            analyzed: true,
+        });
+    }
+
+    let mut parsed = parse_with(compiler, sources)?;
+    let mut errors = StandardJsonOutput {
+        errors: output.errors,
+        ..Default::default()
+    };
+    errors.merge(StandardJsonOutput {
+        errors: parsed.errors,
+        ..Default::default()
+    });
+    errors.dedup_errors();
+    parsed.errors = errors.errors;
+    Ok(ProjectAnalysis {
+        sources: parsed,
+        analyzed: false,
+    })
+}
+
+/// The settings that produced the output of [`compile_with_via_ir_fallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pipeline {
+    /// The input as given by the caller.
+    Original,
+    /// The input with `viaIR` enabled.
+    ViaIr,
+    /// The input with `viaIR` and the optimizer enabled.
+    ViaIrOptimized,
+}
+
+/// The result of [`compile_with_via_ir_fallback`].
+#[derive(Clone, Debug)]
+pub struct ViaIrFallback {
+    /// The output of the last compilation attempt.
+    pub output: StandardJsonOutput,
+    /// The settings used for the last attempt.
+    pub pipeline: Pipeline,
+    /// The input used for the last attempt, to reuse for later builds.
+    pub input: StandardJsonInput,
+}
+
+/// Compile the input, retrying through the IR pipeline on "stack too deep".
+///
+/// The input is first compiled as given. If the legacy code generator fails
+/// with a stack too deep error, it is compiled again with `viaIR: true`. If
+/// that still fails and `optimizer_runs` is set, a last attempt also enables
+/// the optimizer with that many runs, since the IR pipeline relies on the
+/// optimizer to move variables to memory. The returned [`Pipeline`] reports
+/// which configuration produced the output. Other compilation errors are
+/// returned as they are, without retrying.
+pub fn compile_with_via_ir_fallback<C: Compiler>(
+    compiler: &C,
+    input: &StandardJsonInput,
+    optimizer_runs: Option<usize>,
+) -> Result<ViaIrFallback, C::Error> {
+    let output = compiler.compile(input)?;
+    if !output.is_stack_too_deep() || input.settings.via_ir == Some(true) {
+        return Ok(ViaIrFallback {
+            output,
+            pipeline: Pipeline::Original,
+            input: input.clone(),
+        });
+    }
+
+    let mut via_ir = input.clone();
+    via_ir.settings.via_ir = Some(true);
+    let output = compiler.compile(&via_ir)?;
+
+    let optimizer_enabled = via_ir
+        .settings
+        .optimizer
+        .as_ref()
+        .is_some_and(|optimizer| optimizer.enabled);
+    let Some(runs) = optimizer_runs.filter(|_| output.is_stack_too_deep() && !optimizer_enabled)
+    else {
+        return Ok(ViaIrFallback {
+            output,
+            pipeline: Pipeline::ViaIr,
+            input: via_ir,
+        });
+    };
+
+    let mut optimized = via_ir;
+    let details = optimized
+        .settings
+        .optimizer
+        .take()
+        .and_then(|optimizer| optimizer.details);
+    optimized.settings.optimizer = Some(Optimizer {
+        enabled: true,
+        runs,
+        details,
+    });
+    let output = compiler.compile(&optimized)?;
+    Ok(ViaIrFallback {
+        output,
+        pipeline: Pipeline::ViaIrOptimized,
+        input: optimized,
+    })
+}
+
+/// The output of [`compile_timed`], with how long the compilation took.
+#[derive(Clone, Debug)]
+pub struct CompileOutcome {
+    /// The compiler output.
+    pub output: StandardJsonOutput,
+    /// The [name](Compiler::name) of the backend that compiled the input.
+    pub backend: String,
+    /// The wall-clock time the backend took, including starting the
+    /// compiler and parsing its output.
+    pub duration: Duration,
+}
+
+/// Compile the input and measure how long the backend takes.
+pub fn compile_timed<C: Compiler>(
+    compiler: &C,
+    input: &StandardJsonInput,
+) -> Result<CompileOutcome, C::Error> {
+    let start = Instant::now();
+    let output = compiler.compile(input)?;
+    Ok(CompileOutcome {
+        output,
+        backend: compiler.name(),
+        duration: start.elapsed(),
+    })
+}
+
+/// Compilation times aggregated over jobs, by backend and by contract.
+///
+/// A job compiles its contracts together, so the time of a job is counted
+/// for each contract in its output. Contract times tell which contracts make
+/// builds slow, but do not add up to the total.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompileTimings {
+    jobs: usize,
+    total: Duration,
+    backends: BTreeMap<String, Duration>,
+    contracts: BTreeMap<String, Duration>,
+}
+
+impl CompileTimings {
+    /// Timings without any job.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the time of a job.
+    pub fn record(&mut self, outcome: &CompileOutcome) {
+        self.jobs += 1;
+        self.total += outcome.duration;
+        *self.backends.entry(outcome.backend.clone()).or_default() += outcome.duration;
+        for (file, contracts) in &outcome.output.contracts {
+            for name in contracts.keys() {
+                let key = format!("{}:{}", file, name);
+                *self.contracts.entry(key).or_default() += outcome.duration;
+            }
+        }
+    }
+
+    /// The number of jobs recorded.
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// The time of all jobs together.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The time of the jobs of each backend, by backend name.
+    pub fn backends(&self) -> &BTreeMap<String, Duration> {
+        &self.backends
+    }
+
+    /// The time of the jobs that compiled each contract, by fully qualified
+    /// name such as `src/Token.sol:Token`.
+    pub fn contracts(&self) -> &BTreeMap<String, Duration> {
+        &self.contracts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::standard_json_output::{Contract, Error, Evm, Severity};
+
+    /// A backend that reports stack too deep until the given settings are on.
+    #[derive(Default)]
+    struct StackTooDeep {
+        needs_optimizer: bool,
+        calls: Cell<usize>,
+    }
+
+    impl Compiler for StackTooDeep {
+        type Error = ();
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            self.calls.set(self.calls.get() + 1);
+            let via_ir = input.settings.via_ir == Some(true);
+            let optimized = input.settings.optimizer.as_ref().is_some_and(|o| o.enabled);
+            if via_ir && (optimized || !self.needs_optimizer) {
+                return Ok(StandardJsonOutput::default());
+            }
+            Ok(StandardJsonOutput {
+                errors: vec![Error {
+                    r#type: "CompilerError".to_string(),
+                    component: "general".to_string(),
+                    severity: Severity::Error,
+                    message: "Stack too deep. Try compiling with `--via-ir`.".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn aggregates_timings() {
+        let output: StandardJsonOutput = serde_json::from_str(
+            r#"{"contracts": {"A.sol": {"A": {}, "Lib": {}}, "B.sol": {"B": {}}}}"#,
+        )
+        .unwrap();
+        let outcome = compile_timed(&StackTooDeep::default(), &StandardJsonInput::new()).unwrap();
+        assert!(outcome.backend.ends_with("StackTooDeep"));
+
+        let job = |backend: &str, millis, contracts: &[&str]| {
+            let mut output = output.clone();
+            output
+                .contracts
+                .retain(|file, _| contracts.contains(&file.as_str()));
+            CompileOutcome {
+                output,
+                backend: backend.to_string(),
+                duration: Duration::from_millis(millis),
+            }
+        };
+        let mut timings = CompileTimings::new();
+        timings.record(&job("solc-0.8.20", 300, &["A.sol"]));
+        timings.record(&job("solc-0.8.28", 200, &["A.sol", "B.sol"]));
+        timings.record(&job("solc-0.8.28", 100, &[]));
+
+        assert_eq!(timings.jobs(), 3);
+        assert_eq!(timings.total(), Duration::from_millis(600));
+        assert_eq!(
+            timings.backends()["solc-0.8.28"],
+            Duration::from_millis(300)
+        );
+        let contracts: Vec<(&str, u128)> = timings
+            .contracts()
+            .iter()
+            .map(|(name, duration)| (name.as_str(), duration.as_millis()))
+            .collect();
+        assert_eq!(
+            contracts,
+            [("A.sol:A", 500), ("A.sol:Lib", 500), ("B.sol:B", 200)]
+        );
+    }
+
+    #[test]
+    fn retries_with_via_ir() {
+        let compiler = StackTooDeep {
+            needs_optimizer: false,
+            calls: Cell::new(0),
+        };
+        let result =
+            compile_with_via_ir_fallback(&compiler, &StandardJsonInput::new(), Some(200)).unwrap();
+        assert_eq!(result.pipeline, Pipeline::ViaIr);
+        assert_eq!(result.input.settings.via_ir, Some(true));
+        assert!(!result.output.has_errors());
+        assert_eq!(compiler.calls.get(), 2);
+    }
+
+    #[test]
+    fn retries_with_optimizer() {
+        let compiler = StackTooDeep {
+            needs_optimizer: true,
+            calls: Cell::new(0),
+        };
+        let result =
+            compile_with_via_ir_fallback(&compiler, &StandardJsonInput::new(), Some(200)).unwrap();
+        assert_eq!(result.pipeline, Pipeline::ViaIrOptimized);
+        assert_eq!(result.input.settings.optimizer.unwrap().runs, 200);
+        assert!(!result.output.has_errors());
+
+        let compiler = StackTooDeep {
+            needs_optimizer: true,
+            calls: Cell::new(0),
+        };
+        let result =
+            compile_with_via_ir_fallback(&compiler, &StandardJsonInput::new(), None).unwrap();
+        assert_eq!(result.pipeline, Pipeline::ViaIr);
+        assert!(result.output.is_stack_too_deep());
+    }
+
+    /// A backend that returns an empty contract for every source file.
+    struct OneContractPerFile;
+
+    impl Compiler for OneContractPerFile {
+        type Error = ();
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            let mut output = StandardJsonOutput::default();
+            for path in input.sources.keys() {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let contract = Contract {
+                    abi: Some(Abi::new()),
+                    evm: Some(Evm {
+                        bytecode: Some(Bytecode {
+                            object: "6080".to_string(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+                output.contracts.insert(
+                    path.to_string_lossy().into_owned(),
+                    [(name, contract)].into(),
+                );
+            }
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn compile_source_extracts_contract() {
+        let contract = compile_source_with(&OneContractPerFile, "Counter.sol", "").unwrap();
+        assert_eq!(contract.name, "Counter");
+        assert_eq!(contract.bytecode.object, "6080");
+
+        let error = compile_source_with(&StackTooDeep::default(), "A.sol", "").unwrap_err();
+        assert!(matches!(error, CompileError::Compilation(errors) if errors.len() == 1));
+    }
+
+    /// A backend that returns the same output for every input.
+    struct FixedOutput(&'static str);
+
+    impl Compiler for FixedOutput {
+        type Error = ();
+
+        fn compile(&self, _: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            Ok(serde_json::from_str(self.0).unwrap())
+        }
+    }
+
+    #[test]
+    fn compile_source_skips_interfaces() {
+        let backend = FixedOutput(
+            r#"{"contracts": {"Foo.sol": {
+                "IFoo": {"abi": [], "evm": {"bytecode": {"object": ""}}},
+                "Foo": {"abi": [], "evm": {"bytecode": {"object": "6080"}}}
+            }}}"#,
+        );
+        let source = "interface IFoo {}\ncontract Foo is IFoo {}";
+        let contract = compile_source_with(&backend, "Foo.sol", source).unwrap();
+        assert_eq!(contract.name, "Foo");
+
+        let backend = FixedOutput(
+            r#"{"contracts": {"Foo.sol": {
+                "IFoo": {"abi": [], "evm": {"bytecode": {"object": ""}}}
+            }}}"#,
+        );
+        let error = compile_source_with(&backend, "Foo.sol", "interface IFoo {}").unwrap_err();
+        assert!(matches!(error, CompileError::NoContract));
+
+        let backend = FixedOutput(
+            r#"{"contracts": {"Foo.sol": {
+                "Foo": {"abi": [], "evm": {"bytecode": {"object": "6080"}}},
+                "Bar": {"abi": [], "evm": {"bytecode": {"object": "6080"}}}
+            }}}"#,
+        );
+        let error = compile_source_with(&backend, "Foo.sol", "").unwrap_err();
+        assert!(matches!(error, CompileError::MultipleContracts(names) if names.len() == 2));
+    }
+
+    /// A backend that stops after parsing and returns a fixture AST for
+    /// every source but `Broken.sol`, which has a syntax error.
+    struct Parser;
+
+    impl Compiler for Parser {
+        type Error = ();
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            assert!(matches!(
+                input.settings.stop_after,
+                Some(StopAfter::Parsing)
+            ));
+            let selection = input.settings.output_selection.as_ref().unwrap();
+            assert_eq!(selection.get("*", ""), [OutputSelector::Ast]);
+
+            let ast: serde_json::Value = serde_json::from_str(
+                &std::fs::read_to_string("fixtures/ast/codearena-2025-10-covenant/ICovenant.json")
+                    .unwrap(),
+            )
+            .unwrap();
+            let mut output = StandardJsonOutput::default();
+            for (id, path) in input.sources.keys().enumerate() {
+                let broken = path.ends_with("Broken.sol");
+                output.sources.insert(
+                    path.to_string_lossy().into_owned(),
+                    standard_json_output::Source {
+                        id,
+                        ast: (!broken).then(|| ast.clone()),
+                    },
+                );
+                if broken {
+                    output.errors.push(Error {
+                        r#type: "ParserError".to_string(),
+                        severity: Severity::Error,
+                        message: "Expected ';' but got '}'".to_string(),
+                        ..Default::default()
+                    });
+                }
+            }
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn parse_stops_after_parsing() {
+        let parsed = parse_with(
+            &Parser,
+            [
+                ("A.sol", "contract A {}"),
+                ("Broken.sol", "contract B { uint x }"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(parsed.source_units.len(), 1);
+        assert!(parsed.unparsed.is_empty());
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].r#type, "ParserError");
+    }
+
+    /// A backend that fails analysis with a type error, and otherwise acts
+    /// as [`Parser`].
+    struct TypeError;
+
+    impl Compiler for TypeError {
+        type Error = ();
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, ()> {
+            if input.settings.stop_after.is_some() {
+                let mut output = Parser.compile(input)?;
+                output.errors.push(type_error());
+                return Ok(output);
+            }
+            Ok(StandardJsonOutput {
+                errors: vec![type_error()],
+                ..Default::default()
+            })
+        }
+    }
+
+    fn type_error() -> Error {
+        Error {
+            r#type: "TypeError".to_string(),
+            severity: Severity::Error,
+            message: "Undeclared identifier.".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn analyze_falls_back_to_parsing() {
+        let analysis = analyze_with(
+            &TypeError,
+            [("A.sol", "contract A { function f() { g(); } }")],
+        )
+        .unwrap();
+        assert!(!analysis.analyzed);
+        assert_eq!(analysis.sources.source_units.len(), 1);
+        assert_eq!(analysis.sources.errors, [type_error()]);
+
+        let analysis = analyze_with(&OneContractPerFile, [("A.sol", "")]).unwrap();
+        assert!(analysis.analyzed);
+        assert!(analysis.sources.source_units.is_empty());
+    }
+
+    /// Write an executable shell script to use as a fake `solc`.
+    #[cfg(unix)]
+    fn fake_solc(name: &str, script: &str) -> Solc {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = env::temp_dir().join(format!("solc-rs-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("solc");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Solc::new(path)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_executable() {
+        let solc = fake_solc(
+            "run",
+            r#"if [ "$1" = "--version" ]; then
+    echo "solc, the solidity compiler commandline interface"
+    echo "Version: 0.8.28+commit.7893614a.Linux.g++"
+    exit 0
+fi
+cat > /dev/null
+echo "note: remapping ignored" >&2
+echo '{"errors": [{"component": "general", "severity": "warning", "type": "Warning", "message": "w"}]}'"#,
+        );
+        assert_eq!(solc.version().unwrap(), "0.8.28+commit.7893614a.Linux.g++");
+
+        let run = solc
+            .compile_with_stderr(&StandardJsonInput::new().add_source("A.sol", "contract A {}"))
+            .unwrap();
+        assert_eq!(run.stderr, "note: remapping ignored\n");
+        assert_eq!(run.output.errors[0].severity, Severity::Warning);
+
+        let failing = fake_solc("fail", "cat > /dev/null; echo broken >&2; exit 3");
+        let Err(SolcError::Failed { status, stderr }) = failing.compile(&StandardJsonInput::new())
+        else {
+            panic!("expected a failure");
+        };
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(stderr, "broken\n");
+
+        let input =
+            StandardJsonInput::new().evm_version(crate::standard_json_input::EvmVersion::Cancun);
+        let old = failing.with_version(Version::new(0, 8, 20));
+        assert!(matches!(
+            old.compile(&input),
+            Err(SolcError::UnsupportedEvmVersion(_))
+        ));
+    }
+
+    #[cfg(all(unix, feature = "tokio"))]
+    #[tokio::test]
+    async fn compiles_async() {
+        let solc = fake_solc(
+            "async",
+            r#"cat > /dev/null
+echo "note: async" >&2
+echo '{"errors": [{"component": "general", "severity": "warning", "type": "Warning", "message": "w"}]}'"#,
+        );
+        let input = StandardJsonInput::new().add_source("A.sol", "contract A {}");
+        let run = solc.compile_async_with_stderr(&input).await.unwrap();
+        assert_eq!(run.stderr, "note: async\n");
+        assert_eq!(run.output.errors[0].severity, Severity::Warning);
+
+        let failing = fake_solc("async-fail", "cat > /dev/null; exit 3");
+        assert!(matches!(
+            failing.compile_async(&input).await,
+            Err(SolcError::Failed { .. })
+        ));
+
+        let slow = fake_solc("async-timeout", "sleep 5").timeout(Duration::from_millis(100));
+        let started = Instant::now();
+        assert!(matches!(
+            slow.compile_async(&input).await,
+            Err(SolcError::Timeout(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let slow = fake_solc("async-cancel", "sleep 5");
+        let started = Instant::now();
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(100), slow.compile_async(&input));
+        assert!(cancelled.await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reuses_cached_outputs() {
+        let dir = env::temp_dir().join(format!("solc-rs-cached-{}", std::process::id()));
+        let runs = dir.join("runs");
+        let cache = CompilationCache::new(dir.join("cache"));
+        let solc = fake_solc(
+            "cached",
+            &format!(
+                r#"cat > /dev/null
+echo run >> {}
+echo '{{"contracts": {{"A.sol": {{"A": {{"abi": []}}}}}}}}'"#,
+                runs.display()
+            ),
+        )
+        .with_version(Version::new(0, 8, 30));
+        cache.clear().unwrap();
+        let _ = std::fs::remove_file(&runs);
+
+        let input = StandardJsonInput::new().add_source("A.sol", "contract A {}");
+        let output = solc.compile_cached(&input, &cache).unwrap();
+        assert_eq!(solc.compile_cached(&input, &cache).unwrap(), output);
+        assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\n");
+
+        let changed = input.clone().optimizer(200);
+        solc.compile_cached(&changed, &cache).unwrap();
+        assert_eq!(std::fs::read_to_string(&runs).unwrap(), "run\nrun\n");
+        cache.clear().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn recompiles_unhashed_urls() {
+        let dir = env::temp_dir().join(format!("solc-rs-cached-urls-{}", std::process::id()));
+        let source = dir.join("A.sol");
+        let cache = CompilationCache::new(dir.join("cache"));
+        let solc = fake_solc(
+            "cached-urls",
+            &format!(
+                r#"cat > /dev/null
+echo "{{\"contracts\": {{\"A.sol\": {{\"$(cat {})\": {{\"abi\": []}}}}}}}}""#,
+                source.display()
+            ),
+        )
+        .with_version(Version::new(0, 8, 30));
+        cache.clear().unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let input = StandardJsonInput::new().add_source_urls(
+            "A.sol",
+            vec![source.display().to_string()],
+            None,
+        );
+        let names = |output: StandardJsonOutput| {
+            output.contracts["A.sol"]
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        std::fs::write(&source, "A").unwrap();
+        assert_eq!(names(solc.compile_cached(&input, &cache).unwrap()), ["A"]);
+        std::fs::write(&source, "B").unwrap();
+        assert_eq!(names(solc.compile_cached(&input, &cache).unwrap()), ["B"]);
+        assert!(!cache.dir().exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_after_timeout() {
+        let solc = fake_solc("timeout", "sleep 5").timeout(Duration::from_millis(100));
+        let started = Instant::now();
+        let result = solc.compile(&StandardJsonInput::new());
+        assert!(matches!(result, Err(SolcError::Timeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,167 @@
+//! A backend that runs the soljson build of the compiler through solc-js.
+
+use js_sys::{Function, Reflect};
+use semver::Version;
+use wasm_bindgen::{JsCast, JsValue};
+
+use super::Compiler;
+use crate::standard_json_input::{StandardJsonInput, UnsupportedEvmVersion};
+use crate::standard_json_output::StandardJsonOutput;
+
+/// A backend that runs the soljson build of the compiler, the emscripten
+/// build that solc-js loads, so that no native executable is needed.
+///
+/// The backend calls the `compile` function of a solc-js compiler object,
+/// such as the one `require("solc")` returns or one made with
+/// `solc.setupMethods(soljson)`, with the input as a JSON string. Imports
+/// must be given as sources in the input, since no import callback is
+/// passed.
+#[derive(Clone, Debug)]
+pub struct SolcWasm {
+    compile: Function,
+    version: Option<Version>,
+}
+
+impl SolcWasm {
+    /// Create a backend from a solc-js compiler object.
+    ///
+    /// The release is read from its `version` function when it has one.
+    pub fn new(solc: &JsValue) -> Result<Self, SolcWasmError> {
+        let compile = Reflect::get(solc, &JsValue::from_str("compile"))
+            .ok()
+            .and_then(|compile| compile.dyn_into::<Function>().ok())
+            .ok_or(SolcWasmError::NotSolcJs)?;
+        let version = Reflect::get(solc, &JsValue::from_str("version"))
+            .ok()
+            .and_then(|version| version.dyn_into::<Function>().ok())
+            .and_then(|version| version.call0(solc).ok())
+            .and_then(|version| version.as_string())
+            .and_then(|version| parse_version(&version));
+        Ok(Self { compile, version })
+    }
+
+    /// Create a backend from a function that takes Standard JSON input as a
+    /// string and returns the output as a string.
+    pub fn from_function(compile: Function) -> Self {
+        Self {
+            compile,
+            version: None,
+        }
+    }
+
+    /// Declare the release of the compiler, which enables the same checks
+    /// as [`Solc::with_version`](super::Solc::with_version).
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// The release of the compiler, if it is known.
+    pub fn known_version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+}
+
+/// Parse a version string such as `0.8.30+commit.73712a01.Emscripten.clang`.
+fn parse_version(version: &str) -> Option<Version> {
+    let release = version.split(['+', '-']).next()?;
+    Version::parse(release).ok()
+}
+
+impl Compiler for SolcWasm {
+    type Error = SolcWasmError;
+
+    fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, Self::Error> {
+        if let (Some(version), Some(evm_version)) = (&self.version, input.settings.evm_version) {
+            evm_version.check(version)?;
+        }
+        let input = serde_json::to_string(input)?;
+        let output = self
+            .compile
+            .call1(&JsValue::NULL, &JsValue::from_str(&input))
+            .map_err(|error| SolcWasmError::Thrown(format!("{:?}", error)))?;
+        let output = output.as_string().ok_or(SolcWasmError::NotAString)?;
+        Ok(serde_json::from_str(&output)?)
+    }
+
+    fn name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("soljson-v{}", version),
+            None => "soljson".to_string(),
+        }
+    }
+}
+
+/// An error that prevented [`SolcWasm`] from producing an output.
+#[derive(Debug, thiserror::Error)]
+pub enum SolcWasmError {
+    /// The value given as the compiler has no `compile` function.
+    #[error("not a solc-js compiler: no compile function")]
+    NotSolcJs,
+
+    /// The compile function threw an exception.
+    #[error("soljson threw: {0}")]
+    Thrown(String),
+
+    /// The compile function returned something other than a string.
+    #[error("soljson returned a value that is not a string")]
+    NotAString,
+
+    /// The input could not be serialized or the output could not be parsed.
+    #[error("invalid standard JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The input selects an EVM version the compiler release does not know.
+    #[error(transparent)]
+    UnsupportedEvmVersion(#[from] UnsupportedEvmVersion),
+}
+
+#[cfg(test)]
+mod tests {
+    use js_sys::Object;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    fn function(args: &str, body: &str) -> Function {
+        Function::new_with_args(args, body)
+    }
+
+    #[wasm_bindgen_test]
+    fn compiles_through_a_function() {
+        let compile = function(
+            "input",
+            "const sources = Object.keys(JSON.parse(input).sources);\
+             return JSON.stringify({ sources: Object.fromEntries(sources.map((name, id) => [name, { id }])) });",
+        );
+        let solc = SolcWasm::from_function(compile);
+        assert_eq!(solc.name(), "soljson");
+        let input = StandardJsonInput::new().add_source("A.sol", "contract A {}");
+        let output = solc.compile(&input).unwrap();
+        assert_eq!(output.sources["A.sol"].id, 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_solc_js_objects() {
+        let solc = Object::new();
+        assert!(matches!(
+            SolcWasm::new(&solc),
+            Err(SolcWasmError::NotSolcJs)
+        ));
+
+        Reflect::set(&solc, &"compile".into(), &function("input", "return 42;")).unwrap();
+        Reflect::set(
+            &solc,
+            &"version".into(),
+            &function("", "return '0.8.30+commit.73712a01.Emscripten.clang';"),
+        )
+        .unwrap();
+        let solc = SolcWasm::new(&solc).unwrap();
+        assert_eq!(solc.known_version(), Some(&Version::new(0, 8, 30)));
+        assert_eq!(solc.name(), "soljson-v0.8.30");
+        assert!(matches!(
+            solc.compile(&StandardJsonInput::new()),
+            Err(SolcWasmError::NotAString)
+        ));
+    }
+}
@@ -0,0 +1,402 @@
+//! Compiler diagnostics rendered with excerpts of the sources.
+//!
+//! The compiler reports each [`Error`] with byte offsets into a source file.
+//! A [`Renderer`] holds the sources that were compiled and turns these
+//! offsets into lines and columns, printing each diagnostic the way rustc
+//! does: a header with the severity and error code, the location, and the
+//! lines of the source it points at with the range underlined.
+//!
+//! ```text
+//! error[7576]: DeclarationError: Undeclared identifier.
+//!  --> A.sol:3:9
+//!   |
+//! 3 |         x = 1;
+//!   |         ^
+//! ```
+//!
+//! A [`Policy`] decides which diagnostics fail a build, such as denying
+//! warnings except in vendored dependencies.
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::ast::{IndexedSource, LineColumn};
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+use crate::standard_json_output::{Error, Severity, StandardJsonOutput, glob_match};
+
+/// Renders diagnostics with excerpts of the sources they point into.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Renderer {
+    sources: BTreeMap<String, IndexedSource>,
+}
+
+impl Renderer {
+    /// A renderer without sources, which prints locations without excerpts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A renderer for the sources of an input. Sources given by URL are
+    /// left out.
+    pub fn from_input(input: &StandardJsonInput) -> Self {
+        let mut renderer = Self::new();
+        for (name, source) in &input.sources {
+            if let SourceContent::Content { content } = &source.content {
+                renderer = renderer.source(name.to_string_lossy(), content.clone());
+            }
+        }
+        renderer
+    }
+
+    /// Add a source, under the source unit name diagnostics refer to it by.
+    pub fn source(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.sources
+            .insert(name.into(), IndexedSource::new(content));
+        self
+    }
+
+    /// Render a diagnostic, ending with a newline.
+    ///
+    /// The primary location is underlined with `^`, and secondary locations
+    /// follow as notes underlined with `-`. Locations in sources the renderer
+    /// does not have are printed as file names only.
+    pub fn render(&self, error: &Error) -> String {
+        let severity = match error.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+        let mut out = severity.to_string();
+        if let Some(code) = &error.error_code {
+            out.push_str(&format!("[{}]", code));
+        }
+        out.push_str(": ");
+        if !error.r#type.is_empty() && !error.r#type.eq_ignore_ascii_case(severity) {
+            out.push_str(&format!("{}: ", error.r#type));
+        }
+        out.push_str(&error.message);
+        out.push('\n');
+
+        if let Some(location) = &error.source_location {
+            self.excerpt(&location.file, location.start, location.end, '^', &mut out);
+        }
+        for location in &error.secondary_source_locations {
+            out.push_str(&format!("note: {}\n", location.message));
+            self.excerpt(&location.file, location.start, location.end, '-', &mut out);
+        }
+        out
+    }
+
+    /// Render diagnostics, separated by blank lines.
+    pub fn render_all<'a>(&self, errors: impl IntoIterator<Item = &'a Error>) -> String {
+        errors
+            .into_iter()
+            .map(|error| self.render(error))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Write the location of a range and the lines it covers.
+    fn excerpt(&self, file: &str, start: i64, end: i64, marker: char, out: &mut String) {
+        let range = usize::try_from(start).ok().zip(usize::try_from(end).ok());
+        let source = self.sources.get(file);
+        let (Some((start, end)), Some(source)) = (range, source) else {
+            out.push_str(&format!(" --> {}\n", file));
+            return;
+        };
+        let start = start.min(source.content().len());
+        let end = end.clamp(start, source.content().len());
+        let (Some(first), Some(last)) = (source.position(start), source.position(end)) else {
+            out.push_str(&format!(" --> {}\n", file));
+            return;
+        };
+        // A range that ends right after a newline does not cover the next line.
+        let last = if last.column == 1 && end > start {
+            last.line - 1
+        } else {
+            last.line
+        };
+        let width = last.to_string().len();
+        let gutter = " ".repeat(width);
+        out.push_str(&format!(
+            "{}--> {}:{}:{}\n",
+            gutter, file, first.line, first.column
+        ));
+        out.push_str(&format!("{} |\n", gutter));
+
+        for line in first.line..=last {
+            let (Some(text), Some(line_start)) = (
+                source.line(line),
+                source.offset(LineColumn { line, column: 1 }),
+            ) else {
+                break;
+            };
+            out.push_str(&format!("{:>width$} | {}\n", line, text, width = width));
+
+            let from = start.max(line_start) - line_start;
+            let to = end.min(line_start + text.len()).max(from + line_start) - line_start;
+            // Keep tabs in the padding so the markers line up with the text.
+            let padding: String = text[..from]
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let markers = text[from..to].chars().count().max(1);
+            out.push_str(&format!(
+                "{} | {}{}\n",
+                gutter,
+                padding,
+                marker.to_string().repeat(markers)
+            ));
+        }
+    }
+}
+
+/// Which diagnostics fail a build.
+///
+/// Errors always fail. Warnings fail once denied with
+/// [`deny_warnings`](Self::deny_warnings), and diagnostics of any severity
+/// fail if their code is denied with [`deny_codes`](Self::deny_codes).
+/// Allowlists exempt files matching a glob pattern from these rules, for all
+/// codes or only some, but never from errors.
+///
+/// ```
+/// use solc::diagnostics::Policy;
+///
+/// let policy = Policy::new()
+///     .deny_warnings()
+///     .deny_codes(["2519"])
+///     .allow("lib/*")
+///     .allow_codes("test/*", ["2072"]);
+/// # let _ = policy;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Policy {
+    deny_warnings: bool,
+    denied_codes: HashSet<String>,
+    allowed: Vec<(String, Option<HashSet<String>>)>,
+}
+
+impl Policy {
+    /// A policy under which only errors fail.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail on every warning.
+    pub fn deny_warnings(mut self) -> Self {
+        self.deny_warnings = true;
+        self
+    }
+
+    /// Fail on diagnostics with these error codes, whatever their severity.
+    pub fn deny_codes(mut self, codes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.denied_codes.extend(codes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Allow every diagnostic other than errors in files matching a glob
+    /// pattern with `*` and `?`.
+    pub fn allow(mut self, path: impl Into<String>) -> Self {
+        self.allowed.push((path.into(), None));
+        self
+    }
+
+    /// Allow diagnostics with these error codes in files matching a glob
+    /// pattern, unless they are errors.
+    pub fn allow_codes(
+        mut self,
+        path: impl Into<String>,
+        codes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let codes = codes.into_iter().map(Into::into).collect();
+        self.allowed.push((path.into(), Some(codes)));
+        self
+    }
+
+    /// Whether a diagnostic fails the build.
+    pub fn denies(&self, error: &Error) -> bool {
+        if error.severity == Severity::Error {
+            return true;
+        }
+        let denied = (self.deny_warnings && error.severity == Severity::Warning)
+            || error
+                .error_code
+                .as_ref()
+                .is_some_and(|code| self.denied_codes.contains(code));
+        denied && !self.allows(error)
+    }
+
+    fn allows(&self, error: &Error) -> bool {
+        let Some(location) = &error.source_location else {
+            return false;
+        };
+        self.allowed.iter().any(|(path, codes)| {
+            glob_match(path, &location.file)
+                && codes.as_ref().is_none_or(|codes| {
+                    error
+                        .error_code
+                        .as_ref()
+                        .is_some_and(|code| codes.contains(code))
+                })
+        })
+    }
+
+    /// Check the diagnostics of an output, returning those that fail the
+    /// build, in the order the compiler reported them.
+    pub fn check<'a>(&self, output: &'a StandardJsonOutput) -> Result<(), Vec<&'a Error>> {
+        let denied: Vec<&Error> = output
+            .errors
+            .iter()
+            .filter(|error| self.denies(error))
+            .collect();
+        if denied.is_empty() {
+            Ok(())
+        } else {
+            Err(denied)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standard_json_output::{ErrorLocation, SecondarySourceLocation};
+
+    const SOURCE: &str =
+        "contract A {\n    uint y;\n    function f() public {\n\tuint y;\n    }\n}\n";
+
+    fn error(start: i64, end: i64) -> Error {
+        Error {
+            r#type: "DeclarationError".to_string(),
+            component: "general".to_string(),
+            severity: Severity::Error,
+            error_code: Some("2333".to_string()),
+            message: "Identifier already declared.".to_string(),
+            source_location: Some(ErrorLocation {
+                file: "A.sol".to_string(),
+                start,
+                end,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn enforces_policy() {
+        let diagnostic = |severity, code: &str, file: &str| Error {
+            severity,
+            error_code: Some(code.to_string()),
+            source_location: Some(ErrorLocation {
+                file: file.to_string(),
+                start: 0,
+                end: 1,
+            }),
+            ..Default::default()
+        };
+        let output = StandardJsonOutput {
+            errors: vec![
+                diagnostic(Severity::Warning, "2072", "src/A.sol"),
+                diagnostic(Severity::Warning, "2072", "test/A.t.sol"),
+                diagnostic(Severity::Warning, "5667", "test/A.t.sol"),
+                diagnostic(Severity::Warning, "5667", "lib/oz/B.sol"),
+                diagnostic(Severity::Info, "2519", "src/A.sol"),
+            ],
+            ..Default::default()
+        };
+        let codes = |denied: Vec<&Error>| {
+            denied
+                .iter()
+                .map(|error| {
+                    let file = &error.source_location.as_ref().unwrap().file;
+                    format!("{}@{}", error.error_code.as_ref().unwrap(), file)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(Policy::new().check(&output), Ok(()));
+        let policy = Policy::new()
+            .deny_warnings()
+            .deny_codes(["2519"])
+            .allow("lib/*")
+            .allow_codes("test/*", ["2072"]);
+        assert_eq!(
+            codes(policy.check(&output).unwrap_err()),
+            ["2072@src/A.sol", "5667@test/A.t.sol", "2519@src/A.sol"]
+        );
+
+        let policy = Policy::new().allow("*");
+        let mut output = output;
+        output.errors[0].severity = Severity::Error;
+        assert_eq!(
+            codes(policy.check(&output).unwrap_err()),
+            ["2072@src/A.sol"]
+        );
+    }
+
+    #[test]
+    fn renders_excerpts() {
+        let renderer = Renderer::new().source("A.sol", SOURCE);
+        let start = SOURCE.find("\tuint y").unwrap() as i64 + 1;
+        let mut shadowing = error(start, start + 6);
+        shadowing.secondary_source_locations = vec![SecondarySourceLocation {
+            file: "A.sol".to_string(),
+            start: 17,
+            end: 24,
+            message: "The previous declaration is here:".to_string(),
+        }];
+        assert_eq!(
+            renderer.render(&shadowing),
+            "error[2333]: DeclarationError: Identifier already declared.\n\
+             \x20--> A.sol:4:2\n\
+             \x20 |\n\
+             4 | \tuint y;\n\
+             \x20 | \t^^^^^^\n\
+             note: The previous declaration is here:\n\
+             \x20--> A.sol:2:5\n\
+             \x20 |\n\
+             2 |     uint y;\n\
+             \x20 |     -------\n"
+        );
+
+        let start = SOURCE.find("function").unwrap() as i64;
+        let end = SOURCE.rfind('}').unwrap() as i64 - 1;
+        assert_eq!(
+            renderer.render(&error(start, end)),
+            "error[2333]: DeclarationError: Identifier already declared.\n\
+             \x20--> A.sol:3:5\n\
+             \x20 |\n\
+             3 |     function f() public {\n\
+             \x20 |     ^^^^^^^^^^^^^^^^^^^^^\n\
+             4 | \tuint y;\n\
+             \x20 | ^^^^^^^^\n\
+             5 |     }\n\
+             \x20 | ^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn renders_without_sources() {
+        let warning = Error {
+            r#type: "Warning".to_string(),
+            severity: Severity::Warning,
+            message: "Unused local variable.".to_string(),
+            source_location: Some(ErrorLocation {
+                file: "B.sol".to_string(),
+                start: -1,
+                end: -1,
+            }),
+            ..Default::default()
+        };
+        let renderer = Renderer::from_input(&StandardJsonInput::new().add_source("A.sol", SOURCE));
+        assert_eq!(
+            renderer.render_all([&warning, &error(0, 8)]),
+            "warning: Unused local variable.\n\
+             \x20--> B.sol\n\
+             \n\
+             error[2333]: DeclarationError: Identifier already declared.\n\
+             \x20--> A.sol:1:1\n\
+             \x20 |\n\
+             1 | contract A {\n\
+             \x20 | ^^^^^^^^\n"
+        );
+    }
+}
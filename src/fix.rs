@@ -0,0 +1,254 @@
+//! Text edits that fix common problems in sources.
+//!
+//! A [`TextEdit`] replaces a byte range of a source, given as a
+//! [`SourceLocation`] like the locations of the AST, so edits made from an
+//! AST apply to the source it was compiled from. The helpers here make the
+//! edits for sources without an SPDX license header and for pragmas that
+//! do not allow a compiler release, for formatters and fixers to apply
+//! with [`apply_edits`] or to offer to an editor.
+
+use semver::Version;
+
+use crate::ast::{SourceLocation, SourceUnit};
+use crate::pragma::{SourcePragma, VersionRequirement};
+
+/// A replacement of a range of a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range to replace, empty for an insertion.
+    pub range: SourceLocation,
+    /// The text to put in place of the range.
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Replace a range with text.
+    pub fn replace(range: SourceLocation, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Insert text at a byte offset of the source with an index.
+    pub fn insert(offset: usize, source_index: usize, text: impl Into<String>) -> Self {
+        Self::replace(
+            SourceLocation {
+                offset,
+                length: 0,
+                source_index,
+            },
+            text,
+        )
+    }
+}
+
+/// Apply edits to the content of a source.
+///
+/// The edits must be for the same source and must not overlap, since their
+/// ranges refer to the source before any of them is applied. Insertions at
+/// the same offset are applied in the order they are given, before a
+/// replacement starting there.
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> Result<String, EditError> {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.range.offset, edit.range.length));
+
+    let mut result = String::with_capacity(content.len());
+    let mut end = 0;
+    for edit in edits {
+        let range = &edit.range;
+        let start = range.offset;
+        if start < end {
+            return Err(EditError::Overlapping(range.clone()));
+        }
+        let Some(replaced_end) = start.checked_add(range.length) else {
+            return Err(EditError::OutOfRange(range.clone()));
+        };
+        if replaced_end > content.len() {
+            return Err(EditError::OutOfRange(range.clone()));
+        }
+        if !content.is_char_boundary(start) || !content.is_char_boundary(replaced_end) {
+            return Err(EditError::NotCharBoundary(range.clone()));
+        }
+        result.push_str(&content[end..start]);
+        result.push_str(&edit.replacement);
+        end = replaced_end;
+    }
+    result.push_str(&content[end..]);
+    Ok(result)
+}
+
+/// An error returned when edits cannot be applied to a source.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EditError {
+    /// The range of an edit ends past the end of the source.
+    #[error("edit at {}..{} is outside the source", .0.offset, .0.offset.saturating_add(.0.length))]
+    OutOfRange(SourceLocation),
+
+    /// The range of an edit starts before another edit ends.
+    #[error("edit at {}..{} overlaps another edit", .0.offset, .0.offset.saturating_add(.0.length))]
+    Overlapping(SourceLocation),
+
+    /// The range of an edit starts or ends inside a UTF-8 character.
+    #[error("edit at {}..{} splits a character", .0.offset, .0.offset.saturating_add(.0.length))]
+    NotCharBoundary(SourceLocation),
+}
+
+/// The edit that adds an SPDX license header to a source without one, or
+/// `None` if the source already states a license.
+///
+/// The header is inserted as the first line, as in
+/// `// SPDX-License-Identifier: MIT`.
+pub fn license_header(source_unit: &SourceUnit, license: &str) -> Option<TextEdit> {
+    if source_unit.license.is_some() {
+        return None;
+    }
+    Some(TextEdit::insert(
+        0,
+        source_unit.src.source_index,
+        format!("// SPDX-License-Identifier: {}\n", license),
+    ))
+}
+
+/// The edit that makes a pragma also allow a release and the later patch
+/// releases of it, or `None` if it already allows the release.
+///
+/// The pragma keeps the releases it allows, as in `pragma solidity
+/// >=0.6.2 <0.8.0 || ^0.8.30;`.
+pub fn widen_pragma(pragma: &SourcePragma, version: &Version) -> Option<TextEdit> {
+    if pragma.requirement.matches(version) {
+        return None;
+    }
+    Some(TextEdit::replace(
+        pragma.src.clone(),
+        format!("pragma solidity {} || ^{};", pragma.requirement, version),
+    ))
+}
+
+/// The edit that pins a pragma to one release it allows, or `None` if it
+/// does not allow the release or already only allows it.
+pub fn narrow_pragma(pragma: &SourcePragma, version: &Version) -> Option<TextEdit> {
+    let pinned = version.to_string();
+    let exact = VersionRequirement::parse(&pinned).ok()?;
+    if !pragma.requirement.matches(version)
+        || pragma.requirement.alternatives() == exact.alternatives()
+    {
+        return None;
+    }
+    Some(TextEdit::replace(
+        pragma.src.clone(),
+        format!("pragma solidity {};", pinned),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::ast::{PragmaDirective, SourceUnitNode};
+    use crate::pragma::PragmaReport;
+
+    const SOURCE: &str = "pragma solidity >=0.6.2 <0.8.0;\n\ncontract A {}\n";
+
+    fn source_unit() -> SourceUnit {
+        let literals = ["solidity", ">=", "0.6", ".2", "<", "0.8", ".0"];
+        SourceUnit {
+            absolute_path: PathBuf::from("A.sol"),
+            src: SourceLocation {
+                offset: 0,
+                length: SOURCE.len(),
+                source_index: 3,
+            },
+            nodes: vec![SourceUnitNode::PragmaDirective(PragmaDirective {
+                literals: literals.iter().map(ToString::to_string).collect(),
+                src: SourceLocation {
+                    offset: 0,
+                    length: 31,
+                    source_index: 3,
+                },
+                ..Default::default()
+            })],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fixes_license_and_pragma() {
+        let source_unit = source_unit();
+        let report = PragmaReport::from_source_units([&source_unit]).unwrap();
+        let pragma = &report.pragmas[0];
+
+        let license = license_header(&source_unit, "MIT").unwrap();
+        assert_eq!(license.range.source_index, 3);
+        let widen = widen_pragma(pragma, &Version::new(0, 8, 30)).unwrap();
+        assert_eq!(
+            apply_edits(SOURCE, &[widen, license.clone()]).unwrap(),
+            "// SPDX-License-Identifier: MIT\n\
+             pragma solidity >=0.6.2 <0.8.0 || ^0.8.30;\n\ncontract A {}\n"
+        );
+        assert_eq!(widen_pragma(pragma, &Version::new(0, 7, 6)), None);
+
+        let narrow = narrow_pragma(pragma, &Version::new(0, 7, 6)).unwrap();
+        assert_eq!(
+            apply_edits(SOURCE, &[narrow]).unwrap(),
+            "pragma solidity 0.7.6;\n\ncontract A {}\n"
+        );
+        assert_eq!(narrow_pragma(pragma, &Version::new(0, 8, 30)), None);
+        for pinned in ["0.7.6", "=0.7.6", " = 0.7.6"] {
+            let pinned = SourcePragma {
+                requirement: VersionRequirement::parse(pinned).unwrap(),
+                ..pragma.clone()
+            };
+            assert_eq!(narrow_pragma(&pinned, &Version::new(0, 7, 6)), None);
+        }
+
+        let licensed = SourceUnit {
+            license: Some("MIT".to_string()),
+            ..source_unit
+        };
+        assert_eq!(license_header(&licensed, "MIT"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_edits() {
+        let range = |offset, length| SourceLocation {
+            offset,
+            length,
+            source_index: 0,
+        };
+        assert_eq!(
+            apply_edits("abc", &[TextEdit::replace(range(2, 5), "x")]),
+            Err(EditError::OutOfRange(range(2, 5)))
+        );
+        assert_eq!(
+            apply_edits(
+                "abcdef",
+                &[
+                    TextEdit::replace(range(0, 3), "x"),
+                    TextEdit::replace(range(2, 2), "y")
+                ]
+            ),
+            Err(EditError::Overlapping(range(2, 2)))
+        );
+        assert_eq!(
+            apply_edits("é", &[TextEdit::insert(1, 0, "x")]),
+            Err(EditError::NotCharBoundary(range(1, 0)))
+        );
+        assert_eq!(
+            apply_edits(
+                "ab",
+                &[TextEdit::insert(1, 0, "x"), TextEdit::insert(1, 0, "y")]
+            ),
+            Ok("axyb".to_string())
+        );
+        assert_eq!(
+            EditError::Overlapping(range(1, usize::MAX)).to_string(),
+            format!("edit at 1..{} overlaps another edit", usize::MAX)
+        );
+        assert_eq!(
+            EditError::NotCharBoundary(range(1, usize::MAX)).to_string(),
+            format!("edit at 1..{} splits a character", usize::MAX)
+        );
+    }
+}
@@ -0,0 +1,399 @@
+//! Flattening a source and its imports into a single file.
+//!
+//! [`flatten`] follows the [`ImportDirective`]s of an entry source through an
+//! [`ImportResolver`], which supplies the text and AST of each source unit,
+//! usually from a compilation that selected the AST. The sources are written
+//! out so that every source comes after the sources it imports, with their
+//! imports, pragmas, and SPDX license comments removed. The pragmas are
+//! written once at the top of the file, and the licenses are merged into a
+//! single SPDX expression.
+//!
+//! Imports with aliases refer to declarations by names that no longer exist
+//! once the sources share a file, so they are reported as errors rather than
+//! flattened into a file that does not compile.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::ast::{ImportDirective, SourceUnit, SourceUnitNode, printer};
+use crate::graph::ImportCycle;
+
+/// The text and AST of a source unit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolvedSource {
+    /// The source text, which the source ranges of the AST refer to.
+    pub content: String,
+    /// The AST of the source, from the compiler output for the same text.
+    pub ast: SourceUnit,
+}
+
+/// Supplies the sources that a flattened source imports.
+///
+/// Closures taking a source unit name implement this trait, so a
+/// compilation can be flattened by looking up the source in the input and
+/// its AST in the output.
+pub trait ImportResolver {
+    /// The source with a source unit name, as in the `absolutePath` of an
+    /// import directive, or `None` if it is not known.
+    fn resolve(&mut self, path: &Path) -> Option<ResolvedSource>;
+}
+
+impl<F> ImportResolver for F
+where
+    F: FnMut(&Path) -> Option<ResolvedSource>,
+{
+    fn resolve(&mut self, path: &Path) -> Option<ResolvedSource> {
+        self(path)
+    }
+}
+
+/// An error returned when a source cannot be flattened.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FlattenError {
+    /// The resolver does not know a source.
+    #[error("source {} could not be resolved", .0.display())]
+    Unresolved(PathBuf),
+
+    /// A source imports a unit or symbols under an alias.
+    #[error("{} imports {} with an alias", importer.display(), import.display())]
+    AliasedImport { importer: PathBuf, import: PathBuf },
+
+    /// The sources import each other, so neither can come first.
+    #[error(transparent)]
+    Cycle(#[from] ImportCycle),
+}
+
+/// Flatten a source and everything it imports into a single file.
+pub fn flatten(
+    entry: impl AsRef<Path>,
+    resolver: &mut impl ImportResolver,
+) -> Result<String, FlattenError> {
+    let mut flattener = Flattener {
+        resolver,
+        sources: HashMap::new(),
+        stack: Vec::new(),
+        order: Vec::new(),
+    };
+    flattener.visit(entry.as_ref())?;
+    let sources: Vec<(&Path, &ResolvedSource)> = flattener
+        .order
+        .iter()
+        .map(|path| (path.as_path(), &flattener.sources[path]))
+        .collect();
+
+    let mut licenses: Vec<&str> = Vec::new();
+    let mut pragmas: Vec<String> = Vec::new();
+    for (_, source) in &sources {
+        if let Some(license) = source.ast.license.as_deref()
+            && !licenses.contains(&license)
+        {
+            licenses.push(license);
+        }
+        for node in &source.ast.nodes {
+            if let SourceUnitNode::PragmaDirective(pragma) = node {
+                let pragma = printer::print(pragma).trim_end().to_string();
+                if !pragmas.contains(&pragma) {
+                    pragmas.push(pragma);
+                }
+            }
+        }
+    }
+
+    let mut flattened = String::new();
+    if !licenses.is_empty() {
+        flattened.push_str(&format!(
+            "// SPDX-License-Identifier: {}\n",
+            merge_licenses(&licenses)
+        ));
+    }
+    for pragma in &pragmas {
+        flattened.push_str(pragma);
+        flattened.push('\n');
+    }
+    for (path, source) in sources {
+        if !flattened.is_empty() {
+            flattened.push('\n');
+        }
+        flattened.push_str(&format!("// File: {}\n", path.display()));
+        let body = strip(source);
+        if !body.is_empty() {
+            flattened.push('\n');
+            flattened.push_str(&body);
+            flattened.push('\n');
+        }
+    }
+    Ok(flattened)
+}
+
+/// Resolves sources depth first, ordering each after its imports.
+struct Flattener<'r, R> {
+    resolver: &'r mut R,
+    sources: HashMap<PathBuf, ResolvedSource>,
+    /// The sources being visited, each imported by the one before it.
+    stack: Vec<PathBuf>,
+    order: Vec<PathBuf>,
+}
+
+impl<R: ImportResolver> Flattener<'_, R> {
+    fn visit(&mut self, path: &Path) -> Result<(), FlattenError> {
+        if self.sources.contains_key(path) {
+            if let Some(position) = self.stack.iter().position(|source| source == path) {
+                return Err(ImportCycle(self.stack[position..].to_vec()).into());
+            }
+            return Ok(());
+        }
+        let source = self
+            .resolver
+            .resolve(path)
+            .ok_or_else(|| FlattenError::Unresolved(path.to_path_buf()))?;
+        let imports = imports(&source.ast)
+            .map(|import| {
+                if is_aliased(import) {
+                    Err(FlattenError::AliasedImport {
+                        importer: path.to_path_buf(),
+                        import: import.absolute_path.clone(),
+                    })
+                } else {
+                    Ok(import.absolute_path.clone())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.sources.insert(path.to_path_buf(), source);
+
+        self.stack.push(path.to_path_buf());
+        for import in imports {
+            self.visit(&import)?;
+        }
+        self.stack.pop();
+        self.order.push(path.to_path_buf());
+        Ok(())
+    }
+}
+
+fn imports(ast: &SourceUnit) -> impl Iterator<Item = &ImportDirective> {
+    ast.nodes.iter().filter_map(|node| match node {
+        SourceUnitNode::ImportDirective(import) => Some(import),
+        _ => None,
+    })
+}
+
+fn is_aliased(import: &ImportDirective) -> bool {
+    import.is_aliased()
+        || import
+            .imported_symbols()
+            .any(|(name, local_name)| name != local_name)
+}
+
+/// The SPDX expression that all licenses apply under.
+fn merge_licenses(licenses: &[&str]) -> String {
+    if let [license] = licenses {
+        return license.to_string();
+    }
+    licenses
+        .iter()
+        .map(|license| {
+            if license.contains(' ') {
+                format!("({})", license)
+            } else {
+                license.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// The text of a source without its pragmas, imports, and license comment,
+/// and with runs of blank lines they leave behind collapsed.
+fn strip(source: &ResolvedSource) -> String {
+    let mut ranges: Vec<(usize, usize)> = source
+        .ast
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            SourceUnitNode::PragmaDirective(pragma) => Some(&pragma.src),
+            SourceUnitNode::ImportDirective(import) => Some(&import.src),
+            _ => None,
+        })
+        .map(|src| (src.offset, src.offset + src.length))
+        .collect();
+    ranges.sort_unstable();
+
+    let mut content = String::with_capacity(source.content.len());
+    let mut position = 0;
+    for (start, end) in ranges {
+        if let Some(kept) = source.content.get(position..start) {
+            content.push_str(kept);
+            position = end;
+        }
+    }
+    content.push_str(source.content.get(position..).unwrap_or_default());
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end();
+        if line.contains("SPDX-License-Identifier:") {
+            continue;
+        }
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::ast::{Identifier, PragmaDirective, SourceLocation, SymbolAlias};
+
+    /// A source whose AST has the pragmas and imports of its text.
+    fn source(content: &str, license: Option<&str>) -> ResolvedSource {
+        let location = |statement: &str| {
+            let offset = content.find(statement).unwrap();
+            SourceLocation {
+                offset,
+                length: statement.len(),
+                source_index: 0,
+            }
+        };
+        let mut nodes = Vec::new();
+        for segment in content.split_inclusive(';') {
+            let Some(start) = segment.find("pragma ").or_else(|| segment.find("import ")) else {
+                continue;
+            };
+            let statement = &segment[start..];
+            if let Some(literals) = statement.strip_prefix("pragma ") {
+                nodes.push(SourceUnitNode::PragmaDirective(PragmaDirective {
+                    literals: literals
+                        .trim_end_matches(';')
+                        .split(' ')
+                        .map(str::to_string)
+                        .collect(),
+                    src: location(statement),
+                    ..Default::default()
+                }));
+            } else if let Some(rest) = statement.strip_prefix("import ") {
+                let path = rest.split('"').nth(1).unwrap();
+                let symbol_aliases = match rest.split_once(" as ") {
+                    Some((name, local)) if name.starts_with('{') => vec![SymbolAlias {
+                        foreign: Identifier {
+                            name: name.trim_start_matches('{').to_string(),
+                            ..Default::default()
+                        },
+                        local: Some(local.split('}').next().unwrap().to_string()),
+                        ..Default::default()
+                    }],
+                    _ => Vec::new(),
+                };
+                nodes.push(SourceUnitNode::ImportDirective(ImportDirective {
+                    absolute_path: PathBuf::from(path),
+                    file: PathBuf::from(path),
+                    symbol_aliases,
+                    src: location(statement),
+                    ..Default::default()
+                }));
+            }
+        }
+        ResolvedSource {
+            content: content.to_string(),
+            ast: SourceUnit {
+                nodes,
+                license: license.map(str::to_string),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn resolver(
+        sources: BTreeMap<&'static str, ResolvedSource>,
+    ) -> impl FnMut(&Path) -> Option<ResolvedSource> {
+        move |path| sources.get(path.to_str()?).cloned()
+    }
+
+    #[test]
+    fn flattens_in_dependency_order() {
+        let sources = BTreeMap::from([
+            (
+                "Vault.sol",
+                source(
+                    "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.20;\n\nimport \"Token.sol\";\nimport \"Math.sol\";\n\ncontract Vault is Token {}\n",
+                    Some("MIT"),
+                ),
+            ),
+            (
+                "Token.sol",
+                source(
+                    "// SPDX-License-Identifier: GPL-2.0-or-later OR MIT\npragma solidity ^0.8.20;\npragma abicoder v2;\nimport \"Math.sol\";\n\ncontract Token {}\n",
+                    Some("GPL-2.0-or-later OR MIT"),
+                ),
+            ),
+            (
+                "Math.sol",
+                source(
+                    "// SPDX-License-Identifier: MIT\npragma solidity >=0.8.0;\n\nlibrary Math {}\n",
+                    Some("MIT"),
+                ),
+            ),
+        ]);
+        let flattened = flatten("Vault.sol", &mut resolver(sources)).unwrap();
+        assert_eq!(
+            flattened,
+            "// SPDX-License-Identifier: MIT AND (GPL-2.0-or-later OR MIT)\n\
+             pragma solidity >=0.8.0;\n\
+             pragma solidity ^0.8.20;\n\
+             pragma abicoder v2;\n\
+             \n\
+             // File: Math.sol\n\
+             \n\
+             library Math {}\n\
+             \n\
+             // File: Token.sol\n\
+             \n\
+             contract Token {}\n\
+             \n\
+             // File: Vault.sol\n\
+             \n\
+             contract Vault is Token {}\n"
+        );
+    }
+
+    #[test]
+    fn reports_cycles_and_aliases() {
+        let sources = BTreeMap::from([
+            ("A.sol", source("import \"B.sol\";\ncontract A {}\n", None)),
+            ("B.sol", source("import \"C.sol\";\ncontract B {}\n", None)),
+            ("C.sol", source("import \"B.sol\";\ncontract C {}\n", None)),
+        ]);
+        assert_eq!(
+            flatten("A.sol", &mut resolver(sources)),
+            Err(FlattenError::Cycle(ImportCycle(vec![
+                PathBuf::from("B.sol"),
+                PathBuf::from("C.sol"),
+            ])))
+        );
+
+        let sources = BTreeMap::from([(
+            "A.sol",
+            source("import {B as Base} from \"B.sol\";\ncontract A {}\n", None),
+        )]);
+        assert_eq!(
+            flatten("A.sol", &mut resolver(sources)),
+            Err(FlattenError::AliasedImport {
+                importer: PathBuf::from("A.sol"),
+                import: PathBuf::from("B.sol"),
+            })
+        );
+
+        let sources = BTreeMap::from([("A.sol", source("import \"B.sol\";\n", None))]);
+        assert_eq!(
+            flatten("A.sol", &mut resolver(sources)),
+            Err(FlattenError::Unresolved(PathBuf::from("B.sol")))
+        );
+    }
+}
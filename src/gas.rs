@@ -0,0 +1,358 @@
+//! Compare gas estimates and code sizes between two compilations.
+//!
+//! [`GasDiff`] compares two outputs of the same project, for example before
+//! and after a change, and reports every function whose estimated cost
+//! changed and every contract whose code size changed. Its [`Display`]
+//! implementation renders a Markdown table, ready to post as a pull request
+//! comment.
+//!
+//! [`Display`]: std::fmt::Display
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::standard_json_output::{Contract, GasEstimate, StandardJsonOutput};
+
+/// What a gas estimate in a [`GasChange`] measures.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GasItem {
+    /// Deploying the contract.
+    Creation,
+    /// Calling an external function, by signature.
+    External(String),
+    /// Running an internal function, by signature.
+    Internal(String),
+}
+
+impl fmt::Display for GasItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasItem::Creation => f.write_str("(creation)"),
+            GasItem::External(signature) => f.write_str(signature),
+            GasItem::Internal(signature) => write!(f, "{} (internal)", signature),
+        }
+    }
+}
+
+/// A gas estimate that differs between two compilations.
+///
+/// `old` or `new` is `None` when the item only exists in one of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasChange {
+    /// The source file of the contract.
+    pub file: String,
+    /// The contract name.
+    pub contract: String,
+    /// What the estimate measures.
+    pub item: GasItem,
+    /// The estimate in the old compilation.
+    pub old: Option<GasEstimate>,
+    /// The estimate in the new compilation.
+    pub new: Option<GasEstimate>,
+}
+
+impl GasChange {
+    /// Whether the item became more expensive.
+    ///
+    /// A finite estimate becoming unbounded counts as a regression. Added and
+    /// removed items do not.
+    pub fn is_regression(&self) -> bool {
+        matches!((self.old, self.new), (Some(old), Some(new)) if new > old)
+    }
+
+    /// The change in gas, when both estimates are finite.
+    pub fn delta(&self) -> Option<i128> {
+        match (self.old, self.new) {
+            (Some(GasEstimate::Finite(old)), Some(GasEstimate::Finite(new))) => {
+                Some(i128::from(new) - i128::from(old))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Which bytecode a [`SizeChange`] measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CodeKind {
+    /// The creation bytecode, limited to 49152 bytes by EIP-3860.
+    Creation,
+    /// The runtime bytecode, limited to 24576 bytes by EIP-170.
+    Runtime,
+}
+
+/// A bytecode size in bytes that differs between two compilations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SizeChange {
+    /// The source file of the contract.
+    pub file: String,
+    /// The contract name.
+    pub contract: String,
+    /// Which bytecode was measured.
+    pub kind: CodeKind,
+    /// The size in the old compilation.
+    pub old: Option<usize>,
+    /// The size in the new compilation.
+    pub new: Option<usize>,
+}
+
+impl SizeChange {
+    /// Whether the code grew.
+    pub fn is_regression(&self) -> bool {
+        matches!((self.old, self.new), (Some(old), Some(new)) if new > old)
+    }
+}
+
+/// The gas and code size differences between two compilations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasDiff {
+    /// Changed gas estimates, ordered by file, contract, and item.
+    pub gas: Vec<GasChange>,
+    /// Changed code sizes, ordered by file, contract, and kind.
+    pub sizes: Vec<SizeChange>,
+}
+
+impl GasDiff {
+    /// Compare the gas estimates and code sizes of two outputs.
+    ///
+    /// Contracts are matched by source file and name. Only estimates and
+    /// sizes that were selected in both outputs, or in one of them for added
+    /// and removed contracts, can be compared.
+    pub fn new(old: &StandardJsonOutput, new: &StandardJsonOutput) -> Self {
+        let mut diff = GasDiff::default();
+        let old_contracts = contracts(old);
+        let new_contracts = contracts(new);
+        let mut keys: Vec<_> = old_contracts.keys().chain(new_contracts.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let old = old_contracts.get(key).copied();
+            let new = new_contracts.get(key).copied();
+            let (file, contract) = key;
+
+            let old_gas = old.map(gas_items).unwrap_or_default();
+            let new_gas = new.map(gas_items).unwrap_or_default();
+            let mut items: Vec<_> = old_gas.keys().chain(new_gas.keys()).collect();
+            items.sort();
+            items.dedup();
+            for item in items {
+                let change = GasChange {
+                    file: file.to_string(),
+                    contract: contract.to_string(),
+                    item: item.clone(),
+                    old: old_gas.get(item).copied(),
+                    new: new_gas.get(item).copied(),
+                };
+                if change.old != change.new {
+                    diff.gas.push(change);
+                }
+            }
+
+            for kind in [CodeKind::Creation, CodeKind::Runtime] {
+                let change = SizeChange {
+                    file: file.to_string(),
+                    contract: contract.to_string(),
+                    kind,
+                    old: old.and_then(|c| code_size(c, kind)),
+                    new: new.and_then(|c| code_size(c, kind)),
+                };
+                if change.old != change.new {
+                    diff.sizes.push(change);
+                }
+            }
+        }
+        diff
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.gas.is_empty() && self.sizes.is_empty()
+    }
+
+    /// The gas estimates that increased.
+    pub fn regressions(&self) -> impl Iterator<Item = &GasChange> {
+        self.gas.iter().filter(|change| change.is_regression())
+    }
+
+    /// Whether any gas estimate or code size increased.
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some() || self.sizes.iter().any(SizeChange::is_regression)
+    }
+}
+
+impl fmt::Display for GasDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+        if !self.gas.is_empty() {
+            writeln!(f, "| Contract | Function | Before | After | Change |")?;
+            writeln!(f, "| --- | --- | ---: | ---: | ---: |")?;
+            for change in &self.gas {
+                let delta = match change.delta() {
+                    Some(delta) => format!("{:+}", delta),
+                    None => "".to_string(),
+                };
+                writeln!(
+                    f,
+                    "| {}:{} | {} | {} | {} | {} |",
+                    change.file,
+                    change.contract,
+                    change.item,
+                    show(change.old.map(|gas| gas.to_string())),
+                    show(change.new.map(|gas| gas.to_string())),
+                    delta,
+                )?;
+            }
+        }
+
+        if !self.sizes.is_empty() {
+            if !self.gas.is_empty() {
+                writeln!(f)?;
+            }
+            writeln!(f, "| Contract | Code | Before | After | Change |")?;
+            writeln!(f, "| --- | --- | ---: | ---: | ---: |")?;
+            for change in &self.sizes {
+                let delta = match (change.old, change.new) {
+                    (Some(old), Some(new)) => match new.cmp(&old) {
+                        Ordering::Greater => format!("+{}", new - old),
+                        Ordering::Less => format!("-{}", old - new),
+                        Ordering::Equal => "0".to_string(),
+                    },
+                    _ => "".to_string(),
+                };
+                let kind = match change.kind {
+                    CodeKind::Creation => "creation",
+                    CodeKind::Runtime => "runtime",
+                };
+                writeln!(
+                    f,
+                    "| {}:{} | {} | {} | {} | {} |",
+                    change.file,
+                    change.contract,
+                    kind,
+                    show(change.old.map(|size| size.to_string())),
+                    show(change.new.map(|size| size.to_string())),
+                    delta,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn contracts(output: &StandardJsonOutput) -> BTreeMap<(&str, &str), &Contract> {
+    output
+        .contracts
+        .iter()
+        .flat_map(|(file, contracts)| {
+            contracts
+                .iter()
+                .map(move |(name, contract)| ((file.as_str(), name.as_str()), contract))
+        })
+        .collect()
+}
+
+fn gas_items(contract: &Contract) -> BTreeMap<GasItem, GasEstimate> {
+    let mut items = BTreeMap::new();
+    let Some(estimates) = contract
+        .evm
+        .as_ref()
+        .and_then(|evm| evm.gas_estimates.as_ref())
+    else {
+        return items;
+    };
+    if let Some(creation) = &estimates.creation {
+        items.insert(GasItem::Creation, creation.total_cost);
+    }
+    for (signature, gas) in &estimates.external {
+        items.insert(GasItem::External(signature.clone()), *gas);
+    }
+    for (signature, gas) in &estimates.internal {
+        items.insert(GasItem::Internal(signature.clone()), *gas);
+    }
+    items
+}
+
+/// The size in bytes of the code of a contract.
+///
+/// An unlinked library placeholder is 40 characters, as long as the hex of
+/// the 20-byte address that replaces it, so it counts as that address.
+fn code_size(contract: &Contract, kind: CodeKind) -> Option<usize> {
+    let evm = contract.evm.as_ref()?;
+    let bytecode = match kind {
+        CodeKind::Creation => evm.bytecode.as_ref()?,
+        CodeKind::Runtime => &evm.deployed_bytecode.as_ref()?.bytecode,
+    };
+    let object = bytecode
+        .object
+        .strip_prefix("0x")
+        .unwrap_or(&bytecode.object);
+    Some(object.len() / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(transfer: &str, mint: Option<&str>, runtime: &str) -> StandardJsonOutput {
+        let mint = mint
+            .map(|gas| format!(r#", "mint(uint256)": "{}""#, gas))
+            .unwrap_or_default();
+        let json = format!(
+            r#"{{"contracts": {{"Token.sol": {{"Token": {{"evm": {{
+                "deployedBytecode": {{"object": "{}"}},
+                "gasEstimates": {{
+                    "creation": {{"codeDepositCost": "200", "executionCost": "100", "totalCost": "300"}},
+                    "external": {{"transfer(address,uint256)": "{}"{}}}
+                }}
+            }}}}}}}}}}"#,
+            runtime, transfer, mint
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn diff_gas_and_sizes() {
+        let old = output("5000", Some("infinite"), "6080");
+        let new = output("5200", None, "608060");
+        let diff = GasDiff::new(&old, &new);
+
+        assert_eq!(diff.gas.len(), 2);
+        let transfer = &diff.gas[1];
+        assert_eq!(
+            transfer.item,
+            GasItem::External("transfer(address,uint256)".to_string())
+        );
+        assert_eq!(transfer.delta(), Some(200));
+        assert_eq!(diff.regressions().count(), 1);
+        assert_eq!(diff.gas[0].new, None);
+
+        assert_eq!(diff.sizes.len(), 1);
+        assert_eq!(diff.sizes[0].kind, CodeKind::Runtime);
+        assert!(diff.has_regressions());
+
+        let table = diff.to_string();
+        assert!(
+            table.contains("| Token.sol:Token | transfer(address,uint256) | 5000 | 5200 | +200 |")
+        );
+        assert!(table.contains("| Token.sol:Token | mint(uint256) | infinite | - |  |"));
+        assert!(table.contains("| Token.sol:Token | runtime | 2 | 3 | +1 |"));
+
+        assert!(GasDiff::new(&old, &old).is_empty());
+
+        let prefixed = output("5000", Some("infinite"), "0x6080");
+        assert!(GasDiff::new(&old, &prefixed).sizes.is_empty());
+        let placeholder = crate::standard_json_output::library_placeholder("L.sol", "L");
+        let unlinked = output("5000", Some("infinite"), &format!("73{}", placeholder));
+        let diff = GasDiff::new(&old, &unlinked);
+        assert_eq!(diff.sizes[0].new, Some(21));
+    }
+
+    #[test]
+    fn unbounded_estimate_is_regression() {
+        let diff = GasDiff::new(&output("5000", None, ""), &output("infinite", None, ""));
+        assert!(diff.gas[0].is_regression());
+        assert_eq!(diff.gas[0].delta(), None);
+    }
+}
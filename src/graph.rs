@@ -0,0 +1,433 @@
+//! The import graph of a set of Solidity sources.
+//!
+//! Imports are read from the source text without running the compiler, and
+//! resolved the way solc resolves them: paths starting with `./` or `../` are
+//! relative to the importing file, remappings are applied to all other paths,
+//! and the result is a source unit name in the input.
+//!
+//! Build systems can use [`ImportGraph`] to decide what to recompile: when a
+//! source changes, [`ImportGraph::dependents_of`] lists the sources whose
+//! output may change with it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+
+/// The imports between the sources of an input.
+///
+/// Imports of paths that are not sources of the input are kept as edges, but
+/// those paths are not nodes of the graph.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportGraph {
+    imports: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl ImportGraph {
+    /// Build the graph of the sources in an input.
+    ///
+    /// Sources given as URLs have no text to read imports from, so they are
+    /// nodes without edges.
+    pub fn from_input(input: &StandardJsonInput) -> Self {
+        let remappings = input
+            .settings
+            .remappings
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|remapping| Remapping::parse(remapping))
+            .collect::<Vec<_>>();
+
+        let mut graph = ImportGraph::default();
+        for (path, source) in &input.sources {
+            let imports = match &source.content {
+                SourceContent::Content { content } => parse_imports(content)
+                    .into_iter()
+                    .map(|import| resolve_import(path, &import, &remappings))
+                    .collect(),
+                SourceContent::Urls { .. } => BTreeSet::new(),
+            };
+            graph.imports.insert(path.clone(), imports);
+        }
+        graph
+    }
+
+    /// The sources in the graph, in path order.
+    pub fn sources(&self) -> impl Iterator<Item = &Path> {
+        self.imports.keys().map(PathBuf::as_path)
+    }
+
+    /// The paths a source imports directly.
+    pub fn imports_of(&self, path: &Path) -> impl Iterator<Item = &Path> {
+        self.imports
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+    }
+
+    /// The sources that import a path directly.
+    pub fn importers_of<'a>(&'a self, path: &Path) -> impl Iterator<Item = &'a Path> {
+        self.imports
+            .iter()
+            .filter(move |(_, imports)| imports.contains(path))
+            .map(|(source, _)| source.as_path())
+    }
+
+    /// The sources that import a path, directly or indirectly.
+    ///
+    /// These are the sources to recompile when the path changes, in addition
+    /// to the path itself. The path is only included if it imports itself
+    /// through a cycle.
+    pub fn dependents_of(&self, path: &Path) -> BTreeSet<&Path> {
+        let mut dependents = BTreeSet::new();
+        let mut pending: Vec<&Path> = self.importers_of(path).collect();
+        dependents.extend(pending.iter().copied());
+        while let Some(path) = pending.pop() {
+            for importer in self.importers_of(path) {
+                if dependents.insert(importer) {
+                    pending.push(importer);
+                }
+            }
+        }
+        dependents
+    }
+
+    /// The sources ordered so that every source comes after its imports.
+    ///
+    /// Sources that do not depend on each other are in path order. If the
+    /// imports have a cycle there is no such order, and the sources on or
+    /// behind the cycle are returned as the error.
+    pub fn topological_order(&self) -> Result<Vec<&Path>, ImportCycle> {
+        let mut remaining: BTreeMap<&Path, usize> = self
+            .imports
+            .iter()
+            .map(|(path, imports)| {
+                let count = imports
+                    .iter()
+                    .filter(|import| self.imports.contains_key(*import))
+                    .count();
+                (path.as_path(), count)
+            })
+            .collect();
+
+        let mut order = Vec::with_capacity(remaining.len());
+        let mut ready: BTreeSet<&Path> = remaining
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(path, _)| *path)
+            .collect();
+        while let Some(path) = ready.pop_first() {
+            remaining.remove(path);
+            order.push(path);
+            for importer in self.importers_of(path) {
+                if let Some(count) = remaining.get_mut(importer) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(importer);
+                    }
+                }
+            }
+        }
+
+        if remaining.is_empty() {
+            Ok(order)
+        } else {
+            Err(ImportCycle(
+                remaining.into_keys().map(Path::to_path_buf).collect(),
+            ))
+        }
+    }
+
+    /// A source and every path it imports, directly or indirectly.
+    pub fn closure(&self, path: &Path) -> BTreeSet<PathBuf> {
+        let mut closure = BTreeSet::new();
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            if closure.insert(path.clone()) {
+                pending.extend(self.imports_of(&path).map(Path::to_path_buf));
+            }
+        }
+        closure
+    }
+}
+
+/// The sources that could not be ordered because their imports form a cycle.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("import cycle between {}", display_paths(.0))]
+pub struct ImportCycle(pub Vec<PathBuf>);
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A remapping of the form `context:prefix=target`.
+pub(crate) struct Remapping {
+    context: String,
+    prefix: String,
+    target: String,
+}
+
+impl Remapping {
+    pub(crate) fn parse(remapping: &str) -> Option<Self> {
+        let (left, target) = remapping.split_once('=')?;
+        let (context, prefix) = left.split_once(':').unwrap_or(("", left));
+        if prefix.is_empty() {
+            return None;
+        }
+        Some(Self {
+            context: context.to_string(),
+            prefix: prefix.to_string(),
+            target: target.to_string(),
+        })
+    }
+}
+
+/// Resolve an import path to a source unit name.
+pub(crate) fn resolve_import(importer: &Path, import: &str, remappings: &[Remapping]) -> PathBuf {
+    if import.starts_with("./") || import.starts_with("../") {
+        let base = importer.parent().unwrap_or(Path::new(""));
+        return normalize_path(&base.join(import));
+    }
+
+    // The longest matching context wins, then the longest prefix.
+    let importer = importer.to_string_lossy();
+    let remapping = remappings
+        .iter()
+        .filter(|r| importer.starts_with(&r.context) && import.starts_with(&r.prefix))
+        .max_by_key(|r| (r.context.len(), r.prefix.len()));
+    match remapping {
+        Some(r) => PathBuf::from(format!("{}{}", r.target, &import[r.prefix.len()..])),
+        None => PathBuf::from(import),
+    }
+}
+
+/// Remove `.` and `..` segments without touching the file system.
+///
+/// A `..` that would go above the start of a relative path is kept, so
+/// `../x.sol` imported from a file at the root stays `../x.sol`. Above the
+/// root of an absolute path there is nothing, so it is dropped there.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(Component::ParentDir),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Read the import paths of a source.
+///
+/// Comments are skipped, and the path is the string literal of each import
+/// statement, whichever of its forms is used.
+pub(crate) fn parse_imports(source: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut tokens = Tokens::new(source);
+    while let Some(token) = tokens.next() {
+        if token != Token::Word("import") {
+            continue;
+        }
+        let mut path = None;
+        for token in tokens.by_ref() {
+            match token {
+                Token::String(literal) => path = Some(literal),
+                Token::Semicolon => break,
+                _ => {}
+            }
+        }
+        if let Some(path) = path {
+            imports.push(path.to_string());
+        }
+    }
+    imports
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Word(&'a str),
+    String(&'a str),
+    Semicolon,
+    Other,
+}
+
+/// A minimal Solidity tokenizer that knows about comments and strings.
+pub(crate) struct Tokens<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+
+    /// The byte offset in the source just past the last token.
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let rest = &self.source[self.position..];
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("//") {
+                self.position += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                self.position += comment.find("*/").map_or(trimmed.len(), |end| end + 4);
+            } else {
+                break;
+            }
+        }
+
+        let rest = &self.source[self.position..];
+        let first = rest.chars().next()?;
+        if first == '"' || first == '\'' {
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|&(_, c)| {
+                    let end = c == first && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    end
+                })
+                .map_or(rest.len(), |(index, _)| index + 1);
+            self.position += (end + 1).min(rest.len());
+            return Some(Token::String(&rest[1..end]));
+        }
+        if first.is_alphanumeric() || first == '_' || first == '$' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len());
+            self.position += end;
+            return Some(Token::Word(&rest[..end]));
+        }
+        self.position += first.len_utf8();
+        Some(if first == ';' {
+            Token::Semicolon
+        } else {
+            Token::Other
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_import_forms() {
+        let source = r#"
+            // import "commented.sol";
+            /* import "block.sol"; */
+            import "./A.sol";
+            import * as B from '../B.sol';
+            import {C, D as E} from "lib/C.sol";
+            import "F.sol" as F;
+            string constant s = "import \"G.sol\";";
+        "#;
+        assert_eq!(
+            parse_imports(source),
+            vec!["./A.sol", "../B.sol", "lib/C.sol", "F.sol"]
+        );
+    }
+
+    #[test]
+    fn resolve_imports() {
+        let mut input = StandardJsonInput::new()
+            .add_source(
+                "src/token/Token.sol",
+                r#"import "./IERC20.sol"; import "../utils/Math.sol"; import "@oz/Ownable.sol";"#,
+            )
+            .add_source("src/token/IERC20.sol", "")
+            .add_source("src/utils/Math.sol", "");
+        input.settings.remappings = Some(vec!["@oz/=lib/openzeppelin/".to_string()]);
+
+        let graph = ImportGraph::from_input(&input);
+        let imports: Vec<&Path> = graph.imports_of(Path::new("src/token/Token.sol")).collect();
+        assert_eq!(
+            imports,
+            vec![
+                Path::new("lib/openzeppelin/Ownable.sol"),
+                Path::new("src/token/IERC20.sol"),
+                Path::new("src/utils/Math.sol"),
+            ]
+        );
+        assert_eq!(graph.closure(Path::new("src/token/Token.sol")).len(), 4);
+
+        let resolve =
+            |importer: &str, import: &str| resolve_import(Path::new(importer), import, &[]);
+        assert_eq!(resolve("A.sol", "../x.sol"), Path::new("../x.sol"));
+        assert_eq!(
+            resolve("a/A.sol", "../../../x.sol"),
+            Path::new("../../x.sol")
+        );
+        assert_eq!(resolve("a/b/A.sol", "./../x.sol"), Path::new("a/x.sol"));
+        assert_eq!(resolve("/a/A.sol", "../../x.sol"), Path::new("/x.sol"));
+    }
+
+    #[test]
+    fn dependents_and_order() {
+        let input = StandardJsonInput::new()
+            .add_source(
+                "Token.sol",
+                r#"import "./Math.sol"; import "./IERC20.sol";"#,
+            )
+            .add_source("Vault.sol", r#"import "./Token.sol";"#)
+            .add_source("Math.sol", "")
+            .add_source("IERC20.sol", r#"import "./Missing.sol";"#);
+        let graph = ImportGraph::from_input(&input);
+
+        let dependents: Vec<&Path> = graph
+            .dependents_of(Path::new("Math.sol"))
+            .into_iter()
+            .collect();
+        assert_eq!(
+            dependents,
+            vec![Path::new("Token.sol"), Path::new("Vault.sol")]
+        );
+        assert!(graph.dependents_of(Path::new("Vault.sol")).is_empty());
+
+        assert_eq!(
+            graph.topological_order().unwrap(),
+            vec![
+                Path::new("IERC20.sol"),
+                Path::new("Math.sol"),
+                Path::new("Token.sol"),
+                Path::new("Vault.sol"),
+            ]
+        );
+
+        let input = input
+            .add_source("A.sol", r#"import "./B.sol";"#)
+            .add_source("B.sol", r#"import "./A.sol";"#);
+        assert_eq!(
+            ImportGraph::from_input(&input).topological_order(),
+            Err(ImportCycle(vec![
+                PathBuf::from("A.sol"),
+                PathBuf::from("B.sol")
+            ]))
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Split large compilations into smaller jobs.
+//!
+//! The compiler holds every source of an input in memory at once, and a
+//! single invocation uses a single core. [`split_input`] partitions the
+//! sources of a large input into several inputs along the import graph, so
+//! each job stays small and jobs can run in parallel. [`CompileJobs`] runs
+//! many inputs on a bounded number of threads, such as the jobs of a split
+//! input or one input per group of sources that need the same compiler
+//! release.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::compiler::{CompileOutcome, CompileTimings, Compiler, compile_timed};
+use crate::graph::ImportGraph;
+use crate::standard_json_input::StandardJsonInput;
+use crate::standard_json_output::StandardJsonOutput;
+
+/// Partition an input into jobs of at most `max_sources` sources each.
+///
+/// A source can only be compiled together with everything it imports, so
+/// each job holds some entry points (sources no other source imports) and
+/// their imports. Entry points that share dependencies are put in the same
+/// job where they fit, so shared sources are compiled as few times as
+/// possible. An entry point whose imports alone exceed the limit gets a job
+/// of its own. Together the jobs cover every source, and every job keeps the
+/// settings of the input.
+///
+/// Sources shared between jobs appear in the output of each of them. Combine
+/// the outputs with [`StandardJsonOutput::merge`] and then
+/// [`StandardJsonOutput::dedup_errors`].
+///
+/// [`StandardJsonOutput::merge`]: crate::standard_json_output::StandardJsonOutput::merge
+/// [`StandardJsonOutput::dedup_errors`]: crate::standard_json_output::StandardJsonOutput::dedup_errors
+pub fn split_input(input: &StandardJsonInput, max_sources: usize) -> Vec<StandardJsonInput> {
+    let max_sources = max_sources.max(1);
+    let graph = ImportGraph::from_input(input);
+
+    let imported: BTreeSet<&Path> = graph
+        .sources()
+        .flat_map(|path| graph.imports_of(path))
+        .collect();
+    let mut roots: Vec<PathBuf> = graph
+        .sources()
+        .filter(|path| !imported.contains(path))
+        .map(|path| path.to_path_buf())
+        .collect();
+
+    // Sources in an import cycle that nothing else imports have no entry
+    // point, so they become entry points themselves.
+    let covered: BTreeSet<PathBuf> = roots.iter().flat_map(|root| graph.closure(root)).collect();
+    roots.extend(
+        graph
+            .sources()
+            .filter(|path| !covered.contains(*path))
+            .map(|path| path.to_path_buf()),
+    );
+
+    // Only sources in the input can be sent to the compiler. Missing imports
+    // are left for the compiler to resolve or report.
+    let mut closures: Vec<BTreeSet<PathBuf>> = roots
+        .iter()
+        .map(|root| {
+            graph
+                .closure(root)
+                .into_iter()
+                .filter(|path| input.sources.contains_key(path))
+                .collect()
+        })
+        .collect();
+    closures.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+    let mut jobs: Vec<BTreeSet<PathBuf>> = Vec::new();
+    for closure in closures {
+        let best = jobs
+            .iter_mut()
+            .map(|job| (job.union(&closure).count(), job))
+            .filter(|(size, _)| *size <= max_sources)
+            .min_by_key(|(size, _)| *size);
+        match best {
+            Some((_, job)) => job.extend(closure),
+            None => jobs.push(closure),
+        }
+    }
+
+    jobs.into_iter()
+        .map(|sources| StandardJsonInput {
+            language: input.language.clone(),
+            sources: input
+                .sources
+                .iter()
+                .filter(|(path, _)| sources.contains(*path))
+                .map(|(path, source)| (path.clone(), source.clone()))
+                .collect(),
+            settings: input.settings.clone(),
+        })
+        .collect()
+}
+
+/// Inputs to compile concurrently, each with the backend to compile it and
+/// an id to find its output by.
+///
+/// Each job can use a different backend, such as the compiler release its
+/// sources require. Pass `&Solc` or another reference to share one backend
+/// between jobs.
+///
+/// ```no_run
+/// use solc::StandardJsonInput;
+/// use solc::compiler::Solc;
+/// use solc::jobs::CompileJobs;
+///
+/// let solc = Solc::new("solc");
+/// let results = CompileJobs::new()
+///     .workers(4)
+///     .add("a", &solc, StandardJsonInput::new().add_source("A.sol", "contract A {}"))
+///     .add("b", &solc, StandardJsonInput::new().add_source("B.sol", "contract B {}"))
+///     .run();
+/// for (id, output) in results.outputs() {
+///     println!("{}: {} contracts", id, output.contracts.len());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompileJobs<C> {
+    jobs: BTreeMap<String, (C, StandardJsonInput)>,
+    workers: usize,
+}
+
+impl<C> CompileJobs<C> {
+    /// No jobs, run on as many threads as the machine runs in parallel.
+    pub fn new() -> Self {
+        Self {
+            jobs: BTreeMap::new(),
+            workers: thread::available_parallelism().map_or(1, usize::from),
+        }
+    }
+
+    /// Run at most `workers` jobs at the same time.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Add a job. A job added with the id of another replaces it.
+    pub fn add(mut self, id: impl Into<String>, compiler: C, input: StandardJsonInput) -> Self {
+        self.jobs.insert(id.into(), (compiler, input));
+        self
+    }
+
+    /// The number of jobs.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// Whether there are no jobs.
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+impl<C> Default for CompileJobs<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> CompileJobs<C>
+where
+    C: Compiler + Sync,
+    C::Error: Send,
+{
+    /// Compile every input and wait for all of them.
+    ///
+    /// Jobs start in the order of their ids. A backend that fails does not
+    /// stop the other jobs.
+    pub fn run(&self) -> JobResults<C::Error> {
+        let queue = Mutex::new(self.jobs.iter());
+        let results = Mutex::new(BTreeMap::new());
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(self.jobs.len()) {
+                scope.spawn(|| {
+                    loop {
+                        let Some((id, (compiler, input))) = queue.lock().unwrap().next() else {
+                            break;
+                        };
+                        let result = compile_timed(compiler, input);
+                        results.lock().unwrap().insert(id.clone(), result);
+                    }
+                });
+            }
+        });
+        JobResults {
+            results: results.into_inner().unwrap(),
+        }
+    }
+}
+
+/// The results of [`CompileJobs::run`], by job id.
+#[derive(Debug)]
+pub struct JobResults<E> {
+    /// The outcome of every job.
+    pub results: BTreeMap<String, Result<CompileOutcome, E>>,
+}
+
+impl<E> JobResults<E> {
+    /// The outputs of the jobs whose backend ran.
+    pub fn outputs(&self) -> impl Iterator<Item = (&str, &StandardJsonOutput)> {
+        self.results.iter().filter_map(|(id, result)| {
+            let outcome = result.as_ref().ok()?;
+            Some((id.as_str(), &outcome.output))
+        })
+    }
+
+    /// The errors of the jobs whose backend failed.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &E)> {
+        self.results
+            .iter()
+            .filter_map(|(id, result)| Some((id.as_str(), result.as_ref().err()?)))
+    }
+
+    /// The outputs of all jobs merged into one, with the diagnostics that
+    /// several jobs report only once.
+    pub fn merged(&self) -> StandardJsonOutput {
+        let mut merged = StandardJsonOutput::default();
+        for (_, output) in self.outputs() {
+            merged.merge(output.clone());
+        }
+        merged.dedup_errors();
+        merged
+    }
+
+    /// How long the jobs whose backend ran took.
+    pub fn timings(&self) -> CompileTimings {
+        let mut timings = CompileTimings::new();
+        for outcome in self.results.values().flatten() {
+            timings.record(outcome);
+        }
+        timings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    fn job_sources(jobs: &[StandardJsonInput]) -> Vec<Vec<String>> {
+        let mut jobs: Vec<Vec<String>> = jobs
+            .iter()
+            .map(|job| {
+                let mut sources: Vec<String> = job
+                    .sources
+                    .keys()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                sources.sort();
+                sources
+            })
+            .collect();
+        jobs.sort();
+        jobs
+    }
+
+    #[test]
+    fn split_along_imports() {
+        let input = StandardJsonInput::new()
+            .add_source("A.sol", r#"import "./B.sol";"#)
+            .add_source("B.sol", "")
+            .add_source("C.sol", r#"import "./B.sol";"#)
+            .add_source("D.sol", "")
+            .add_source("E.sol", r#"import "./F.sol";"#)
+            .add_source("F.sol", r#"import "./E.sol";"#);
+
+        assert_eq!(
+            job_sources(&split_input(&input, 2)),
+            vec![
+                vec!["A.sol", "B.sol"],
+                vec!["B.sol", "C.sol"],
+                vec!["D.sol"],
+                vec!["E.sol", "F.sol"],
+            ]
+        );
+        assert_eq!(
+            job_sources(&split_input(&input, 3)),
+            vec![
+                vec!["A.sol", "B.sol", "C.sol"],
+                vec!["D.sol", "E.sol", "F.sol"]
+            ]
+        );
+        assert_eq!(split_input(&input, 100).len(), 1);
+    }
+
+    /// Counts how many compilations run at once.
+    #[derive(Default)]
+    struct Counting {
+        running: AtomicUsize,
+        most: AtomicUsize,
+    }
+
+    impl Compiler for Counting {
+        type Error = String;
+
+        fn compile(&self, input: &StandardJsonInput) -> Result<StandardJsonOutput, String> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most.fetch_max(running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            if input.sources.is_empty() {
+                return Err("no sources".to_string());
+            }
+            let mut output = StandardJsonOutput::default();
+            for path in input.sources.keys() {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                output
+                    .contracts
+                    .entry(path.display().to_string())
+                    .or_default()
+                    .insert(name, Default::default());
+            }
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn runs_jobs_concurrently() {
+        let compiler = Counting::default();
+        let mut jobs = CompileJobs::new().workers(3);
+        for i in 0..8 {
+            let input = StandardJsonInput::new().add_source(format!("C{}.sol", i), "");
+            jobs = jobs.add(format!("job-{}", i), &compiler, input);
+        }
+        let jobs = jobs.add("empty", &compiler, StandardJsonInput::new());
+        assert_eq!(jobs.len(), 9);
+
+        let results = jobs.run();
+        assert_eq!(results.results.len(), 9);
+        assert!(compiler.most.load(Ordering::SeqCst) > 1);
+        assert!(compiler.most.load(Ordering::SeqCst) <= 3);
+
+        let outputs: Vec<&str> = results.outputs().map(|(id, _)| id).collect();
+        assert_eq!(outputs.len(), 8);
+        assert_eq!(outputs[0], "job-0");
+        let failures: Vec<(&str, &String)> = results.failures().collect();
+        assert_eq!(failures, [("empty", &"no sources".to_string())]);
+        assert_eq!(results.merged().contracts.len(), 8);
+        assert_eq!(results.timings().jobs(), 8);
+    }
+}
@@ -4,8 +4,27 @@
 //! compiler's Standard JSON interface.
 
 pub use abi::Abi;
+pub use compiler::{CompiledContract, compile_source, parse_with_solc};
 pub use standard_json_input::StandardJsonInput;
 
 pub mod abi;
+pub mod assembly;
 pub mod ast;
+pub mod cache;
+pub mod compiler;
+pub mod diagnostics;
+pub mod fix;
+pub mod flatten;
+pub mod gas;
+pub mod graph;
+pub mod jobs;
+pub mod metadata;
+pub mod opcodes;
+pub mod pragma;
+pub mod resolver;
+pub mod selectors;
+pub mod sourcemap;
 pub mod standard_json_input;
+pub mod standard_json_output;
+#[cfg(all(feature = "download", not(target_arch = "wasm32")))]
+pub mod versions;
@@ -0,0 +1,317 @@
+//! Metadata appended to contract bytecode.
+//!
+//! The compiler appends a CBOR-encoded map to the runtime bytecode, followed
+//! by its length as two big-endian bytes. The map holds the hash of the
+//! contract's metadata file, under `ipfs` or `bzzr1` (`bzzr0` before 0.6.0),
+//! and the compiler version under `solc`. Verification tools use it to find
+//! the metadata file and the compiler a deployed contract was built with.
+//!
+//! The encoding is described in the [Solidity documentation].
+//!
+//! [Solidity documentation]: https://docs.soliditylang.org/en/latest/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode
+
+use semver::Version;
+
+/// The metadata decoded from the end of a bytecode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BytecodeMetadata {
+    /// The IPFS multihash of the metadata file, 34 bytes long.
+    pub ipfs: Option<Vec<u8>>,
+    /// The Swarm hash of the metadata file, used before 0.6.0.
+    pub bzzr0: Option<[u8; 32]>,
+    /// The Swarm hash of the metadata file.
+    pub bzzr1: Option<[u8; 32]>,
+    /// The version of the compiler.
+    pub solc: Option<Version>,
+    /// Whether experimental features were enabled.
+    pub experimental: bool,
+}
+
+impl BytecodeMetadata {
+    /// The IPFS hash in its usual base58 form, such as `Qm...`.
+    pub fn ipfs_cid(&self) -> Option<String> {
+        self.ipfs.as_deref().map(base58)
+    }
+}
+
+/// An error returned when bytecode has no valid metadata.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MetadataError {
+    /// The bytecode is not valid hex.
+    #[error("invalid bytecode hex: {0}")]
+    InvalidHex(String),
+
+    /// The length at the end of the bytecode is larger than the bytecode.
+    #[error("metadata length {0} exceeds the bytecode")]
+    InvalidLength(usize),
+
+    /// The metadata is not a CBOR map of the expected shape.
+    #[error("invalid CBOR metadata: {0}")]
+    InvalidCbor(String),
+
+    /// The metadata nests maps deeper than [`MAX_DEPTH`].
+    #[error("CBOR metadata nests maps deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+}
+
+/// The deepest nesting of maps the decoder reads.
+///
+/// The compiler emits a single flat map, and the bytes at the end of code
+/// on chain may be crafted, so deeper nesting is rejected rather than read
+/// with unbounded recursion.
+pub const MAX_DEPTH: usize = 16;
+
+/// Split bytecode into the code and its metadata.
+///
+/// The metadata is the CBOR map without the two length bytes. Fails if the
+/// length does not fit in the bytecode, which is the case for bytecode
+/// compiled without metadata.
+pub fn split(bytecode: &[u8]) -> Result<(&[u8], &[u8]), MetadataError> {
+    let length = bytecode
+        .len()
+        .checked_sub(2)
+        .ok_or(MetadataError::InvalidLength(bytecode.len()))?;
+    let cbor_length = u16::from_be_bytes([bytecode[length], bytecode[length + 1]]) as usize;
+    let start = length
+        .checked_sub(cbor_length)
+        .ok_or(MetadataError::InvalidLength(cbor_length))?;
+    Ok((&bytecode[..start], &bytecode[start..length]))
+}
+
+/// Decode the metadata at the end of bytecode.
+pub fn decode(bytecode: &[u8]) -> Result<BytecodeMetadata, MetadataError> {
+    let (_, cbor) = split(bytecode)?;
+    let mut decoder = Decoder { data: cbor };
+    let entries = match decoder.item(0)? {
+        Item::Map(entries) => entries,
+        _ => return Err(invalid("metadata is not a map")),
+    };
+    if !decoder.data.is_empty() {
+        return Err(invalid("trailing bytes after the map"));
+    }
+
+    let mut metadata = BytecodeMetadata::default();
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("ipfs", Item::Bytes(hash)) => metadata.ipfs = Some(hash),
+            ("bzzr0", Item::Bytes(hash)) => metadata.bzzr0 = Some(swarm_hash(hash)?),
+            ("bzzr1", Item::Bytes(hash)) => metadata.bzzr1 = Some(swarm_hash(hash)?),
+            ("solc", Item::Bytes(version)) => match version[..] {
+                [major, minor, patch] => {
+                    metadata.solc = Some(Version::new(major.into(), minor.into(), patch.into()))
+                }
+                _ => return Err(invalid("solc version is not three bytes")),
+            },
+            ("solc", Item::Text(version)) => {
+                metadata.solc = Some(Version::parse(&version).map_err(|e| invalid(&e.to_string()))?)
+            }
+            ("experimental", Item::Bool(experimental)) => metadata.experimental = experimental,
+            ("ipfs" | "bzzr0" | "bzzr1" | "solc" | "experimental", _) => {
+                return Err(invalid(&format!("unexpected type for `{}`", key)));
+            }
+            _ => {}
+        }
+    }
+    Ok(metadata)
+}
+
+/// Decode the metadata at the end of bytecode given as hex.
+///
+/// The hex string may have a `0x` prefix.
+pub fn decode_hex(bytecode: &str) -> Result<BytecodeMetadata, MetadataError> {
+    let bytecode = bytecode.strip_prefix("0x").unwrap_or(bytecode);
+    let bytecode = hex::decode(bytecode).map_err(|e| MetadataError::InvalidHex(e.to_string()))?;
+    decode(&bytecode)
+}
+
+fn invalid(message: &str) -> MetadataError {
+    MetadataError::InvalidCbor(message.to_string())
+}
+
+fn swarm_hash(hash: Vec<u8>) -> Result<[u8; 32], MetadataError> {
+    hash.try_into()
+        .map_err(|_| invalid("swarm hash is not 32 bytes"))
+}
+
+/// The CBOR items that can appear in the metadata map.
+enum Item {
+    Unsigned,
+    Bytes(Vec<u8>),
+    Text(String),
+    Map(Vec<(String, Item)>),
+    Bool(bool),
+}
+
+/// A decoder for the subset of CBOR the compiler emits.
+struct Decoder<'a> {
+    data: &'a [u8],
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, length: usize) -> Result<&[u8], MetadataError> {
+        if self.data.len() < length {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    /// Read the major type and argument of the next item.
+    fn header(&mut self) -> Result<(u8, u64), MetadataError> {
+        let initial = self.take(1)?[0];
+        let argument = match initial & 0x1f {
+            info @ 0..24 => info.into(),
+            info @ 24..28 => {
+                let bytes = self.take(1 << (info - 24))?;
+                bytes
+                    .iter()
+                    .fold(0u64, |value, byte| value << 8 | u64::from(*byte))
+            }
+            _ => return Err(invalid("indefinite lengths are not supported")),
+        };
+        Ok((initial >> 5, argument))
+    }
+
+    /// Read the next item, inside `depth` maps.
+    fn item(&mut self, depth: usize) -> Result<Item, MetadataError> {
+        let (major, argument) = self.header()?;
+        let length = usize::try_from(argument).map_err(|_| invalid("length too large"));
+        match major {
+            0 => Ok(Item::Unsigned),
+            2 => Ok(Item::Bytes(self.take(length?)?.to_vec())),
+            3 => String::from_utf8(self.take(length?)?.to_vec())
+                .map(Item::Text)
+                .map_err(|_| invalid("text is not UTF-8")),
+            5 => {
+                if depth >= MAX_DEPTH {
+                    return Err(MetadataError::TooDeep);
+                }
+                let mut entries = Vec::new();
+                for _ in 0..length? {
+                    let Item::Text(key) = self.item(depth + 1)? else {
+                        return Err(invalid("map key is not text"));
+                    };
+                    entries.push((key, self.item(depth + 1)?));
+                }
+                Ok(Item::Map(entries))
+            }
+            7 => match argument {
+                20 => Ok(Item::Bool(false)),
+                21 => Ok(Item::Bool(true)),
+                _ => Err(invalid("unsupported simple value")),
+            },
+            _ => Err(invalid(&format!("unsupported major type {}", major))),
+        }
+    }
+}
+
+/// Encode bytes in base58 with the Bitcoin alphabet, as IPFS does.
+fn base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    // Digits in base 58, least significant first.
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = u32::from(*byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|digit| ALPHABET[*digit as usize] as char),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A runtime bytecode ending in metadata as emitted by solc 0.8.26.
+    const RUNTIME: &str = "6080604052600080fdfea2646970667358221220\
+        dceca8706b29e917dacf25fceef95acac8d90d765ac926663ce4096195952b61\
+        64736f6c634300081a0033";
+
+    #[test]
+    fn decode_ipfs_and_version() {
+        let metadata = decode_hex(RUNTIME).unwrap();
+        assert_eq!(metadata.solc, Some(Version::new(0, 8, 26)));
+        assert_eq!(
+            metadata.ipfs_cid().as_deref(),
+            Some("QmdD3hpMj6mEFVy9DP4QqjHaoeYbhKsYvApX1YZNfjTVWp")
+        );
+        assert!(metadata.bzzr1.is_none());
+        assert!(!metadata.experimental);
+
+        let bytecode = hex::decode(RUNTIME).unwrap();
+        let (code, cbor) = split(&bytecode).unwrap();
+        assert_eq!(code, hex::decode("6080604052600080fdfe").unwrap());
+        assert_eq!(cbor.len(), 0x33);
+    }
+
+    #[test]
+    fn decode_swarm_and_prerelease() {
+        // {"bzzr1": <32 bytes>, "solc": "0.6.0-nightly"}
+        let mut cbor = vec![0xa2, 0x65];
+        cbor.extend(b"bzzr1");
+        cbor.extend([0x58, 0x20]);
+        cbor.extend([0xab; 32]);
+        cbor.push(0x64);
+        cbor.extend(b"solc");
+        cbor.push(0x6d);
+        cbor.extend(b"0.6.0-nightly");
+        let mut bytecode = vec![0x00];
+        bytecode.extend(&cbor);
+        bytecode.extend((cbor.len() as u16).to_be_bytes());
+
+        let metadata = decode(&bytecode).unwrap();
+        assert_eq!(metadata.bzzr1, Some([0xab; 32]));
+        assert_eq!(
+            metadata.solc,
+            Some(Version::parse("0.6.0-nightly").unwrap())
+        );
+    }
+
+    #[test]
+    fn reject_missing_metadata() {
+        assert_eq!(
+            decode(&[0x60, 0x80, 0xff, 0xff]),
+            Err(MetadataError::InvalidLength(0xffff))
+        );
+        assert_eq!(decode(&[0x00]), Err(MetadataError::InvalidLength(1)));
+        assert!(matches!(
+            decode(&[0x60, 0x00, 0x01]),
+            Err(MetadataError::InvalidCbor(_))
+        ));
+    }
+
+    #[test]
+    fn reject_deep_nesting() {
+        // {"a": {"a": ...}} nested past the limit, as one-entry maps.
+        let mut cbor = Vec::new();
+        for _ in 0..=MAX_DEPTH {
+            cbor.extend([0xa1, 0x61, b'a']);
+        }
+        cbor.push(0xa0);
+        let length = (cbor.len() as u16).to_be_bytes();
+        cbor.extend(length);
+        assert_eq!(decode(&cbor), Err(MetadataError::TooDeep));
+
+        let mut cbor = vec![0xa1, 0x61, b'a', 0xa0];
+        cbor.extend([0x00, 0x04]);
+        assert_eq!(decode(&cbor), Ok(BytecodeMetadata::default()));
+    }
+}
@@ -0,0 +1,359 @@
+//! EVM instruction listing types.
+//!
+//! The compiler reports `evm.bytecode.opcodes` as a single space-separated
+//! string such as `PUSH1 0x80 PUSH1 0x40 MSTORE`. This module parses that
+//! listing into typed instructions with their byte offsets, and can check the
+//! listing against the raw bytecode it was generated from.
+
+/// An EVM opcode, identified by its byte value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Opcode(pub u8);
+
+/// Opcode names as printed by solc, paired with their byte values.
+const NAMES: &[(u8, &str)] = &[
+    (0x00, "STOP"),
+    (0x01, "ADD"),
+    (0x02, "MUL"),
+    (0x03, "SUB"),
+    (0x04, "DIV"),
+    (0x05, "SDIV"),
+    (0x06, "MOD"),
+    (0x07, "SMOD"),
+    (0x08, "ADDMOD"),
+    (0x09, "MULMOD"),
+    (0x0a, "EXP"),
+    (0x0b, "SIGNEXTEND"),
+    (0x10, "LT"),
+    (0x11, "GT"),
+    (0x12, "SLT"),
+    (0x13, "SGT"),
+    (0x14, "EQ"),
+    (0x15, "ISZERO"),
+    (0x16, "AND"),
+    (0x17, "OR"),
+    (0x18, "XOR"),
+    (0x19, "NOT"),
+    (0x1a, "BYTE"),
+    (0x1b, "SHL"),
+    (0x1c, "SHR"),
+    (0x1d, "SAR"),
+    (0x1e, "CLZ"),
+    (0x20, "KECCAK256"),
+    (0x30, "ADDRESS"),
+    (0x31, "BALANCE"),
+    (0x32, "ORIGIN"),
+    (0x33, "CALLER"),
+    (0x34, "CALLVALUE"),
+    (0x35, "CALLDATALOAD"),
+    (0x36, "CALLDATASIZE"),
+    (0x37, "CALLDATACOPY"),
+    (0x38, "CODESIZE"),
+    (0x39, "CODECOPY"),
+    (0x3a, "GASPRICE"),
+    (0x3b, "EXTCODESIZE"),
+    (0x3c, "EXTCODECOPY"),
+    (0x3d, "RETURNDATASIZE"),
+    (0x3e, "RETURNDATACOPY"),
+    (0x3f, "EXTCODEHASH"),
+    (0x40, "BLOCKHASH"),
+    (0x41, "COINBASE"),
+    (0x42, "TIMESTAMP"),
+    (0x43, "NUMBER"),
+    (0x44, "PREVRANDAO"),
+    (0x45, "GASLIMIT"),
+    (0x46, "CHAINID"),
+    (0x47, "SELFBALANCE"),
+    (0x48, "BASEFEE"),
+    (0x49, "BLOBHASH"),
+    (0x4a, "BLOBBASEFEE"),
+    (0x50, "POP"),
+    (0x51, "MLOAD"),
+    (0x52, "MSTORE"),
+    (0x53, "MSTORE8"),
+    (0x54, "SLOAD"),
+    (0x55, "SSTORE"),
+    (0x56, "JUMP"),
+    (0x57, "JUMPI"),
+    (0x58, "PC"),
+    (0x59, "MSIZE"),
+    (0x5a, "GAS"),
+    (0x5b, "JUMPDEST"),
+    (0x5c, "TLOAD"),
+    (0x5d, "TSTORE"),
+    (0x5e, "MCOPY"),
+    (0x5f, "PUSH0"),
+    (0xa0, "LOG0"),
+    (0xa1, "LOG1"),
+    (0xa2, "LOG2"),
+    (0xa3, "LOG3"),
+    (0xa4, "LOG4"),
+    (0xf0, "CREATE"),
+    (0xf1, "CALL"),
+    (0xf2, "CALLCODE"),
+    (0xf3, "RETURN"),
+    (0xf4, "DELEGATECALL"),
+    (0xf5, "CREATE2"),
+    (0xfa, "STATICCALL"),
+    (0xfd, "REVERT"),
+    (0xfe, "INVALID"),
+    (0xff, "SELFDESTRUCT"),
+];
+
+/// Names used by older compiler releases for opcodes that were later renamed.
+const ALIASES: &[(u8, &str)] = &[(0x20, "SHA3"), (0x44, "DIFFICULTY"), (0xff, "SUICIDE")];
+
+impl Opcode {
+    /// The mnemonic for this opcode, or `None` for bytes that are not valid
+    /// instructions.
+    pub fn name(self) -> Option<String> {
+        match self.0 {
+            0x60..=0x7f => Some(format!("PUSH{}", self.0 - 0x5f)),
+            0x80..=0x8f => Some(format!("DUP{}", self.0 - 0x7f)),
+            0x90..=0x9f => Some(format!("SWAP{}", self.0 - 0x8f)),
+            byte => NAMES
+                .iter()
+                .find(|(b, _)| *b == byte)
+                .map(|(_, name)| name.to_string()),
+        }
+    }
+
+    /// Look up an opcode by its mnemonic, including legacy names such as `SHA3`.
+    pub fn from_name(name: &str) -> Option<Opcode> {
+        let numbered = |prefix: &str, base: u8, max: u8| {
+            name.strip_prefix(prefix)
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=max).contains(n))
+                .map(|n| Opcode(base + n - 1))
+        };
+        numbered("PUSH", 0x60, 32)
+            .or_else(|| numbered("DUP", 0x80, 16))
+            .or_else(|| numbered("SWAP", 0x90, 16))
+            .or_else(|| {
+                NAMES
+                    .iter()
+                    .chain(ALIASES.iter())
+                    .find(|(_, n)| *n == name)
+                    .map(|(byte, _)| Opcode(*byte))
+            })
+    }
+
+    /// The number of immediate data bytes that follow this opcode.
+    pub fn push_size(self) -> usize {
+        match self.0 {
+            0x60..=0x7f => (self.0 - 0x5f) as usize,
+            _ => 0,
+        }
+    }
+}
+
+/// A single instruction in EVM bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// The byte offset of the opcode in the bytecode.
+    pub offset: usize,
+    /// The opcode.
+    pub opcode: Opcode,
+    /// The immediate data of a `PUSHn` instruction, always `n` bytes long.
+    pub push_data: Option<Vec<u8>>,
+}
+
+impl Instruction {
+    /// The number of bytes this instruction takes in the bytecode.
+    pub fn size(&self) -> usize {
+        1 + self.opcode.push_size()
+    }
+}
+
+/// An error returned when an opcode listing cannot be parsed or does not
+/// match the bytecode.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum OpcodesError {
+    /// A token is neither a known mnemonic nor a hex byte.
+    #[error("unknown opcode `{0}`")]
+    UnknownOpcode(String),
+
+    /// A `PUSHn` instruction is not followed by its immediate value.
+    #[error("missing push data for instruction at offset {0}")]
+    MissingPushData(usize),
+
+    /// The immediate value of a `PUSHn` instruction is not valid hex or is
+    /// longer than `n` bytes.
+    #[error("invalid push data `{data}` for instruction at offset {offset}")]
+    InvalidPushData { offset: usize, data: String },
+
+    /// The bytecode is not valid hex.
+    #[error("invalid bytecode hex: {0}")]
+    InvalidHex(String),
+
+    /// The listing and the bytecode disagree at an offset.
+    #[error("opcode listing does not match bytecode at offset {0}")]
+    Mismatch(usize),
+
+    /// The listing and the bytecode have a different number of instructions.
+    #[error("opcode listing has {listing} instructions but bytecode has {bytecode}")]
+    LengthMismatch { listing: usize, bytecode: usize },
+}
+
+/// Parse an opcode listing as printed by the compiler.
+///
+/// Offsets are computed from the instruction sizes, so they match the
+/// positions of the instructions in the bytecode the listing came from.
+pub fn parse(listing: &str) -> Result<Vec<Instruction>, OpcodesError> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    let mut tokens = listing.split_whitespace();
+
+    while let Some(token) = tokens.next() {
+        let opcode = match Opcode::from_name(token) {
+            Some(opcode) => opcode,
+            None => token
+                .strip_prefix("0x")
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .map(Opcode)
+                .ok_or_else(|| OpcodesError::UnknownOpcode(token.to_string()))?,
+        };
+
+        let push_data = match opcode.push_size() {
+            0 => None,
+            size => {
+                let data = tokens.next().ok_or(OpcodesError::MissingPushData(offset))?;
+                Some(
+                    parse_push_data(data, size).ok_or_else(|| OpcodesError::InvalidPushData {
+                        offset,
+                        data: data.to_string(),
+                    })?,
+                )
+            }
+        };
+
+        let instruction = Instruction {
+            offset,
+            opcode,
+            push_data,
+        };
+        offset += instruction.size();
+        instructions.push(instruction);
+    }
+
+    Ok(instructions)
+}
+
+/// Parse a push value printed as a number without leading zeros, such as
+/// `0x80`, into exactly `size` bytes.
+fn parse_push_data(data: &str, size: usize) -> Option<Vec<u8>> {
+    let digits = data.strip_prefix("0x")?;
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > size * 2 {
+        return None;
+    }
+    let padded = format!("{:0>width$}", digits, width = size * 2);
+    hex::decode(padded).ok()
+}
+
+/// Split bytecode into instructions.
+///
+/// A `PUSHn` at the end of the code with fewer than `n` bytes left is padded
+/// with zeros, the same way the compiler prints it.
+pub fn disassemble(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+
+    while offset < code.len() {
+        let opcode = Opcode(code[offset]);
+        let push_data = match opcode.push_size() {
+            0 => None,
+            size => {
+                let start = (offset + 1).min(code.len());
+                let end = (offset + 1 + size).min(code.len());
+                let mut data = code[start..end].to_vec();
+                data.resize(size, 0);
+                Some(data)
+            }
+        };
+        let instruction = Instruction {
+            offset,
+            opcode,
+            push_data,
+        };
+        offset += instruction.size();
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+/// Check that an opcode listing describes the given bytecode hex.
+///
+/// The hex string may have a `0x` prefix. Unlinked bytecode that still
+/// contains library placeholders is not valid hex and is rejected.
+pub fn reconcile(instructions: &[Instruction], object: &str) -> Result<(), OpcodesError> {
+    let object = object.strip_prefix("0x").unwrap_or(object);
+    let code = hex::decode(object).map_err(|e| OpcodesError::InvalidHex(e.to_string()))?;
+    let expected = disassemble(&code);
+
+    if let Some((listed, actual)) = instructions
+        .iter()
+        .zip(&expected)
+        .find(|(listed, actual)| listed != actual)
+    {
+        return Err(OpcodesError::Mismatch(listed.offset.min(actual.offset)));
+    }
+    if instructions.len() != expected.len() {
+        return Err(OpcodesError::LengthMismatch {
+            listing: instructions.len(),
+            bytecode: expected.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_listing() {
+        let instructions = parse("PUSH1 0x80 PUSH1 0x40 MSTORE PUSH0 PUSH2 0x1 0xA5 STOP").unwrap();
+        let offsets: Vec<usize> = instructions.iter().map(|i| i.offset).collect();
+        assert_eq!(offsets, vec![0, 2, 4, 5, 6, 9, 10]);
+        assert_eq!(instructions[0].push_data, Some(vec![0x80]));
+        assert_eq!(instructions[4].push_data, Some(vec![0x00, 0x01]));
+        assert_eq!(instructions[5].opcode, Opcode(0xa5));
+        assert_eq!(instructions[5].opcode.name(), None);
+        assert_eq!(Opcode::from_name("SHA3"), Some(Opcode(0x20)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("PUSH1"), Err(OpcodesError::MissingPushData(0)));
+        assert_eq!(
+            parse("MSTORE FOO"),
+            Err(OpcodesError::UnknownOpcode("FOO".to_string()))
+        );
+        assert!(matches!(
+            parse("PUSH1 0x1234"),
+            Err(OpcodesError::InvalidPushData { offset: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn reconcile_with_object() {
+        let instructions = parse("PUSH1 0x80 PUSH1 0x40 MSTORE CALLVALUE").unwrap();
+        assert!(matches!(
+            reconcile(&instructions, "__$1234$__"),
+            Err(OpcodesError::InvalidHex(_))
+        ));
+        assert_eq!(reconcile(&instructions, "608060405234"), Ok(()));
+        assert_eq!(
+            reconcile(&instructions, "608060415234"),
+            Err(OpcodesError::Mismatch(2))
+        );
+        assert_eq!(
+            reconcile(&instructions, "60806040523400"),
+            Err(OpcodesError::LengthMismatch {
+                listing: 4,
+                bytecode: 5
+            })
+        );
+    }
+}
@@ -0,0 +1,519 @@
+//! Compiler version requirements from `pragma solidity`.
+//!
+//! A source states the compiler releases it compiles with in a pragma such
+//! as `pragma solidity >=0.6.2 <0.9.0;`. The AST keeps the pragma as the
+//! tokens the parser read, such as `["solidity", ">=", "0.6", ".2", "<",
+//! "0.9", ".0"]`. A [`VersionRequirement`] is the requirement parsed from
+//! those tokens, and [`source_pragmas`] parses it from the text of a source
+//! that has not been compiled. A [`PragmaReport`] gathers the requirements of
+//! every source of a project, to find the releases that compile all of them
+//! and the sources that keep a project from moving to a release.
+//!
+//! [`PragmaDirective::kind`] parses the other pragmas too, telling apart
+//! `pragma solidity`, `pragma abicoder` and `pragma experimental`.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use semver::{Version, VersionReq};
+
+use crate::ast::{PragmaDirective, SourceLocation, SourceUnit, SourceUnitNode};
+use crate::graph::{Token, Tokens};
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+
+/// A requirement on the compiler version: alternatives separated by `||`,
+/// each a set of comparators that must all match.
+///
+/// Solidity reads a version without an operator, such as `0.8.30`, as an
+/// exact version, and accepts ranges such as `0.6.0 - 0.8.0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRequirement {
+    text: String,
+    alternatives: Vec<VersionReq>,
+}
+
+impl VersionRequirement {
+    /// Parse a requirement as written after `pragma solidity`.
+    pub fn parse(text: &str) -> Result<Self, PragmaError> {
+        let invalid = |message: String| PragmaError::Invalid {
+            requirement: text.to_string(),
+            message,
+        };
+        let alternatives = text
+            .split("||")
+            .map(|alternative| {
+                let comparators = comparators(alternative).ok_or_else(|| {
+                    invalid(format!("`{}` is not a version range", alternative.trim()))
+                })?;
+                VersionReq::parse(&comparators.join(", ")).map_err(|e| invalid(e.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            text: text.trim().to_string(),
+            alternatives,
+        })
+    }
+
+    /// Parse the literals of a pragma directive, or return `None` for a
+    /// pragma other than `pragma solidity`.
+    pub fn from_literals(literals: &[String]) -> Option<Result<Self, PragmaError>> {
+        let (first, tokens) = literals.split_first()?;
+        if first != "solidity" {
+            return None;
+        }
+        // The parser splits versions at dots, so `0.8.0` is read as `0.8`
+        // and `.0`. Join those back, and separate comparators with spaces.
+        let mut text = String::new();
+        for token in tokens {
+            let operator = text.ends_with(['^', '~', '<', '>', '=']);
+            if !text.is_empty() && !token.starts_with('.') && !operator {
+                text.push(' ');
+            }
+            text.push_str(token);
+        }
+        Some(Self::parse(&text))
+    }
+
+    /// Whether a compiler release meets the requirement.
+    pub fn matches(&self, version: &Version) -> bool {
+        self.alternatives
+            .iter()
+            .any(|requirement| requirement.matches(version))
+    }
+
+    /// The requirement as written, such as `>=0.6.2 <0.9.0`.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The alternatives of the requirement, one for each part separated by
+    /// `||`, any of which a release must meet.
+    pub fn alternatives(&self) -> &[VersionReq] {
+        &self.alternatives
+    }
+}
+
+/// A pragma directive, parsed from its literals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PragmaKind {
+    /// `pragma solidity`, with the compiler releases it allows.
+    Solidity(VersionRequirement),
+    /// `pragma abicoder`, with the ABI coder it selects.
+    AbiCoder(AbiCoder),
+    /// `pragma experimental`, with the feature it enables, such as
+    /// `ABIEncoderV2` or `SMTChecker`.
+    Experimental(String),
+}
+
+/// The ABI coder a `pragma abicoder` selects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbiCoder {
+    /// `pragma abicoder v1;`
+    V1,
+    /// `pragma abicoder v2;`, the default since Solidity 0.8.0.
+    V2,
+}
+
+impl PragmaDirective {
+    /// The compiler version requirement of a `pragma solidity` directive,
+    /// or `None` for other pragmas.
+    pub fn version_requirement(&self) -> Option<Result<VersionRequirement, PragmaError>> {
+        VersionRequirement::from_literals(&self.literals)
+    }
+
+    /// What the directive is, parsed from its literals, which are kept as
+    /// they are so that the directive serializes unchanged.
+    pub fn kind(&self) -> Result<PragmaKind, PragmaError> {
+        if let Some(requirement) = self.version_requirement() {
+            return requirement.map(PragmaKind::Solidity);
+        }
+        match self.literals.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["abicoder", "v1"] => Ok(PragmaKind::AbiCoder(AbiCoder::V1)),
+            ["abicoder", "v2"] => Ok(PragmaKind::AbiCoder(AbiCoder::V2)),
+            ["experimental", feature] => Ok(PragmaKind::Experimental(
+                feature.trim_matches('"').to_string(),
+            )),
+            _ => Err(PragmaError::Unknown(self.literals.join(" "))),
+        }
+    }
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// The comparators of one alternative of a requirement in the syntax of the
+/// `semver` crate, or `None` if a range has no bounds on one side.
+fn comparators(alternative: &str) -> Option<Vec<String>> {
+    let tokens: Vec<&str> = alternative.split_whitespace().collect();
+    if let [low, "-", high] = tokens[..] {
+        return Some(vec![format!(">={}", low), format!("<={}", high)]);
+    }
+    let mut comparators = Vec::new();
+    let mut operator = String::new();
+    for token in tokens {
+        if token.chars().all(|c| "^~<>=".contains(c)) {
+            operator.push_str(token);
+            continue;
+        }
+        let comparator = format!("{}{}", operator, token);
+        operator.clear();
+        match comparator.starts_with(|c: char| c.is_ascii_digit()) {
+            true => comparators.push(format!("={}", comparator)),
+            false => comparators.push(comparator),
+        }
+    }
+    (operator.is_empty() && !comparators.is_empty()).then_some(comparators)
+}
+
+/// The `pragma solidity` directives in the text of a source.
+///
+/// Comments and string literals are skipped, so this works on sources that
+/// have not been compiled. The locations of the directives are byte ranges
+/// of the text, with the source index given.
+pub fn source_pragmas(
+    path: impl Into<PathBuf>,
+    content: &str,
+    source_index: usize,
+) -> Result<Vec<SourcePragma>, PragmaError> {
+    let path = path.into();
+    let mut pragmas = Vec::new();
+    let mut tokens = Tokens::new(content);
+    while let Some(token) = tokens.next() {
+        if token != Token::Word("pragma") {
+            continue;
+        }
+        let start = tokens.position() - "pragma".len();
+        let directive_start = tokens.position();
+        if !tokens.by_ref().any(|token| token == Token::Semicolon) {
+            break;
+        }
+        let end = tokens.position();
+        let directive = content[directive_start..end - 1].trim_start();
+        let rest = directive.strip_prefix("solidity");
+        if let Some(text) = rest.filter(|text| text.starts_with(char::is_whitespace)) {
+            pragmas.push(SourcePragma {
+                path: path.clone(),
+                requirement: VersionRequirement::parse(text)?,
+                src: SourceLocation {
+                    offset: start,
+                    length: end - start,
+                    source_index,
+                },
+            });
+        }
+    }
+    Ok(pragmas)
+}
+
+/// An error returned when a pragma or version requirement cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PragmaError {
+    /// The requirement is not valid version range syntax.
+    #[error("invalid version requirement `{requirement}`: {message}")]
+    Invalid {
+        requirement: String,
+        message: String,
+    },
+
+    /// The pragma is not one the compiler knows, such as `pragma abicoder v3`.
+    #[error("unknown pragma `{0}`")]
+    Unknown(String),
+}
+
+/// A `pragma solidity` directive of a source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourcePragma {
+    /// The path of the source, as in its AST.
+    pub path: PathBuf,
+    /// The requirement the pragma states.
+    pub requirement: VersionRequirement,
+    /// The location of the pragma directive.
+    pub src: SourceLocation,
+}
+
+/// The version requirements of the sources of a project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PragmaReport {
+    /// Every `pragma solidity` directive, in the order of the sources.
+    pub pragmas: Vec<SourcePragma>,
+}
+
+impl PragmaReport {
+    /// Collect the pragmas of source units. Sources without a pragma accept
+    /// every release.
+    pub fn from_source_units<'a>(
+        source_units: impl IntoIterator<Item = &'a SourceUnit>,
+    ) -> Result<Self, PragmaError> {
+        let mut pragmas = Vec::new();
+        for source_unit in source_units {
+            for node in &source_unit.nodes {
+                let SourceUnitNode::PragmaDirective(pragma) = node else {
+                    continue;
+                };
+                if let Some(requirement) = pragma.version_requirement() {
+                    pragmas.push(SourcePragma {
+                        path: source_unit.absolute_path.clone(),
+                        requirement: requirement?,
+                        src: pragma.src.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Self { pragmas })
+    }
+
+    /// Collect the pragmas of the sources of an input from their text.
+    ///
+    /// Sources given as URLs are not read, and accept every release. The
+    /// source indices of the locations are those the compiler assigns, in
+    /// the byte order of the source names rather than the order of paths.
+    pub fn from_input(input: &StandardJsonInput) -> Result<Self, PragmaError> {
+        let mut sources: Vec<_> = input.sources.iter().collect();
+        sources.sort_by_cached_key(|(path, _)| path.to_string_lossy().into_owned());
+        let mut pragmas = Vec::new();
+        for (index, (path, source)) in sources.into_iter().enumerate() {
+            if let SourceContent::Content { content } = &source.content {
+                pragmas.extend(source_pragmas(path, content, index)?);
+            }
+        }
+        Ok(Self { pragmas })
+    }
+
+    /// Whether every source compiles with a release.
+    pub fn allows(&self, version: &Version) -> bool {
+        self.pragmas
+            .iter()
+            .all(|pragma| pragma.requirement.matches(version))
+    }
+
+    /// The releases among `versions` that every source compiles with, which
+    /// is the intersection of the requirements over the known releases.
+    pub fn satisfying<'v>(
+        &self,
+        versions: impl IntoIterator<Item = &'v Version>,
+    ) -> Vec<&'v Version> {
+        versions
+            .into_iter()
+            .filter(|version| self.allows(version))
+            .collect()
+    }
+
+    /// The newest release among `versions` that every source compiles
+    /// with.
+    pub fn newest<'v>(
+        &self,
+        versions: impl IntoIterator<Item = &'v Version>,
+    ) -> Option<&'v Version> {
+        self.satisfying(versions).into_iter().max()
+    }
+
+    /// The distinct requirements of the sources, in the order they first
+    /// appear.
+    pub fn requirements(&self) -> Vec<&VersionRequirement> {
+        let mut requirements: Vec<&VersionRequirement> = Vec::new();
+        for pragma in &self.pragmas {
+            if !requirements.contains(&&pragma.requirement) {
+                requirements.push(&pragma.requirement);
+            }
+        }
+        requirements
+    }
+
+    /// The pragmas that keep a project from compiling with a release.
+    pub fn blocking(&self, version: &Version) -> Vec<&SourcePragma> {
+        self.pragmas
+            .iter()
+            .filter(|pragma| !pragma.requirement.matches(version))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literals(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(ToString::to_string).collect()
+    }
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_requirements() {
+        let requirement = VersionRequirement::from_literals(&literals(&[
+            "solidity", ">=", "0.6", ".2", "<", "0.9", ".0",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(requirement.as_str(), ">=0.6.2 <0.9.0");
+        assert!(requirement.matches(&version("0.8.30")));
+        assert!(!requirement.matches(&version("0.6.1")));
+
+        let exact = VersionRequirement::from_literals(&literals(&["solidity", "0.8", ".13"]))
+            .unwrap()
+            .unwrap();
+        assert!(exact.matches(&version("0.8.13")));
+        assert!(!exact.matches(&version("0.8.14")));
+
+        let cases = [
+            ("^0.8.0", "0.8.30", true),
+            ("^0.8.0", "0.9.0", false),
+            ("~0.8.1", "0.8.9", true),
+            ("^0.4.24 || ^0.5.0", "0.5.17", true),
+            ("^0.4.24 || ^0.5.0", "0.6.0", false),
+            ("0.6.0 - 0.8.0", "0.8.0", true),
+            ("0.6.0 - 0.8.0", "0.8.1", false),
+            (">= 0.8.0", "0.8.0", true),
+            ("0.8", "0.8.7", true),
+        ];
+        for (text, release, expected) in cases {
+            let requirement = VersionRequirement::parse(text).unwrap();
+            assert_eq!(
+                requirement.matches(&version(release)),
+                expected,
+                "{} {}",
+                text,
+                release
+            );
+        }
+
+        assert!(VersionRequirement::from_literals(&literals(&["abicoder", "v2"])).is_none());
+        assert!(VersionRequirement::parse(">=").is_err());
+        assert!(VersionRequirement::parse("^0.8.x.1").is_err());
+    }
+
+    #[test]
+    fn parses_pragma_kinds() {
+        let kind = |tokens: &[&str]| {
+            PragmaDirective {
+                literals: literals(tokens),
+                ..Default::default()
+            }
+            .kind()
+        };
+        assert_eq!(
+            kind(&["solidity", "^", "0.8", ".0"]),
+            Ok(PragmaKind::Solidity(
+                VersionRequirement::parse("^0.8.0").unwrap()
+            ))
+        );
+        assert_eq!(
+            kind(&["abicoder", "v1"]),
+            Ok(PragmaKind::AbiCoder(AbiCoder::V1))
+        );
+        assert_eq!(
+            kind(&["experimental", "ABIEncoderV2"]),
+            Ok(PragmaKind::Experimental("ABIEncoderV2".to_string()))
+        );
+        assert_eq!(
+            kind(&["experimental", "\"SMTChecker\""]),
+            Ok(PragmaKind::Experimental("SMTChecker".to_string()))
+        );
+        assert_eq!(
+            kind(&["abicoder", "v3"]),
+            Err(PragmaError::Unknown("abicoder v3".to_string()))
+        );
+        assert!(kind(&["solidity", ">="]).is_err());
+        assert!(kind(&[]).is_err());
+
+        for (_, source_unit) in crate::ast::all_fixtures() {
+            for node in &source_unit.nodes {
+                if let SourceUnitNode::PragmaDirective(pragma) = node {
+                    assert!(pragma.kind().is_ok(), "{:?}", pragma.literals);
+                    let json = serde_json::to_value(pragma).unwrap();
+                    assert_eq!(
+                        serde_json::from_value::<PragmaDirective>(json).unwrap(),
+                        *pragma
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_source_text() {
+        let source = r#"// SPDX-License-Identifier: MIT
+// pragma solidity ^0.4.0;
+/* pragma solidity ^0.5.0; */
+pragma solidity >=0.6.2 <0.9.0;
+pragma abicoder v2;
+pragma   solidity
+    ^0.8.0;
+
+contract A {
+    string s = "pragma solidity ^0.7.0;";
+}
+"#;
+        let pragmas = source_pragmas("A.sol", source, 2).unwrap();
+        let requirements: Vec<&str> = pragmas.iter().map(|p| p.requirement.as_str()).collect();
+        assert_eq!(requirements, [">=0.6.2 <0.9.0", "^0.8.0"]);
+        let src = &pragmas[0].src;
+        assert_eq!(
+            &source[src.offset..src.offset + src.length],
+            "pragma solidity >=0.6.2 <0.9.0;"
+        );
+        assert_eq!(src.source_index, 2);
+        assert_eq!(pragmas[1].requirement.alternatives().len(), 1);
+
+        assert!(source_pragmas("B.sol", "pragma solidity ^0.8.x.1;", 0).is_err());
+
+        let input = StandardJsonInput::new()
+            .add_source("B.sol", "pragma solidity ^0.8.20;")
+            .add_source("A.sol", source)
+            .add_source_urls("C.sol", vec!["https://example.com/C.sol".to_string()], None);
+        let report = PragmaReport::from_input(&input).unwrap();
+        assert_eq!(report.pragmas.len(), 3);
+        assert_eq!(report.pragmas[2].src.source_index, 1);
+        assert_eq!(report.requirements().len(), 3);
+        let releases: Vec<Version> = (0..=30).map(|patch| Version::new(0, 8, patch)).collect();
+        assert_eq!(report.newest(&releases), Some(&version("0.8.30")));
+        assert_eq!(report.satisfying(&releases).len(), 11);
+
+        let input = StandardJsonInput::new()
+            .add_source("a/b.sol", "pragma solidity ^0.8.0;")
+            .add_source("a.sol", "pragma solidity ^0.7.0;");
+        let report = PragmaReport::from_input(&input).unwrap();
+        let indices: Vec<(&str, usize)> = report
+            .pragmas
+            .iter()
+            .map(|p| (p.requirement.as_str(), p.src.source_index))
+            .collect();
+        assert_eq!(indices, [("^0.7.0", 0), ("^0.8.0", 1)]);
+    }
+
+    #[test]
+    fn reports_blocking_pragmas() {
+        let source_units: Vec<SourceUnit> = crate::ast::all_fixtures()
+            .into_iter()
+            .map(|(_, source_unit)| source_unit)
+            .collect();
+        let report = PragmaReport::from_source_units(&source_units).unwrap();
+        assert!(report.pragmas.len() > 400);
+
+        let releases: Vec<Version> = (0..=30).map(|patch| Version::new(0, 8, patch)).collect();
+        assert!(report.satisfying(&releases).is_empty());
+        let blocking = report.blocking(&version("0.8.30"));
+        assert!(!blocking.is_empty());
+        for pragma in blocking {
+            assert!(!pragma.requirement.matches(&version("0.8.30")));
+            assert!(pragma.src.length > 0);
+        }
+
+        let pinned: Vec<&SourceUnit> = source_units
+            .iter()
+            .filter(|source_unit| {
+                report
+                    .blocking(&version("0.8.30"))
+                    .iter()
+                    .all(|pragma| pragma.path != source_unit.absolute_path)
+            })
+            .collect();
+        let report = PragmaReport::from_source_units(pinned).unwrap();
+        assert_eq!(report.satisfying(&releases), [&version("0.8.30")]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Collecting the sources of a project from disk.
+//!
+//! A [`Resolver`] starts from entry files or directories of a project, reads
+//! the `import` statements of each source, and follows them to every source
+//! the entries need. Imports are resolved to source unit names the same way
+//! as in [`ImportGraph`](crate::graph::ImportGraph): relative paths against
+//! the importing source, and other paths through the remappings. A source
+//! unit name is read from the project root, or from an include path such as
+//! `node_modules` when the root does not have it, which is how Hardhat
+//! projects import packages.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::graph::{Remapping, parse_imports, resolve_import};
+use crate::standard_json_input::{SourceContent, StandardJsonInput};
+
+/// Collects the sources of a project and everything they import.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Resolver {
+    root: PathBuf,
+    remappings: Vec<String>,
+    include_paths: Vec<PathBuf>,
+}
+
+/// An error that stopped a [`Resolver`] from collecting sources.
+#[derive(Debug, thiserror::Error)]
+pub enum ResolveError {
+    /// An imported source is neither in the project nor in an include path.
+    #[error("{} imported by {} was not found", name.display(), importer.display())]
+    NotFound { name: PathBuf, importer: PathBuf },
+
+    /// A file or directory could not be read.
+    #[error("failed to read {}: {error}", path.display())]
+    Io { path: PathBuf, error: io::Error },
+}
+
+impl Resolver {
+    /// A resolver for the project in `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add remappings of the form `context:prefix=target`, as Foundry and
+    /// solc take them.
+    pub fn remappings(mut self, remappings: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.remappings
+            .extend(remappings.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add the remappings in the `remappings.txt` of the project root, one
+    /// per line, if the file exists.
+    pub fn remappings_file(self) -> Result<Self, ResolveError> {
+        let path = self.root.join("remappings.txt");
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(self),
+            Err(error) => return Err(ResolveError::Io { path, error }),
+        };
+        let remappings: Vec<String> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(self.remappings(remappings))
+    }
+
+    /// Add a directory to read source unit names from when the project root
+    /// does not have them, such as `node_modules`. Relative paths are
+    /// relative to the project root.
+    pub fn include_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.include_paths.push(path.into());
+        self
+    }
+
+    /// Add the entries and every source they import to `input`.
+    ///
+    /// Entries are files or directories relative to the project root;
+    /// directories add every `.sol` file below them. Sources that `input`
+    /// already has are not read again, but their imports are followed. The
+    /// remappings are added to the settings of the input, so that the
+    /// compiler resolves the imports the same way.
+    pub fn resolve(
+        &self,
+        mut input: StandardJsonInput,
+        entries: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> Result<StandardJsonInput, ResolveError> {
+        let mut settings_remappings = input.settings.remappings.take().unwrap_or_default();
+        for remapping in &self.remappings {
+            if !settings_remappings.contains(remapping) {
+                settings_remappings.push(remapping.clone());
+            }
+        }
+        let remappings: Vec<Remapping> = settings_remappings
+            .iter()
+            .filter_map(|remapping| Remapping::parse(remapping))
+            .collect();
+        if !settings_remappings.is_empty() {
+            input.settings.remappings = Some(settings_remappings);
+        }
+
+        let mut pending: VecDeque<(PathBuf, Option<PathBuf>)> = VecDeque::new();
+        for entry in entries {
+            for name in self.entry_sources(entry.as_ref())? {
+                pending.push_back((name, None));
+            }
+        }
+
+        let mut visited = BTreeSet::new();
+        while let Some((name, importer)) = pending.pop_front() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            let content = match input.sources.get(&name).map(|source| &source.content) {
+                Some(SourceContent::Content { content }) => content.clone(),
+                Some(SourceContent::Urls { .. }) => continue,
+                None => {
+                    let content = self.read(&name, importer.as_deref())?;
+                    input = input.add_source(name.clone(), content.clone());
+                    content
+                }
+            };
+            for import in parse_imports(&content) {
+                let import = resolve_import(&name, &import, &remappings);
+                if !visited.contains(&import) {
+                    pending.push_back((import, Some(name.clone())));
+                }
+            }
+        }
+        Ok(input)
+    }
+
+    /// The source unit names of an entry, which is a file or a directory.
+    fn entry_sources(&self, entry: &Path) -> Result<Vec<PathBuf>, ResolveError> {
+        let path = self.root.join(entry);
+        if !path.is_dir() {
+            return Ok(vec![entry.to_path_buf()]);
+        }
+        let mut sources = Vec::new();
+        let mut directories = vec![entry.to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            let path = self.root.join(&directory);
+            let entries = fs::read_dir(&path).map_err(|error| ResolveError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            for entry in entries {
+                let entry = entry.map_err(|error| ResolveError::Io {
+                    path: path.clone(),
+                    error,
+                })?;
+                let name = directory.join(entry.file_name());
+                if entry.path().is_dir() {
+                    directories.push(name);
+                } else if name.extension().is_some_and(|extension| extension == "sol") {
+                    sources.push(name);
+                }
+            }
+        }
+        sources.sort();
+        Ok(sources)
+    }
+
+    /// Read a source unit from the project root or an include path.
+    fn read(&self, name: &Path, importer: Option<&Path>) -> Result<String, ResolveError> {
+        let directories = std::iter::once(self.root.clone()).chain(
+            self.include_paths
+                .iter()
+                .map(|include_path| self.root.join(include_path)),
+        );
+        for directory in directories {
+            let path = directory.join(name);
+            match fs::read_to_string(&path) {
+                Ok(content) => return Ok(content),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(ResolveError::Io { path, error }),
+            }
+        }
+        match importer {
+            Some(importer) => Err(ResolveError::NotFound {
+                name: name.to_path_buf(),
+                importer: importer.to_path_buf(),
+            }),
+            None => Err(ResolveError::Io {
+                path: self.root.join(name),
+                error: io::ErrorKind::NotFound.into(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root =
+            env::temp_dir().join(format!("solc-rs-resolver-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn names(input: &StandardJsonInput) -> Vec<&str> {
+        input
+            .sources
+            .keys()
+            .map(|name| name.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn follows_imports_through_remappings() {
+        let root = project(
+            "remappings",
+            &[
+                ("remappings.txt", "forge-std/=lib/forge-std/src/\n"),
+                (
+                    "src/Vault.sol",
+                    r#"import "./token/Token.sol"; import "forge-std/Test.sol";"#,
+                ),
+                ("src/token/Token.sol", r#"import "../Math.sol";"#),
+                ("src/Math.sol", ""),
+                ("src/Unused.sol", ""),
+                ("lib/forge-std/src/Test.sol", r#"import "./Vm.sol";"#),
+                ("lib/forge-std/src/Vm.sol", ""),
+            ],
+        );
+        let input = Resolver::new(&root)
+            .remappings_file()
+            .unwrap()
+            .resolve(StandardJsonInput::new(), ["src/Vault.sol"])
+            .unwrap();
+        assert_eq!(
+            names(&input),
+            vec![
+                "lib/forge-std/src/Test.sol",
+                "lib/forge-std/src/Vm.sol",
+                "src/Math.sol",
+                "src/Vault.sol",
+                "src/token/Token.sol",
+            ]
+        );
+        assert_eq!(
+            input.settings.remappings,
+            Some(vec!["forge-std/=lib/forge-std/src/".to_string()])
+        );
+
+        let input = Resolver::new(&root)
+            .remappings(["forge-std/=lib/forge-std/src/"])
+            .resolve(StandardJsonInput::new(), ["src"])
+            .unwrap();
+        assert_eq!(input.sources.len(), 6);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn reads_packages_from_include_paths() {
+        let root = project(
+            "include",
+            &[
+                (
+                    "contracts/Token.sol",
+                    r#"import "@openzeppelin/contracts/ERC20.sol";"#,
+                ),
+                ("node_modules/@openzeppelin/contracts/ERC20.sol", ""),
+            ],
+        );
+        let resolver = Resolver::new(&root);
+        let error = resolver
+            .resolve(StandardJsonInput::new(), ["contracts/Token.sol"])
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ResolveError::NotFound { ref name, ref importer }
+                if name == Path::new("@openzeppelin/contracts/ERC20.sol")
+                    && importer == Path::new("contracts/Token.sol")
+        ));
+
+        let input = resolver
+            .include_path("node_modules")
+            .resolve(StandardJsonInput::new(), ["contracts/Token.sol"])
+            .unwrap();
+        assert_eq!(
+            names(&input),
+            vec!["@openzeppelin/contracts/ERC20.sol", "contracts/Token.sol"]
+        );
+        assert_eq!(input.settings.remappings, None);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,620 @@
+//! Function, event, and error signatures and their selectors.
+//!
+//! Calldata, logs, and revert data identify what they encode only by a hash
+//! of the signature: the 4-byte selector of a function or error, and the
+//! 32-byte topic of an event. This module collects signatures from ABIs and
+//! compiler outputs, exports them in the formats accepted by public signature
+//! databases ([4byte.directory] and [openchain.xyz]), and reads local copies of
+//! such databases back for reverse lookups. [`SelectorDatabase`] stores
+//! signatures in a compact binary file for offline lookups.
+//!
+//! [4byte.directory]: https://www.4byte.directory
+//! [openchain.xyz]: https://openchain.xyz/signatures
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::abi::{Abi, AbiItem, keccak256};
+use crate::standard_json_output::StandardJsonOutput;
+
+/// What a signature describes, which decides the length of its selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SignatureKind {
+    /// A function, identified by a 4-byte selector.
+    Function,
+    /// An event, identified by a 32-byte topic.
+    Event,
+    /// A custom error, identified by a 4-byte selector.
+    Error,
+}
+
+/// A canonical signature such as `transfer(address,uint256)`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signature {
+    /// What the signature describes.
+    pub kind: SignatureKind,
+    /// The signature text, without parameter names or spaces.
+    pub text: String,
+}
+
+impl Signature {
+    /// Create a signature of the given kind.
+    pub fn new(kind: SignatureKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    /// The selector: 4 bytes for functions and errors, 32 bytes for events.
+    pub fn selector(&self) -> Vec<u8> {
+        let hash = keccak256(self.text.as_bytes());
+        match self.kind {
+            SignatureKind::Event => hash.to_vec(),
+            SignatureKind::Function | SignatureKind::Error => hash[..4].to_vec(),
+        }
+    }
+
+    /// The selector as a `0x`-prefixed hex string.
+    pub fn selector_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.selector()))
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SignatureKind::Function => write!(f, "function {}", self.text),
+            SignatureKind::Event => write!(f, "event {}", self.text),
+            SignatureKind::Error => write!(f, "error {}", self.text),
+        }
+    }
+}
+
+/// An error reading a signature database.
+#[derive(Debug, thiserror::Error)]
+pub enum SignaturesError {
+    /// The database is not valid JSON or does not have the expected shape.
+    #[error("invalid signature database: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A line of a text database is not a signature.
+    #[error("invalid signature on line {line}: `{text}`")]
+    InvalidLine { line: usize, text: String },
+
+    /// A database entry lists a selector that does not match its signature.
+    #[error("selector {selector} does not match signature `{signature}`")]
+    SelectorMismatch { selector: String, signature: String },
+}
+
+/// A set of signatures, kept sorted and without duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Signatures {
+    entries: BTreeSet<Signature>,
+}
+
+impl Signatures {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the signatures of all functions, events, and errors in an ABI.
+    ///
+    /// Anonymous events are skipped, since they have no topic.
+    pub fn from_abi(abi: &Abi) -> Self {
+        let mut signatures = Self::new();
+        signatures.extend_from_abi(abi);
+        signatures
+    }
+
+    /// Collect the signatures of every contract in a compiler output.
+    pub fn from_output(output: &StandardJsonOutput) -> Self {
+        let mut signatures = Self::new();
+        for contract in output.contracts.values().flat_map(|c| c.values()) {
+            if let Some(abi) = &contract.abi {
+                signatures.extend_from_abi(abi);
+            }
+        }
+        signatures
+    }
+
+    /// Add the signatures of an ABI to the set.
+    pub fn extend_from_abi(&mut self, abi: &Abi) {
+        for item in &abi.items {
+            let signature = match item {
+                AbiItem::Function(function) => {
+                    Signature::new(SignatureKind::Function, function.signature())
+                }
+                AbiItem::Event(event) if !event.anonymous => {
+                    Signature::new(SignatureKind::Event, event.signature())
+                }
+                AbiItem::Error(error) => Signature::new(SignatureKind::Error, error.signature()),
+                _ => continue,
+            };
+            self.insert(signature);
+        }
+    }
+
+    /// Add a signature. Returns whether it was not present yet.
+    pub fn insert(&mut self, signature: Signature) -> bool {
+        self.entries.insert(signature)
+    }
+
+    /// Iterate over the signatures in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &Signature> {
+        self.entries.iter()
+    }
+
+    /// The number of signatures.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the signatures whose selector is `selector`.
+    ///
+    /// A 4-byte selector matches functions and errors, and a 32-byte topic
+    /// matches events. Unrelated signatures can share a 4-byte selector, so
+    /// there may be more than one match.
+    pub fn lookup<'a>(&'a self, selector: &'a [u8]) -> impl Iterator<Item = &'a Signature> {
+        self.entries
+            .iter()
+            .filter(move |signature| signature.selector() == selector)
+    }
+
+    /// Export the signatures for [4byte.directory].
+    ///
+    /// The result has one function or error signature per line, the text
+    /// format of its signature submission form. Events are not included,
+    /// since the directory only lists them when imported from an ABI.
+    ///
+    /// [4byte.directory]: https://www.4byte.directory
+    pub fn to_4byte(&self) -> String {
+        self.entries
+            .iter()
+            .filter(|signature| signature.kind != SignatureKind::Event)
+            .map(|signature| format!("{}\n", signature.text))
+            .collect()
+    }
+
+    /// Export the signatures in the [openchain.xyz] import format.
+    ///
+    /// The result is a JSON object with `function` and `event` lists of
+    /// signature text. Errors are listed as functions, since they share the
+    /// 4-byte selector space.
+    ///
+    /// [openchain.xyz]: https://openchain.xyz/signatures
+    pub fn to_openchain(&self) -> Value {
+        let list = |event: bool| {
+            self.entries
+                .iter()
+                .filter(|signature| (signature.kind == SignatureKind::Event) == event)
+                .map(|signature| Value::String(signature.text.clone()))
+                .collect::<Vec<_>>()
+        };
+        json!({ "function": list(false), "event": list(true) })
+    }
+
+    /// Read signatures in the [openchain.xyz] formats.
+    ///
+    /// Both the import format written by [`Signatures::to_openchain`] and the
+    /// lookup response format (`{"result": {"function": {"0x…": [{"name":
+    /// …}]}}}`) are accepted. In the lookup format every selector is checked
+    /// against its signatures.
+    ///
+    /// [openchain.xyz]: https://openchain.xyz/signatures
+    pub fn from_openchain(json: &str) -> Result<Self, SignaturesError> {
+        let value: Value = serde_json::from_str(json)?;
+        let sections = value.get("result").unwrap_or(&value);
+        let mut signatures = Self::new();
+        for (key, kind) in [
+            ("function", SignatureKind::Function),
+            ("event", SignatureKind::Event),
+        ] {
+            match sections.get(key) {
+                Some(Value::Array(texts)) => {
+                    for text in texts {
+                        let text = serde_json::from_value::<String>(text.clone())?;
+                        signatures.insert(Signature::new(kind, text));
+                    }
+                }
+                Some(Value::Object(by_selector)) => {
+                    signatures.read_openchain_lookup(kind, by_selector)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(signatures)
+    }
+
+    fn read_openchain_lookup(
+        &mut self,
+        kind: SignatureKind,
+        by_selector: &Map<String, Value>,
+    ) -> Result<(), SignaturesError> {
+        #[derive(Deserialize)]
+        struct Entry {
+            name: String,
+        }
+
+        for (selector, entries) in by_selector {
+            let entries = Option::<Vec<Entry>>::deserialize(entries)?;
+            for entry in entries.into_iter().flatten() {
+                let signature = Signature::new(kind, entry.name);
+                if !signature.selector_hex().eq_ignore_ascii_case(selector) {
+                    return Err(SignaturesError::SelectorMismatch {
+                        selector: selector.clone(),
+                        signature: signature.text,
+                    });
+                }
+                self.insert(signature);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read signatures from a text database with one entry per line.
+    ///
+    /// Each line is a signature, optionally preceded by `event` or `error` to
+    /// set its kind (functions otherwise) and by its hex selector, as in
+    /// `0xa9059cbb transfer(address,uint256)` or the comma-separated
+    /// `0xa9059cbb,transfer(address,uint256)`. Selectors that are given are
+    /// checked. Empty lines and lines starting with `#` are skipped.
+    pub fn from_text(text: &str) -> Result<Self, SignaturesError> {
+        let mut signatures = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || SignaturesError::InvalidLine {
+                line: index + 1,
+                text: line.to_string(),
+            };
+
+            let (selector, rest) = match line.split_once([' ', ',', '\t']) {
+                Some((selector, rest)) if selector.starts_with("0x") => {
+                    (Some(selector), rest.trim())
+                }
+                _ => (None, line),
+            };
+            let (kind, text) = if let Some(text) = rest.strip_prefix("event ") {
+                (SignatureKind::Event, text.trim())
+            } else if let Some(text) = rest.strip_prefix("error ") {
+                (SignatureKind::Error, text.trim())
+            } else {
+                (
+                    SignatureKind::Function,
+                    rest.strip_prefix("function ").unwrap_or(rest).trim(),
+                )
+            };
+            if !text.ends_with(')') || !text.contains('(') || text.contains(' ') {
+                return Err(invalid());
+            }
+
+            let signature = Signature::new(kind, text);
+            if let Some(selector) = selector
+                && !signature.selector_hex().eq_ignore_ascii_case(selector)
+            {
+                return Err(SignaturesError::SelectorMismatch {
+                    selector: selector.to_string(),
+                    signature: signature.text,
+                });
+            }
+            signatures.insert(signature);
+        }
+        Ok(signatures)
+    }
+}
+
+impl Extend<Signature> for Signatures {
+    fn extend<T: IntoIterator<Item = Signature>>(&mut self, iter: T) {
+        self.entries.extend(iter);
+    }
+}
+
+impl FromIterator<Signature> for Signatures {
+    fn from_iter<T: IntoIterator<Item = Signature>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// The first bytes of a [`SelectorDatabase`] file, including the format version.
+const DATABASE_MAGIC: &[u8; 8] = b"SOLCSEL1";
+
+/// A selector to signature database for offline lookups.
+///
+/// Entries are kept sorted by selector, so lookups are a binary search and the
+/// file format is the entries written in order. Each entry is its kind byte,
+/// its selector (4 bytes, or 32 for events), and its signature text prefixed
+/// with a big-endian `u16` length, after an 8-byte header and a `u32` count.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectorDatabase {
+    entries: Vec<(Vec<u8>, Signature)>,
+}
+
+/// An error reading or writing a [`SelectorDatabase`] file.
+#[derive(Debug, thiserror::Error)]
+pub enum SelectorDatabaseError {
+    /// The file could not be read or written.
+    #[error("selector database I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The file is not a selector database or is truncated.
+    #[error("invalid selector database: {0}")]
+    InvalidFormat(&'static str),
+}
+
+impl SelectorDatabase {
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the signatures of every contract in a compiler output.
+    pub fn add_output(&mut self, output: &StandardJsonOutput) {
+        self.add_signatures(&Signatures::from_output(output));
+    }
+
+    /// Add signatures, skipping those already in the database.
+    pub fn add_signatures(&mut self, signatures: &Signatures) {
+        for signature in signatures.iter() {
+            let selector = signature.selector();
+            let key = (selector.as_slice(), signature);
+            if let Err(index) = self
+                .entries
+                .binary_search_by(|(s, sig)| (s.as_slice(), sig).cmp(&key))
+            {
+                self.entries.insert(index, (selector, signature.clone()));
+            }
+        }
+    }
+
+    /// The number of signatures in the database.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the database is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the signatures with this selector or event topic.
+    pub fn lookup(&self, selector: &[u8]) -> impl Iterator<Item = &Signature> {
+        let start = self
+            .entries
+            .partition_point(|(s, _)| s.as_slice() < selector);
+        self.entries[start..]
+            .iter()
+            .take_while(move |(s, _)| s.as_slice() == selector)
+            .map(|(_, signature)| signature)
+    }
+
+    /// Find the functions that calldata may be calling, from its selector.
+    ///
+    /// Errors are skipped, since they share the selector space but are never
+    /// called. Calldata shorter than a selector matches nothing.
+    pub fn label_calldata(&self, calldata: &[u8]) -> impl Iterator<Item = &Signature> {
+        calldata
+            .get(..4)
+            .into_iter()
+            .flat_map(|selector| self.lookup(selector))
+            .filter(|signature| signature.kind == SignatureKind::Function)
+    }
+
+    /// Read a database from a file written by [`SelectorDatabase::save`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SelectorDatabaseError> {
+        Self::read_from(io::BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Write the database to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SelectorDatabaseError> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the database in its binary format.
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), SelectorDatabaseError> {
+        let count = u32::try_from(self.entries.len())
+            .map_err(|_| SelectorDatabaseError::InvalidFormat("too many entries"))?;
+        writer.write_all(DATABASE_MAGIC)?;
+        writer.write_all(&count.to_be_bytes())?;
+        for (selector, signature) in &self.entries {
+            let length = u16::try_from(signature.text.len())
+                .map_err(|_| SelectorDatabaseError::InvalidFormat("signature too long"))?;
+            let kind = match signature.kind {
+                SignatureKind::Function => 0u8,
+                SignatureKind::Event => 1,
+                SignatureKind::Error => 2,
+            };
+            writer.write_all(&[kind])?;
+            writer.write_all(selector)?;
+            writer.write_all(&length.to_be_bytes())?;
+            writer.write_all(signature.text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read a database in its binary format.
+    ///
+    /// Selectors are stored so that lookups need no hashing, and are trusted
+    /// as they are. The entries must be in sorted order, as written by
+    /// [`SelectorDatabase::write_to`].
+    pub fn read_from(mut reader: impl Read) -> Result<Self, SelectorDatabaseError> {
+        let mut magic = [0u8; 8];
+        read_exact(&mut reader, &mut magic)?;
+        if &magic != DATABASE_MAGIC {
+            return Err(SelectorDatabaseError::InvalidFormat("bad header"));
+        }
+        let mut count = [0u8; 4];
+        read_exact(&mut reader, &mut count)?;
+
+        let mut entries: Vec<(Vec<u8>, Signature)> = Vec::new();
+        for _ in 0..u32::from_be_bytes(count) {
+            let mut kind = [0u8; 1];
+            read_exact(&mut reader, &mut kind)?;
+            let kind = match kind[0] {
+                0 => SignatureKind::Function,
+                1 => SignatureKind::Event,
+                2 => SignatureKind::Error,
+                _ => return Err(SelectorDatabaseError::InvalidFormat("unknown entry kind")),
+            };
+            let mut selector = vec![0u8; if kind == SignatureKind::Event { 32 } else { 4 }];
+            read_exact(&mut reader, &mut selector)?;
+            let mut length = [0u8; 2];
+            read_exact(&mut reader, &mut length)?;
+            let mut text = vec![0u8; u16::from_be_bytes(length).into()];
+            read_exact(&mut reader, &mut text)?;
+            let text = String::from_utf8(text)
+                .map_err(|_| SelectorDatabaseError::InvalidFormat("signature is not UTF-8"))?;
+
+            let entry = (selector, Signature::new(kind, text));
+            if entries.last().is_some_and(|last| *last >= entry) {
+                return Err(SelectorDatabaseError::InvalidFormat(
+                    "entries are not sorted",
+                ));
+            }
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Read exactly `buf.len()` bytes, reporting a short read as a truncated file.
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), SelectorDatabaseError> {
+    reader.read_exact(buf).map_err(|error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => SelectorDatabaseError::InvalidFormat("truncated file"),
+        _ => SelectorDatabaseError::Io(error),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[
+        {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+         "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}],
+         "outputs": [{"name": "", "type": "bool"}]},
+        {"type": "function", "name": "submit", "stateMutability": "nonpayable",
+         "inputs": [{"name": "order", "type": "tuple[]", "components": [
+             {"name": "maker", "type": "address"}, {"name": "amounts", "type": "uint256[2]"}]}],
+         "outputs": []},
+        {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}]},
+        {"type": "event", "name": "Hidden", "anonymous": true, "inputs": []},
+        {"type": "error", "name": "InsufficientBalance", "inputs": [{"name": "needed", "type": "uint256"}]}
+    ]"#;
+
+    #[test]
+    fn signatures_from_abi() {
+        let abi: Abi = serde_json::from_str(ABI).unwrap();
+        let signatures = Signatures::from_abi(&abi);
+        let texts: Vec<String> = signatures.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "function submit((address,uint256[2])[])",
+                "function transfer(address,uint256)",
+                "event Transfer(address,address,uint256)",
+                "error InsufficientBalance(uint256)",
+            ]
+        );
+
+        let transfer = signatures.lookup(&[0xa9, 0x05, 0x9c, 0xbb]).next().unwrap();
+        assert_eq!(transfer.text, "transfer(address,uint256)");
+        let topic = hex::decode("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+            .unwrap();
+        assert_eq!(
+            signatures.lookup(&topic).next().unwrap().kind,
+            SignatureKind::Event
+        );
+    }
+
+    #[test]
+    fn export_and_import() {
+        let abi: Abi = serde_json::from_str(ABI).unwrap();
+        let signatures = Signatures::from_abi(&abi);
+
+        assert_eq!(
+            signatures.to_4byte(),
+            "submit((address,uint256[2])[])\ntransfer(address,uint256)\nInsufficientBalance(uint256)\n"
+        );
+
+        let openchain = signatures.to_openchain();
+        assert_eq!(openchain["event"][0], "Transfer(address,address,uint256)");
+        let imported = Signatures::from_openchain(&openchain.to_string()).unwrap();
+        assert_eq!(imported.len(), 4);
+
+        let lookup = r#"{"ok": true, "result": {
+            "function": {"0xa9059cbb": [{"name": "transfer(address,uint256)", "filtered": false}], "0x12345678": null},
+            "event": {}
+        }}"#;
+        let imported = Signatures::from_openchain(lookup).unwrap();
+        assert_eq!(
+            imported.iter().next().unwrap().text,
+            "transfer(address,uint256)"
+        );
+
+        let text = "# local database\n0xa9059cbb transfer(address,uint256)\n0x08c379a0,error Error(string)\nevent Transfer(address,address,uint256)\n";
+        let imported = Signatures::from_text(text).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert!(matches!(
+            Signatures::from_text("0xdeadbeef transfer(address,uint256)"),
+            Err(SignaturesError::SelectorMismatch { .. })
+        ));
+        assert!(matches!(
+            Signatures::from_text("not a signature"),
+            Err(SignaturesError::InvalidLine { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn selector_database() {
+        let abi: Abi = serde_json::from_str(ABI).unwrap();
+        let mut database = SelectorDatabase::new();
+        database.add_signatures(&Signatures::from_abi(&abi));
+        database.add_signatures(&Signatures::from_abi(&abi));
+        assert_eq!(database.len(), 4);
+
+        let mut file = Vec::new();
+        database.write_to(&mut file).unwrap();
+        let database = SelectorDatabase::read_from(file.as_slice()).unwrap();
+        assert_eq!(database.len(), 4);
+
+        let calldata = hex::decode("a9059cbb000000000000000000000000").unwrap();
+        let labels: Vec<&str> = database
+            .label_calldata(&calldata)
+            .map(|signature| signature.text.as_str())
+            .collect();
+        assert_eq!(labels, vec!["transfer(address,uint256)"]);
+        assert_eq!(database.label_calldata(&[0xa9]).count(), 0);
+
+        let topic = hex::decode("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+            .unwrap();
+        assert_eq!(database.lookup(&topic).count(), 1);
+
+        assert!(matches!(
+            SelectorDatabase::read_from(&file[..file.len() - 1]),
+            Err(SelectorDatabaseError::InvalidFormat("truncated file"))
+        ));
+    }
+}
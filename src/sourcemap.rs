@@ -0,0 +1,214 @@
+//! Source maps of EVM bytecode.
+//!
+//! The compiler reports `evm.bytecode.sourceMap` as one `s:l:f:j:m` entry
+//! per instruction, separated by `;`. Each entry gives the source range an
+//! instruction was generated from, the kind of jump it performs, and how
+//! deeply it is nested in modifiers. The string is compressed: an empty or
+//! missing field repeats the value of the previous entry. This module parses
+//! it into one [`SourceMapEntry`] per instruction, so that debuggers and
+//! coverage tools can find the source of the instruction at a program
+//! counter.
+//!
+//! The format is described in the [Solidity documentation].
+//!
+//! [Solidity documentation]: https://docs.soliditylang.org/en/latest/internals/source_mappings.html
+
+use crate::ast::SourceLocation;
+use crate::opcodes::Instruction;
+
+/// The kind of jump an instruction performs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Jump {
+    /// A jump into a function, written `i`.
+    Into,
+    /// A return from a function, written `o`.
+    Out,
+    /// Any other instruction, written `-`.
+    #[default]
+    Regular,
+}
+
+/// The source mapping of one instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SourceMapEntry {
+    /// The byte offset of the source range, or -1 if there is none.
+    pub offset: i64,
+    /// The length of the source range in bytes, or -1 if there is none.
+    pub length: i64,
+    /// The index of the source file, or -1 for instructions that belong to
+    /// no source, such as those the optimizer inserts.
+    pub file: i64,
+    /// The kind of jump the instruction performs.
+    pub jump: Jump,
+    /// How many modifiers the instruction is nested in.
+    pub modifier_depth: usize,
+}
+
+impl SourceMapEntry {
+    /// The source range of the instruction, or `None` if it has none.
+    ///
+    /// The file index may also refer to a source the compiler generated,
+    /// which is listed in `generatedSources` rather than in the input.
+    pub fn location(&self) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            offset: usize::try_from(self.offset).ok()?,
+            length: usize::try_from(self.length).ok()?,
+            source_index: usize::try_from(self.file).ok()?,
+        })
+    }
+}
+
+/// An error returned when a source map cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SourceMapError {
+    /// A field is not a number, or not a jump type for the jump field.
+    #[error("invalid field `{field}` in source map entry {entry}")]
+    InvalidField { entry: usize, field: String },
+
+    /// An entry has more than five fields.
+    #[error("source map entry {0} has too many fields")]
+    TooManyFields(usize),
+}
+
+/// Parse a compressed source map into one entry per instruction.
+///
+/// The entry at index `n` belongs to the `n`-th instruction of the bytecode,
+/// counting instructions rather than bytes. Fields left out of the first
+/// entry default to -1 for the source range and file, `-` for the jump, and
+/// 0 for the modifier depth.
+pub fn parse(source_map: &str) -> Result<Vec<SourceMapEntry>, SourceMapError> {
+    if source_map.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    let mut current = SourceMapEntry {
+        offset: -1,
+        length: -1,
+        file: -1,
+        ..Default::default()
+    };
+    for (index, entry) in source_map.split(';').enumerate() {
+        let invalid = |field: &str| SourceMapError::InvalidField {
+            entry: index,
+            field: field.to_string(),
+        };
+        let parse_int = |field: &str| field.parse::<i64>().map_err(|_| invalid(field));
+
+        for (position, field) in entry.split(':').enumerate() {
+            if position > 4 {
+                return Err(SourceMapError::TooManyFields(index));
+            }
+            if field.is_empty() {
+                continue;
+            }
+            match position {
+                0 => current.offset = parse_int(field)?,
+                1 => current.length = parse_int(field)?,
+                2 => current.file = parse_int(field)?,
+                3 => {
+                    current.jump = match field {
+                        "i" => Jump::Into,
+                        "o" => Jump::Out,
+                        "-" => Jump::Regular,
+                        _ => return Err(invalid(field)),
+                    }
+                }
+                _ => current.modifier_depth = field.parse().map_err(|_| invalid(field))?,
+            }
+        }
+        entries.push(current);
+    }
+    Ok(entries)
+}
+
+/// Find the source range of the instruction at a byte offset in the code.
+///
+/// The instructions are those of the bytecode the source map belongs to, as
+/// returned by [`opcodes::parse`](crate::opcodes::parse) or
+/// [`opcodes::disassemble`](crate::opcodes::disassemble). Returns `None` if
+/// no instruction starts at `pc` or it has no source range.
+pub fn location_at(
+    entries: &[SourceMapEntry],
+    instructions: &[Instruction],
+    pc: usize,
+) -> Option<SourceLocation> {
+    let index = instructions
+        .binary_search_by_key(&pc, |instruction| instruction.offset)
+        .ok()?;
+    entries.get(index)?.location()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes;
+
+    #[test]
+    fn parse_compressed() {
+        let entries = parse("1:2:1;:9;2:1:2;;-1:0:-1:o:1;::::2").unwrap();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(
+            entries[1],
+            SourceMapEntry {
+                offset: 1,
+                length: 9,
+                file: 1,
+                jump: Jump::Regular,
+                modifier_depth: 0,
+            }
+        );
+        assert_eq!(entries[3], entries[2]);
+        assert_eq!(entries[4].jump, Jump::Out);
+        assert_eq!(entries[4].location(), None);
+        assert_eq!(entries[5].modifier_depth, 2);
+        assert_eq!(entries[5].file, -1);
+        assert_eq!(
+            entries[2].location(),
+            Some(SourceLocation {
+                offset: 2,
+                length: 1,
+                source_index: 2
+            })
+        );
+
+        assert_eq!(parse("").unwrap(), []);
+        assert_eq!(
+            parse("1:2:x"),
+            Err(SourceMapError::InvalidField {
+                entry: 0,
+                field: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse("1;1:2:0:j"),
+            Err(SourceMapError::InvalidField {
+                entry: 1,
+                field: "j".to_string()
+            })
+        );
+        assert_eq!(parse("1:2:0:-:0:0"), Err(SourceMapError::TooManyFields(0)));
+        assert_eq!(parse("1:2:0:-:0:"), Err(SourceMapError::TooManyFields(0)));
+        assert_eq!(
+            parse("1:2:0:-:0::::"),
+            Err(SourceMapError::TooManyFields(0))
+        );
+        assert_eq!(parse("1;::::::"), Err(SourceMapError::TooManyFields(1)));
+    }
+
+    #[test]
+    fn locate_program_counter() {
+        let instructions = opcodes::parse("PUSH1 0x80 PUSH1 0x40 MSTORE").unwrap();
+        let entries = parse("58:100:0:-:0;;10:5").unwrap();
+        assert_eq!(
+            location_at(&entries, &instructions, 4),
+            Some(SourceLocation {
+                offset: 10,
+                length: 5,
+                source_index: 0
+            })
+        );
+        assert_eq!(location_at(&entries, &instructions, 2).unwrap().offset, 58);
+        assert_eq!(location_at(&entries, &instructions, 1), None);
+    }
+}
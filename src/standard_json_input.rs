@@ -3,9 +3,11 @@
 //! This module provides types for the compiler's `--standard-json` interface,
 //! including source files, language settings, and compilation options.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::path::PathBuf;
 
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
 /// Solidity compiler Standard JSON input.
@@ -16,7 +18,7 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct StandardJsonInput {
     pub language: Language,
-    pub sources: HashMap<PathBuf, Source>,
+    pub sources: BTreeMap<PathBuf, Source>,
     pub settings: Settings,
 }
 
@@ -33,7 +35,7 @@ pub enum Language {
 }
 
 /// Source file entry with optional hash validation.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Source {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keccak256: Option<String>,
@@ -42,7 +44,7 @@ pub struct Source {
 }
 
 /// Source content as embedded text or URL references.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum SourceContent {
     Content { content: String },
@@ -61,20 +63,226 @@ pub struct Settings {
     pub optimizer: Option<Optimizer>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub evm_version: Option<EvmVersion>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "viaIR",
+        alias = "viaIr",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub via_ir: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub eof_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub debug: Option<DebugSettings>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<MetadataSettings>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub libraries: Option<HashMap<String, HashMap<String, String>>>,
+    pub libraries: Option<BTreeMap<String, BTreeMap<String, String>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub output_selection: Option<HashMap<String, HashMap<String, Vec<String>>>>,
+    pub output_selection: Option<OutputSelection>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_checker: Option<ModelCheckerSettings>,
 }
 
+/// The outputs to produce, by file and contract name.
+///
+/// Both names may be `*` for all files or contracts. Outputs of a whole
+/// file, such as [`OutputSelector::Ast`], are selected under the empty
+/// contract name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OutputSelection(pub BTreeMap<String, BTreeMap<String, Vec<OutputSelector>>>);
+
+impl OutputSelection {
+    /// Select outputs for the contract `contract` in `file`.
+    ///
+    /// Outputs that are already selected are not added again.
+    pub fn add(
+        &mut self,
+        file: impl Into<String>,
+        contract: impl Into<String>,
+        selectors: impl IntoIterator<Item = OutputSelector>,
+    ) -> &mut Self {
+        let selected = self
+            .0
+            .entry(file.into())
+            .or_default()
+            .entry(contract.into())
+            .or_default();
+        for selector in selectors {
+            if !selected.contains(&selector) {
+                selected.push(selector);
+            }
+        }
+        self
+    }
+
+    /// The outputs selected for the contract `contract` in `file`, exactly
+    /// as written, without matching `*` against other names.
+    pub fn get(&self, file: &str, contract: &str) -> &[OutputSelector] {
+        self.0
+            .get(file)
+            .and_then(|contracts| contracts.get(contract))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+macro_rules! output_selectors {
+    ($($(#[$doc:meta])* $variant:ident => $name:literal,)*) => {
+        /// An output that can be selected in an [`OutputSelection`].
+        ///
+        /// Selecting an output also selects the outputs nested in it, so
+        /// [`EvmBytecode`](Self::EvmBytecode) selects all of
+        /// `evm.bytecode.*`. Names not known to this crate are kept as
+        /// [`Other`](Self::Other).
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum OutputSelector {
+            $($(#[$doc])* $variant,)*
+            /// Any other output name.
+            Other(String),
+        }
+
+        impl OutputSelector {
+            /// The name of the output in Standard JSON.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(OutputSelector::$variant => $name,)*
+                    OutputSelector::Other(name) => name,
+                }
+            }
+        }
+
+        impl From<&str> for OutputSelector {
+            fn from(name: &str) -> Self {
+                match name {
+                    $($name => OutputSelector::$variant,)*
+                    _ => OutputSelector::Other(name.to_string()),
+                }
+            }
+        }
+    };
+}
+
+output_selectors! {
+    /// Every output.
+    All => "*",
+    /// The ABI.
+    Abi => "abi",
+    /// The AST of a file, selected under the empty contract name.
+    Ast => "ast",
+    /// The developer documentation.
+    Devdoc => "devdoc",
+    /// The user documentation.
+    Userdoc => "userdoc",
+    /// The metadata.
+    Metadata => "metadata",
+    /// The Yul intermediate representation before optimization.
+    Ir => "ir",
+    /// The AST of the intermediate representation before optimization.
+    IrAst => "irAst",
+    /// The Yul intermediate representation after optimization.
+    IrOptimized => "irOptimized",
+    /// The AST of the intermediate representation after optimization.
+    IrOptimizedAst => "irOptimizedAst",
+    /// The storage layout.
+    StorageLayout => "storageLayout",
+    /// The transient storage layout.
+    TransientStorageLayout => "transientStorageLayout",
+    /// Every EVM output.
+    Evm => "evm",
+    /// The assembly listing.
+    EvmAssembly => "evm.assembly",
+    /// The legacy assembly as JSON.
+    EvmLegacyAssembly => "evm.legacyAssembly",
+    /// Every output of the creation bytecode.
+    EvmBytecode => "evm.bytecode",
+    /// The creation bytecode.
+    EvmBytecodeObject => "evm.bytecode.object",
+    /// The opcodes of the creation bytecode.
+    EvmBytecodeOpcodes => "evm.bytecode.opcodes",
+    /// The source map of the creation bytecode.
+    EvmBytecodeSourceMap => "evm.bytecode.sourceMap",
+    /// The library placeholders in the creation bytecode.
+    EvmBytecodeLinkReferences => "evm.bytecode.linkReferences",
+    /// The sources generated for the creation bytecode.
+    EvmBytecodeGeneratedSources => "evm.bytecode.generatedSources",
+    /// Debug information on the functions of the creation bytecode.
+    EvmBytecodeFunctionDebugData => "evm.bytecode.functionDebugData",
+    /// Every output of the runtime bytecode.
+    EvmDeployedBytecode => "evm.deployedBytecode",
+    /// The runtime bytecode.
+    EvmDeployedBytecodeObject => "evm.deployedBytecode.object",
+    /// The opcodes of the runtime bytecode.
+    EvmDeployedBytecodeOpcodes => "evm.deployedBytecode.opcodes",
+    /// The source map of the runtime bytecode.
+    EvmDeployedBytecodeSourceMap => "evm.deployedBytecode.sourceMap",
+    /// The library placeholders in the runtime bytecode.
+    EvmDeployedBytecodeLinkReferences => "evm.deployedBytecode.linkReferences",
+    /// The places of immutable variables in the runtime bytecode.
+    EvmDeployedBytecodeImmutableReferences => "evm.deployedBytecode.immutableReferences",
+    /// The sources generated for the runtime bytecode.
+    EvmDeployedBytecodeGeneratedSources => "evm.deployedBytecode.generatedSources",
+    /// Debug information on the functions of the runtime bytecode.
+    EvmDeployedBytecodeFunctionDebugData => "evm.deployedBytecode.functionDebugData",
+    /// The selectors of the external functions.
+    EvmMethodIdentifiers => "evm.methodIdentifiers",
+    /// The gas estimates.
+    EvmGasEstimates => "evm.gasEstimates",
+}
+
+impl Serialize for OutputSelector {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputSelector {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(OutputSelector::from(name.as_str()))
+    }
+}
+
+/// Outputs being selected with [`Settings::select`], until the files and
+/// contracts to select them for are given.
+#[must_use = "outputs are only selected by calling a `for_*` method"]
+pub struct Selection<'a> {
+    settings: &'a mut Settings,
+    selectors: Vec<OutputSelector>,
+}
+
+impl<'a> Selection<'a> {
+    /// Also select `selector`.
+    pub fn and(mut self, selector: OutputSelector) -> Self {
+        self.selectors.push(selector);
+        self
+    }
+
+    /// Select the outputs for contracts named `contract` in every file, or
+    /// for every contract if it is `*`.
+    pub fn for_contract(self, contract: impl Into<String>) -> &'a mut Settings {
+        self.for_contract_in("*", contract)
+    }
+
+    /// Select the outputs for the contract `contract` in `file`.
+    pub fn for_contract_in(
+        self,
+        file: impl Into<String>,
+        contract: impl Into<String>,
+    ) -> &'a mut Settings {
+        self.settings
+            .output_selection
+            .get_or_insert_with(OutputSelection::default)
+            .add(file, contract, self.selectors);
+        self.settings
+    }
+
+    /// Select outputs of a whole file, such as the AST, for `file`, or for
+    /// every file if it is `*`.
+    pub fn for_file(self, file: impl Into<String>) -> &'a mut Settings {
+        self.for_contract_in(file, "")
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum StopAfter {
@@ -129,7 +337,7 @@ pub struct YulDetails {
 #[serde(rename_all = "camelCase")]
 pub struct ModelCheckerSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub contracts: Option<HashMap<String, Vec<String>>>,
+    pub contracts: Option<BTreeMap<String, Vec<String>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub div_mod_no_slacks: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -152,7 +360,115 @@ pub struct ModelCheckerSettings {
     pub timeout: Option<u64>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl ModelCheckerSettings {
+    /// Settings that run the given engine, with everything else left to the
+    /// compiler's defaults.
+    pub fn new(engine: ModelCheckerEngine) -> Self {
+        Self {
+            engine: Some(engine),
+            ..Default::default()
+        }
+    }
+
+    /// Settings that run the CHC engine, which proves properties for any
+    /// number of transactions.
+    pub fn chc() -> Self {
+        Self::new(ModelCheckerEngine::Chc)
+    }
+
+    /// Settings that run the BMC engine, which checks each function on its
+    /// own and is faster but reports more false positives.
+    pub fn bmc() -> Self {
+        Self::new(ModelCheckerEngine::Bmc)
+    }
+
+    /// CHC settings that check for arithmetic underflow, overflow, and
+    /// division by zero.
+    pub fn arithmetic() -> Self {
+        Self::chc().targets([
+            ModelCheckerTarget::Underflow,
+            ModelCheckerTarget::Overflow,
+            ModelCheckerTarget::DivByZero,
+        ])
+    }
+
+    /// CHC settings that check assertions, and accesses of arrays out of
+    /// bounds or popped when empty.
+    pub fn assertions() -> Self {
+        Self::chc().targets([
+            ModelCheckerTarget::Assert,
+            ModelCheckerTarget::PopEmptyArray,
+            ModelCheckerTarget::OutOfBounds,
+        ])
+    }
+
+    /// Only check the given properties.
+    pub fn targets(mut self, targets: impl IntoIterator<Item = ModelCheckerTarget>) -> Self {
+        self.targets = Some(targets.into_iter().collect());
+        self
+    }
+
+    /// Only check the contract `name` in `file`. Can be called several
+    /// times to check several contracts.
+    pub fn contract(mut self, file: impl Into<String>, name: impl Into<String>) -> Self {
+        self.contracts
+            .get_or_insert_with(BTreeMap::new)
+            .entry(file.into())
+            .or_default()
+            .push(name.into());
+        self
+    }
+
+    /// Use the given solvers.
+    pub fn solvers(mut self, solvers: impl IntoIterator<Item = Solver>) -> Self {
+        self.solvers = Some(solvers.into_iter().collect());
+        self
+    }
+
+    /// Report the given kinds of invariants the CHC engine finds.
+    pub fn invariants(mut self, invariants: impl IntoIterator<Item = Invariant>) -> Self {
+        self.invariants = Some(invariants.into_iter().collect());
+        self
+    }
+
+    /// Set the timeout of each solver query in milliseconds.
+    pub fn timeout(mut self, milliseconds: u64) -> Self {
+        self.timeout = Some(milliseconds);
+        self
+    }
+
+    /// Set how calls to external contracts are modeled.
+    pub fn ext_calls(mut self, ext_calls: ExtCalls) -> Self {
+        self.ext_calls = Some(ext_calls);
+        self
+    }
+
+    /// Encode division and modulo without slack variables.
+    pub fn div_mod_no_slacks(mut self, enabled: bool) -> Self {
+        self.div_mod_no_slacks = Some(enabled);
+        self
+    }
+
+    /// Report the targets that were proved safe.
+    pub fn show_proved_safe(mut self, enabled: bool) -> Self {
+        self.show_proved_safe = Some(enabled);
+        self
+    }
+
+    /// Report each target that could not be proved, instead of a count.
+    pub fn show_unproved(mut self, enabled: bool) -> Self {
+        self.show_unproved = Some(enabled);
+        self
+    }
+
+    /// Report each unsupported language feature, instead of a count.
+    pub fn show_unsupported(mut self, enabled: bool) -> Self {
+        self.show_unsupported = Some(enabled);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ModelCheckerEngine {
     All,
@@ -183,7 +499,7 @@ pub enum Solver {
     Z3,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ModelCheckerTarget {
     ConstantCondition,
@@ -216,7 +532,7 @@ pub enum RevertStrings {
 }
 
 /// Metadata settings for compiled bytecode.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MetadataSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -236,8 +552,8 @@ pub enum BytecodeHash {
     None,
 }
 
-/// Target EVM version for code generation.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Target EVM version for code generation, ordered from the oldest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum EvmVersion {
     Homestead,
@@ -256,11 +572,352 @@ pub enum EvmVersion {
     Osaka,
 }
 
+impl EvmVersion {
+    /// Every EVM version, from the oldest.
+    const ALL: [EvmVersion; 14] = [
+        EvmVersion::Homestead,
+        EvmVersion::TangerineWhistle,
+        EvmVersion::SpuriousDragon,
+        EvmVersion::Byzantium,
+        EvmVersion::Constantinople,
+        EvmVersion::Petersburg,
+        EvmVersion::Istanbul,
+        EvmVersion::Berlin,
+        EvmVersion::London,
+        EvmVersion::Paris,
+        EvmVersion::Shanghai,
+        EvmVersion::Cancun,
+        EvmVersion::Prague,
+        EvmVersion::Osaka,
+    ];
+
+    /// The name of the EVM version in Standard JSON, such as `cancun`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EvmVersion::Homestead => "homestead",
+            EvmVersion::TangerineWhistle => "tangerineWhistle",
+            EvmVersion::SpuriousDragon => "spuriousDragon",
+            EvmVersion::Byzantium => "byzantium",
+            EvmVersion::Constantinople => "constantinople",
+            EvmVersion::Petersburg => "petersburg",
+            EvmVersion::Istanbul => "istanbul",
+            EvmVersion::Berlin => "berlin",
+            EvmVersion::London => "london",
+            EvmVersion::Paris => "paris",
+            EvmVersion::Shanghai => "shanghai",
+            EvmVersion::Cancun => "cancun",
+            EvmVersion::Prague => "prague",
+            EvmVersion::Osaka => "osaka",
+        }
+    }
+
+    /// The first compiler release that accepts this EVM version.
+    pub fn introduced_in(self) -> Version {
+        match self {
+            EvmVersion::Homestead
+            | EvmVersion::TangerineWhistle
+            | EvmVersion::SpuriousDragon
+            | EvmVersion::Byzantium
+            | EvmVersion::Constantinople => Version::new(0, 4, 21),
+            EvmVersion::Petersburg => Version::new(0, 5, 5),
+            EvmVersion::Istanbul => Version::new(0, 5, 14),
+            EvmVersion::Berlin => Version::new(0, 8, 5),
+            EvmVersion::London => Version::new(0, 8, 7),
+            EvmVersion::Paris => Version::new(0, 8, 18),
+            EvmVersion::Shanghai => Version::new(0, 8, 20),
+            EvmVersion::Cancun => Version::new(0, 8, 24),
+            EvmVersion::Prague => Version::new(0, 8, 27),
+            EvmVersion::Osaka => Version::new(0, 8, 29),
+        }
+    }
+
+    /// Check that a compiler release accepts this EVM version.
+    pub fn check(self, version: &Version) -> Result<(), UnsupportedEvmVersion> {
+        if self.introduced_in() <= *version {
+            Ok(())
+        } else {
+            Err(UnsupportedEvmVersion {
+                evm_version: self,
+                compiler: version.clone(),
+            })
+        }
+    }
+
+    /// The newest EVM version a compiler release accepts, or `None` for
+    /// releases before `evmVersion` was a setting.
+    pub fn latest_for(version: &Version) -> Option<EvmVersion> {
+        EvmVersion::ALL
+            .into_iter()
+            .rev()
+            .find(|evm_version| evm_version.introduced_in() <= *version)
+    }
+}
+
+/// An EVM version that a compiler release does not know, from
+/// [`EvmVersion::check`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "EVM version {} requires solc {} or newer, not {compiler}",
+    evm_version.as_str(),
+    evm_version.introduced_in()
+)]
+pub struct UnsupportedEvmVersion {
+    pub evm_version: EvmVersion,
+    pub compiler: Version,
+}
+
+impl Settings {
+    /// Remove or rewrite the settings a compiler release does not support,
+    /// returning what was changed.
+    ///
+    /// Settings newer than the release are removed, except the EVM version,
+    /// which is lowered to the newest one the release accepts. `viaIR` is
+    /// removed before 0.8.13, when the IR pipeline stopped being
+    /// experimental.
+    pub fn adapt_for(&mut self, version: &Version) -> Vec<SettingChange> {
+        let before = serde_json::to_value(&*self).expect("settings serialize to JSON");
+        let older = |major, minor, patch| *version < Version::new(major, minor, patch);
+
+        if older(0, 8, 13) {
+            self.via_ir = None;
+        }
+        if older(0, 8, 29) {
+            self.eof_version = None;
+        }
+        if let Some(evm_version) = self.evm_version
+            && evm_version.introduced_in() > *version
+        {
+            self.evm_version = EvmVersion::latest_for(version);
+        }
+        if older(0, 6, 3) {
+            self.debug = None;
+        }
+        if let Some(metadata) = &mut self.metadata {
+            if older(0, 8, 18) {
+                metadata.append_cbor = None;
+            }
+            if older(0, 6, 0) {
+                metadata.bytecode_hash = None;
+            }
+        }
+
+        let after = serde_json::to_value(&*self).expect("settings serialize to JSON");
+        let mut changes = Vec::new();
+        diff_values("", Some(&before), Some(&after), &mut changes);
+        changes
+    }
+
+    /// Start selecting an output, as in
+    /// `settings.select(OutputSelector::Abi).for_contract("*")`.
+    pub fn select(&mut self, selector: OutputSelector) -> Selection<'_> {
+        Selection {
+            settings: self,
+            selectors: vec![selector],
+        }
+    }
+
+    /// Embed the source text in the contract metadata.
+    ///
+    /// Sets `metadata.useLiteralContent`, so the metadata contains the
+    /// sources themselves instead of only their hashes. The metadata is then
+    /// enough to verify the contract without fetching any source. Use
+    /// [`StandardJsonInput::embed_sources`] to also check that every source
+    /// has content to embed.
+    pub fn embed_sources(mut self) -> Self {
+        self.metadata
+            .get_or_insert_with(MetadataSettings::default)
+            .use_literal_content = Some(true);
+        self
+    }
+}
+
+/// An error returned by [`StandardJsonInput::embed_sources`].
+#[derive(Debug, thiserror::Error)]
+pub enum EmbedSourcesError {
+    /// Some sources are given as URLs, so the compiler cannot embed them.
+    #[error("sources given as URLs cannot be embedded: {}", display_paths(.0))]
+    UrlSources(Vec<PathBuf>),
+}
+
+/// A problem found by [`StandardJsonInput::validate`], which the compiler
+/// would reject or silently misread.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InputError {
+    /// The input has no sources.
+    #[error("no sources")]
+    NoSources,
+
+    /// A source is given by URL, but without any URL.
+    #[error("source {} has no content and no URLs", .0.display())]
+    NoUrls(PathBuf),
+
+    /// The content of a source does not have the hash given for it.
+    #[error("source {} has keccak256 {actual}, not {expected}", path.display())]
+    HashMismatch {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+
+    /// A library address is not 20 bytes of hex with a `0x` prefix.
+    #[error("library {file}:{name} has invalid address `{address}`")]
+    InvalidLibraryAddress {
+        file: String,
+        name: String,
+        address: String,
+    },
+
+    /// A remapping is not of the form `[context:]prefix=target`.
+    #[error("invalid remapping `{0}`")]
+    InvalidRemapping(String),
+
+    /// The optimizer is enabled for code run zero times.
+    #[error("the optimizer is enabled with zero runs")]
+    ZeroOptimizerRuns,
+
+    /// An output is selected that is only produced after analysis, while
+    /// compilation stops after parsing.
+    #[error("`{}` is selected, but compilation stops after parsing", .0.as_str())]
+    OutputAfterStop(OutputSelector),
+}
+
+/// The differences between two inputs, from [`StandardJsonInput::diff`].
+///
+/// Remappings are compared as sets, since their order only matters between
+/// remappings of the same prefix. Other settings are compared field by
+/// field, under their Standard JSON paths such as `optimizer.runs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputDiff {
+    /// Sources only in the new input.
+    pub added_sources: Vec<PathBuf>,
+    /// Sources only in the old input.
+    pub removed_sources: Vec<PathBuf>,
+    /// Sources in both inputs whose content, URLs, or hash differ.
+    pub changed_sources: Vec<PathBuf>,
+    /// Remappings only in the new input.
+    pub added_remappings: Vec<String>,
+    /// Remappings only in the old input.
+    pub removed_remappings: Vec<String>,
+    /// The language and the settings that differ.
+    pub changed_settings: Vec<SettingChange>,
+}
+
+/// A setting with different values in two inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingChange {
+    /// The path of the setting, such as `optimizer.runs` or `language`.
+    pub path: String,
+    /// The value in the old input, or `None` if it is not set.
+    pub old: Option<serde_json::Value>,
+    /// The value in the new input, or `None` if it is not set.
+    pub new: Option<serde_json::Value>,
+}
+
+impl InputDiff {
+    /// Whether the inputs are the same.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for InputDiff {
+    /// One line per difference, starting with `+` for additions, `-` for
+    /// removals, and `~` for changes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in &self.added_sources {
+            writeln!(f, "+ source {}", path.display())?;
+        }
+        for path in &self.removed_sources {
+            writeln!(f, "- source {}", path.display())?;
+        }
+        for path in &self.changed_sources {
+            writeln!(f, "~ source {}", path.display())?;
+        }
+        for remapping in &self.added_remappings {
+            writeln!(f, "+ remapping {}", remapping)?;
+        }
+        for remapping in &self.removed_remappings {
+            writeln!(f, "- remapping {}", remapping)?;
+        }
+        let value = |value: &Option<serde_json::Value>| match value {
+            Some(value) => value.to_string(),
+            None => "unset".to_string(),
+        };
+        for change in &self.changed_settings {
+            writeln!(
+                f,
+                "~ {}: {} -> {}",
+                change.path,
+                value(&change.old),
+                value(&change.new)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Add the changes between two JSON values under `path`, descending into
+/// objects so each changed field is reported on its own.
+fn diff_values(
+    path: &str,
+    old: Option<&serde_json::Value>,
+    new: Option<&serde_json::Value>,
+    changes: &mut Vec<SettingChange>,
+) {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = match path {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", path, key),
+                };
+                diff_values(&path, old.get(key), new.get(key), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(SettingChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// Whether a remapping has the form `[context:]prefix=target`, with a
+/// prefix that is not empty.
+fn is_valid_remapping(remapping: &str) -> bool {
+    let Some((context_prefix, _target)) = remapping.split_once('=') else {
+        return false;
+    };
+    let prefix = context_prefix
+        .split_once(':')
+        .map_or(context_prefix, |(_context, prefix)| prefix);
+    !prefix.is_empty()
+}
+
+/// Whether an address is 20 bytes of hex with a `0x` prefix.
+fn is_valid_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl StandardJsonInput {
     pub fn new() -> Self {
         Self {
             language: Language::Solidity,
-            sources: HashMap::new(),
+            sources: BTreeMap::new(),
             settings: Settings::default(),
         }
     }
@@ -298,11 +955,237 @@ impl StandardJsonInput {
         self.settings.model_checker = Some(settings);
         self
     }
+
+    /// Enable the optimizer, tuned for code that runs about `runs` times.
+    ///
+    /// Detailed optimizer settings already given are kept.
+    pub fn optimizer(mut self, runs: usize) -> Self {
+        let optimizer = self.settings.optimizer.get_or_insert(Optimizer {
+            enabled: true,
+            runs,
+            details: None,
+        });
+        optimizer.enabled = true;
+        optimizer.runs = runs;
+        self
+    }
+
+    /// Compile for an EVM version.
+    pub fn evm_version(mut self, evm_version: EvmVersion) -> Self {
+        self.settings.evm_version = Some(evm_version);
+        self
+    }
+
+    /// Generate code through the Yul IR pipeline, or the legacy one.
+    pub fn via_ir(mut self, enabled: bool) -> Self {
+        self.settings.via_ir = Some(enabled);
+        self
+    }
+
+    /// Produce EOF containers of a version instead of legacy EVM code.
+    pub fn eof_version(mut self, version: u8) -> Self {
+        self.settings.eof_version = Some(version);
+        self
+    }
+
+    /// Add import remappings, such as `@openzeppelin/=lib/openzeppelin/`.
+    pub fn remappings(mut self, remappings: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.settings
+            .remappings
+            .get_or_insert_with(Vec::new)
+            .extend(remappings.into_iter().map(Into::into));
+        self
+    }
+
+    /// Link the library `name` of `file` at an address, given as hex with a
+    /// `0x` prefix.
+    pub fn library(
+        mut self,
+        file: impl Into<String>,
+        name: impl Into<String>,
+        address: impl Into<String>,
+    ) -> Self {
+        self.settings
+            .libraries
+            .get_or_insert_with(BTreeMap::new)
+            .entry(file.into())
+            .or_default()
+            .insert(name.into(), address.into());
+        self
+    }
+
+    /// Set the outputs to produce, replacing any selected before. Use
+    /// [`Settings::select`] to add to them instead.
+    pub fn output_selection(mut self, selection: OutputSelection) -> Self {
+        self.settings.output_selection = Some(selection);
+        self
+    }
+
+    /// Check the input for mistakes before it is sent to the compiler.
+    ///
+    /// Returns every problem found, in the order the fields are checked:
+    /// sources, remappings, optimizer, libraries, and then the outputs
+    /// selected.
+    pub fn validate(&self) -> Result<(), Vec<InputError>> {
+        let mut errors = Vec::new();
+        if self.sources.is_empty() {
+            errors.push(InputError::NoSources);
+        }
+        for (path, source) in &self.sources {
+            match &source.content {
+                SourceContent::Urls { urls } if urls.is_empty() => {
+                    errors.push(InputError::NoUrls(path.clone()));
+                }
+                SourceContent::Urls { .. } => {}
+                SourceContent::Content { content } => {
+                    let Some(expected) = &source.keccak256 else {
+                        continue;
+                    };
+                    let actual = hex::encode(crate::abi::keccak256(content.as_bytes()));
+                    let given = expected.strip_prefix("0x").unwrap_or(expected);
+                    if !given.eq_ignore_ascii_case(&actual) {
+                        errors.push(InputError::HashMismatch {
+                            path: path.clone(),
+                            expected: expected.clone(),
+                            actual: format!("0x{}", actual),
+                        });
+                    }
+                }
+            }
+        }
+
+        let settings = &self.settings;
+        for remapping in settings.remappings.iter().flatten() {
+            if !is_valid_remapping(remapping) {
+                errors.push(InputError::InvalidRemapping(remapping.clone()));
+            }
+        }
+        if settings
+            .optimizer
+            .as_ref()
+            .is_some_and(|optimizer| optimizer.enabled && optimizer.runs == 0)
+        {
+            errors.push(InputError::ZeroOptimizerRuns);
+        }
+        for (file, libraries) in settings.libraries.iter().flatten() {
+            for (name, address) in libraries {
+                if !is_valid_address(address) {
+                    errors.push(InputError::InvalidLibraryAddress {
+                        file: file.clone(),
+                        name: name.clone(),
+                        address: address.clone(),
+                    });
+                }
+            }
+        }
+        if matches!(settings.stop_after, Some(StopAfter::Parsing)) {
+            let mut reported = Vec::new();
+            let selected = settings
+                .output_selection
+                .iter()
+                .flat_map(|selection| selection.0.values().flat_map(BTreeMap::values).flatten());
+            for selector in selected {
+                if *selector != OutputSelector::Ast && !reported.contains(selector) {
+                    reported.push(selector.clone());
+                    errors.push(InputError::OutputAfterStop(selector.clone()));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The differences from this input to `new`, such as to explain why an
+    /// input no longer matches a cached or verified build.
+    pub fn diff(&self, new: &StandardJsonInput) -> InputDiff {
+        let mut diff = InputDiff::default();
+        for (path, source) in &new.sources {
+            match self.sources.get(path) {
+                None => diff.added_sources.push(path.clone()),
+                Some(old) if old != source => diff.changed_sources.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed_sources = self
+            .sources
+            .keys()
+            .filter(|path| !new.sources.contains_key(*path))
+            .cloned()
+            .collect();
+
+        let remappings = |input: &StandardJsonInput| -> BTreeSet<String> {
+            input
+                .settings
+                .remappings
+                .iter()
+                .flatten()
+                .cloned()
+                .collect()
+        };
+        let (old_remappings, new_remappings) = (remappings(self), remappings(new));
+        diff.added_remappings = new_remappings
+            .difference(&old_remappings)
+            .cloned()
+            .collect();
+        diff.removed_remappings = old_remappings
+            .difference(&new_remappings)
+            .cloned()
+            .collect();
+
+        let settings = |input: &StandardJsonInput| {
+            let mut value = serde_json::json!({
+                "language": input.language,
+                "settings": input.settings,
+            });
+            value["settings"]
+                .as_object_mut()
+                .expect("settings serialize to an object")
+                .remove("remappings");
+            value
+        };
+        diff_values(
+            "",
+            Some(&settings(self)),
+            Some(&settings(new)),
+            &mut diff.changed_settings,
+        );
+        for change in &mut diff.changed_settings {
+            if let Some(path) = change.path.strip_prefix("settings.") {
+                change.path = path.to_string();
+            }
+        }
+        diff
+    }
+
+    /// Embed the source text in the contract metadata, for self-contained
+    /// verification.
+    ///
+    /// This is [`Settings::embed_sources`], after checking that every source
+    /// is given as content. Sources given as URLs are reported by path.
+    pub fn embed_sources(mut self) -> Result<Self, EmbedSourcesError> {
+        let mut urls: Vec<PathBuf> = self
+            .sources
+            .iter()
+            .filter(|(_, source)| matches!(source.content, SourceContent::Urls { .. }))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !urls.is_empty() {
+            urls.sort();
+            return Err(EmbedSourcesError::UrlSources(urls));
+        }
+        self.settings = self.settings.embed_sources();
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::path::Path;
 
     use super::*;
     use walkdir::WalkDir;
@@ -351,6 +1234,285 @@ mod tests {
         assert!(json["settings"]["modelChecker"]["targets"].is_array());
     }
 
+    #[test]
+    fn model_checker_builder() {
+        let settings = ModelCheckerSettings::arithmetic()
+            .contract("A.sol", "A")
+            .contract("A.sol", "B")
+            .solvers([Solver::Z3])
+            .timeout(60_000)
+            .show_unproved(true);
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "contracts": {"A.sol": ["A", "B"]},
+                "engine": "chc",
+                "showUnproved": true,
+                "solvers": ["z3"],
+                "targets": ["underflow", "overflow", "divByZero"],
+                "timeout": 60000
+            })
+        );
+
+        let json = serde_json::to_value(ModelCheckerSettings::bmc()).unwrap();
+        assert_eq!(json, serde_json::json!({"engine": "bmc"}));
+    }
+
+    #[test]
+    fn output_selection() {
+        let mut settings = Settings::default();
+        settings
+            .select(OutputSelector::Abi)
+            .and(OutputSelector::EvmBytecodeObject)
+            .for_contract("*")
+            .select(OutputSelector::Ast)
+            .for_file("*")
+            .select(OutputSelector::Abi)
+            .and(OutputSelector::from("evm.custom"))
+            .for_contract("*");
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(
+            json["outputSelection"],
+            serde_json::json!({
+                "*": {
+                    "*": ["abi", "evm.bytecode.object", "evm.custom"],
+                    "": ["ast"]
+                }
+            })
+        );
+
+        let selection: OutputSelection =
+            serde_json::from_value(json["outputSelection"].clone()).unwrap();
+        assert_eq!(
+            selection.get("*", "*"),
+            [
+                OutputSelector::Abi,
+                OutputSelector::EvmBytecodeObject,
+                OutputSelector::Other("evm.custom".to_string()),
+            ]
+        );
+        assert_eq!(selection.get("*", ""), [OutputSelector::Ast]);
+        assert!(selection.get("A.sol", "*").is_empty());
+    }
+
+    #[test]
+    fn settings_builder() {
+        let mut selection = OutputSelection::default();
+        selection.add("*", "*", [OutputSelector::Abi]);
+        let input = StandardJsonInput::new()
+            .optimizer(1)
+            .optimizer(200)
+            .evm_version(EvmVersion::Cancun)
+            .via_ir(true)
+            .eof_version(1)
+            .remappings(["@oz/=lib/oz/"])
+            .remappings(vec!["ds-test/=lib/ds-test/".to_string()])
+            .library(
+                "lib/Math.sol",
+                "Math",
+                "0x0000000000000000000000000000000000000001",
+            )
+            .output_selection(selection);
+
+        let json = serde_json::to_value(&input.settings).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "remappings": ["@oz/=lib/oz/", "ds-test/=lib/ds-test/"],
+                "optimizer": {"enabled": true, "runs": 200},
+                "evmVersion": "cancun",
+                "viaIR": true,
+                "eofVersion": 1,
+                "libraries": {
+                    "lib/Math.sol": {"Math": "0x0000000000000000000000000000000000000001"}
+                },
+                "outputSelection": {"*": {"*": ["abi"]}}
+            })
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let content = "contract A {}";
+        let hash = format!(
+            "0x{}",
+            hex::encode(crate::abi::keccak256(content.as_bytes()))
+        );
+        let mut input = StandardJsonInput::new()
+            .add_source("A.sol", content)
+            .add_source_urls("B.sol", vec!["ipfs://Qm".to_string()], None)
+            .remappings(["@oz/=lib/oz/", "ctx:ds-test/=lib/ds-test/"])
+            .optimizer(200)
+            .library("A.sol", "L", "0x000000000000000000000000000000000000dEaD");
+        input.sources.get_mut(Path::new("A.sol")).unwrap().keccak256 = Some(hash);
+        input.settings.select(OutputSelector::Ast).for_file("*");
+        assert_eq!(input.validate(), Ok(()));
+
+        input.settings.stop_after = Some(StopAfter::Parsing);
+        input
+            .settings
+            .select(OutputSelector::Abi)
+            .and(OutputSelector::EvmBytecode)
+            .for_contract("*");
+        input.settings.select(OutputSelector::Abi).for_contract("A");
+        let mut input = input
+            .add_source("A.sol", "contract B {}")
+            .add_source_urls("C.sol", Vec::new(), None)
+            .remappings(["=lib/", "no-target"])
+            .optimizer(0)
+            .library("A.sol", "M", "dead");
+        input.sources.get_mut(Path::new("A.sol")).unwrap().keccak256 = Some("0x00".to_string());
+        let errors = input.validate().unwrap_err();
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                format!(
+                    "source A.sol has keccak256 0x{}, not 0x00",
+                    hex::encode(crate::abi::keccak256(b"contract B {}"))
+                )
+                .as_str(),
+                "source C.sol has no content and no URLs",
+                "invalid remapping `=lib/`",
+                "invalid remapping `no-target`",
+                "the optimizer is enabled with zero runs",
+                "library A.sol:M has invalid address `dead`",
+                "`abi` is selected, but compilation stops after parsing",
+                "`evm.bytecode` is selected, but compilation stops after parsing",
+            ]
+        );
+        assert_eq!(
+            StandardJsonInput::new().validate(),
+            Err(vec![InputError::NoSources])
+        );
+    }
+
+    #[test]
+    fn diff_inputs() {
+        let old = StandardJsonInput::new()
+            .add_source("A.sol", "contract A {}")
+            .add_source("B.sol", "contract B {}")
+            .add_source("C.sol", "contract C {}")
+            .remappings(["@oz/=lib/oz/", "ds-test/=lib/ds-test/"])
+            .optimizer(200)
+            .evm_version(EvmVersion::Paris);
+        assert!(old.diff(&old).is_empty());
+
+        let new = StandardJsonInput::new()
+            .add_source("A.sol", "contract A {}")
+            .add_source("C.sol", "contract C { uint x; }")
+            .add_source("D.sol", "contract D {}")
+            .remappings(["ds-test/=lib/ds-test/", "@oz/=lib/oz-v5/"])
+            .optimizer(1000)
+            .via_ir(true);
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_sources, [PathBuf::from("D.sol")]);
+        assert_eq!(diff.removed_sources, [PathBuf::from("B.sol")]);
+        assert_eq!(diff.changed_sources, [PathBuf::from("C.sol")]);
+        assert_eq!(
+            diff.to_string(),
+            "+ source D.sol\n\
+             - source B.sol\n\
+             ~ source C.sol\n\
+             + remapping @oz/=lib/oz-v5/\n\
+             - remapping @oz/=lib/oz/\n\
+             ~ evmVersion: \"paris\" -> unset\n\
+             ~ optimizer.runs: 200 -> 1000\n\
+             ~ viaIR: unset -> true\n"
+        );
+    }
+
+    #[test]
+    fn adapt_settings() {
+        let input = StandardJsonInput::new()
+            .evm_version(EvmVersion::Cancun)
+            .via_ir(true)
+            .optimizer(200)
+            .eof_version(1);
+        let mut settings = input.settings.clone();
+        assert_eq!(
+            settings.adapt_for(&Version::new(0, 8, 29)),
+            [],
+            "supported settings are kept"
+        );
+
+        let changes = settings.adapt_for(&Version::new(0, 8, 12));
+        let changes: Vec<(&str, Option<String>)> = changes
+            .iter()
+            .map(|change| {
+                let new = change.new.as_ref().map(ToString::to_string);
+                (change.path.as_str(), new)
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("eofVersion", None),
+                ("evmVersion", Some("\"london\"".to_string())),
+                ("viaIR", None),
+            ]
+        );
+        assert_eq!(settings.optimizer.unwrap().runs, 200);
+
+        let mut settings = input.settings;
+        settings.adapt_for(&Version::new(0, 4, 11));
+        assert_eq!(settings.evm_version, None);
+
+        assert_eq!(
+            EvmVersion::latest_for(&Version::new(0, 8, 24)),
+            Some(EvmVersion::Cancun)
+        );
+        assert!(EvmVersion::Paris < EvmVersion::Shanghai);
+        for evm_version in EvmVersion::ALL {
+            assert_eq!(
+                serde_json::to_value(evm_version).unwrap(),
+                evm_version.as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn check_evm_version() {
+        assert_eq!(EvmVersion::Cancun.check(&Version::new(0, 8, 24)), Ok(()));
+        let error = EvmVersion::Cancun
+            .check(&Version::new(0, 8, 23))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "EVM version cancun requires solc 0.8.24 or newer, not 0.8.23"
+        );
+    }
+
+    #[test]
+    fn deterministic_serialization() {
+        let input = StandardJsonInput::new()
+            .add_source("B.sol", "contract B {}")
+            .add_source("A.sol", "contract A {}");
+        let json = serde_json::to_string(&input).unwrap();
+        assert!(json.find("A.sol").unwrap() < json.find("B.sol").unwrap());
+        assert_eq!(json, serde_json::to_string(&input.clone()).unwrap());
+    }
+
+    #[test]
+    fn embed_sources() {
+        let input = StandardJsonInput::new()
+            .add_source("A.sol", "contract A {}")
+            .embed_sources()
+            .unwrap();
+        let json = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["settings"]["metadata"]["useLiteralContent"], true);
+
+        let result = StandardJsonInput::new()
+            .add_source("A.sol", "contract A {}")
+            .add_source_urls("B.sol", vec!["ipfs://Qm...".to_string()], None)
+            .embed_sources();
+        assert!(matches!(
+            result,
+            Err(EmbedSourcesError::UrlSources(paths)) if paths == vec![PathBuf::from("B.sol")]
+        ));
+    }
+
     #[test]
     fn fixtures() {
         for entry in WalkDir::new("fixtures/standard-json-input")
@@ -361,7 +1523,7 @@ mod tests {
                 continue;
             }
 
-            if entry.path().extension().map_or(false, |e| e == "json") {
+            if entry.path().extension().is_some_and(|e| e == "json") {
                 let content =
                     fs::read_to_string(entry.path()).expect("Failed to read fixture file");
                 let _input: StandardJsonInput = serde_json::from_str(&content)
@@ -0,0 +1,1771 @@
+//! Solidity compiler Standard JSON output types.
+//!
+//! This module provides types for the output of the compiler's
+//! `--standard-json` interface, including diagnostics, compiled contracts,
+//! and the compiler-generated sources their bytecode references.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi::{
+    Abi, AbiItem, Constructor, ConstructorArgumentsError, StateMutability, Token, keccak256,
+};
+use crate::assembly::Assembly;
+use crate::ast::{LocationIndex, SourceLocation, SourceUnit, YulBlock};
+use crate::metadata::{self, BytecodeMetadata, MetadataError};
+use crate::opcodes::{self, Instruction, OpcodesError};
+use crate::sourcemap::{self, SourceMapEntry, SourceMapError};
+use crate::standard_json_input::{ModelCheckerEngine, ModelCheckerTarget};
+
+/// Solidity compiler Standard JSON output.
+///
+/// Top-level object returned by the compiler's `--standard-json` interface.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandardJsonOutput {
+    /// Errors, warnings, and informational messages from the compiler.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Error>,
+    /// Per-source outputs, keyed by source file name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, Source>,
+    /// Compiled contracts, keyed by source file name and then contract name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contracts: BTreeMap<String, BTreeMap<String, Contract>>,
+}
+
+impl StandardJsonOutput {
+    /// Whether any diagnostic has error severity.
+    ///
+    /// The compiler still returns an output object when compilation fails, so
+    /// this is the check for whether the compilation succeeded.
+    pub fn has_errors(&self) -> bool {
+        self.errors
+            .iter()
+            .any(|error| error.severity == Severity::Error)
+    }
+
+    /// Whether compilation failed because the legacy code generator ran out
+    /// of stack slots.
+    pub fn is_stack_too_deep(&self) -> bool {
+        self.errors.iter().any(Error::is_stack_too_deep)
+    }
+
+    /// Find a contract by name in any source file.
+    ///
+    /// Returns the source file name with the contract. If several files
+    /// define a contract with this name, the first file in name order wins.
+    pub fn find_contract(&self, name: &str) -> Option<(&str, &Contract)> {
+        self.contracts.iter().find_map(|(file, contracts)| {
+            contracts
+                .get(name)
+                .map(|contract| (file.as_str(), contract))
+        })
+    }
+
+    /// Keep only the contracts matching a glob pattern, dropping the rest.
+    ///
+    /// In the pattern, `*` matches any sequence of characters and `?` any
+    /// single character. A pattern with a `:` is matched against the fully
+    /// qualified name `file:Contract`, and one without against the contract
+    /// name alone. Source files left without contracts are removed from
+    /// [`contracts`](Self::contracts), while [`sources`](Self::sources) are
+    /// kept, since the ASTs of the remaining contracts refer to them.
+    pub fn contracts_matching(&mut self, pattern: &str) -> &mut Self {
+        self.retain_contracts(|file, name| match pattern.contains(':') {
+            true => glob_match(pattern, &format!("{}:{}", file, name)),
+            false => glob_match(pattern, name),
+        })
+    }
+
+    /// Keep only the contracts with a name, dropping the rest.
+    ///
+    /// The name is either a contract name, which keeps the contracts with
+    /// that name in every source file, or a fully qualified `file:Contract`.
+    pub fn only_contract(&mut self, name: &str) -> &mut Self {
+        let (file, name) = match name.rsplit_once(':') {
+            Some((file, name)) => (Some(file), name),
+            None => (None, name),
+        };
+        self.retain_contracts(|f, n| n == name && file.is_none_or(|file| file == f))
+    }
+
+    fn retain_contracts(&mut self, mut keep: impl FnMut(&str, &str) -> bool) -> &mut Self {
+        self.contracts.retain(|file, contracts| {
+            contracts.retain(|name, _| keep(file, name));
+            !contracts.is_empty()
+        });
+        self
+    }
+
+    /// Merge the output of another compilation job into this one.
+    ///
+    /// Sources and contracts from `other` replace those with the same source
+    /// file and name. Diagnostics are appended as they are. Call [`dedup_errors`] afterwards
+    /// to drop the copies that every job reports for shared sources.
+    ///
+    /// [`dedup_errors`]: StandardJsonOutput::dedup_errors
+    pub fn merge(&mut self, other: StandardJsonOutput) {
+        self.errors.extend(other.errors);
+        self.sources.extend(other.sources);
+        for (file, contracts) in other.contracts {
+            self.contracts.entry(file).or_default().extend(contracts);
+        }
+    }
+
+    /// Remove repeated diagnostics, keeping the first occurrence.
+    ///
+    /// Two diagnostics are the same when they have the same severity, error
+    /// code, message, and source location. When a project is compiled in
+    /// several jobs, a warning in a shared dependency is reported once per
+    /// job, so merged outputs contain many such copies.
+    pub fn dedup_errors(&mut self) {
+        let mut seen = HashSet::new();
+        self.errors.retain(|error| {
+            seen.insert((
+                error.severity,
+                error.error_code.clone(),
+                error.message.clone(),
+                error.source_location.clone(),
+            ))
+        });
+    }
+}
+
+/// Whether a glob pattern with `*` and `?` matches the whole of a text.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` and of the text it was tried against, to
+    // backtrack to when the rest of the pattern fails to match.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A diagnostic reported by the compiler.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Error {
+    /// The error category, such as `TypeError` or `CompilerError`.
+    pub r#type: String,
+    /// The compiler component that reported the error, usually `general`.
+    pub component: String,
+    /// How serious the diagnostic is.
+    pub severity: Severity,
+    /// The numeric error code, absent for some internal errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// The message without location information.
+    pub message: String,
+    /// The message with source location and code excerpt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted_message: Option<String>,
+    /// The location the diagnostic refers to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_location: Option<ErrorLocation>,
+    /// Further locations the diagnostic refers to, such as the previous
+    /// declaration of a name declared twice.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_source_locations: Vec<SecondarySourceLocation>,
+}
+
+impl Error {
+    /// Whether this is the "stack too deep" code generation error.
+    ///
+    /// The legacy code generator reports this when a function needs more
+    /// than 16 reachable stack slots. Compiling through the IR pipeline
+    /// usually avoids it.
+    ///
+    /// The compiler reports this error without an error code, so the check
+    /// is a best-effort match on the message, which every release so far
+    /// words as "Stack too deep". A release that rewords it would not be
+    /// recognised.
+    pub fn is_stack_too_deep(&self) -> bool {
+        self.severity == Severity::Error
+            && self.message.to_ascii_lowercase().contains("stack too deep")
+    }
+}
+
+/// A finding of the SMTChecker, traced back to the AST node it is about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModelCheckerFinding<'a> {
+    /// The diagnostic the finding was reported as.
+    pub error: &'a Error,
+    /// The engine that reported the finding.
+    pub engine: ModelCheckerEngine,
+    /// The property that was checked, if the message names one.
+    pub target: Option<ModelCheckerTarget>,
+    /// Whether the property was violated, proved, or neither.
+    pub status: ModelCheckerStatus,
+    /// The id of the node the finding is about, if it has a location that
+    /// matches a node.
+    pub node: Option<i64>,
+}
+
+/// The outcome of checking a property with the SMTChecker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModelCheckerStatus {
+    /// A counterexample was found.
+    Violated,
+    /// The property could be neither proved nor violated.
+    Unproved,
+    /// The property was proved to hold.
+    Safe,
+    /// The message is not about a single property, such as a summary of
+    /// unproved targets.
+    Other,
+}
+
+impl StandardJsonOutput {
+    /// Collect the findings of the SMTChecker and trace them back to nodes.
+    ///
+    /// Findings are the diagnostics whose message starts with `CHC:` or
+    /// `BMC:`. The index should be built from the ASTs of the same
+    /// compilation, so that file names and ranges match.
+    pub fn model_checker_findings(&self, index: &LocationIndex) -> Vec<ModelCheckerFinding<'_>> {
+        self.errors
+            .iter()
+            .filter_map(|error| {
+                let (engine, message) = if let Some(message) = error.message.strip_prefix("CHC: ") {
+                    (ModelCheckerEngine::Chc, message)
+                } else {
+                    (
+                        ModelCheckerEngine::Bmc,
+                        error.message.strip_prefix("BMC: ")?,
+                    )
+                };
+                let node = error.source_location.as_ref().and_then(|location| {
+                    let src = SourceLocation {
+                        offset: usize::try_from(location.start).ok()?,
+                        length: usize::try_from(location.end - location.start).ok()?,
+                        source_index: index.source_index(&location.file)?,
+                    };
+                    index.node_at(&src).map(|node| node.id)
+                });
+                Some(ModelCheckerFinding {
+                    error,
+                    engine,
+                    target: model_checker_target(message),
+                    status: model_checker_status(message),
+                    node,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The property an SMTChecker message is about.
+fn model_checker_target(message: &str) -> Option<ModelCheckerTarget> {
+    const TARGETS: &[(&str, ModelCheckerTarget)] = &[
+        ("Assertion violation", ModelCheckerTarget::Assert),
+        ("Overflow", ModelCheckerTarget::Overflow),
+        ("Underflow", ModelCheckerTarget::Underflow),
+        ("Division by zero", ModelCheckerTarget::DivByZero),
+        ("Empty array \"pop\"", ModelCheckerTarget::PopEmptyArray),
+        ("Out of bounds access", ModelCheckerTarget::OutOfBounds),
+        ("Insufficient funds", ModelCheckerTarget::Balance),
+        ("Condition is always", ModelCheckerTarget::ConstantCondition),
+    ];
+    TARGETS
+        .iter()
+        .find(|(prefix, _)| message.starts_with(prefix))
+        .map(|(_, target)| *target)
+}
+
+fn model_checker_status(message: &str) -> ModelCheckerStatus {
+    if message.contains("might happen") {
+        ModelCheckerStatus::Unproved
+    } else if message.contains("happens here")
+        || message.contains("happen here")
+        || message.starts_with("Condition is always")
+    {
+        ModelCheckerStatus::Violated
+    } else if message.contains("is safe") || message.contains("proved safe") {
+        ModelCheckerStatus::Safe
+    } else {
+        ModelCheckerStatus::Other
+    }
+}
+
+/// The severity of a compiler diagnostic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+/// A byte range in a source file that a diagnostic refers to.
+///
+/// Offsets are `-1` when the compiler has no precise location.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLocation {
+    pub file: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// A further location a diagnostic refers to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondarySourceLocation {
+    pub file: String,
+    pub start: i64,
+    pub end: i64,
+    /// What the location shows, such as `The previous declaration is here:`.
+    #[serde(default)]
+    pub message: String,
+}
+
+/// The compiler output for a single source file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    /// The source id used by source maps.
+    pub id: usize,
+    /// The AST of the source as raw JSON.
+    ///
+    /// The AST is kept as JSON so that an AST the typed nodes cannot
+    /// represent does not make the whole output fail to parse. Use
+    /// [`Source::source_unit`] to get the typed AST.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ast: Option<serde_json::Value>,
+}
+
+impl Source {
+    /// Deserialize the AST into typed nodes.
+    ///
+    /// Returns `Ok(None)` if the AST was not selected.
+    pub fn source_unit(&self) -> Result<Option<SourceUnit>, serde_json::Error> {
+        self.ast.as_ref().map(SourceUnit::deserialize).transpose()
+    }
+}
+
+/// The compiler output for a single contract.
+///
+/// Every field is only present when the matching output was requested in the
+/// output selection.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contract {
+    /// The contract ABI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abi: Option<Abi>,
+    /// The contract metadata as a JSON string.
+    ///
+    /// The string is kept as is, because its hash is embedded in the
+    /// bytecode and must be computed over the exact text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    /// The user documentation from NatSpec comments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userdoc: Option<serde_json::Value>,
+    /// The developer documentation from NatSpec comments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devdoc: Option<serde_json::Value>,
+    /// The Yul intermediate representation before optimization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ir: Option<String>,
+    /// The Yul intermediate representation after optimization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ir_optimized: Option<String>,
+    /// Where the state variables are stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_layout: Option<StorageLayout>,
+    /// Where the transient state variables are stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transient_storage_layout: Option<StorageLayout>,
+    /// The EVM-related outputs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evm: Option<Evm>,
+    /// The eWasm outputs of compilers that could produce them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ewasm: Option<Ewasm>,
+}
+
+impl Contract {
+    /// Decode the constructor arguments from the input of the transaction
+    /// that deployed this contract.
+    ///
+    /// Requires the `abi` and `evm.bytecode.object` outputs. A contract
+    /// without a constructor in its ABI takes no arguments.
+    pub fn decode_constructor_arguments(
+        &self,
+        input: &[u8],
+    ) -> Result<Vec<Token>, ConstructorArgumentsError> {
+        let abi = self
+            .abi
+            .as_ref()
+            .ok_or(ConstructorArgumentsError::MissingOutput("abi"))?;
+        let object = self
+            .evm
+            .as_ref()
+            .and_then(|evm| evm.bytecode.as_ref())
+            .map(|bytecode| bytecode.object.as_str())
+            .filter(|object| !object.is_empty())
+            .ok_or(ConstructorArgumentsError::MissingOutput(
+                "evm.bytecode.object",
+            ))?;
+        let bytecode = hex::decode(object)
+            .map_err(|e| ConstructorArgumentsError::InvalidBytecode(e.to_string()))?;
+        let constructor = abi
+            .items
+            .iter()
+            .find_map(|item| match item {
+                AbiItem::Constructor(constructor) => Some(constructor.clone()),
+                _ => None,
+            })
+            .unwrap_or(Constructor {
+                inputs: Vec::new(),
+                state_mutability: StateMutability::Nonpayable,
+            });
+        constructor.decode_arguments(&bytecode, input)
+    }
+
+    /// Check the selectors the compiler reported in `evm.methodIdentifiers`
+    /// against those computed from the functions of an ABI, such as the ABI
+    /// of this contract or one built from its AST.
+    ///
+    /// Returns every mismatch, ordered by signature.
+    pub fn verify_selectors(&self, abi: &Abi) -> Result<(), Vec<SelectorMismatch>> {
+        let Some(identifiers) = self
+            .evm
+            .as_ref()
+            .and_then(|evm| evm.method_identifiers.as_ref())
+        else {
+            return Err(vec![SelectorMismatch::MissingOutput]);
+        };
+        let computed: BTreeMap<String, String> = abi
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                AbiItem::Function(function) => {
+                    Some((function.signature(), hex::encode(function.selector())))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let signatures: BTreeSet<&String> = computed.keys().chain(identifiers.keys()).collect();
+        let mismatches: Vec<SelectorMismatch> = signatures
+            .into_iter()
+            .filter_map(|signature| {
+                let signature = signature.clone();
+                match (computed.get(&signature), identifiers.get(&signature)) {
+                    (Some(_), None) => Some(SelectorMismatch::NotReported(signature)),
+                    (None, Some(_)) => Some(SelectorMismatch::NotInAbi(signature)),
+                    (Some(computed), Some(reported))
+                        if !computed.eq_ignore_ascii_case(reported) =>
+                    {
+                        Some(SelectorMismatch::Different {
+                            signature,
+                            computed: computed.clone(),
+                            reported: reported.clone(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+/// A disagreement found by [`Contract::verify_selectors`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SelectorMismatch {
+    /// The output has no `evm.methodIdentifiers` to check against.
+    #[error("evm.methodIdentifiers was not selected")]
+    MissingOutput,
+
+    /// A function of the ABI has no selector in the output.
+    #[error("{0} is in the ABI but has no method identifier")]
+    NotReported(String),
+
+    /// The output has a selector for a function the ABI lacks.
+    #[error("{0} has a method identifier but is not in the ABI")]
+    NotInAbi(String),
+
+    /// The selector computed for a function differs from the reported one.
+    #[error("{signature} has selector {computed}, but the compiler reported {reported}")]
+    Different {
+        signature: String,
+        computed: String,
+        reported: String,
+    },
+}
+
+/// The storage layout of a contract.
+///
+/// Each state variable is a [`StorageSlot`], whose type is described by a
+/// [`StorageType`] in [`types`](Self::types). Comparing the layouts of two
+/// versions of a contract shows whether an upgrade moves existing variables.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageLayout {
+    /// The state variables, in the order of their slots.
+    pub storage: Vec<StorageSlot>,
+    /// The types of the state variables and of their members, keyed by type
+    /// identifier. The compiler writes `null` for contracts without state.
+    pub types: Option<BTreeMap<String, StorageType>>,
+}
+
+impl StorageLayout {
+    /// Find a state variable by name.
+    pub fn slot(&self, label: &str) -> Option<&StorageSlot> {
+        self.storage.iter().find(|slot| slot.label == label)
+    }
+
+    /// Find the type of a state variable or member.
+    pub fn type_of(&self, slot: &StorageSlot) -> Option<&StorageType> {
+        self.types.as_ref()?.get(&slot.r#type)
+    }
+}
+
+/// The location of a state variable or a struct member in storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlot {
+    /// The id of the variable declaration in the AST.
+    pub ast_id: i64,
+    /// The contract that declares the variable, such as `src/A.sol:A`.
+    pub contract: String,
+    /// The name of the variable.
+    pub label: String,
+    /// The byte offset of the variable within its slot.
+    pub offset: u64,
+    /// The slot, as a decimal string since it can exceed 64 bits.
+    pub slot: String,
+    /// The type identifier, a key of [`StorageLayout::types`].
+    #[serde(rename = "type")]
+    pub r#type: String,
+}
+
+/// A type in a storage layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageType {
+    /// How values of the type are laid out.
+    pub encoding: StorageEncoding,
+    /// The Solidity name of the type, such as `mapping(address => uint256)`.
+    pub label: String,
+    /// The number of bytes the type takes, as a decimal string.
+    pub number_of_bytes: String,
+    /// The element type of an array.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// The key type of a mapping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The value type of a mapping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The members of a struct, with slots relative to the struct.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageSlot>>,
+}
+
+/// How values of a [`StorageType`] are laid out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEncoding {
+    /// Stored in place, from the slot and offset of the variable.
+    #[default]
+    Inplace,
+    /// Stored at the hash of the key and the slot of the mapping.
+    Mapping,
+    /// Elements stored from the hash of the slot, with the length in the slot.
+    DynamicArray,
+    /// Stored in place when short, and like a dynamic array otherwise.
+    Bytes,
+}
+
+/// The EVM-related outputs of a contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Evm {
+    /// The assembly listing of the contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assembly: Option<String>,
+    /// The assembly of the contract as JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legacy_assembly: Option<Assembly>,
+    /// The creation bytecode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytecode: Option<Bytecode>,
+    /// The runtime bytecode, as stored on chain after deployment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployed_bytecode: Option<DeployedBytecode>,
+    /// Gas estimates for deployment and each function.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_estimates: Option<GasEstimates>,
+    /// The selector of each external function as hex, keyed by its
+    /// signature, such as `a9059cbb` for `transfer(address,uint256)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method_identifiers: Option<BTreeMap<String, String>>,
+}
+
+/// The compiler's gas estimates for a contract.
+///
+/// Functions are keyed by their signature, such as `transfer(address,uint256)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasEstimates {
+    /// The cost of deploying the contract.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation: Option<CreationGasEstimates>,
+    /// The cost of calling each external function.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external: BTreeMap<String, GasEstimate>,
+    /// The cost of running each internal function.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub internal: BTreeMap<String, GasEstimate>,
+}
+
+/// The compiler's gas estimates for deploying a contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationGasEstimates {
+    /// The cost of storing the runtime code.
+    pub code_deposit_cost: GasEstimate,
+    /// The cost of running the constructor.
+    pub execution_cost: GasEstimate,
+    /// The sum of both costs.
+    pub total_cost: GasEstimate,
+}
+
+/// A gas estimate, which is unbounded when the cost depends on the input.
+///
+/// The compiler reports estimates as strings, using `infinite` for functions
+/// with loops or dynamic data whose cost has no static bound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GasEstimate {
+    /// A fixed upper bound.
+    Finite(u64),
+    /// No static bound.
+    #[default]
+    Infinite,
+}
+
+impl fmt::Display for GasEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasEstimate::Finite(gas) => write!(f, "{}", gas),
+            GasEstimate::Infinite => f.write_str("infinite"),
+        }
+    }
+}
+
+impl Serialize for GasEstimate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for GasEstimate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if value == "infinite" {
+            return Ok(GasEstimate::Infinite);
+        }
+        value
+            .parse()
+            .map(GasEstimate::Finite)
+            .map_err(|_| serde::de::Error::custom(format!("invalid gas estimate `{}`", value)))
+    }
+}
+
+/// Compiled EVM bytecode for a contract.
+///
+/// This is the `evm.bytecode` (or `evm.deployedBytecode`) entry of a
+/// contract's output. Every field is only present when the matching output
+/// was requested in the output selection.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bytecode {
+    /// The bytecode as a hex string without the `0x` prefix.
+    #[serde(default)]
+    pub object: String,
+    /// The opcode listing as a space-separated string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opcodes: Option<String>,
+    /// The compressed source mapping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_map: Option<String>,
+    /// Sources generated by the compiler, such as ABI encoding helpers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated_sources: Vec<GeneratedSource>,
+    /// The places of library addresses left to link, by source file and
+    /// then library name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub link_references: BTreeMap<String, BTreeMap<String, Vec<LinkReference>>>,
+}
+
+impl Bytecode {
+    /// Whether the bytecode is an EOF container, as the compiler produces
+    /// when `eofVersion` is set, rather than legacy EVM code.
+    ///
+    /// EOF code has immediate arguments beyond those of `PUSHn`, so its
+    /// opcode listing does not split into [`Instruction`]s the legacy way.
+    pub fn is_eof(&self) -> bool {
+        let object = self.object.strip_prefix("0x").unwrap_or(&self.object);
+        object
+            .get(..4)
+            .is_some_and(|magic| magic.eq_ignore_ascii_case("ef00"))
+    }
+
+    /// Find a compiler-generated source by its source id.
+    ///
+    /// Source maps refer to files by index. Indices that do not match any
+    /// input source belong to generated sources, which this method resolves.
+    pub fn generated_source(&self, id: usize) -> Option<&GeneratedSource> {
+        self.generated_sources.iter().find(|source| source.id == id)
+    }
+
+    /// Parse the opcode listing into instructions with byte offsets.
+    ///
+    /// When the bytecode object is also present, the listing is checked
+    /// against it, so a listing that does not describe this bytecode is
+    /// reported as an error. Returns `Ok(None)` if opcodes were not selected.
+    pub fn instructions(&self) -> Result<Option<Vec<Instruction>>, OpcodesError> {
+        let Some(listing) = &self.opcodes else {
+            return Ok(None);
+        };
+        let instructions = opcodes::parse(listing)?;
+        if !self.object.is_empty() {
+            opcodes::reconcile(&instructions, &self.object)?;
+        }
+        Ok(Some(instructions))
+    }
+
+    /// Parse the source map into one entry per instruction.
+    ///
+    /// Returns `Ok(None)` if the source map was not selected.
+    pub fn source_map_entries(&self) -> Result<Option<Vec<SourceMapEntry>>, SourceMapError> {
+        self.source_map.as_deref().map(sourcemap::parse).transpose()
+    }
+
+    /// Decode the CBOR metadata the compiler appends to the bytecode.
+    ///
+    /// Fails for bytecode compiled without metadata, and for unlinked
+    /// bytecode, which is not valid hex.
+    pub fn metadata(&self) -> Result<BytecodeMetadata, MetadataError> {
+        metadata::decode_hex(&self.object)
+    }
+
+    /// Write the address of the library `library` of `file` over its
+    /// placeholders, and drop it from the link references.
+    ///
+    /// The address is hex, with or without a `0x` prefix. Placeholders are
+    /// found through the link references, or by their text if those were
+    /// not selected. Every reference is checked to hold the placeholder of
+    /// the library before any is written, so a failed call leaves the
+    /// bytecode as it was.
+    pub fn link(
+        &mut self,
+        file: &str,
+        library: &str,
+        address: &str,
+    ) -> Result<&mut Self, LinkError> {
+        let hex_address = address.strip_prefix("0x").unwrap_or(address);
+        if hex_address.len() != 40 || !hex_address.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(LinkError::InvalidAddress(address.to_string()));
+        }
+        let unknown = || LinkError::UnknownLibrary {
+            file: file.to_string(),
+            library: library.to_string(),
+        };
+
+        let placeholder = library_placeholder(file, library);
+        let references = self
+            .link_references
+            .get(file)
+            .and_then(|libraries| libraries.get(library));
+        match references {
+            Some(references) => {
+                let prefix = if self.object.starts_with("0x") { 2 } else { 0 };
+                let mut ranges = Vec::with_capacity(references.len());
+                for reference in references {
+                    let Some(range) = hex_range(prefix, reference.start, reference.length) else {
+                        return Err(LinkError::OutOfRange(*reference));
+                    };
+                    match self.object.get(range.clone()) {
+                        None => return Err(LinkError::OutOfRange(*reference)),
+                        Some(text)
+                            if text != placeholder && text != legacy_placeholder(file, library) =>
+                        {
+                            return Err(LinkError::NotPlaceholder(*reference));
+                        }
+                        Some(_) => ranges.push(range),
+                    }
+                }
+                for range in ranges {
+                    self.object.replace_range(range, hex_address);
+                }
+                let libraries = self.link_references.get_mut(file).unwrap();
+                libraries.remove(library);
+                if libraries.is_empty() {
+                    self.link_references.remove(file);
+                }
+            }
+            None => {
+                if !self.object.contains(&placeholder) {
+                    return Err(unknown());
+                }
+                self.object = self.object.replace(&placeholder, hex_address);
+            }
+        }
+        Ok(self)
+    }
+
+    /// The bytecode as bytes, once every library is linked.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LinkError> {
+        let unlinked: Vec<String> = self
+            .link_references
+            .iter()
+            .flat_map(|(file, libraries)| {
+                libraries
+                    .keys()
+                    .map(move |library| format!("{}:{}", file, library))
+            })
+            .collect();
+        if !unlinked.is_empty() {
+            return Err(LinkError::Unlinked(unlinked));
+        }
+        let object = self.object.strip_prefix("0x").unwrap_or(&self.object);
+        hex::decode(object).map_err(|e| LinkError::InvalidHex(e.to_string()))
+    }
+}
+
+/// The placeholder the compiler writes in place of the address of the
+/// library `library` of `file`: `__$`, the first 17 bytes of the hash of
+/// `file:library` as hex, and `$__`.
+pub fn library_placeholder(file: &str, library: &str) -> String {
+    let hash = keccak256(format!("{}:{}", file, library).as_bytes());
+    format!("__${}$__", hex::encode(&hash[..17]))
+}
+
+/// The placeholder compilers before 0.5.0 write: `__`, the first 36
+/// characters of `file:library`, and `_` up to 40 characters.
+fn legacy_placeholder(file: &str, library: &str) -> String {
+    let name: String = format!("{}:{}", file, library).chars().take(36).collect();
+    format!("__{:_<38}", name)
+}
+
+/// The range of hex digits after a prefix of `prefix` digits that holds
+/// `length` bytes at byte offset `start`, or `None` if it overflows.
+fn hex_range(prefix: usize, start: usize, length: usize) -> Option<Range<usize>> {
+    let start = start.checked_mul(2)?.checked_add(prefix)?;
+    let end = length.checked_mul(2)?.checked_add(start)?;
+    Some(start..end)
+}
+
+/// A byte range in the bytecode that holds the address of a library.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkReference {
+    /// The offset of the address in bytes.
+    pub start: usize,
+    /// The size of the address in bytes, always 20.
+    pub length: usize,
+}
+
+/// An error returned by [`Bytecode::link`] and [`Bytecode::to_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LinkError {
+    /// The address is not 20 bytes of hex.
+    #[error("invalid library address `{0}`")]
+    InvalidAddress(String),
+
+    /// The bytecode has no placeholder for the library.
+    #[error("bytecode does not reference library {file}:{library}")]
+    UnknownLibrary { file: String, library: String },
+
+    /// A link reference is past the end of the bytecode.
+    #[error("link reference at {} of {} bytes is out of range", .0.start, .0.length)]
+    OutOfRange(LinkReference),
+
+    /// A link reference does not hold the placeholder of its library.
+    #[error("link reference at {} of {} bytes is not a library placeholder", .0.start, .0.length)]
+    NotPlaceholder(LinkReference),
+
+    /// Libraries are left to link, by fully qualified name.
+    #[error("unlinked libraries: {}", .0.join(", "))]
+    Unlinked(Vec<String>),
+
+    /// The bytecode is not valid hex.
+    #[error("invalid bytecode hex: {0}")]
+    InvalidHex(String),
+}
+
+/// The runtime bytecode of a contract.
+///
+/// This is the `evm.deployedBytecode` entry of a contract's output. It has
+/// the fields of [`Bytecode`], and also locates the immutable variables,
+/// whose values the constructor writes into the runtime code.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployedBytecode {
+    /// The fields shared with the creation bytecode.
+    #[serde(flatten)]
+    pub bytecode: Bytecode,
+    /// The places each immutable variable is stored in the runtime code,
+    /// keyed by the AST id of its declaration.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub immutable_references: BTreeMap<String, Vec<ImmutableReference>>,
+}
+
+impl DeployedBytecode {
+    /// Write the value of the immutable variable declared by AST node `id`
+    /// into the runtime code, at each of its references.
+    ///
+    /// The compiler leaves immutable values zeroed in its output, while the
+    /// code on chain holds the values the constructor wrote. Patching those
+    /// values in makes the compiled code comparable with the deployed code.
+    /// Every reference is checked to be zeroed before any is written, so a
+    /// failed call leaves the code as it was.
+    pub fn set_immutable(&mut self, id: i64, value: [u8; 32]) -> Result<&mut Self, ImmutableError> {
+        let references = self
+            .immutable_references
+            .get(&id.to_string())
+            .ok_or(ImmutableError::UnknownImmutable(id))?;
+        let object = &self.bytecode.object;
+        let prefix = if object.starts_with("0x") { 2 } else { 0 };
+        let mut writes = Vec::with_capacity(references.len());
+        for reference in references {
+            let Some(range) = hex_range(prefix, reference.start, reference.length) else {
+                return Err(ImmutableError::OutOfRange(*reference));
+            };
+            match object.get(range.clone()) {
+                Some(text) if reference.length <= 32 => {
+                    if text.bytes().any(|digit| digit != b'0') {
+                        return Err(ImmutableError::NotZero(*reference));
+                    }
+                }
+                _ => return Err(ImmutableError::OutOfRange(*reference)),
+            }
+            writes.push((range, hex::encode(&value[32 - reference.length..])));
+        }
+        for (range, bytes) in writes {
+            self.bytecode.object.replace_range(range, &bytes);
+        }
+        Ok(self)
+    }
+
+    /// Read the values of the immutable variables from deployed code, by the
+    /// AST id of their declaration, such as to pass to
+    /// [`set_immutable`](Self::set_immutable).
+    ///
+    /// Each value is read at the first reference of its variable. Fails if
+    /// the references of a variable hold different values, which means the
+    /// code was not compiled from this output.
+    pub fn read_immutables(&self, code: &[u8]) -> Result<BTreeMap<i64, [u8; 32]>, ImmutableError> {
+        let mut values = BTreeMap::new();
+        for (id, references) in &self.immutable_references {
+            let id: i64 = id
+                .parse()
+                .map_err(|_| ImmutableError::InvalidId(id.clone()))?;
+            let mut value = None;
+            for reference in references {
+                let bytes = reference
+                    .start
+                    .checked_add(reference.length)
+                    .and_then(|end| code.get(reference.start..end))
+                    .filter(|bytes| bytes.len() <= 32)
+                    .ok_or(ImmutableError::OutOfRange(*reference))?;
+                let mut word = [0; 32];
+                word[32 - bytes.len()..].copy_from_slice(bytes);
+                match value {
+                    None => value = Some(word),
+                    Some(first) if first != word => {
+                        return Err(ImmutableError::Inconsistent(id));
+                    }
+                    Some(_) => {}
+                }
+            }
+            if let Some(value) = value {
+                values.insert(id, value);
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// An error returned by [`DeployedBytecode::set_immutable`] and
+/// [`DeployedBytecode::read_immutables`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ImmutableError {
+    /// The output has no references for an immutable variable with this id.
+    #[error("no immutable references for AST node {0}")]
+    UnknownImmutable(i64),
+
+    /// A key of the immutable references is not an AST id.
+    #[error("invalid AST id `{0}` in immutable references")]
+    InvalidId(String),
+
+    /// A reference is longer than a word or past the end of the code.
+    #[error("immutable reference at {} of {} bytes is out of range", .0.start, .0.length)]
+    OutOfRange(ImmutableReference),
+
+    /// A reference to set does not hold zeros, as the compiler leaves it.
+    #[error("immutable reference at {} of {} bytes is not zeroed", .0.start, .0.length)]
+    NotZero(ImmutableReference),
+
+    /// The references of a variable hold different values in the code.
+    #[error("immutable of AST node {0} has different values in the code")]
+    Inconsistent(i64),
+}
+
+/// A byte range in the runtime code that holds an immutable value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImmutableReference {
+    /// The offset of the value in bytes.
+    pub start: usize,
+    /// The size of the value in bytes, 32 for every type.
+    pub length: usize,
+}
+
+/// A source file generated by the compiler.
+///
+/// The compiler emits Yul utility code (for example ABI encoders and
+/// overflow checks) that is not part of any input file. Each generated source
+/// has its own id in the source id space, its Yul AST, and its text.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedSource {
+    /// The Yul AST of the generated source.
+    pub ast: YulBlock,
+    /// The text of the generated source.
+    pub contents: String,
+    /// The source id used by source maps.
+    pub id: usize,
+    /// The source language, usually `Yul`.
+    pub language: String,
+    /// The name of the generated source, such as `#utility.yul`.
+    pub name: String,
+}
+
+/// The legacy eWasm output of a contract.
+///
+/// Compilers up to 0.8.x could emit eWasm code under the `ewasm` output key.
+/// Support was removed later, but outputs produced by those releases still
+/// contain the section.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ewasm {
+    /// The S-expression text format of the module.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wast: Option<String>,
+    /// The binary format of the module as a hex string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::YulStatement;
+
+    #[test]
+    fn generated_sources() {
+        let json = r##"{
+            "object": "6080",
+            "sourceMap": "58:100:0:-:0;;;",
+            "generatedSources": [{
+                "ast": {
+                    "nodeType": "YulBlock",
+                    "src": "0:120:1",
+                    "statements": [{
+                        "nodeType": "YulFunctionDefinition",
+                        "src": "7:50:1",
+                        "name": "cleanup_t_uint256",
+                        "parameters": [{"name": "value", "nodeType": "YulTypedName", "src": "34:5:1", "type": ""}],
+                        "returnVariables": [{"name": "cleaned", "nodeType": "YulTypedName", "src": "44:7:1", "type": ""}],
+                        "body": {
+                            "nodeType": "YulBlock",
+                            "src": "52:5:1",
+                            "statements": [
+                                {"nodeType": "YulVariableDeclaration", "src": "53:5:1", "value": null,
+                                 "variables": [{"name": "tmp", "nodeType": "YulTypedName", "src": "57:3:1", "type": ""}]},
+                                {"nodeType": "YulLeave", "src": "60:5:1"}
+                            ]
+                        }
+                    }, {
+                        "nodeType": "YulFunctionDefinition",
+                        "src": "60:40:1",
+                        "name": "revert_error_empty",
+                        "body": {"nodeType": "YulBlock", "src": "90:10:1", "statements": []}
+                    }]
+                },
+                "contents": "{ function cleanup_t_uint256(value) -> cleaned { let tmp leave } function revert_error_empty() { } }",
+                "id": 1,
+                "language": "Yul",
+                "name": "#utility.yul"
+            }]
+        }"##;
+
+        let bytecode: Bytecode = serde_json::from_str(json).unwrap();
+        let source = bytecode.generated_source(1).unwrap();
+        assert_eq!(source.name, "#utility.yul");
+        assert!(bytecode.generated_source(0).is_none());
+        let entries = bytecode.source_map_entries().unwrap().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].length, 100);
+
+        let YulStatement::YulFunctionDefinition(function) = &source.ast.statements[0] else {
+            panic!("expected a function definition");
+        };
+        assert_eq!(function.return_variables[0].name, "cleaned");
+        assert!(matches!(
+            function.body.statements[1],
+            YulStatement::YulLeave(_)
+        ));
+    }
+
+    #[test]
+    fn ewasm_fields() {
+        let ewasm: Ewasm =
+            serde_json::from_str(r#"{"wast": "(module)", "wasm": "0061736d01000000"}"#).unwrap();
+        assert_eq!(ewasm.wast.as_deref(), Some("(module)"));
+        assert_eq!(ewasm.wasm.as_deref(), Some("0061736d01000000"));
+
+        let empty: Ewasm = serde_json::from_str("{}").unwrap();
+        assert_eq!(serde_json::to_string(&empty).unwrap(), "{}");
+
+        let contract: Contract =
+            serde_json::from_str(r#"{"ewasm": {"wast": "(module)"}}"#).unwrap();
+        assert_eq!(contract.ewasm.unwrap().wast.as_deref(), Some("(module)"));
+    }
+
+    #[test]
+    fn eof_bytecode() {
+        let json = r#"{
+            "evm": {
+                "bytecode": {"object": "ef00010100040200010001040000000080000000"},
+                "deployedBytecode": {"object": "6080604052"}
+            }
+        }"#;
+        let contract: Contract = serde_json::from_str(json).unwrap();
+        let evm = contract.evm.unwrap();
+        assert!(evm.bytecode.unwrap().is_eof());
+        assert!(!evm.deployed_bytecode.unwrap().bytecode.is_eof());
+    }
+
+    #[test]
+    fn merge_and_dedup_errors() {
+        let warning = |file: &str| Error {
+            r#type: "Warning".to_string(),
+            component: "general".to_string(),
+            severity: Severity::Warning,
+            error_code: Some("2072".to_string()),
+            message: "Unused local variable.".to_string(),
+            formatted_message: Some(format!("Warning: Unused local variable.\n --> {}", file)),
+            source_location: Some(ErrorLocation {
+                file: file.to_string(),
+                start: 10,
+                end: 20,
+            }),
+            secondary_source_locations: Vec::new(),
+        };
+
+        let mut output = StandardJsonOutput {
+            errors: vec![warning("Shared.sol"), warning("A.sol")],
+            ..Default::default()
+        };
+        output.merge(StandardJsonOutput {
+            errors: vec![warning("Shared.sol"), warning("B.sol")],
+            ..Default::default()
+        });
+        assert_eq!(output.errors.len(), 4);
+
+        output.dedup_errors();
+        let files: Vec<&str> = output
+            .errors
+            .iter()
+            .map(|e| e.source_location.as_ref().unwrap().file.as_str())
+            .collect();
+        assert_eq!(files, vec!["Shared.sol", "A.sol", "B.sol"]);
+    }
+
+    #[test]
+    fn filter_contracts() {
+        let json = r#"{
+            "sources": {"src/Token.sol": {"id": 0}, "test/Token.t.sol": {"id": 1}},
+            "contracts": {
+                "src/Token.sol": {"Token": {}, "IToken": {}},
+                "test/Token.t.sol": {"Token": {}, "TokenTest": {}}
+            }
+        }"#;
+        let output: StandardJsonOutput = serde_json::from_str(json).unwrap();
+        let names = |output: &StandardJsonOutput| {
+            output
+                .contracts
+                .iter()
+                .flat_map(|(file, contracts)| {
+                    contracts
+                        .keys()
+                        .map(move |name| format!("{}:{}", file, name))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut filtered = output.clone();
+        filtered.contracts_matching("*Token");
+        assert_eq!(
+            names(&filtered),
+            [
+                "src/Token.sol:IToken",
+                "src/Token.sol:Token",
+                "test/Token.t.sol:Token"
+            ]
+        );
+        filtered.contracts_matching("src/*:?Token");
+        assert_eq!(names(&filtered), ["src/Token.sol:IToken"]);
+        assert_eq!(filtered.sources.len(), 2);
+
+        let mut filtered = output.clone();
+        filtered.only_contract("Token");
+        assert_eq!(
+            names(&filtered),
+            ["src/Token.sol:Token", "test/Token.t.sol:Token"]
+        );
+        filtered.only_contract("test/Token.t.sol:Token");
+        assert_eq!(names(&filtered), ["test/Token.t.sol:Token"]);
+
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b", "aXbY"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn verify_selectors() {
+        let json = r#"{
+            "abi": [
+                {"type": "function", "name": "transfer", "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}], "outputs": [{"name": "", "type": "bool"}], "stateMutability": "nonpayable"},
+                {"type": "function", "name": "totalSupply", "inputs": [], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"},
+                {"type": "event", "name": "Transfer", "inputs": [], "anonymous": false}
+            ],
+            "evm": {"methodIdentifiers": {"totalSupply()": "18160DDD", "transfer(address,uint256)": "a9059cbb"}}
+        }"#;
+        let contract: Contract = serde_json::from_str(json).unwrap();
+        let abi = contract.abi.clone().unwrap();
+        assert_eq!(contract.verify_selectors(&abi), Ok(()));
+
+        let mut wrong = contract.clone();
+        let identifiers = wrong
+            .evm
+            .as_mut()
+            .unwrap()
+            .method_identifiers
+            .as_mut()
+            .unwrap();
+        identifiers.insert("totalSupply()".to_string(), "00000000".to_string());
+        identifiers.insert(
+            "approve(address,uint256)".to_string(),
+            "095ea7b3".to_string(),
+        );
+        identifiers.remove("transfer(address,uint256)");
+        let messages: Vec<String> = wrong
+            .verify_selectors(&abi)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "approve(address,uint256) has a method identifier but is not in the ABI",
+                "totalSupply() has selector 18160ddd, but the compiler reported 00000000",
+                "transfer(address,uint256) is in the ABI but has no method identifier",
+            ]
+        );
+
+        let mut missing = contract;
+        missing.evm = None;
+        assert_eq!(
+            missing.verify_selectors(&abi),
+            Err(vec![SelectorMismatch::MissingOutput])
+        );
+    }
+
+    #[test]
+    fn link_libraries() {
+        let math = library_placeholder("lib/Math.sol", "Math");
+        let strings = library_placeholder("lib/Strings.sol", "Strings");
+        assert_eq!(math.len(), 40);
+        let object = format!("73{}3173{}f373{}00", math, strings, math);
+        let json = serde_json::json!({
+            "object": object,
+            "linkReferences": {
+                "lib/Math.sol": {"Math": [{"start": 1, "length": 20}, {"start": 45, "length": 20}]},
+                "lib/Strings.sol": {"Strings": [{"start": 23, "length": 20}]}
+            }
+        });
+        let mut bytecode: Bytecode = serde_json::from_value(json).unwrap();
+        let mut unreferenced = Bytecode {
+            link_references: BTreeMap::new(),
+            ..bytecode.clone()
+        };
+
+        assert_eq!(
+            bytecode.to_bytes(),
+            Err(LinkError::Unlinked(vec![
+                "lib/Math.sol:Math".to_string(),
+                "lib/Strings.sol:Strings".to_string()
+            ]))
+        );
+        assert_eq!(
+            bytecode.link("lib/Math.sol", "Math", "0x1234").unwrap_err(),
+            LinkError::InvalidAddress("0x1234".to_string())
+        );
+        assert!(matches!(
+            bytecode.link("lib/Math.sol", "Strings", &format!("{:040}", 1)),
+            Err(LinkError::UnknownLibrary { .. })
+        ));
+
+        let mut broken = bytecode.clone();
+        broken
+            .link_references
+            .get_mut("lib/Math.sol")
+            .unwrap()
+            .get_mut("Math")
+            .unwrap()[1]
+            .start = 50;
+        assert_eq!(
+            broken.link("lib/Math.sol", "Math", &format!("{:040}", 1)),
+            Err(LinkError::OutOfRange(LinkReference {
+                start: 50,
+                length: 20
+            }))
+        );
+        broken
+            .link_references
+            .get_mut("lib/Math.sol")
+            .unwrap()
+            .get_mut("Math")
+            .unwrap()[1]
+            .start = usize::MAX;
+        assert_eq!(
+            broken.link("lib/Math.sol", "Math", &format!("{:040}", 1)),
+            Err(LinkError::OutOfRange(LinkReference {
+                start: usize::MAX,
+                length: 20
+            }))
+        );
+        broken
+            .link_references
+            .get_mut("lib/Math.sol")
+            .unwrap()
+            .get_mut("Math")
+            .unwrap()[1]
+            .start = 23;
+        assert_eq!(
+            broken.link("lib/Math.sol", "Math", &format!("{:040}", 1)),
+            Err(LinkError::NotPlaceholder(LinkReference {
+                start: 23,
+                length: 20
+            }))
+        );
+        assert_eq!(broken.object, bytecode.object);
+
+        let mut prefixed = Bytecode {
+            object: format!("0x{}", object),
+            ..bytecode.clone()
+        };
+        prefixed
+            .link("lib/Math.sol", "Math", &format!("{:040}", 1))
+            .unwrap();
+        assert!(prefixed.object.starts_with(&format!("0x73{:040}31", 1)));
+
+        let legacy = legacy_placeholder("lib/Math.sol", "Math");
+        assert_eq!(legacy, "__lib/Math.sol:Math_____________________");
+        let mut old = Bytecode {
+            object: format!("73{}00", legacy),
+            link_references: BTreeMap::from([(
+                "lib/Math.sol".to_string(),
+                BTreeMap::from([(
+                    "Math".to_string(),
+                    vec![LinkReference {
+                        start: 1,
+                        length: 20,
+                    }],
+                )]),
+            )]),
+            ..Default::default()
+        };
+        old.link("lib/Math.sol", "Math", &format!("{:040}", 1))
+            .unwrap();
+        assert_eq!(old.object, format!("73{:040}00", 1));
+
+        let one = format!("0x{:040}", 1);
+        let two = format!("{:040}", 2);
+        let linked = bytecode
+            .link("lib/Math.sol", "Math", &one)
+            .unwrap()
+            .link("lib/Strings.sol", "Strings", &two)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        let expected =
+            hex::decode(format!("73{}3173{}f373{}00", &one[2..], two, &one[2..])).unwrap();
+        assert_eq!(linked, expected);
+        assert!(bytecode.link_references.is_empty());
+
+        let linked = unreferenced
+            .link("lib/Math.sol", "Math", &one)
+            .unwrap()
+            .link("lib/Strings.sol", "Strings", &two)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert_eq!(linked, expected);
+    }
+
+    #[test]
+    fn patch_immutables() {
+        let json = serde_json::json!({
+            "object": format!("7f{}507f{}5000", "00".repeat(32), "00".repeat(32)),
+            "immutableReferences": {"12": [{"start": 1, "length": 32}, {"start": 35, "length": 32}]}
+        });
+        let compiled: DeployedBytecode = serde_json::from_value(json).unwrap();
+        let mut value = [0; 32];
+        value[31] = 0x2a;
+        let mut deployed = compiled.clone();
+        deployed.set_immutable(12, value).unwrap();
+        assert_eq!(
+            deployed.bytecode.object,
+            format!("7f{}2a507f{}2a5000", "00".repeat(31), "00".repeat(31))
+        );
+
+        let code = deployed.bytecode.to_bytes().unwrap();
+        let values = compiled.read_immutables(&code).unwrap();
+        assert_eq!(values, BTreeMap::from([(12, value)]));
+        let mut patched = compiled.clone();
+        for (id, value) in values {
+            patched.set_immutable(id, value).unwrap();
+        }
+        assert_eq!(patched.bytecode.to_bytes().unwrap(), code);
+
+        let mut tampered = code.clone();
+        tampered[40] = 1;
+        assert_eq!(
+            compiled.read_immutables(&tampered),
+            Err(ImmutableError::Inconsistent(12))
+        );
+        assert_eq!(
+            compiled.read_immutables(&code[..40]),
+            Err(ImmutableError::OutOfRange(ImmutableReference {
+                start: 35,
+                length: 32
+            }))
+        );
+        assert_eq!(
+            compiled.clone().set_immutable(7, value).unwrap_err(),
+            ImmutableError::UnknownImmutable(7)
+        );
+
+        let overflow = ImmutableReference {
+            start: usize::MAX - 1,
+            length: 32,
+        };
+        let mut huge = compiled.clone();
+        huge.immutable_references
+            .insert("12".to_string(), vec![overflow]);
+        assert_eq!(
+            huge.clone().set_immutable(12, value).unwrap_err(),
+            ImmutableError::OutOfRange(overflow)
+        );
+        assert_eq!(
+            huge.read_immutables(&code),
+            Err(ImmutableError::OutOfRange(overflow))
+        );
+
+        let mut half = compiled.clone();
+        half.bytecode.object.replace_range(72..74, "01");
+        assert_eq!(
+            half.set_immutable(12, value).unwrap_err(),
+            ImmutableError::NotZero(ImmutableReference {
+                start: 35,
+                length: 32
+            })
+        );
+        assert!(
+            half.bytecode.object[2..66]
+                .bytes()
+                .all(|digit| digit == b'0')
+        );
+
+        let mut prefixed = compiled.clone();
+        prefixed.bytecode.object.insert_str(0, "0x");
+        prefixed.set_immutable(12, value).unwrap();
+        assert_eq!(
+            prefixed.bytecode.object,
+            format!("0x{}", deployed.bytecode.object)
+        );
+    }
+
+    #[test]
+    fn contracts() {
+        let json = r#"{
+            "contracts": {
+                "A.sol": {
+                    "A": {
+                        "abi": [{"type": "function", "name": "f", "inputs": [], "outputs": [], "stateMutability": "pure"}],
+                        "evm": {"bytecode": {"object": "6080604052"}}
+                    }
+                }
+            }
+        }"#;
+
+        let output: StandardJsonOutput = serde_json::from_str(json).unwrap();
+        let (file, contract) = output.find_contract("A").unwrap();
+        assert_eq!(file, "A.sol");
+        assert_eq!(contract.abi.as_ref().unwrap().items.len(), 1);
+        assert_eq!(
+            contract
+                .evm
+                .as_ref()
+                .unwrap()
+                .bytecode
+                .as_ref()
+                .unwrap()
+                .object,
+            "6080604052"
+        );
+        assert!(output.find_contract("B").is_none());
+    }
+
+    #[test]
+    fn constructor_arguments() {
+        let json = r#"{
+            "abi": [{"type": "constructor", "inputs": [
+                {"name": "owner", "type": "address"},
+                {"name": "name", "type": "string"}
+            ], "stateMutability": "nonpayable"}],
+            "evm": {"bytecode": {"object": "6080604052"}}
+        }"#;
+        let contract: Contract = serde_json::from_str(json).unwrap();
+        let mut input = hex::decode("6080604052").unwrap();
+        input.extend([0u8; 31]);
+        input.push(0x01);
+        input.extend([0u8; 31]);
+        input.push(0x40);
+        input.extend([0u8; 31]);
+        input.push(0x02);
+        input.extend(b"ok");
+        input.extend([0u8; 30]);
+
+        let mut owner = [0u8; 20];
+        owner[19] = 0x01;
+        assert_eq!(
+            contract.decode_constructor_arguments(&input).unwrap(),
+            [Token::Address(owner), Token::String("ok".to_string())]
+        );
+        assert_eq!(
+            contract.decode_constructor_arguments(&input[1..]),
+            Err(ConstructorArgumentsError::BytecodeMismatch)
+        );
+        assert_eq!(
+            Contract::default().decode_constructor_arguments(&input),
+            Err(ConstructorArgumentsError::MissingOutput("abi"))
+        );
+    }
+
+    #[test]
+    fn model_checker_findings() {
+        let source_unit = SourceUnit {
+            id: 3,
+            absolute_path: "A.sol".into(),
+            src: SourceLocation {
+                offset: 0,
+                length: 100,
+                source_index: 0,
+            },
+            ..Default::default()
+        };
+        let index = LocationIndex::new(&[source_unit]);
+        let finding = |message: &str, start: i64, end: i64| Error {
+            r#type: "Warning".to_string(),
+            severity: Severity::Warning,
+            message: message.to_string(),
+            source_location: Some(ErrorLocation {
+                file: "A.sol".to_string(),
+                start,
+                end,
+            }),
+            ..Default::default()
+        };
+        let output = StandardJsonOutput {
+            errors: vec![
+                finding(
+                    "CHC: Overflow (resulting value larger than 255) happens here.",
+                    10,
+                    15,
+                ),
+                finding("BMC: Division by zero might happen here.", 0, 100),
+                finding(
+                    "CHC: 2 verification condition(s) could not be proved.",
+                    -1,
+                    -1,
+                ),
+                finding("Unused local variable.", 10, 15),
+            ],
+            ..Default::default()
+        };
+
+        let findings = output.model_checker_findings(&index);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].engine, ModelCheckerEngine::Chc);
+        assert_eq!(findings[0].target, Some(ModelCheckerTarget::Overflow));
+        assert_eq!(findings[0].status, ModelCheckerStatus::Violated);
+        assert_eq!(findings[0].node, Some(3));
+        assert_eq!(findings[1].engine, ModelCheckerEngine::Bmc);
+        assert_eq!(findings[1].target, Some(ModelCheckerTarget::DivByZero));
+        assert_eq!(findings[1].status, ModelCheckerStatus::Unproved);
+        assert_eq!(findings[2].target, None);
+        assert_eq!(findings[2].status, ModelCheckerStatus::Other);
+        assert_eq!(findings[2].node, None);
+    }
+
+    #[test]
+    fn full_output() {
+        let json = r#"{
+            "errors": [{
+                "component": "general",
+                "errorCode": "2018",
+                "formattedMessage": "Warning: Function state mutability can be restricted to pure",
+                "message": "Function state mutability can be restricted to pure",
+                "severity": "warning",
+                "sourceLocation": {"end": 120, "file": "A.sol", "start": 80},
+                "type": "Warning"
+            }],
+            "sources": {
+                "A.sol": {
+                    "id": 0,
+                    "ast": {
+                        "absolutePath": "A.sol",
+                        "exportedSymbols": {},
+                        "id": 1,
+                        "nodeType": "SourceUnit",
+                        "nodes": [],
+                        "src": "0:0:0"
+                    }
+                }
+            },
+            "contracts": {
+                "A.sol": {
+                    "A": {
+                        "abi": [],
+                        "metadata": "{\"compiler\":{\"version\":\"0.8.28+commit.7893614a\"}}",
+                        "userdoc": {"kind": "user", "methods": {}, "version": 1},
+                        "devdoc": {"kind": "dev", "methods": {}, "version": 1},
+                        "ir": "object \"A_1\" {}",
+                        "storageLayout": {
+                            "storage": [
+                                {"astId": 3, "contract": "A.sol:A", "label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+                                {"astId": 7, "contract": "A.sol:A", "label": "balances", "offset": 0, "slot": "1", "type": "t_mapping(t_address,t_uint256)"}
+                            ],
+                            "types": {
+                                "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+                                "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+                                "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"}
+                            }
+                        },
+                        "transientStorageLayout": {"storage": [], "types": null},
+                        "evm": {
+                            "bytecode": {"object": "6080", "sourceMap": "0:0:0:-:0"},
+                            "deployedBytecode": {
+                                "object": "6080",
+                                "immutableReferences": {"5": [{"start": 10, "length": 32}]}
+                            }
+                        }
+                    }
+                }
+            }
+        }"#;
+
+        let output: StandardJsonOutput = serde_json::from_str(json).unwrap();
+        assert!(!output.has_errors());
+        assert_eq!(output.errors[0].severity, Severity::Warning);
+        assert!(
+            output.sources["A.sol"]
+                .source_unit()
+                .unwrap()
+                .unwrap()
+                .nodes
+                .is_empty()
+        );
+
+        let (_, contract) = output.find_contract("A").unwrap();
+        assert!(contract.metadata.as_ref().unwrap().contains("0.8.28"));
+        assert_eq!(contract.userdoc.as_ref().unwrap()["kind"], "user");
+        assert!(contract.ir_optimized.is_none());
+        let layout = contract.storage_layout.as_ref().unwrap();
+        let balances = layout.slot("balances").unwrap();
+        assert_eq!(balances.slot, "1");
+        let mapping = layout.type_of(balances).unwrap();
+        assert_eq!(mapping.encoding, StorageEncoding::Mapping);
+        assert_eq!(mapping.value.as_deref(), Some("t_uint256"));
+        assert!(
+            contract
+                .transient_storage_layout
+                .as_ref()
+                .unwrap()
+                .types
+                .is_none()
+        );
+        let deployed = contract
+            .evm
+            .as_ref()
+            .unwrap()
+            .deployed_bytecode
+            .as_ref()
+            .unwrap();
+        assert_eq!(deployed.bytecode.object, "6080");
+        assert_eq!(
+            deployed.immutable_references["5"],
+            vec![ImmutableReference {
+                start: 10,
+                length: 32
+            }]
+        );
+
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(
+            value,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+}
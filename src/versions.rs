@@ -0,0 +1,482 @@
+//! Download and manage solc releases.
+//!
+//! [`VersionManager`] lists the releases published at
+//! [binaries.soliditylang.org], downloads a specific version into a cache
+//! directory after checking its hash, and hands back a [`Solc`] backend
+//! pinned to that version. The cache uses the same layout as [svm], so
+//! compilers installed by either tool are shared.
+//!
+//! This module needs the `download` feature, which brings in an HTTP client.
+//!
+//! [binaries.soliditylang.org]: https://binaries.soliditylang.org
+//! [svm]: https://github.com/alloy-rs/svm-rs
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::abi::keccak256;
+use crate::compiler::Solc;
+use crate::pragma::PragmaReport;
+
+/// The default location of the release list and binaries.
+pub const DEFAULT_BASE_URL: &str = "https://binaries.soliditylang.org";
+
+/// A platform that solc releases are built for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Platform {
+    /// Linux on x86-64, with statically linked builds.
+    LinuxAmd64,
+    /// macOS, on x86-64 and on Apple Silicon.
+    MacosAmd64,
+    /// Windows on x86-64.
+    WindowsAmd64,
+}
+
+impl Platform {
+    /// The platform this program runs on, if releases are built for it.
+    ///
+    /// Apple Silicon runs the macOS builds, which are universal binaries
+    /// since 0.8.24, and older ones through Rosetta.
+    pub fn current() -> Option<Self> {
+        match (env::consts::OS, env::consts::ARCH) {
+            ("linux", "x86_64") => Some(Platform::LinuxAmd64),
+            ("macos", "x86_64" | "aarch64") => Some(Platform::MacosAmd64),
+            ("windows", "x86_64") => Some(Platform::WindowsAmd64),
+            _ => None,
+        }
+    }
+
+    /// The name of the platform's directory on the release server.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::LinuxAmd64 => "linux-amd64",
+            Platform::MacosAmd64 => "macosx-amd64",
+            Platform::WindowsAmd64 => "windows-amd64",
+        }
+    }
+}
+
+/// The releases published for a platform, as listed in its `list.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseList {
+    /// Every build, including nightly builds.
+    pub builds: Vec<Build>,
+    /// The file name of each release build, by version.
+    pub releases: BTreeMap<Version, String>,
+    /// The newest release.
+    pub latest_release: Version,
+}
+
+impl ReleaseList {
+    /// The release versions, oldest first.
+    pub fn versions(&self) -> impl Iterator<Item = &Version> {
+        self.releases.keys()
+    }
+
+    /// The release build of a version.
+    pub fn build(&self, version: &Version) -> Option<&Build> {
+        let path = self.releases.get(version)?;
+        self.builds.iter().find(|build| &build.path == path)
+    }
+}
+
+/// A single compiler build.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Build {
+    /// The file name of the binary on the release server.
+    pub path: String,
+    /// The compiler version, without prerelease or build metadata.
+    pub version: Version,
+    /// The prerelease tag of nightly builds, such as `nightly.2024.1.1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prerelease: Option<String>,
+    /// The full version, such as `0.8.28+commit.7893614a`.
+    pub long_version: String,
+    /// The Keccak-256 hash of the binary, `0x`-prefixed, which
+    /// [`VersionManager::install`] checks the download against.
+    pub keccak256: String,
+}
+
+/// An error listing or installing compiler versions.
+#[derive(Debug, thiserror::Error)]
+pub enum VersionsError {
+    /// The request to the release server failed.
+    #[error("failed to download {url}: {source}")]
+    Http {
+        url: String,
+        source: Box<ureq::Error>,
+    },
+
+    /// A file could not be read or written.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The release list could not be parsed.
+    #[error("invalid release list: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// No releases are built for this platform.
+    #[error("no solc releases for {}/{}", env::consts::OS, env::consts::ARCH)]
+    UnsupportedPlatform,
+
+    /// The version was never released for the platform.
+    #[error("solc {0} is not released for this platform")]
+    UnknownVersion(Version),
+
+    /// No release meets the requirements of every source.
+    #[error("no solc release satisfies {}", .requirements.join(", "))]
+    NoMatchingVersion { requirements: Vec<String> },
+
+    /// The downloaded binary does not have the published hash.
+    #[error("checksum mismatch for {path}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// Lists, downloads, and caches solc releases.
+#[derive(Clone, Debug)]
+pub struct VersionManager {
+    dir: PathBuf,
+    platform: Option<Platform>,
+    base_url: String,
+}
+
+impl VersionManager {
+    /// Manage compilers cached in `dir`.
+    ///
+    /// Each version is stored as `<dir>/<version>/solc-<version>`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            platform: Platform::current(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// The cache directory of svm, `~/.svm`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+        Some(PathBuf::from(home).join(".svm"))
+    }
+
+    /// Download builds for `platform` instead of the current one.
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Download from a mirror of the release server.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Fetch the list of releases for the platform.
+    pub fn releases(&self) -> Result<ReleaseList, VersionsError> {
+        let platform = self.platform.ok_or(VersionsError::UnsupportedPlatform)?;
+        let list = self.download(&format!("{}/list.json", platform.as_str()))?;
+        Ok(serde_json::from_slice(&list)?)
+    }
+
+    /// The versions in the cache, oldest first.
+    pub fn installed(&self) -> Result<Vec<Version>, VersionsError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        let mut versions = Vec::new();
+        for entry in entries {
+            let name = entry?.file_name();
+            if let Some(version) = name.to_str().and_then(|name| Version::parse(name).ok())
+                && self.binary_path(&version).is_file()
+            {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// A backend for a cached version, without downloading it.
+    pub fn find_installed(&self, version: &Version) -> Option<Solc> {
+        let path = self.binary_path(version);
+        path.is_file()
+            .then(|| Solc::new(path).with_version(version.clone()))
+    }
+
+    /// A backend for a version, downloading it first if it is not cached.
+    ///
+    /// The binary is checked against the Keccak-256 hash in the release list
+    /// and only moved into the cache once it matches, so an interrupted or
+    /// corrupted download never leaves a broken compiler behind.
+    pub fn install(&self, version: &Version) -> Result<Solc, VersionsError> {
+        if let Some(solc) = self.find_installed(version) {
+            return Ok(solc);
+        }
+
+        let platform = self.platform.ok_or(VersionsError::UnsupportedPlatform)?;
+        let releases = self.releases()?;
+        let build = releases
+            .build(version)
+            .ok_or_else(|| VersionsError::UnknownVersion(version.clone()))?;
+        let binary = self.download(&format!("{}/{}", platform.as_str(), build.path))?;
+
+        let actual = format!("0x{}", hex::encode(keccak256(&binary)));
+        if !actual.eq_ignore_ascii_case(&build.keccak256) {
+            return Err(VersionsError::ChecksumMismatch {
+                path: build.path.clone(),
+                expected: build.keccak256.clone(),
+                actual,
+            });
+        }
+
+        let path = self.binary_path(version);
+        let dir = path.parent().expect("binary path has a parent");
+        fs::create_dir_all(dir)?;
+        let partial = dir.join(format!(".solc-{}.partial", version));
+        fs::write(&partial, &binary)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+        }
+        fs::rename(&partial, &path)?;
+        Ok(Solc::new(path).with_version(version.clone()))
+    }
+
+    /// A backend for the newest release that every source allows.
+    ///
+    /// An installed release is used when one meets the requirements, even
+    /// if a newer one could be downloaded, so that builds work offline once
+    /// a compiler is installed. Otherwise the newest matching release in the
+    /// release list is downloaded.
+    pub fn resolve(&self, pragmas: &PragmaReport) -> Result<Solc, VersionsError> {
+        let installed = self.installed()?;
+        let version = match pragmas.newest(&installed) {
+            Some(version) => version.clone(),
+            None => {
+                let releases = self.releases()?;
+                pragmas
+                    .newest(releases.versions())
+                    .cloned()
+                    .ok_or_else(|| VersionsError::NoMatchingVersion {
+                        requirements: pragmas
+                            .requirements()
+                            .iter()
+                            .map(|requirement| requirement.to_string())
+                            .collect(),
+                    })?
+            }
+        };
+        self.install(&version)
+    }
+
+    fn binary_path(&self, version: &Version) -> PathBuf {
+        self.dir
+            .join(version.to_string())
+            .join(format!("solc-{}", version))
+    }
+
+    fn download(&self, path: &str) -> Result<Vec<u8>, VersionsError> {
+        let url = format!("{}/{}", self.base_url, path);
+        let response = ureq::get(&url)
+            .call()
+            .map_err(|source| VersionsError::Http {
+                url: url.clone(),
+                source: Box::new(source),
+            })?;
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body)?;
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    use super::*;
+    use crate::standard_json_input::StandardJsonInput;
+
+    /// Serve `files` over HTTP and count the requests.
+    fn serve(files: Vec<(&'static str, Vec<u8>)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let path = request.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = match files.iter().find(|(name, _)| *name == path) {
+                    Some((_, body)) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", Vec::new()),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn release_list(binary: &[u8]) -> Vec<u8> {
+        let keccak = format!("0x{}", hex::encode(keccak256(binary)));
+        serde_json::to_vec(&serde_json::json!({
+            "builds": [{
+                "path": "solc-linux-amd64-v0.8.28+commit.7893614a",
+                "version": "0.8.28",
+                "build": "commit.7893614a",
+                "longVersion": "0.8.28+commit.7893614a",
+                "keccak256": keccak,
+                "sha256": "0x00",
+                "urls": []
+            }],
+            "releases": {"0.8.28": "solc-linux-amd64-v0.8.28+commit.7893614a"},
+            "latestRelease": "0.8.28"
+        }))
+        .unwrap()
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("solc-rs-versions-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn install_and_reuse() {
+        let binary = b"#!/bin/sh\necho solc\n".to_vec();
+        let (url, requests) = serve(vec![
+            ("/linux-amd64/list.json", release_list(&binary)),
+            (
+                "/linux-amd64/solc-linux-amd64-v0.8.28+commit.7893614a",
+                binary.clone(),
+            ),
+        ]);
+        let manager = VersionManager::new(cache_dir("install"))
+            .platform(Platform::LinuxAmd64)
+            .base_url(url);
+        let version = Version::new(0, 8, 28);
+
+        let releases = manager.releases().unwrap();
+        assert_eq!(releases.latest_release, version);
+        assert_eq!(
+            releases.build(&version).unwrap().long_version,
+            "0.8.28+commit.7893614a"
+        );
+        assert!(manager.installed().unwrap().is_empty());
+
+        let solc = manager.install(&version).unwrap();
+        assert_eq!(fs::read(solc.path()).unwrap(), binary);
+        assert!(solc.path().ends_with("0.8.28/solc-0.8.28"));
+        assert_eq!(manager.installed().unwrap(), vec![version.clone()]);
+
+        let count = requests.load(Ordering::SeqCst);
+        manager.install(&version).unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), count);
+
+        assert!(matches!(
+            manager.install(&Version::new(0, 8, 29)),
+            Err(VersionsError::UnknownVersion(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_pragmas() {
+        let binary = b"#!/bin/sh\necho solc\n".to_vec();
+        let (url, requests) = serve(vec![
+            ("/linux-amd64/list.json", release_list(&binary)),
+            (
+                "/linux-amd64/solc-linux-amd64-v0.8.28+commit.7893614a",
+                binary.clone(),
+            ),
+        ]);
+        let dir = cache_dir("resolve");
+        let manager = VersionManager::new(&dir)
+            .platform(Platform::LinuxAmd64)
+            .base_url(url);
+        let pragmas = |source: &str| {
+            PragmaReport::from_input(&StandardJsonInput::new().add_source("A.sol", source)).unwrap()
+        };
+
+        // An installed release that matches is used without downloading.
+        let installed = dir.join("0.8.20").join("solc-0.8.20");
+        fs::create_dir_all(installed.parent().unwrap()).unwrap();
+        fs::write(&installed, "").unwrap();
+        let solc = manager
+            .resolve(&pragmas("pragma solidity ^0.8.0;"))
+            .unwrap();
+        assert_eq!(solc.known_version(), Some(&Version::new(0, 8, 20)));
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+        let solc = manager
+            .resolve(&pragmas("pragma solidity >=0.8.21;"))
+            .unwrap();
+        assert_eq!(solc.known_version(), Some(&Version::new(0, 8, 28)));
+        assert_eq!(fs::read(solc.path()).unwrap(), binary);
+
+        let Err(VersionsError::NoMatchingVersion { requirements }) =
+            manager.resolve(&pragmas("pragma solidity ^0.9.0;"))
+        else {
+            panic!("expected no matching version");
+        };
+        assert_eq!(requirements, ["^0.9.0"]);
+    }
+
+    #[test]
+    fn reject_checksum_mismatch() {
+        let (url, _) = serve(vec![
+            ("/linux-amd64/list.json", release_list(b"expected")),
+            (
+                "/linux-amd64/solc-linux-amd64-v0.8.28+commit.7893614a",
+                b"tampered".to_vec(),
+            ),
+        ]);
+        let manager = VersionManager::new(cache_dir("checksum"))
+            .platform(Platform::LinuxAmd64)
+            .base_url(url);
+        let version = Version::new(0, 8, 28);
+
+        assert!(matches!(
+            manager.install(&version),
+            Err(VersionsError::ChecksumMismatch { .. })
+        ));
+        assert!(manager.find_installed(&version).is_none());
+    }
+}